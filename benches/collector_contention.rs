@@ -59,10 +59,10 @@ fn bench_collector_contention(c: &mut Criterion) {
                         let c = Arc::clone(&collector);
                         handles.push(thread::spawn(move || {
                             // Each thread records into its own key
-                            let key: &'static str = Box::leak(format!("key_{i}").into_boxed_str());
+                            let key = format!("key_{i}");
                             for j in 0..10_000u64 {
                                 let v = u128::from((i as u64).wrapping_mul(31) ^ j) % 1_000 + 1;
-                                c.record_duration(key, Duration::from_nanos(v));
+                                c.record_duration(key.clone(), Duration::from_nanos(v));
                             }
                         }));
                     }
@@ -10,21 +10,21 @@ use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criteri
 fn build_collector(keys: usize, per_key: usize) -> Collector {
     let c = Collector::with_capacity(keys);
     for k in 0..keys {
-        let name: &'static str = Box::leak(format!("key_{k}").into_boxed_str());
+        let name = format!("key_{k}");
         for i in 0..per_key {
             // distribute some values
             let v = (i as u128 % 1000) + 1;
-            c.record_duration(name, Duration::from_nanos(v));
+            c.record_duration(name.clone(), Duration::from_nanos(v));
         }
     }
     c
 }
 
 #[cfg(feature = "std")]
-fn build_arrays(keys: usize, per_key: usize) -> Vec<(&'static str, Vec<Duration>)> {
+fn build_arrays(keys: usize, per_key: usize) -> Vec<(String, Vec<Duration>)> {
     let mut out = Vec::with_capacity(keys);
     for k in 0..keys {
-        let name: &'static str = Box::leak(format!("key_{k}").into_boxed_str());
+        let name = format!("key_{k}");
         let mut v = Vec::with_capacity(per_key);
         for i in 0..per_key {
             let d: u128 = (i as u128 % 1000) + 1;
@@ -99,13 +99,76 @@ fn bench_timer_drop_throughput(c: &mut Criterion) {
     group.finish();
 }
 
+// Registration churn: a background thread keeps adding new metric names
+// while the measured thread records into an existing one. With a
+// copy-on-write metric map the hot path never waits on the publish lock, so
+// the p99 variant should stay flat as churn increases.
+#[cfg(all(feature = "perf-tests", feature = "metrics"))]
+fn bench_watch_record_under_churn(c: &mut Criterion) {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let mut group = c.benchmark_group("watch/record_under_churn");
+    for &churn in &[false, true] {
+        let w = Watch::new();
+        w.record("hot", 1);
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = churn.then(|| {
+            let (w, stop) = (w.clone(), Arc::clone(&stop));
+            std::thread::spawn(move || {
+                let mut i = 0u64;
+                while !stop.load(Ordering::Relaxed) {
+                    w.record(&format!("churn{}", i % 512), 1);
+                    if i % 512 == 511 {
+                        w.clear();
+                        w.record("hot", 1);
+                    }
+                    i += 1;
+                }
+            })
+        });
+
+        group.bench_function(format!("mean/churn={}", churn), |b| {
+            b.iter_custom(|iters| {
+                let start = Instant::now();
+                for _ in 0..iters {
+                    w.record("hot", 123);
+                }
+                start.elapsed()
+            })
+        });
+        // Reports the 99th percentile of individual calls, scaled per iteration.
+        group.bench_function(format!("p99/churn={}", churn), |b| {
+            b.iter_custom(|iters| {
+                let mut lat: Vec<u64> = Vec::with_capacity(iters as usize);
+                for _ in 0..iters {
+                    let t = Instant::now();
+                    w.record("hot", 123);
+                    lat.push(t.elapsed().as_nanos() as u64);
+                }
+                lat.sort_unstable();
+                let p99 = lat[(lat.len() * 99 / 100).min(lat.len() - 1)];
+                Duration::from_nanos(p99 * iters)
+            })
+        });
+
+        stop.store(true, Ordering::Relaxed);
+        if let Some(h) = handle {
+            let _ = h.join();
+        }
+    }
+    group.finish();
+}
+
 #[cfg(all(feature = "perf-tests", feature = "metrics"))]
 criterion_group!(
     watch_timer_hot,
     bench_watch_record_hot,
     bench_watch_record_instant,
     bench_watch_snapshot_scaling,
-    bench_timer_drop_throughput
+    bench_timer_drop_throughput,
+    bench_watch_record_under_churn
 );
 #[cfg(all(feature = "perf-tests", feature = "metrics"))]
 criterion_main!(watch_timer_hot);
@@ -0,0 +1,304 @@
+#![cfg(all(feature = "std", feature = "metrics"))]
+//! Cross-process aggregation of `Watch` histograms.
+//!
+//! Prefork servers and multi-process test harnesses keep one `Watch` per
+//! process. Each process writes its histograms to a file with
+//! [`Watch::dump`](crate::Watch::dump), typically [`process_file`] in a
+//! directory shared by all workers; [`from_files`] or [`from_dir`] then
+//! merges the files into one [`FrozenSnapshot`], ready for any exporter.
+//!
+//! The format is a compact binary encoding: a `BMHS` magic and version byte,
+//! then per metric its name and bucket counts, with every integer stored as
+//! an unsigned LEB128 varint. Merging assumes every process used the same
+//! histogram backend and precision (see [`HistogramSnapshot::merge`]).
+//!
+//! # Examples
+//! ```
+//! use benchmark::{aggregate, Watch};
+//!
+//! let dir = std::env::temp_dir().join(format!("bm-agg-doc-{}", std::process::id()));
+//! std::fs::create_dir_all(&dir)?;
+//! // In each worker process:
+//! let w = Watch::new();
+//! w.record("req", 1_000);
+//! w.dump(aggregate::process_file(&dir))?;
+//!
+//! // In the parent or a separate tool:
+//! let merged = aggregate::from_dir(&dir)?;
+//! assert_eq!(merged.get("req").unwrap().count, 1);
+//! # std::fs::remove_dir_all(&dir)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::histogram::{Bin, HistogramSnapshot};
+use crate::watch::{now_epoch_nanos, WatchStats};
+use crate::{FrozenSnapshot, Watch};
+
+const MAGIC: &[u8; 4] = b"BMHS";
+const VERSION: u8 = 1;
+/// File extension used by [`process_file`] and picked up by [`from_dir`].
+pub const EXTENSION: &str = "bmhs";
+
+/// The well-known dump file of the current process in `dir`:
+/// `<dir>/benchmark-<pid>.bmhs`.
+pub fn process_file(dir: impl AsRef<Path>) -> PathBuf {
+    dir.as_ref()
+        .join(format!("benchmark-{}.{EXTENSION}", std::process::id()))
+}
+
+/// Encode named histograms in the dump format.
+pub fn encode<'a>(
+    histograms: impl IntoIterator<Item = (&'a str, &'a HistogramSnapshot)>,
+) -> Vec<u8> {
+    let histograms: Vec<_> = histograms.into_iter().collect();
+    let mut out = Vec::with_capacity(16 + histograms.len() * 64);
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    put(&mut out, histograms.len() as u64);
+    for (name, h) in histograms {
+        put(&mut out, name.len() as u64);
+        out.extend_from_slice(name.as_bytes());
+        put(&mut out, h.sum());
+        // Extremes are stored +1 so that 0 means "none".
+        put(&mut out, h.min().map_or(0, |v| v.saturating_add(1)));
+        put(&mut out, h.max().map_or(0, |v| v.saturating_add(1)));
+        let buckets: Vec<(u64, u64, u64)> = h.buckets().collect();
+        put(&mut out, buckets.len() as u64);
+        for (low, high, count) in buckets {
+            put(&mut out, low);
+            put(&mut out, high - low);
+            put(&mut out, count);
+        }
+    }
+    out
+}
+
+/// Decode a dump into `(name, histogram)` pairs.
+///
+/// # Errors
+/// Returns [`io::ErrorKind::InvalidData`] if `bytes` is not a valid dump.
+pub fn decode(bytes: &[u8]) -> io::Result<Vec<(String, HistogramSnapshot)>> {
+    let rest = bytes
+        .strip_prefix(MAGIC.as_slice())
+        .ok_or_else(|| invalid("not a benchmark histogram dump"))?;
+    let (&version, mut rest) = rest.split_first().ok_or_else(|| invalid("truncated"))?;
+    if version != VERSION {
+        return Err(invalid("unsupported dump version"));
+    }
+    let r = &mut rest;
+    let metrics = take(r)?;
+    let mut out = Vec::new();
+    for _ in 0..metrics {
+        let len = usize::try_from(take(r)?).map_err(|_| invalid("name too long"))?;
+        if r.len() < len {
+            return Err(invalid("truncated"));
+        }
+        let (name, tail) = r.split_at(len);
+        *r = tail;
+        let name = std::str::from_utf8(name).map_err(|_| invalid("metric name is not UTF-8"))?;
+        let sum = take(r)?;
+        let min = take(r)?.checked_sub(1);
+        let max = take(r)?.checked_sub(1);
+        let buckets = take(r)?;
+        let mut bins = Vec::new();
+        for _ in 0..buckets {
+            let low = take(r)?;
+            let high = low
+                .checked_add(take(r)?)
+                .ok_or_else(|| invalid("bucket out of range"))?;
+            let count = take(r)?;
+            bins.push(Bin {
+                low,
+                high,
+                count,
+                sum: None,
+            });
+        }
+        out.push((
+            name.to_string(),
+            HistogramSnapshot::from_bins(bins, sum, min, max),
+        ));
+    }
+    if !r.is_empty() {
+        return Err(invalid("trailing bytes"));
+    }
+    Ok(out)
+}
+
+/// Read and merge the dumps at `paths` into one snapshot, combining
+/// metrics of the same name.
+///
+/// # Errors
+/// Returns the first error reading or decoding a file.
+pub fn from_files<I, P>(paths: I) -> io::Result<FrozenSnapshot>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut merged: HashMap<String, HistogramSnapshot> = HashMap::new();
+    for path in paths {
+        for (name, h) in decode(&fs::read(path)?)? {
+            merged
+                .entry(name)
+                .and_modify(|acc| *acc = acc.merge(&h))
+                .or_insert(h);
+        }
+    }
+    let stats: HashMap<String, WatchStats> = merged
+        .iter()
+        .map(|(name, h)| (name.clone(), WatchStats::from_snapshot(h)))
+        .collect();
+    let mut histograms: Vec<(String, HistogramSnapshot)> = merged.into_iter().collect();
+    histograms.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(FrozenSnapshot::new(
+        now_epoch_nanos(),
+        stats.into(),
+        histograms,
+    ))
+}
+
+/// Merge every `*.bmhs` dump in `dir` (see [`from_files`]).
+///
+/// # Errors
+/// Returns any error listing `dir` or reading or decoding a dump.
+pub fn from_dir(dir: impl AsRef<Path>) -> io::Result<FrozenSnapshot> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == EXTENSION) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    from_files(paths)
+}
+
+impl Watch {
+    /// Write every metric's histogram to `path` in the [`aggregate`](crate::aggregate)
+    /// dump format, replacing the file.
+    ///
+    /// The file is written next to `path` and renamed into place, so readers
+    /// never see a partial dump. Rates, timestamps and other Watch-level
+    /// state are not included.
+    ///
+    /// # Errors
+    /// Returns any error writing or renaming the file.
+    pub fn dump(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let histograms = self.histogram_snapshots();
+        let bytes = encode(histograms.iter().map(|(n, h)| (n.as_str(), h)));
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, path)
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("histogram dump: {msg}"))
+}
+
+/// Append `v` as an unsigned LEB128 varint.
+fn put(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        #[allow(clippy::cast_possible_truncation)]
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    out.push(v as u8);
+}
+
+/// Read an unsigned LEB128 varint from the front of `input`.
+fn take(input: &mut &[u8]) -> io::Result<u64> {
+    let mut v: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = input.split_first().ok_or_else(|| invalid("truncated"))?;
+        *input = rest;
+        let bits = u64::from(byte & 0x7F);
+        if shift == 63 && bits > 1 {
+            return Err(invalid("varint overflow"));
+        }
+        v |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(v);
+        }
+    }
+    Err(invalid("varint overflow"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint_roundtrip() {
+        for v in [0, 1, 127, 128, 300, u64::from(u32::MAX), u64::MAX] {
+            let mut buf = Vec::new();
+            put(&mut buf, v);
+            let mut r = buf.as_slice();
+            assert_eq!(take(&mut r).unwrap(), v);
+            assert!(r.is_empty());
+        }
+        assert!(take(&mut [0xFF; 11].as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_dump_and_merge_processes() {
+        let dir = std::env::temp_dir().join(format!("benchmark-agg-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let a = Watch::new();
+        let b = Watch::new();
+        for v in [100, 200] {
+            a.record("req", v);
+        }
+        b.record("req", 5_000);
+        b.record("db", 40);
+        a.dump(dir.join("a.bmhs")).unwrap();
+        b.dump(dir.join("b.bmhs")).unwrap();
+        fs::write(dir.join("ignored.txt"), b"x").unwrap();
+
+        let merged = from_dir(&dir).unwrap();
+        assert_eq!(merged.stats().names().collect::<Vec<_>>(), ["db", "req"]);
+        let req = merged.get("req").unwrap();
+        assert_eq!(req.count, 3);
+        assert_eq!(req.total_ns, 5_300);
+        assert_eq!(merged.histogram("db").unwrap().count(), 1);
+        assert_eq!(
+            merged
+                .histogram("req")
+                .unwrap()
+                .buckets()
+                .map(|b| b.2)
+                .sum::<u64>(),
+            3
+        );
+
+        assert!(from_files([dir.join("ignored.txt")]).is_err());
+        assert!(from_files([dir.join("missing.bmhs")]).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_decode_rejects_corrupt_input() {
+        let h = crate::histogram::Histogram::new();
+        h.record(42);
+        let snap = h.snapshot();
+        let bytes = encode([("x", &snap)]);
+        assert_eq!(decode(&bytes).unwrap(), [("x".to_string(), snap)]);
+        for cut in 0..bytes.len() {
+            assert!(decode(&bytes[..cut]).is_err(), "accepted {cut} bytes");
+        }
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert!(decode(&extra).is_err());
+        let mut wrong = bytes;
+        wrong[4] = 9;
+        assert!(decode(&wrong).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+#![cfg(feature = "alloc-count")]
+//! Allocation counting for microbenchmarks.
+//!
+//! [`CountingAllocator`] wraps the system allocator and counts, per thread,
+//! every allocation, deallocation and allocated byte. Install it as the
+//! global allocator of a benchmark or test binary, then use
+//! [`measure_alloc`] to report allocations next to time:
+//!
+//! ```
+//! use benchmark::alloc::{measure_alloc, CountingAllocator};
+//!
+//! #[global_allocator]
+//! static ALLOC: CountingAllocator = CountingAllocator;
+//!
+//! fn main() {
+//!     let (v, _elapsed, stats) = measure_alloc(|| vec![0u8; 1024]);
+//!     assert_eq!(stats.allocs, 1);
+//!     assert!(stats.bytes >= 1024);
+//!     drop(v);
+//! }
+//! ```
+//!
+//! Counts are kept per thread, so work on other threads does not leak into
+//! a measurement, and allocations made by threads `f` spawns are not
+//! counted. A `realloc` counts as one allocation of the new size and one
+//! deallocation. Without the allocator installed every count reads zero;
+//! [`CountingAllocator::is_installed`] tells the two apart.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::Duration;
+
+/// Set by the first counted allocation.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static ALLOCS: Cell<u64> = const { Cell::new(0) };
+    static DEALLOCS: Cell<u64> = const { Cell::new(0) };
+    static BYTES: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Add `n` to a thread-local counter; a no-op while the thread is being torn
+/// down.
+#[inline]
+fn bump(counter: &'static std::thread::LocalKey<Cell<u64>>, n: u64) {
+    let _ = counter.try_with(|c| c.set(c.get().wrapping_add(n)));
+}
+
+/// Allocation counts of one measured region, from [`measure_alloc`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AllocStats {
+    /// Number of allocations (including reallocations).
+    pub allocs: u64,
+    /// Number of deallocations (including reallocations).
+    pub deallocs: u64,
+    /// Bytes requested by those allocations.
+    pub bytes: u64,
+}
+
+impl AllocStats {
+    /// Counts made by the current thread so far.
+    pub fn current() -> Self {
+        let read =
+            |c: &'static std::thread::LocalKey<Cell<u64>>| c.try_with(Cell::get).unwrap_or(0);
+        Self {
+            allocs: read(&ALLOCS),
+            deallocs: read(&DEALLOCS),
+            bytes: read(&BYTES),
+        }
+    }
+
+    /// Counts made between `earlier` and `self`.
+    #[must_use]
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            allocs: self.allocs.wrapping_sub(earlier.allocs),
+            deallocs: self.deallocs.wrapping_sub(earlier.deallocs),
+            bytes: self.bytes.wrapping_sub(earlier.bytes),
+        }
+    }
+}
+
+/// A global allocator that forwards to [`System`] and counts allocations per
+/// thread (see the [module docs](self)).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    /// Whether a `CountingAllocator` has served any allocation, i.e. is
+    /// installed as the global allocator.
+    pub fn is_installed() -> bool {
+        INSTALLED.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    fn count_alloc(size: usize) {
+        // Check first so allocations do not all write one shared cache line.
+        if !INSTALLED.load(Ordering::Relaxed) {
+            INSTALLED.store(true, Ordering::Relaxed);
+        }
+        bump(&ALLOCS, 1);
+        bump(&BYTES, size as u64);
+    }
+}
+
+// SAFETY: every method forwards to `System` with the caller's arguments
+// unchanged; the counting only touches `Cell`s in const-initialized
+// thread-locals, which never allocate.
+#[allow(unsafe_code)]
+unsafe impl GlobalAlloc for CountingAllocator {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::count_alloc(layout.size());
+        }
+        ptr
+    }
+
+    #[inline]
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::count_alloc(layout.size());
+        }
+        ptr
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        bump(&DEALLOCS, 1);
+    }
+
+    #[inline]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            Self::count_alloc(new_size);
+            bump(&DEALLOCS, 1);
+        }
+        new
+    }
+}
+
+/// Run `f`, returning its result, elapsed time and the allocations the
+/// current thread made meanwhile.
+///
+/// Counts are zero unless [`CountingAllocator`] is the global allocator.
+pub fn measure_alloc<T, F: FnOnce() -> T>(f: F) -> (T, Duration, AllocStats) {
+    let before = AllocStats::current();
+    let start = crate::clock::__now_ns();
+    let out = f();
+    let elapsed = crate::clock::__elapsed_since(start);
+    let stats = AllocStats::current().since(&before);
+    (out, elapsed, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOC: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_counts_allocations_of_the_closure() {
+        let (v, _, stats) = measure_alloc(|| {
+            let mut v: Vec<u64> = Vec::with_capacity(4);
+            v.extend([1, 2, 3, 4]);
+            let s = String::from("hello");
+            drop(s);
+            v
+        });
+        assert!(CountingAllocator::is_installed());
+        assert_eq!(v.len(), 4);
+        assert_eq!(stats.allocs, 2);
+        assert_eq!(stats.deallocs, 1);
+        assert_eq!(stats.bytes, 32 + 5);
+    }
+
+    #[test]
+    fn test_other_threads_are_not_counted() {
+        let (_, _, stats) = measure_alloc(|| {
+            std::thread::scope(|s| s.spawn(|| vec![0u8; 4096]).join().unwrap().len())
+        });
+        assert!(stats.bytes < 4096, "{stats:?}");
+    }
+}
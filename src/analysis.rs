@@ -0,0 +1,290 @@
+#![cfg(feature = "std")]
+//! Statistical comparison of benchmark samples.
+//!
+//! Means of two noisy runs differ even when nothing changed. These helpers
+//! quantify that uncertainty so a CI gate can say "B is faster than A with
+//! p < 0.05" instead of comparing raw means:
+//!
+//! - [`bootstrap_ci`] estimates a confidence interval for the mean by
+//!   resampling (no normality assumption, reproducible via a fixed seed).
+//! - [`mann_whitney`] runs the two-sided Mann-Whitney U test, a rank-based
+//!   test that is robust to the long right tails typical of latency data.
+
+use crate::Duration;
+
+/// Bootstrap resamples drawn by [`bootstrap_ci`].
+pub const DEFAULT_RESAMPLES: usize = 10_000;
+/// Seed used by [`bootstrap_ci`], so repeated calls agree.
+const DEFAULT_SEED: u64 = 0x5EED_2549;
+
+/// Nearest-rank `pct`th percentile of non-empty, ascending `sorted`: the
+/// value at rank `ceil(n * pct / 100)`. The one rule behind `Stats` and
+/// `BenchResult` medians, so both report the same value for the same samples.
+#[cfg(any(feature = "collector", feature = "benchmark"))]
+pub(crate) fn nearest_rank<T: Copy>(sorted: &[T], pct: usize) -> T {
+    let rank = ((sorted.len() * pct + 99) / 100).max(1);
+    sorted[rank - 1]
+}
+
+/// A confidence interval for the mean of a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfidenceInterval {
+    /// Sample mean (point estimate).
+    pub estimate: Duration,
+    /// Lower bound.
+    pub lower: Duration,
+    /// Upper bound.
+    pub upper: Duration,
+}
+
+impl ConfidenceInterval {
+    /// True if `d` lies within `[lower, upper]`.
+    pub fn contains(&self, d: Duration) -> bool {
+        self.lower <= d && d <= self.upper
+    }
+
+    /// True if the two intervals share no values.
+    pub fn is_disjoint(&self, other: &ConfidenceInterval) -> bool {
+        self.upper < other.lower || other.upper < self.lower
+    }
+}
+
+/// Percentile-bootstrap confidence interval for the mean of `samples`.
+///
+/// `confidence` is the coverage, e.g. `0.95`; it is clamped to `(0, 1)`.
+/// Uses [`DEFAULT_RESAMPLES`] resamples and a fixed seed. Returns `None` for
+/// an empty sample.
+///
+/// # Examples
+/// ```
+/// use benchmark::analysis::bootstrap_ci;
+/// use benchmark::Duration;
+///
+/// let samples: Vec<Duration> = (90..=110).map(Duration::from_nanos).collect();
+/// let ci = bootstrap_ci(&samples, 0.95).unwrap();
+/// assert_eq!(ci.estimate.as_nanos(), 100);
+/// assert!(ci.lower.as_nanos() < 100 && ci.upper.as_nanos() > 100);
+/// ```
+pub fn bootstrap_ci(samples: &[Duration], confidence: f64) -> Option<ConfidenceInterval> {
+    bootstrap_ci_with(samples, confidence, DEFAULT_RESAMPLES, DEFAULT_SEED)
+}
+
+/// [`bootstrap_ci`] with an explicit resample count (min 1) and PRNG seed.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+pub fn bootstrap_ci_with(
+    samples: &[Duration],
+    confidence: f64,
+    resamples: usize,
+    seed: u64,
+) -> Option<ConfidenceInterval> {
+    if samples.is_empty() {
+        return None;
+    }
+    let n = samples.len();
+    let values: Vec<u128> = samples.iter().map(Duration::as_nanos).collect();
+    let mean = |sum: u128| Duration::from_nanos(sum / n as u128);
+
+    let mut rng = SplitMix64(seed);
+    let mut means: Vec<u128> = (0..resamples.max(1))
+        .map(|_| {
+            (0..n)
+                .map(|_| values[(rng.next() % n as u64) as usize])
+                .sum::<u128>()
+        })
+        .collect();
+    means.sort_unstable();
+
+    let confidence = if confidence.is_finite() {
+        confidence.clamp(f64::EPSILON, 1.0 - f64::EPSILON)
+    } else {
+        0.95
+    };
+    let tail = (1.0 - confidence) / 2.0;
+    let last = means.len() - 1;
+    let at = |q: f64| means[((q * last as f64).round() as usize).min(last)];
+    Some(ConfidenceInterval {
+        estimate: mean(values.iter().sum()),
+        lower: mean(at(tail)),
+        upper: mean(at(1.0 - tail)),
+    })
+}
+
+/// Result of a two-sided Mann-Whitney U test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MannWhitney {
+    /// U statistic of the first sample.
+    pub u: f64,
+    /// Normal-approximation z score (tie- and continuity-corrected).
+    pub z: f64,
+    /// Two-sided p-value.
+    pub p_value: f64,
+    /// Probability that a random value from `a` is smaller than one from `b`
+    /// (ties count half); above `0.5` means `a` tends to be faster.
+    pub prob_a_less: f64,
+}
+
+impl MannWhitney {
+    /// True if `p_value < alpha`.
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+
+/// Two-sided Mann-Whitney U test of whether `a` and `b` come from the same
+/// distribution.
+///
+/// Uses the normal approximation, which is accurate for roughly 20 or more
+/// samples per side. Returns `None` if either sample is empty.
+///
+/// # Examples
+/// ```
+/// use benchmark::analysis::mann_whitney;
+/// use benchmark::Duration;
+///
+/// let a: Vec<Duration> = (100..130).map(Duration::from_nanos).collect();
+/// let b: Vec<Duration> = (120..150).map(Duration::from_nanos).collect();
+/// let t = mann_whitney(&a, &b).unwrap();
+/// assert!(t.is_significant(0.05)); // a is faster than b with p < 0.05
+/// assert!(t.prob_a_less > 0.5);
+/// ```
+#[allow(clippy::cast_precision_loss)]
+pub fn mann_whitney(a: &[Duration], b: &[Duration]) -> Option<MannWhitney> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let mut all: Vec<(u128, bool)> = a
+        .iter()
+        .map(|d| (d.as_nanos(), true))
+        .chain(b.iter().map(|d| (d.as_nanos(), false)))
+        .collect();
+    all.sort_unstable_by_key(|&(v, _)| v);
+
+    // Average ranks over ties; accumulate the tie correction Σ(t³ - t).
+    let mut rank_sum_a = 0.0;
+    let mut tie_correction = 0.0;
+    let mut start = 0;
+    while start < all.len() {
+        let end = start
+            + all[start..]
+                .iter()
+                .take_while(|v| v.0 == all[start].0)
+                .count();
+        let run = (end - start) as f64;
+        let rank = (start + end + 1) as f64 / 2.0; // mean of 1-based ranks start+1..=end
+        rank_sum_a += rank * all[start..end].iter().filter(|(_, in_a)| *in_a).count() as f64;
+        tie_correction += run * run * run - run;
+        start = end;
+    }
+
+    let u = rank_sum_a - n1 * (n1 + 1.0) / 2.0;
+    let total = n1 + n2;
+    let mean_u = n1 * n2 / 2.0;
+    let var_u =
+        n1 * n2 / 12.0 * ((total + 1.0) - tie_correction / (total * (total - 1.0)).max(1.0));
+    let (z, p_value) = if var_u > 0.0 {
+        let diff = u - mean_u;
+        let z = (diff - 0.5 * diff.signum()) / var_u.sqrt();
+        (z, erfc(z.abs() / std::f64::consts::SQRT_2).min(1.0))
+    } else {
+        (0.0, 1.0)
+    };
+    Some(MannWhitney {
+        u,
+        z,
+        p_value,
+        // U counts pairs where a > b; flip to the "a is smaller" view.
+        prob_a_less: 1.0 - u / (n1 * n2),
+    })
+}
+
+/// Complementary error function (Numerical Recipes `erfcc`, |error| < 1.2e-7).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let r = t * poly.exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+/// `SplitMix64` PRNG: tiny, fast, and good enough for shuffling and resampling.
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ns(v: &[u128]) -> Vec<Duration> {
+        v.iter().copied().map(Duration::from_nanos).collect()
+    }
+
+    #[test]
+    fn test_erfc_reference_values() {
+        assert!((erfc(0.0) - 1.0).abs() < 1e-6);
+        assert!((erfc(1.0) - 0.157_299_2).abs() < 1e-6);
+        assert!((erfc(-1.0) - 1.842_700_8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_bootstrap_ci_is_reproducible_and_narrows() {
+        let small = ns(&[80, 120, 90, 110, 100]);
+        let a = bootstrap_ci(&small, 0.95).unwrap();
+        assert_eq!(a, bootstrap_ci(&small, 0.95).unwrap());
+        assert!(a.contains(a.estimate));
+
+        let wide = bootstrap_ci(&small, 0.99).unwrap();
+        assert!(wide.lower <= a.lower && wide.upper >= a.upper);
+
+        let flat = bootstrap_ci(&ns(&[7; 10]), 0.95).unwrap();
+        assert_eq!((flat.lower.as_nanos(), flat.upper.as_nanos()), (7, 7));
+        assert!(bootstrap_ci(&[], 0.95).is_none());
+    }
+
+    #[test]
+    fn test_mann_whitney_matches_textbook_example() {
+        // Fully separated samples of 5: U = 0, continuity-corrected z = -12 / sqrt(275 / 12).
+        let a = ns(&[1, 2, 3, 4, 5]);
+        let b = ns(&[6, 7, 8, 9, 10]);
+        let t = mann_whitney(&a, &b).unwrap();
+        assert!(t.u.abs() < f64::EPSILON);
+        assert!((t.z + 2.5067).abs() < 1e-3, "z {}", t.z);
+        assert!((t.p_value - 0.0122).abs() < 1e-3, "p {}", t.p_value);
+        assert!((t.prob_a_less - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_mann_whitney_identical_samples() {
+        let a = ns(&[5, 5, 5, 6, 6]);
+        let t = mann_whitney(&a, &a).unwrap();
+        assert!(!t.is_significant(0.05));
+        assert!((t.prob_a_less - 0.5).abs() < f64::EPSILON);
+        let same = mann_whitney(&ns(&[3; 4]), &ns(&[3; 4])).unwrap();
+        assert!((same.p_value - 1.0).abs() < f64::EPSILON);
+        assert!(mann_whitney(&a, &[]).is_none());
+    }
+}
@@ -0,0 +1,169 @@
+#![cfg(all(feature = "std", feature = "metrics"))]
+//! Rolling median/MAD anomaly detection over `Watch` history.
+//!
+//! History entries hold cumulative stats, so the detector first derives the
+//! mean latency of each sampling interval from consecutive `(count, mean)`
+//! pairs. Each interval value is then scored against the median and median
+//! absolute deviation (MAD) of the preceding window:
+//!
+//! `score = |x - median| / (1.4826 * MAD)`
+//!
+//! A point is anomalous when `score > sensitivity` (3.0 is a common choice).
+//! Median/MAD are robust to the very outliers being detected, unlike mean/stddev.
+
+use crate::watch::WatchStats;
+
+/// Number of preceding intervals used as the rolling baseline.
+const WINDOW: usize = 20;
+/// Minimum baseline intervals required before a point can be scored.
+const MIN_BASELINE: usize = 5;
+/// Scale factor making MAD a consistent estimator of the standard deviation.
+const MAD_SCALE: f64 = 1.4826;
+
+/// A flagged latency shift in a metric's history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anomaly {
+    /// Capture timestamp of the anomalous interval (ns since UNIX epoch).
+    pub timestamp: u128,
+    /// Mean latency observed during the interval (ns).
+    pub value: f64,
+    /// Median of the rolling baseline (ns).
+    pub median: f64,
+    /// Median absolute deviation of the rolling baseline (ns).
+    pub mad: f64,
+    /// Robust z-score of `value` against the baseline.
+    pub score: f64,
+}
+
+/// Derive per-interval mean latencies from cumulative history entries.
+///
+/// Intervals without new samples are skipped. A drop in cumulative count
+/// (metric cleared) restarts the series from the current entry.
+pub(crate) fn interval_means(history: &[(u128, WatchStats)]) -> Vec<(u128, f64)> {
+    let mut out = Vec::with_capacity(history.len());
+    let mut prev: Option<&WatchStats> = None;
+    for (ts, cur) in history {
+        #[allow(clippy::cast_precision_loss)]
+        let (dc, dsum) = match prev {
+            Some(p) if cur.count >= p.count => (
+                cur.count - p.count,
+                cur.mean * cur.count as f64 - p.mean * p.count as f64,
+            ),
+            _ => (cur.count, cur.mean * cur.count as f64),
+        };
+        if dc > 0 {
+            #[allow(clippy::cast_precision_loss)]
+            out.push((*ts, (dsum / dc as f64).max(0.0)));
+        }
+        prev = Some(cur);
+    }
+    out
+}
+
+/// Score every point in `series` that has a sufficient baseline.
+pub(crate) fn detect(series: &[(u128, f64)], sensitivity: f64) -> Vec<Anomaly> {
+    (MIN_BASELINE..series.len())
+        .filter_map(|i| check_point(series, i, sensitivity))
+        .collect()
+}
+
+/// Score only the newest point in `series`.
+pub(crate) fn check_last(series: &[(u128, f64)], sensitivity: f64) -> Option<Anomaly> {
+    let last = series.len().checked_sub(1)?;
+    if last < MIN_BASELINE {
+        return None;
+    }
+    check_point(series, last, sensitivity)
+}
+
+fn check_point(series: &[(u128, f64)], idx: usize, sensitivity: f64) -> Option<Anomaly> {
+    let start = idx.saturating_sub(WINDOW);
+    let mut window: Vec<f64> = series[start..idx].iter().map(|&(_, v)| v).collect();
+    let median = median_in_place(&mut window);
+    let mut deviations: Vec<f64> = window.iter().map(|v| (v - median).abs()).collect();
+    let mad = median_in_place(&mut deviations);
+
+    // A perfectly flat baseline has MAD 0; fall back to a small relative scale
+    // so identical values are not flagged but real shifts still are.
+    let scale = if mad > 0.0 {
+        MAD_SCALE * mad
+    } else {
+        (median.abs() * 1e-3).max(1.0)
+    };
+    let (timestamp, value) = series[idx];
+    let score = (value - median).abs() / scale;
+    (score > sensitivity).then_some(Anomaly {
+        timestamp,
+        value,
+        median,
+        mad,
+        score,
+    })
+}
+
+fn median_in_place(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cumulative(intervals: &[(u64, f64)]) -> Vec<(u128, WatchStats)> {
+        let mut count = 0u64;
+        let mut sum = 0.0;
+        let mut out = Vec::new();
+        for (i, &(n, mean)) in intervals.iter().enumerate() {
+            count += n;
+            #[allow(clippy::cast_precision_loss)]
+            {
+                sum += mean * n as f64;
+            }
+            let mut s = WatchStats::EMPTY;
+            s.count = count;
+            #[allow(clippy::cast_precision_loss)]
+            {
+                s.mean = sum / count as f64;
+            }
+            out.push((i as u128, s));
+        }
+        out
+    }
+
+    #[test]
+    fn test_interval_means_from_cumulative() {
+        let h = cumulative(&[(10, 100.0), (10, 300.0), (0, 0.0), (5, 50.0)]);
+        let m = interval_means(&h);
+        assert_eq!(m.len(), 3);
+        assert!((m[0].1 - 100.0).abs() < 1e-9);
+        assert!((m[1].1 - 300.0).abs() < 1e-9);
+        assert!((m[2].1 - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_detects_latency_shift() {
+        let mut iv: Vec<(u64, f64)> = (0..12).map(|i| (100, 1_000.0 + f64::from(i % 3))).collect();
+        iv.push((100, 5_000.0));
+        let series = interval_means(&cumulative(&iv));
+        let found = detect(&series, 3.0);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].timestamp, 12);
+        assert!(check_last(&series, 3.0).is_some());
+    }
+
+    #[test]
+    fn test_flat_series_is_quiet() {
+        let iv: Vec<(u64, f64)> = (0..30).map(|_| (10, 500.0)).collect();
+        let series = interval_means(&cumulative(&iv));
+        assert!(detect(&series, 3.0).is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "metrics")]
+//! Apdex / SLO counters backing the `apdex*` fields of `WatchStats`.
+//!
+//! A metric with a target `T` (see `MetricConfig::apdex_target`) classifies
+//! every recorded sample as satisfied (`<= T`), tolerating (`<= 4T`) or
+//! frustrated, with one relaxed atomic increment per record.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::WatchStats;
+
+/// Satisfied / tolerating / frustrated counts for one metric.
+pub(crate) struct ApdexCounter {
+    target: u64,
+    satisfied: AtomicU64,
+    tolerating: AtomicU64,
+    frustrated: AtomicU64,
+}
+
+impl ApdexCounter {
+    /// Counter for target `target_ns` (at least 1ns).
+    pub(crate) fn new(target_ns: u64) -> Self {
+        Self {
+            target: target_ns.max(1),
+            satisfied: AtomicU64::new(0),
+            tolerating: AtomicU64::new(0),
+            frustrated: AtomicU64::new(0),
+        }
+    }
+
+    /// Classify one sample.
+    #[inline]
+    pub(crate) fn hit(&self, value_ns: u64) {
+        let zone = if value_ns <= self.target {
+            &self.satisfied
+        } else if value_ns <= self.target.saturating_mul(4) {
+            &self.tolerating
+        } else {
+            &self.frustrated
+        };
+        zone.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Fill the `apdex*` and `under_target_pct` fields of `stats`.
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn fill(&self, stats: &mut WatchStats) {
+        let satisfied = self.satisfied.load(Ordering::Relaxed);
+        let tolerating = self.tolerating.load(Ordering::Relaxed);
+        let frustrated = self.frustrated.load(Ordering::Relaxed);
+        stats.apdex_target_ns = self.target;
+        stats.apdex_satisfied = satisfied;
+        stats.apdex_tolerating = tolerating;
+        stats.apdex_frustrated = frustrated;
+        let total = satisfied + tolerating + frustrated;
+        if total > 0 {
+            let total = total as f64;
+            stats.apdex = (satisfied as f64 + tolerating as f64 / 2.0) / total;
+            stats.under_target_pct = satisfied as f64 / total * 100.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zones_and_score() {
+        let a = ApdexCounter::new(100);
+        for v in [1, 100, 101, 400, 401, u64::MAX] {
+            a.hit(v);
+        }
+        let mut s = WatchStats::EMPTY;
+        a.fill(&mut s);
+        assert_eq!(s.apdex_target_ns, 100);
+        assert_eq!(
+            (s.apdex_satisfied, s.apdex_tolerating, s.apdex_frustrated),
+            (2, 2, 2)
+        );
+        assert!((s.apdex - 0.5).abs() < 1e-12);
+        assert!((s.under_target_pct - 100.0 / 3.0).abs() < 1e-9);
+
+        // A target near u64::MAX must not overflow the tolerating bound.
+        let wide = ApdexCounter::new(u64::MAX / 2);
+        wide.hit(u64::MAX);
+        let mut s = WatchStats::EMPTY;
+        wide.fill(&mut s);
+        assert_eq!(s.apdex_tolerating, 1);
+    }
+}
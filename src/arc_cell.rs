@@ -0,0 +1,194 @@
+#![cfg(feature = "lock-free")]
+//! A lock-free, atomically replaceable `Arc`.
+//!
+//! `Watch` publishes its metric table through this cell with the
+//! `lock-free` feature, so neither refreshing a thread's cached table nor
+//! registering a new metric takes a lock.
+//!
+//! Readers count themselves in `readers` for the few instructions between
+//! loading the pointer and taking their own strong reference. A writer that
+//! replaces the value retires the cell's old reference onto a list; the list
+//! is dropped only when `readers` is seen at zero *after* the list was
+//! detached, at which point no reader can still be about to take a reference
+//! to anything on it.
+
+use core::marker::PhantomData;
+use core::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering::SeqCst};
+use std::sync::Arc;
+
+/// A replaced value waiting for in-flight readers to finish.
+struct Retired<T> {
+    // Only held to be dropped.
+    _value: Arc<T>,
+    next: *mut Retired<T>,
+}
+
+/// An `Arc<T>` that can be loaded and compare-and-swapped without locks.
+pub(crate) struct ArcCell<T> {
+    /// The cell's own reference, from `Arc::into_raw`.
+    ptr: AtomicPtr<T>,
+    /// Readers between loading `ptr` and incrementing its strong count.
+    readers: AtomicUsize,
+    /// Singly linked list of replaced values.
+    retired: AtomicPtr<Retired<T>>,
+    /// Send/Sync like the `Arc<T>`s the cell hands out.
+    _owns: PhantomData<Arc<T>>,
+}
+
+impl<T> ArcCell<T> {
+    pub(crate) fn new(value: Arc<T>) -> Self {
+        Self {
+            ptr: AtomicPtr::new(Arc::into_raw(value).cast_mut()),
+            readers: AtomicUsize::new(0),
+            retired: AtomicPtr::new(ptr::null_mut()),
+            _owns: PhantomData,
+        }
+    }
+
+    /// The current value.
+    #[allow(unsafe_code)]
+    pub(crate) fn load(&self) -> Arc<T> {
+        self.readers.fetch_add(1, SeqCst);
+        let p = self.ptr.load(SeqCst);
+        // SAFETY: `p` came from `Arc::into_raw` and its cell reference is
+        // only released after `readers` is seen at zero, which cannot happen
+        // before the decrement below.
+        let value = unsafe {
+            Arc::increment_strong_count(p);
+            Arc::from_raw(p)
+        };
+        if self.readers.fetch_sub(1, SeqCst) == 1 {
+            self.collect();
+        }
+        value
+    }
+
+    /// Replace the value with `new` if it is still `current` (compared by
+    /// pointer); otherwise hand `new` back.
+    #[allow(unsafe_code)]
+    pub(crate) fn compare_exchange(&self, current: &Arc<T>, new: Arc<T>) -> Result<(), Arc<T>> {
+        let current = Arc::as_ptr(current).cast_mut();
+        let new = Arc::into_raw(new).cast_mut();
+        match self.ptr.compare_exchange(current, new, SeqCst, SeqCst) {
+            Ok(old) => {
+                // SAFETY: the swap moved the cell's reference to `old` to us.
+                self.retire(unsafe { Arc::from_raw(old) });
+                self.collect();
+                Ok(())
+            }
+            // SAFETY: `new` was not stored, so its reference is still ours.
+            Err(_) => Err(unsafe { Arc::from_raw(new) }),
+        }
+    }
+
+    fn retire(&self, value: Arc<T>) {
+        let node = Box::into_raw(Box::new(Retired {
+            _value: value,
+            next: ptr::null_mut(),
+        }));
+        self.push_list(node, node);
+    }
+
+    /// Prepend the chain `head..=tail`, which the caller owns.
+    #[allow(unsafe_code)]
+    fn push_list(&self, head: *mut Retired<T>, tail: *mut Retired<T>) {
+        let mut top = self.retired.load(SeqCst);
+        loop {
+            // SAFETY: the chain is unpublished, so `tail` is ours to write.
+            unsafe { (*tail).next = top };
+            match self.retired.compare_exchange(top, head, SeqCst, SeqCst) {
+                Ok(_) => return,
+                Err(now) => top = now,
+            }
+        }
+    }
+
+    /// Drop the retired values if no reader can still reach them.
+    #[allow(unsafe_code)]
+    fn collect(&self) {
+        if self.retired.load(SeqCst).is_null() {
+            return;
+        }
+        let head = self.retired.swap(ptr::null_mut(), SeqCst);
+        if head.is_null() {
+            return;
+        }
+        if self.readers.load(SeqCst) == 0 {
+            // SAFETY: every node was replaced before the detach above, and
+            // every reader that could have loaded one has since finished.
+            unsafe { free_list(head) };
+            return;
+        }
+        let mut tail = head;
+        // SAFETY: the detached chain is owned by this call until re-pushed.
+        unsafe {
+            while !(*tail).next.is_null() {
+                tail = (*tail).next;
+            }
+        }
+        self.push_list(head, tail);
+    }
+}
+
+/// Free a detached chain of retired nodes.
+#[allow(unsafe_code)]
+unsafe fn free_list<T>(mut node: *mut Retired<T>) {
+    while !node.is_null() {
+        // SAFETY: the caller owns the chain; each node came from `Box::into_raw`.
+        let boxed = unsafe { Box::from_raw(node) };
+        node = boxed.next;
+    }
+}
+
+impl<T> Drop for ArcCell<T> {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        // SAFETY: `&mut self` rules out readers; the pointer and the chain
+        // hold references owned by the cell.
+        unsafe {
+            drop(Arc::from_raw(*self.ptr.get_mut()));
+            free_list(*self.retired.get_mut());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_and_reclaim() {
+        let first = Arc::new(1);
+        let cell = ArcCell::new(Arc::clone(&first));
+        let seen = cell.load();
+        assert_eq!(*seen, 1);
+        assert!(cell.compare_exchange(&first, Arc::new(2)).is_ok());
+        assert_eq!(*cell.compare_exchange(&first, Arc::new(3)).unwrap_err(), 3);
+        assert_eq!(*cell.load(), 2);
+        // Only the test's handles keep the first value alive once collected.
+        assert_eq!(Arc::strong_count(&first), 2);
+        drop(seen);
+        assert_eq!(Arc::strong_count(&first), 1);
+    }
+
+    #[test]
+    fn test_concurrent_increments_are_not_lost() {
+        let cell = ArcCell::new(Arc::new(0u64));
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..1_000 {
+                        loop {
+                            let cur = cell.load();
+                            if cell.compare_exchange(&cur, Arc::new(*cur + 1)).is_ok() {
+                                break;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(*cell.load(), 4_000);
+    }
+}
@@ -0,0 +1,604 @@
+#![cfg(feature = "std")]
+//! Clock sources used to timestamp measurements.
+//!
+//! Everything in the crate times with `std::time::Instant` by default
+//! ([`SystemClock`]). The [`Clock`] trait lets callers swap in another source:
+//! [`ManualClock`] only moves when told to, for deterministic tests; with the
+//! `tsc` feature, [`TscClock`] reads the CPU cycle counter (`rdtsc`
+//! on `x86_64`, `cntvct_el0` on `aarch64`), which is several times cheaper
+//! than `Instant::now()` and matters when the measured region is only tens of
+//! nanoseconds.
+//!
+//! `Instant::now()` panics on `wasm32-unknown-unknown`. With the `wasm`
+//! feature, the process-wide clock behind [`SystemClock`], `measure` and the
+//! timing macros reads a callback installed with `set_now_fn`, falling back
+//! to `performance.now()` (via `js-sys`) in the browser and to `Instant`
+//! everywhere else, WASI included.
+//!
+//! Wall-clock timestamps (`Measurement::timestamp`, collector samples) are
+//! anchored: the wall time is read once, paired with a reading of the
+//! monotonic clock above, and later timestamps are that anchor plus the
+//! monotonic offset. An NTP step or a manual clock change therefore cannot
+//! reorder them. [`raw_unix_now_ns`] reads the wall clock directly.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use crate::Duration;
+
+/// A monotonic time source with nanosecond units.
+///
+/// Readings are relative to an arbitrary, per-clock origin; only differences
+/// between two readings of the same clock are meaningful.
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// Current reading in nanoseconds since this clock's origin.
+    fn now_ns(&self) -> u64;
+}
+
+/// Default clock backed by `std::time::Instant`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now_ns(&self) -> u64 {
+        system_now_ns()
+    }
+}
+
+/// A clock that only advances when told to.
+///
+/// Clones share the same time, so a test can hand one clone to a `Watch` or
+/// `Timer` and drive it from another.
+///
+/// # Examples
+/// ```
+/// use benchmark::{Clock, ManualClock};
+/// let clock = ManualClock::new();
+/// let handle = clock.clone(); // e.g. passed to `WatchBuilder::clock`
+/// clock.advance(1_500);
+/// assert_eq!(handle.now_ns(), 1_500);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// Create a clock reading 0.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a clock reading `ns`.
+    pub fn starting_at(ns: u64) -> Self {
+        Self {
+            now: Arc::new(AtomicU64::new(ns)),
+        }
+    }
+
+    /// Set the current reading.
+    pub fn set(&self, ns: u64) {
+        self.now.store(ns, Ordering::SeqCst);
+    }
+
+    /// Move the clock forward by `ns` (saturating).
+    pub fn advance(&self, ns: u64) {
+        let _ = self
+            .now
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |v| {
+                Some(v.saturating_add(ns))
+            });
+    }
+}
+
+impl Clock for ManualClock {
+    #[inline]
+    fn now_ns(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+/// User-provided time source installed by [`set_now_fn`].
+#[cfg(feature = "wasm")]
+static NOW_FN: OnceLock<fn() -> u64> = OnceLock::new();
+
+/// Install the process-wide time source, in nanoseconds from any fixed origin.
+///
+/// Every [`SystemClock`] reading, `measure`, `time!` and the other timing
+/// macros go through it afterwards. Only the first call wins; later calls
+/// return `false` and leave the installed function in place. Install it
+/// before the first measurement so all readings share one origin.
+///
+/// # Examples
+/// ```
+/// use benchmark::{set_now_fn, Clock, SystemClock};
+/// fn now() -> u64 {
+///     // e.g. `(performance.now() * 1e6) as u64` from a host binding
+///     7
+/// }
+/// set_now_fn(now);
+/// assert_eq!(SystemClock.now_ns(), 7);
+/// ```
+#[cfg(feature = "wasm")]
+pub fn set_now_fn(now: fn() -> u64) -> bool {
+    NOW_FN.set(now).is_ok()
+}
+
+/// Process-wide reading shared by every `SystemClock`: the `set_now_fn`
+/// callback if installed, else `performance.now()` in the browser, else an
+/// `Instant` origin.
+#[inline]
+pub(crate) fn system_now_ns() -> u64 {
+    #[cfg(feature = "wasm")]
+    if let Some(now) = NOW_FN.get() {
+        return now();
+    }
+    #[cfg(all(feature = "wasm", target_arch = "wasm32", target_os = "unknown"))]
+    {
+        performance_now_ns()
+    }
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32", target_os = "unknown")))]
+    {
+        static ORIGIN: OnceLock<std::time::Instant> = OnceLock::new();
+        let ns = ORIGIN
+            .get_or_init(std::time::Instant::now)
+            .elapsed()
+            .as_nanos();
+        u64::try_from(ns).unwrap_or(u64::MAX)
+    }
+}
+
+/// Browser clock: `performance.now()` when the host has it, else `Date.now()`.
+///
+/// Both report fractional milliseconds; the latter only has millisecond
+/// resolution and is not monotonic, so it is a last resort.
+#[cfg(all(feature = "wasm", target_arch = "wasm32", target_os = "unknown"))]
+fn performance_now_ns() -> u64 {
+    use js_sys::wasm_bindgen::{JsCast, JsValue};
+    use js_sys::{Function, Reflect};
+
+    thread_local! {
+        static PERFORMANCE: Option<(JsValue, Function)> = {
+            let perf = Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+                .ok()
+                .filter(|p| !p.is_undefined());
+            perf.and_then(|p| {
+                let now = Reflect::get(&p, &JsValue::from_str("now")).ok()?;
+                Some((p, now.dyn_into::<Function>().ok()?))
+            })
+        };
+    }
+    let ms = PERFORMANCE
+        .with(|p| {
+            p.as_ref()
+                .and_then(|(perf, now)| now.call0(perf).ok()?.as_f64())
+        })
+        .unwrap_or_else(js_sys::Date::now);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let ns = (ms * 1e6) as u64;
+    ns
+}
+
+/// The wall-clock time captured once per process, paired with the monotonic
+/// reading taken at the same moment.
+///
+/// # Examples
+/// ```
+/// use benchmark::wall_anchor;
+/// let a = wall_anchor();
+/// assert_eq!(a.to_unix_ns(a.monotonic_ns), a.unix_ns);
+/// assert_eq!(a.to_unix_ns(a.monotonic_ns + 5), a.unix_ns + 5);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WallAnchor {
+    /// Wall-clock nanoseconds since the Unix epoch at the anchor.
+    pub unix_ns: u128,
+    /// Process-wide monotonic reading at the anchor (see [`SystemClock`]).
+    pub monotonic_ns: u64,
+}
+
+impl WallAnchor {
+    /// Convert a [`SystemClock`] reading to Unix-epoch nanoseconds.
+    ///
+    /// Readings from before the anchor map to the anchor itself. Returns 0
+    /// when no wall clock was available.
+    #[inline]
+    pub fn to_unix_ns(self, monotonic_ns: u64) -> u128 {
+        if self.unix_ns == 0 {
+            return 0;
+        }
+        self.unix_ns + u128::from(monotonic_ns.saturating_sub(self.monotonic_ns))
+    }
+}
+
+/// The process-wide wall anchor, captured on first use.
+///
+/// # Examples
+/// ```
+/// use benchmark::{raw_unix_now_ns, wall_anchor};
+/// # if raw_unix_now_ns() > 0 {
+/// assert!(wall_anchor().unix_ns <= raw_unix_now_ns() + 1_000_000_000);
+/// # }
+/// ```
+pub fn wall_anchor() -> WallAnchor {
+    static ANCHOR: OnceLock<WallAnchor> = OnceLock::new();
+    *ANCHOR.get_or_init(|| WallAnchor {
+        monotonic_ns: system_now_ns(),
+        unix_ns: raw_unix_now_ns(),
+    })
+}
+
+/// Anchored wall-clock nanoseconds since the Unix epoch.
+///
+/// This is [`wall_anchor`] plus the monotonic time elapsed since. Readings
+/// never go backwards, even when the system clock is stepped; over a long
+/// run they can drift from [`raw_unix_now_ns`] by however much the system
+/// clock was adjusted. Returns 0 where no wall clock is available.
+///
+/// # Examples
+/// ```
+/// use benchmark::unix_now_ns;
+/// let a = unix_now_ns();
+/// let b = unix_now_ns();
+/// assert!(b >= a);
+/// ```
+#[inline]
+pub fn unix_now_ns() -> u128 {
+    wall_anchor().to_unix_ns(system_now_ns())
+}
+
+/// Raw wall-clock nanoseconds since the Unix epoch, read from the system
+/// clock every call; it can jump backwards or forwards.
+///
+/// Returns 0 where no wall clock is available (Miri, or a browser without
+/// the `wasm` feature).
+///
+/// # Examples
+/// ```
+/// use benchmark::raw_unix_now_ns;
+/// # #[cfg(not(miri))]
+/// assert!(raw_unix_now_ns() > 1_600_000_000_000_000_000);
+/// ```
+#[inline]
+pub fn raw_unix_now_ns() -> u128 {
+    #[cfg(miri)]
+    {
+        0
+    }
+    #[cfg(all(not(miri), target_arch = "wasm32", target_os = "unknown"))]
+    {
+        #[cfg(feature = "wasm")]
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let ns = (js_sys::Date::now() * 1e6) as u128;
+        #[cfg(not(feature = "wasm"))]
+        let ns = 0;
+        ns
+    }
+    #[cfg(all(not(miri), not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos())
+    }
+}
+
+/// Anchored wall-clock timestamp for `Measurement::timestamp`.
+#[doc(hidden)]
+#[inline]
+pub fn __unix_now_ns() -> u128 {
+    unix_now_ns()
+}
+
+/// Monotonic reading used by `measure` and the timing macros.
+#[doc(hidden)]
+#[inline]
+pub fn __now_ns() -> u64 {
+    system_now_ns()
+}
+
+/// Nanoseconds elapsed since a [`__now_ns`] reading, as a crate `Duration`.
+#[doc(hidden)]
+#[inline]
+pub fn __elapsed_since(start: u64) -> Duration {
+    Duration::from_nanos(u128::from(system_now_ns().saturating_sub(start)))
+}
+
+/// Measures the execution time of a function using `clock`.
+///
+/// Works with any [`Clock`], including `&dyn Clock`.
+///
+/// # Examples
+/// ```
+/// use benchmark::{measure_with, ManualClock};
+/// let clock = ManualClock::new();
+/// let (v, d) = measure_with(&clock, || {
+///     clock.advance(42);
+///     2 + 2
+/// });
+/// assert_eq!(v, 4);
+/// # #[cfg(feature = "benchmark")]
+/// assert_eq!(d.as_nanos(), 42);
+/// ```
+#[cfg(feature = "benchmark")]
+#[inline]
+pub fn measure_with<C, T, F>(clock: &C, f: F) -> (T, Duration)
+where
+    C: Clock + ?Sized,
+    F: FnOnce() -> T,
+{
+    let start = clock.now_ns();
+    let result = f();
+    let elapsed = clock.now_ns().saturating_sub(start);
+    (result, Duration::from_nanos(u128::from(elapsed)))
+}
+
+/// Measures the execution time of a function using `clock` (disabled version).
+#[cfg(not(feature = "benchmark"))]
+#[inline]
+pub fn measure_with<C, T, F>(_clock: &C, f: F) -> (T, Duration)
+where
+    C: Clock + ?Sized,
+    F: FnOnce() -> T,
+{
+    (f(), Duration::ZERO)
+}
+
+/// Alias of [`measure_with`].
+///
+/// # Examples
+/// ```
+/// use benchmark::{measure_with_clock, SystemClock};
+/// let (v, _d) = measure_with_clock(&SystemClock, || 2 + 2);
+/// assert_eq!(v, 4);
+/// ```
+#[inline]
+pub fn measure_with_clock<C, T, F>(clock: &C, f: F) -> (T, Duration)
+where
+    C: Clock + ?Sized,
+    F: FnOnce() -> T,
+{
+    measure_with(clock, f)
+}
+
+#[cfg(feature = "tsc")]
+pub use self::tsc::TscClock;
+
+#[cfg(feature = "tsc")]
+mod tsc {
+    use super::{system_now_ns, Clock};
+    use std::time::Instant;
+
+    /// Busy-wait used to calibrate cycles against `Instant`.
+    const CALIBRATION_NS: u128 = 5_000_000;
+    /// Fixed-point shift for the ticks-to-ns multiplier.
+    const SHIFT: u32 = 32;
+
+    /// Cycle-counter clock (`rdtsc` / `cntvct_el0`), calibrated against `Instant`.
+    ///
+    /// Assumes an invariant counter (constant rate across frequency changes
+    /// and cores), which holds on modern `x86_64` and all `aarch64` systems. On
+    /// other architectures, or if the counter does not advance, it falls back
+    /// to [`SystemClock`](super::SystemClock) behaviour; see
+    /// [`is_hardware`](Self::is_hardware).
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{measure_with_clock, TscClock};
+    /// let clock = TscClock::new();
+    /// let (_, d) = measure_with_clock(&clock, || (0..100u64).sum::<u64>());
+    /// # let _ = d;
+    /// ```
+    #[derive(Debug, Clone, Copy)]
+    pub struct TscClock {
+        origin: u64,
+        // ns per tick in 32.32 fixed point; 0 means "use the system clock".
+        mult: u64,
+    }
+
+    impl Default for TscClock {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl TscClock {
+        /// Calibrate the counter frequency (busy-waits ~5ms).
+        pub fn new() -> Self {
+            let Some(t0) = ticks() else {
+                return Self { origin: 0, mult: 0 };
+            };
+            let start = Instant::now();
+            while start.elapsed().as_nanos() < CALIBRATION_NS {
+                std::hint::spin_loop();
+            }
+            let elapsed = start.elapsed().as_nanos();
+            let t1 = ticks().unwrap_or(t0);
+            let delta = u128::from(t1.saturating_sub(t0));
+            let mult = (elapsed << SHIFT)
+                .checked_div(delta)
+                .and_then(|m| u64::try_from(m).ok())
+                .unwrap_or(0);
+            Self { origin: t0, mult }
+        }
+
+        /// True if readings come from the hardware counter rather than the fallback.
+        pub fn is_hardware(&self) -> bool {
+            self.mult != 0
+        }
+
+        /// Calibrated counter frequency in Hz (0 when using the fallback).
+        pub fn frequency_hz(&self) -> u64 {
+            if self.mult == 0 {
+                return 0;
+            }
+            u64::try_from((1_000_000_000u128 << SHIFT) / u128::from(self.mult)).unwrap_or(0)
+        }
+    }
+
+    impl Clock for TscClock {
+        #[inline]
+        fn now_ns(&self) -> u64 {
+            if self.mult == 0 {
+                return system_now_ns();
+            }
+            let t = ticks().unwrap_or(self.origin).wrapping_sub(self.origin);
+            let ns = (u128::from(t) * u128::from(self.mult)) >> SHIFT;
+            u64::try_from(ns).unwrap_or(u64::MAX)
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[inline]
+    #[allow(unsafe_code, clippy::unnecessary_wraps)]
+    fn ticks() -> Option<u64> {
+        // SAFETY: `rdtsc` is available on every x86_64 CPU and has no
+        // memory-safety preconditions.
+        Some(unsafe { core::arch::x86_64::_rdtsc() })
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    #[inline]
+    #[allow(unsafe_code, clippy::unnecessary_wraps)]
+    fn ticks() -> Option<u64> {
+        let v: u64;
+        // SAFETY: reading the virtual counter is permitted at EL0 on all
+        // mainstream aarch64 OSes and only writes the output register.
+        unsafe {
+            core::arch::asm!("mrs {}, cntvct_el0", out(reg) v, options(nomem, nostack));
+        }
+        Some(v)
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    #[inline]
+    fn ticks() -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "tsc")]
+    use std::time::Instant;
+
+    #[test]
+    fn test_system_clock_monotonic() {
+        let a = SystemClock.now_ns();
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        let b = SystemClock.now_ns();
+        assert!(b >= a + 500_000, "{a} -> {b}");
+    }
+
+    #[test]
+    fn test_elapsed_since_uses_system_clock() {
+        let start = __now_ns();
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        assert!(__elapsed_since(start).as_nanos() >= 500_000);
+        // A reading from the future saturates instead of wrapping.
+        assert_eq!(__elapsed_since(u64::MAX).as_nanos(), 0);
+        #[cfg(not(miri))]
+        assert!(__unix_now_ns() > 1_600_000_000_000_000_000);
+    }
+
+    #[cfg(not(miri))]
+    #[test]
+    fn test_anchored_timestamps_follow_monotonic_clock() {
+        let anchor = wall_anchor();
+        assert_eq!(wall_anchor(), anchor);
+        let raw = raw_unix_now_ns();
+        let mut last = unix_now_ns();
+        // Anchored and raw readings agree until the system clock is stepped.
+        assert!(last.abs_diff(raw) < 1_000_000_000, "{last} vs {raw}");
+        for _ in 0..1_000 {
+            let now = unix_now_ns();
+            assert!(now >= last);
+            last = now;
+        }
+        assert_eq!(anchor.to_unix_ns(0), anchor.unix_ns);
+        let none = WallAnchor {
+            unix_ns: 0,
+            monotonic_ns: 0,
+        };
+        assert_eq!(none.to_unix_ns(10), 0);
+    }
+
+    #[cfg(feature = "tsc")]
+    #[test]
+    fn test_tsc_tracks_instant() {
+        let clock = TscClock::new();
+        let start = Instant::now();
+        let a = clock.now_ns();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let b = clock.now_ns();
+        let real = start.elapsed().as_nanos();
+        #[allow(clippy::cast_precision_loss)]
+        let ratio = f64::from(u32::try_from((b - a) / 1_000).unwrap()) / (real as f64 / 1_000.0);
+        assert!((0.5..1.5).contains(&ratio), "ratio {ratio}");
+        if clock.is_hardware() {
+            assert!(clock.frequency_hz() > 1_000_000);
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_watch_timer_uses_builder_clock() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        #[derive(Debug, Default)]
+        struct Step(AtomicU64);
+        impl Clock for Step {
+            fn now_ns(&self) -> u64 {
+                self.0.fetch_add(250, Ordering::Relaxed)
+            }
+        }
+
+        let w = crate::WatchBuilder::new().clock(Step::default()).build();
+        assert_eq!(crate::Timer::new(w.clone(), "op").stop(), 250);
+        assert_eq!(w.snapshot()["op"].max, 250);
+    }
+
+    #[test]
+    fn test_manual_clock_shared_between_clones() {
+        let a = ManualClock::starting_at(10);
+        let b = a.clone();
+        b.advance(5);
+        assert_eq!(a.now_ns(), 15);
+        a.set(u64::MAX - 1);
+        b.advance(10);
+        assert_eq!(a.now_ns(), u64::MAX);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_timer_with_explicit_clock() {
+        let watch_clock = ManualClock::new();
+        let timer_clock = ManualClock::new();
+        let w = crate::WatchBuilder::new()
+            .clock(watch_clock.clone())
+            .build();
+        let t = crate::Timer::new_with_clock(w.clone(), "op", Arc::new(timer_clock.clone()));
+        watch_clock.advance(1_000);
+        timer_clock.advance(70);
+        assert_eq!(t.stop(), 70);
+        assert_eq!(w.snapshot()["op"].max, 70);
+
+        let ((), d) = measure_with(&timer_clock as &dyn Clock, || timer_clock.advance(3));
+        #[cfg(feature = "benchmark")]
+        assert_eq!(d.as_nanos(), 3);
+        let _ = d;
+    }
+
+    #[cfg(feature = "benchmark")]
+    #[test]
+    fn test_measure_with_clock() {
+        let (v, d) = measure_with_clock(&SystemClock, || {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            7
+        });
+        assert_eq!(v, 7);
+        assert!(d.as_nanos() >= 500_000);
+    }
+}
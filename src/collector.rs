@@ -1,7 +1,9 @@
 //! Thread-safe collection of measurements.
 
 use crate::{Duration, Measurement};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::io;
 use std::sync::{Arc, RwLock};
 
 /// Basic statistics for a set of measurements.
@@ -34,6 +36,168 @@ pub struct Stats {
     pub max: Duration,
     /// Mean (average) duration.
     pub mean: Duration,
+    /// Median (50th percentile, nearest rank).
+    pub median: Duration,
+    /// 95th percentile (nearest rank).
+    pub p95: Duration,
+    /// Population standard deviation.
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Aggregate raw durations; `None` if `durations` is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Duration, Stats};
+    /// let ds: Vec<Duration> = [2, 4, 4, 4, 5, 5, 7, 9]
+    ///     .iter()
+    ///     .map(|&n| Duration::from_nanos(n))
+    ///     .collect();
+    /// let s = Stats::from_durations(&ds).unwrap();
+    /// assert_eq!(s.median.as_nanos(), 4);
+    /// assert_eq!(s.p95.as_nanos(), 9);
+    /// assert_eq!(s.stddev.as_nanos(), 2);
+    /// ```
+    pub fn from_durations(durations: &[Duration]) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<u128> = durations.iter().map(Duration::as_nanos).collect();
+        sorted.sort_unstable();
+
+        let count = sorted.len() as u64;
+        let total = sorted.iter().fold(0u128, |acc, &n| acc.saturating_add(n));
+        let mean = total / u128::from(count);
+        #[allow(clippy::cast_precision_loss)]
+        let variance = {
+            let m = total as f64 / count as f64;
+            sorted.iter().map(|&n| (n as f64 - m).powi(2)).sum::<f64>() / count as f64
+        };
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let stddev = variance.sqrt().round() as u128;
+
+        Some(Self {
+            count,
+            total: Duration::from_nanos(total),
+            min: Duration::from_nanos(sorted[0]),
+            max: Duration::from_nanos(sorted[sorted.len() - 1]),
+            mean: Duration::from_nanos(mean),
+            median: Duration::from_nanos(crate::analysis::nearest_rank(&sorted, 50)),
+            p95: Duration::from_nanos(crate::analysis::nearest_rank(&sorted, 95)),
+            stddev: Duration::from_nanos(stddev),
+        })
+    }
+
+    /// Aggregate the durations of `measurements` (e.g. from `benchmark!`);
+    /// `None` if empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{benchmark, Stats};
+    /// let (_, measurements) = benchmark!("sum", 100, { (0..64u64).sum::<u64>() });
+    /// if let Some(s) = Stats::from_measurements(&measurements) {
+    ///     assert_eq!(s.count, 100);
+    ///     assert!(s.min <= s.median && s.median <= s.p95 && s.p95 <= s.max);
+    /// }
+    /// ```
+    pub fn from_measurements(measurements: &[Measurement]) -> Option<Self> {
+        let durations: Vec<Duration> = measurements.iter().map(|m| m.duration).collect();
+        Self::from_durations(&durations)
+    }
+}
+
+/// Running aggregates for one name, maintained on every record.
+///
+/// Returned by [`Collector::stats_streaming`] in O(1), without touching the
+/// raw samples. There is no median or p95 here; those need the samples (see
+/// [`Collector::stats`]).
+///
+/// # Examples
+/// ```
+/// use benchmark::{Collector, Duration};
+/// let c = Collector::new();
+/// for n in [2, 4, 4, 4, 5, 5, 7, 9] {
+///     c.record_duration("op", Duration::from_nanos(n));
+/// }
+/// let s = c.stats_streaming("op").unwrap();
+/// assert_eq!((s.count, s.min.as_nanos(), s.max.as_nanos()), (8, 2, 9));
+/// assert_eq!((s.mean.as_nanos(), s.stddev.as_nanos()), (5, 2));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StreamingStats {
+    /// Number of measurements.
+    pub count: u64,
+    /// Total duration of all measurements.
+    pub total: Duration,
+    /// Minimum duration.
+    pub min: Duration,
+    /// Maximum duration.
+    pub max: Duration,
+    /// Mean (average) duration.
+    pub mean: Duration,
+    /// Population standard deviation.
+    pub stddev: Duration,
+}
+
+/// Samples and running aggregates recorded under one name.
+#[derive(Clone, Debug, Default)]
+struct Series {
+    samples: Vec<Duration>,
+    /// Unix-epoch nanoseconds of each sample, parallel to `samples` when
+    /// the collector keeps timestamps, empty otherwise.
+    timestamps: Vec<u128>,
+    count: u64,
+    total: u128,
+    min: u128,
+    max: u128,
+    /// Welford running mean and sum of squared deviations, in ns.
+    mean: f64,
+    m2: f64,
+}
+
+impl Series {
+    #[allow(clippy::cast_precision_loss)]
+    fn push(&mut self, duration: Duration, keep_sample: bool, timestamp: Option<u128>) {
+        let n = duration.as_nanos();
+        if self.count == 0 {
+            self.min = n;
+            self.max = n;
+        } else {
+            self.min = self.min.min(n);
+            self.max = self.max.max(n);
+        }
+        self.count += 1;
+        self.total = self.total.saturating_add(n);
+        let x = n as f64;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+        if keep_sample {
+            self.samples.push(duration);
+            self.timestamps.extend(timestamp);
+        }
+    }
+
+    fn streaming(&self) -> Option<StreamingStats> {
+        if self.count == 0 {
+            return None;
+        }
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        let stddev = (self.m2 / self.count as f64).sqrt().round() as u128;
+        Some(StreamingStats {
+            count: self.count,
+            total: Duration::from_nanos(self.total),
+            min: Duration::from_nanos(self.min),
+            max: Duration::from_nanos(self.max),
+            mean: Duration::from_nanos(self.total / u128::from(self.count)),
+            stddev: Duration::from_nanos(stddev),
+        })
+    }
 }
 
 /// A thread-safe collector for measurements.
@@ -41,9 +205,20 @@ pub struct Stats {
 /// This collector uses an `Arc<RwLock<HashMap>>` to allow multiple threads
 /// to record measurements concurrently. The collector can be cloned to share
 /// across threads.
+///
+/// Names are `Cow<'static, str>`: string literals are stored without
+/// allocating, and names built at runtime can be passed as a `String`.
+///
+/// Alongside the raw samples, each name keeps running aggregates, so
+/// [`stats_streaming`](Self::stats_streaming) is O(1). Collectors built with
+/// [`without_samples`](Self::without_samples) keep only the aggregates;
+/// ones built [`with_timestamps`](Self::with_timestamps) also keep when each
+/// sample was taken.
 #[derive(Clone, Debug)]
 pub struct Collector {
-    measurements: Arc<RwLock<HashMap<&'static str, Vec<Duration>>>>,
+    measurements: Arc<RwLock<HashMap<Cow<'static, str>, Series>>>,
+    keep_samples: bool,
+    keep_timestamps: bool,
 }
 
 impl Collector {
@@ -59,6 +234,8 @@ impl Collector {
     pub fn new() -> Self {
         Self {
             measurements: Arc::new(RwLock::new(HashMap::new())),
+            keep_samples: true,
+            keep_timestamps: false,
         }
     }
 
@@ -74,9 +251,150 @@ impl Collector {
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             measurements: Arc::new(RwLock::new(HashMap::with_capacity(capacity))),
+            keep_samples: true,
+            keep_timestamps: false,
+        }
+    }
+
+    /// Keep only the running aggregates, not the raw samples.
+    ///
+    /// Memory per name stays constant however much is recorded.
+    /// [`stats_streaming`](Self::stats_streaming) works as usual. The
+    /// sample-based methods ([`stats`](Self::stats),
+    /// [`all_stats`](Self::all_stats), [`drain`](Self::drain) and
+    /// [`take_samples`](Self::take_samples)) see no samples.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Collector, Duration};
+    /// let c = Collector::new().without_samples();
+    /// c.record_duration("op", Duration::from_nanos(10));
+    /// assert_eq!(c.stats_streaming("op").unwrap().count, 1);
+    /// assert!(c.stats("op").is_none());
+    /// ```
+    #[must_use]
+    pub fn without_samples(mut self) -> Self {
+        self.keep_samples = false;
+        self
+    }
+
+    /// Also keep the Unix-epoch timestamp of every sample, for
+    /// [`export_ndjson`](Self::export_ndjson) and
+    /// [`drain_ndjson`](Self::drain_ndjson) lines.
+    ///
+    /// Off by default: it costs a clock read per
+    /// [`record_duration`](Self::record_duration) and 16 bytes per sample.
+    /// Has no effect on a collector built
+    /// [`without_samples`](Self::without_samples).
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Collector, Duration, Measurement};
+    /// let c = Collector::new().with_timestamps();
+    /// c.record(&Measurement::new("db", Duration::from_nanos(1_200), 42));
+    /// let mut out = Vec::new();
+    /// c.export_ndjson(&mut out).unwrap();
+    /// assert_eq!(out, b"{\"name\":\"db\",\"ns\":1200,\"ts\":42}\n");
+    /// ```
+    #[must_use]
+    pub fn with_timestamps(mut self) -> Self {
+        self.keep_timestamps = true;
+        self
+    }
+
+    /// Reserves room for at least `additional` more samples under `name`.
+    ///
+    /// Creates the entry if needed; until something is recorded it has no
+    /// stats. Does nothing to the samples of a collector built
+    /// [`without_samples`](Self::without_samples).
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Collector, Duration};
+    /// let c = Collector::new();
+    /// c.reserve("db", 1_000);
+    /// assert!(c.stats("db").is_none());
+    /// let bytes = c.memory_usage()[0].1;
+    /// for _ in 0..1_000 {
+    ///     c.record_duration("db", Duration::from_nanos(5));
+    /// }
+    /// assert_eq!(c.memory_usage()[0].1, bytes); // no reallocation
+    /// ```
+    pub fn reserve(&self, name: impl Into<Cow<'static, str>>, additional: usize) {
+        let mut lock = self
+            .measurements
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let series = lock.entry(name.into()).or_default();
+        if self.keep_samples {
+            series.samples.reserve(additional);
+            if self.keep_timestamps {
+                series.timestamps.reserve(additional);
+            }
         }
     }
 
+    /// Releases spare capacity held by the sample buffers and the name map.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Collector, Duration};
+    /// let c = Collector::new();
+    /// c.reserve("io", 4_096);
+    /// c.record_duration("io", Duration::from_nanos(1));
+    /// let before = c.memory_usage()[0].1;
+    /// c.shrink();
+    /// assert!(c.memory_usage()[0].1 < before);
+    /// assert_eq!(c.stats("io").unwrap().count, 1);
+    /// ```
+    pub fn shrink(&self) {
+        let mut lock = self
+            .measurements
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        for series in lock.values_mut() {
+            series.samples.shrink_to_fit();
+            series.timestamps.shrink_to_fit();
+        }
+        lock.shrink_to_fit();
+    }
+
+    /// Approximate bytes held for each name.
+    ///
+    /// Counts the per-name aggregates, the capacity of the sample and
+    /// timestamp buffers, and an owned name's heap allocation. The hash
+    /// map's own overhead is not included.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Collector, Duration};
+    /// let c = Collector::new();
+    /// c.record_duration("a", Duration::from_nanos(1));
+    /// let usage = c.memory_usage();
+    /// assert_eq!(usage[0].0, "a");
+    /// let total: usize = usage.iter().map(|(_, bytes)| bytes).sum();
+    /// assert!(total > 0);
+    /// ```
+    pub fn memory_usage(&self) -> Vec<(String, usize)> {
+        let lock = self
+            .measurements
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        lock.iter()
+            .map(|(name, series)| {
+                let name_bytes = match name {
+                    Cow::Borrowed(_) => 0,
+                    Cow::Owned(owned) => owned.capacity(),
+                };
+                let bytes = std::mem::size_of::<Series>()
+                    + series.samples.capacity() * std::mem::size_of::<Duration>()
+                    + series.timestamps.capacity() * std::mem::size_of::<u128>()
+                    + name_bytes;
+                (name.to_string(), bytes)
+            })
+            .collect()
+    }
+
     /// Records a measurement.
     ///
     /// # Poisoning
@@ -98,16 +416,21 @@ impl Collector {
             .measurements
             .write()
             .unwrap_or_else(std::sync::PoisonError::into_inner);
-        lock.entry(measurement.name)
+        lock.entry(Cow::Borrowed(measurement.name))
             .or_default()
-            .push(measurement.duration);
+            .push(
+                measurement.duration,
+                self.keep_samples,
+                self.keep_timestamps.then_some(measurement.timestamp),
+            );
     }
 
     /// Records a duration directly.
     ///
-    /// # Panics
-    ///
-    /// Panics if the lock is poisoned.
+    /// `name` can be a `&'static str` or an owned `String`. An owned name is
+    /// only kept the first time it is seen. A collector built
+    /// [`with_timestamps`](Self::with_timestamps) stamps the sample with the
+    /// current wall-clock time.
     ///
     /// # Examples
     /// ```
@@ -117,22 +440,51 @@ impl Collector {
     /// let s = c.stats("db_query").unwrap();
     /// assert_eq!(s.count, 1);
     /// assert_eq!(s.total.as_nanos(), 5_000);
+    ///
+    /// // Runtime-generated names need no `Box::leak`.
+    /// for shard in 0..2 {
+    ///     c.record_duration(format!("shard_{shard}"), Duration::from_nanos(10));
+    /// }
+    /// assert_eq!(c.stats("shard_1").unwrap().count, 1);
     /// ```
-    pub fn record_duration(&self, name: &'static str, duration: Duration) {
+    pub fn record_duration(&self, name: impl Into<Cow<'static, str>>, duration: Duration) {
+        let timestamp =
+            (self.keep_samples && self.keep_timestamps).then(crate::clock::__unix_now_ns);
         let mut lock = self
             .measurements
             .write()
             .unwrap_or_else(std::sync::PoisonError::into_inner);
-        lock.entry(name).or_default().push(duration);
+        lock.entry(name.into())
+            .or_default()
+            .push(duration, self.keep_samples, timestamp);
     }
 
-    /// Gets statistics for a named measurement.
-    ///
-    /// Returns `None` if no measurements exist for the given name.
+    /// Runs `f`, records how long it took under `name`, and returns its output.
     ///
-    /// # Panics
+    /// # Examples
+    /// ```
+    /// use benchmark::Collector;
+    /// let c = Collector::new();
+    /// let v = c.record_closure("build", || vec![1, 2, 3]);
+    /// assert_eq!(v.len(), 3);
+    /// assert_eq!(c.stats("build").unwrap().count, 1);
+    /// ```
+    pub fn record_closure<T, F: FnOnce() -> T>(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        f: F,
+    ) -> T {
+        let start = crate::clock::system_now_ns();
+        let out = f();
+        self.record_duration(name, crate::clock::__elapsed_since(start));
+        out
+    }
+
+    /// Gets statistics for a named measurement.
     ///
-    /// Panics if the lock is poisoned.
+    /// Returns `None` if no samples exist for the given name. This copies
+    /// and sorts the samples; use [`stats_streaming`](Self::stats_streaming)
+    /// when median and p95 are not needed.
     ///
     /// # Examples
     /// ```
@@ -151,49 +503,40 @@ impl Collector {
                 .measurements
                 .read()
                 .unwrap_or_else(std::sync::PoisonError::into_inner);
-            lock.get(name)?.clone()
+            lock.get(name)?.samples.clone()
         };
 
-        if durations.is_empty() {
-            return None;
-        }
-
-        // Single pass: compute total, min, max
-        let mut iter = durations.iter().copied();
-        let first = iter.next()?;
-        let mut total: u128 = first.as_nanos();
-        let mut min = first;
-        let mut max = first;
-        for d in iter {
-            let n = d.as_nanos();
-            total = total.saturating_add(n);
-            if d < min {
-                min = d;
-            }
-            if d > max {
-                max = d;
-            }
-        }
+        Stats::from_durations(&durations)
+    }
 
-        let count = durations.len() as u64;
-        let mean = Duration::from_nanos(total / u128::from(count));
-        Some(Stats {
-            count,
-            total: Duration::from_nanos(total),
-            min,
-            max,
-            mean,
-        })
+    /// Gets the running aggregates for a named measurement in O(1).
+    ///
+    /// Returns `None` if nothing was recorded under `name`. Unlike
+    /// [`stats`](Self::stats), no samples are copied, and it also works for
+    /// collectors built [`without_samples`](Self::without_samples).
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Collector, Duration};
+    /// let c = Collector::new();
+    /// assert!(c.stats_streaming("x").is_none());
+    /// c.record_duration("x", Duration::from_nanos(10));
+    /// c.record_duration("x", Duration::from_nanos(30));
+    /// let s = c.stats_streaming("x").unwrap();
+    /// assert_eq!((s.count, s.mean.as_nanos(), s.stddev.as_nanos()), (2, 20, 10));
+    /// ```
+    pub fn stats_streaming(&self, name: &str) -> Option<StreamingStats> {
+        self.measurements
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(name)?
+            .streaming()
     }
 
     /// Gets statistics for all measurements.
     ///
     /// Returns a vector of (name, stats) pairs.
     ///
-    /// # Panics
-    ///
-    /// Panics if the lock is poisoned.
-    ///
     /// # Examples
     /// ```
     /// use benchmark::{Collector, Duration};
@@ -207,59 +550,26 @@ impl Collector {
     /// ```
     pub fn all_stats(&self) -> Vec<(String, Stats)> {
         // Snapshot names and their vectors under a read lock, then compute outside to avoid nested locking
-        let snapshot: Vec<(&'static str, Vec<Duration>)> = {
+        let snapshot: Vec<(Cow<'static, str>, Vec<Duration>)> = {
             let lock = self
                 .measurements
                 .read()
                 .unwrap_or_else(std::sync::PoisonError::into_inner);
-            lock.iter().map(|(&name, v)| (name, v.clone())).collect()
+            lock.iter()
+                .map(|(name, series)| (name.clone(), series.samples.clone()))
+                .collect()
         };
 
-        let mut out = Vec::with_capacity(snapshot.len());
-        for (name, durations) in snapshot {
-            if durations.is_empty() {
-                continue;
-            }
-
-            // Single pass per key
-            let mut iter = durations.iter().copied();
-            if let Some(first) = iter.next() {
-                let mut total: u128 = first.as_nanos();
-                let mut min = first;
-                let mut max = first;
-                for d in iter {
-                    let n = d.as_nanos();
-                    total = total.saturating_add(n);
-                    if d < min {
-                        min = d;
-                    }
-                    if d > max {
-                        max = d;
-                    }
-                }
-                let count = durations.len() as u64;
-                let mean = Duration::from_nanos(total / u128::from(count));
-                out.push((
-                    name.to_string(),
-                    Stats {
-                        count,
-                        total: Duration::from_nanos(total),
-                        min,
-                        max,
-                        mean,
-                    },
-                ));
-            }
-        }
-        out
+        snapshot
+            .into_iter()
+            .filter_map(|(name, durations)| {
+                Stats::from_durations(&durations).map(|s| (name.into_owned(), s))
+            })
+            .collect()
     }
 
     /// Clears all measurements.
     ///
-    /// # Panics
-    ///
-    /// Panics if the lock is poisoned.
-    ///
     /// # Examples
     /// ```
     /// use benchmark::{Collector, Duration};
@@ -279,10 +589,6 @@ impl Collector {
 
     /// Clears measurements for a specific name.
     ///
-    /// # Panics
-    ///
-    /// Panics if the lock is poisoned.
-    ///
     /// # Examples
     /// ```
     /// use benchmark::{Collector, Duration};
@@ -298,6 +604,172 @@ impl Collector {
             .unwrap_or_else(std::sync::PoisonError::into_inner);
         lock.remove(name);
     }
+
+    /// Moves all accumulated samples out of the collector, leaving it empty.
+    ///
+    /// The internal map is swapped under a single write lock, so every
+    /// concurrent `record` lands either in the returned batch or in the
+    /// collector afterwards; no sample is lost or duplicated.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Collector, Duration};
+    /// let c = Collector::new();
+    /// c.record_duration("io", Duration::from_nanos(10));
+    /// c.record_duration("io", Duration::from_nanos(20));
+    /// let batch = c.drain();
+    /// assert_eq!(batch["io"].len(), 2);
+    /// assert!(c.stats("io").is_none());
+    /// ```
+    pub fn drain(&self) -> HashMap<Cow<'static, str>, Vec<Duration>> {
+        let mut lock = self
+            .measurements
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let capacity = lock.len();
+        std::mem::replace(&mut *lock, HashMap::with_capacity(capacity))
+            .into_iter()
+            .map(|(name, series)| (name, series.samples))
+            .collect()
+    }
+
+    /// Moves the samples recorded for `name` out of the collector.
+    ///
+    /// Returns `None` if nothing was recorded under `name`.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Collector, Duration};
+    /// let c = Collector::new();
+    /// c.record_duration("a", Duration::from_nanos(1));
+    /// c.record_duration("b", Duration::from_nanos(2));
+    /// assert_eq!(c.take_samples("a").unwrap().len(), 1);
+    /// assert!(c.take_samples("a").is_none());
+    /// assert!(c.stats("b").is_some());
+    /// ```
+    pub fn take_samples(&self, name: &str) -> Option<Vec<Duration>> {
+        let mut lock = self
+            .measurements
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        lock.remove(name).map(|series| series.samples)
+    }
+
+    /// Writes every sample as newline-delimited JSON, one object per line:
+    /// `{"name":"db","ns":1200}`.
+    ///
+    /// `ns` is the duration in nanoseconds. Collectors built
+    /// [`with_timestamps`](Self::with_timestamps) add `ts`, the sample's
+    /// Unix-epoch timestamp in nanoseconds. Lines are grouped by name, in recording order within a
+    /// name. The samples are copied out under the read lock and written
+    /// after it is released; use [`drain_ndjson`](Self::drain_ndjson) to
+    /// avoid the copy. Returns the number of lines written.
+    ///
+    /// # Errors
+    /// Returns the first error from `out`.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Collector, Duration, Measurement};
+    /// let c = Collector::new();
+    /// c.record(&Measurement::new("db", Duration::from_nanos(1_200), 42));
+    /// let mut out = Vec::new();
+    /// assert_eq!(c.export_ndjson(&mut out).unwrap(), 1);
+    /// assert_eq!(out, b"{\"name\":\"db\",\"ns\":1200}\n");
+    /// assert_eq!(c.stats("db").unwrap().count, 1); // still there
+    /// ```
+    pub fn export_ndjson<W: io::Write>(&self, out: W) -> io::Result<usize> {
+        let snapshot: Vec<(Cow<'static, str>, Series)> = {
+            let lock = self
+                .measurements
+                .read()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            lock.iter()
+                .filter(|(_, series)| !series.samples.is_empty())
+                .map(|(name, series)| (name.clone(), series.clone()))
+                .collect()
+        };
+        write_ndjson(snapshot, out)
+    }
+
+    /// Like [`export_ndjson`](Self::export_ndjson), but moves the samples out
+    /// of the collector first, as [`drain`](Self::drain) does.
+    ///
+    /// Each name's buffers are freed as soon as its lines are written, so
+    /// peak memory does not grow beyond what was already held. Samples
+    /// recorded meanwhile stay in the collector for the next drain. On a
+    /// write error the remaining drained samples are dropped.
+    ///
+    /// # Errors
+    /// Returns the first error from `out`.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Collector, Duration};
+    /// let c = Collector::new();
+    /// c.record_duration("io", Duration::from_nanos(10));
+    /// c.record_duration("io", Duration::from_nanos(20));
+    /// let mut out = Vec::new();
+    /// assert_eq!(c.drain_ndjson(&mut out).unwrap(), 2);
+    /// assert_eq!(String::from_utf8(out).unwrap().lines().count(), 2);
+    /// assert!(c.stats("io").is_none());
+    /// ```
+    pub fn drain_ndjson<W: io::Write>(&self, out: W) -> io::Result<usize> {
+        let drained = {
+            let mut lock = self
+                .measurements
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            let capacity = lock.len();
+            std::mem::replace(&mut *lock, HashMap::with_capacity(capacity))
+        };
+        write_ndjson(drained, out)
+    }
+}
+
+/// Write one NDJSON line per sample, dropping each series once written.
+fn write_ndjson<I, W>(series: I, mut out: W) -> io::Result<usize>
+where
+    I: IntoIterator<Item = (Cow<'static, str>, Series)>,
+    W: io::Write,
+{
+    use std::io::Write as _;
+    let mut out = io::BufWriter::new(&mut out);
+    let mut lines = 0;
+    for (name, series) in series {
+        let mut escaped = String::with_capacity(name.len());
+        push_json_escaped(&mut escaped, &name);
+        let stamped = series.timestamps.len() == series.samples.len();
+        for (i, duration) in series.samples.iter().enumerate() {
+            write!(
+                out,
+                "{{\"name\":\"{escaped}\",\"ns\":{}",
+                duration.as_nanos()
+            )?;
+            if stamped {
+                write!(out, ",\"ts\":{}", series.timestamps[i])?;
+            }
+            writeln!(out, "}}")?;
+            lines += 1;
+        }
+    }
+    out.flush()?;
+    Ok(lines)
+}
+
+/// Append `s` to `out` with JSON string escaping (without the quotes).
+pub(crate) fn push_json_escaped(out: &mut String, s: &str) {
+    use std::fmt::Write as _;
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
 }
 
 impl Default for Collector {
@@ -306,6 +778,46 @@ impl Default for Collector {
     }
 }
 
+/// Pipes measuring results into a [`Collector`].
+///
+/// Implemented for a [`Measurement`] (returned unchanged) and for the
+/// `(output, Measurement)` pairs returned by [`measure_named`](crate::measure_named),
+/// which yield the output, so measuring and collecting chain in one expression.
+///
+/// # Examples
+/// ```
+/// use benchmark::{measure_named, Collector, RecordTo};
+/// let c = Collector::new();
+/// let sum = measure_named("sum", || (1..=4u32).sum::<u32>()).record_to(&c);
+/// assert_eq!(sum, 10);
+/// assert_eq!(c.stats("sum").unwrap().count, 1);
+/// ```
+pub trait RecordTo {
+    /// What remains after recording.
+    type Output;
+
+    /// Record the measurement into `collector`.
+    fn record_to(self, collector: &Collector) -> Self::Output;
+}
+
+impl RecordTo for Measurement {
+    type Output = Measurement;
+
+    fn record_to(self, collector: &Collector) -> Measurement {
+        collector.record(&self);
+        self
+    }
+}
+
+impl<T> RecordTo for (T, Measurement) {
+    type Output = T;
+
+    fn record_to(self, collector: &Collector) -> T {
+        collector.record(&self.1);
+        self.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,6 +838,43 @@ mod tests {
         assert_eq!(stats.mean.as_nanos(), 2000);
     }
 
+    #[test]
+    fn test_stats_percentiles_and_stddev() {
+        let ds: Vec<Duration> = (1..=100).rev().map(Duration::from_nanos).collect();
+        let s = Stats::from_durations(&ds).unwrap();
+        assert_eq!((s.min.as_nanos(), s.max.as_nanos()), (1, 100));
+        assert_eq!(s.median.as_nanos(), 50);
+        assert_eq!(s.p95.as_nanos(), 95);
+        assert_eq!(s.stddev.as_nanos(), 29); // sqrt((100² - 1) / 12) ≈ 28.87
+
+        let one = Stats::from_durations(&[Duration::from_nanos(7)]).unwrap();
+        assert_eq!(
+            (one.median, one.p95, one.stddev),
+            (one.min, one.max, Duration::ZERO)
+        );
+        assert!(Stats::from_durations(&[]).is_none());
+
+        let ms = [
+            Measurement::new("op", Duration::from_nanos(10), 0),
+            Measurement::new("op", Duration::from_nanos(30), 0),
+        ];
+        let s = Stats::from_measurements(&ms).unwrap();
+        assert_eq!((s.mean.as_nanos(), s.stddev.as_nanos()), (20, 10));
+    }
+
+    #[test]
+    fn test_record_closure_and_record_to() {
+        let c = Collector::new();
+        assert_eq!(c.record_closure("f", || 7), 7);
+        let m = Measurement::new("m", Duration::from_nanos(40), 0).record_to(&c);
+        assert_eq!(m.duration.as_nanos(), 40);
+        let out = ("x", Measurement::new("m", Duration::from_nanos(60), 0)).record_to(&c);
+        assert_eq!(out, "x");
+        assert_eq!(c.stats("f").unwrap().count, 1);
+        let s = c.stats("m").unwrap();
+        assert_eq!((s.count, s.total.as_nanos()), (2, 100));
+    }
+
     #[test]
     fn test_collector_multiple_names() {
         let collector = Collector::new();
@@ -341,6 +890,113 @@ mod tests {
         assert_eq!(all.len(), 2);
     }
 
+    #[test]
+    fn test_owned_and_static_names_share_a_key() {
+        let collector = Collector::new();
+        collector.record_duration("req", Duration::from_nanos(1));
+        collector.record_duration(String::from("req"), Duration::from_nanos(2));
+        collector.record(&Measurement::new("req", Duration::from_nanos(3), 0));
+        let id = 7;
+        collector.record_closure(format!("job_{id}"), || ());
+
+        assert_eq!(collector.stats("req").unwrap().count, 3);
+        assert_eq!(collector.stats("job_7").unwrap().count, 1);
+        let drained = collector.drain();
+        assert!(matches!(
+            drained.get_key_value("req"),
+            Some((Cow::Borrowed(_), _))
+        ));
+        assert!(matches!(
+            drained.get_key_value("job_7"),
+            Some((Cow::Owned(_), _))
+        ));
+    }
+
+    #[test]
+    fn test_streaming_stats_match_sample_stats() {
+        let collector = Collector::new();
+        let lean = Collector::new().without_samples();
+        for n in [120, 7, 7, 3_000, 45, 45, 90, 1] {
+            collector.record_duration("op", Duration::from_nanos(n));
+            lean.record_duration("op", Duration::from_nanos(n));
+        }
+
+        let full = collector.stats("op").unwrap();
+        let streaming = collector.stats_streaming("op").unwrap();
+        assert_eq!(
+            (
+                streaming.count,
+                streaming.total,
+                streaming.min,
+                streaming.max
+            ),
+            (full.count, full.total, full.min, full.max)
+        );
+        assert_eq!((streaming.mean, streaming.stddev), (full.mean, full.stddev));
+        assert_eq!(lean.stats_streaming("op"), Some(streaming));
+
+        assert!(lean.stats("op").is_none());
+        assert!(lean.all_stats().is_empty());
+        assert_eq!(lean.take_samples("op"), Some(Vec::new()));
+        assert!(lean.stats_streaming("op").is_none());
+    }
+
+    #[test]
+    fn test_reserve_shrink_and_memory_usage() {
+        let collector = Collector::new();
+        collector.reserve("a", 64);
+        collector.reserve(String::from("bb"), 0);
+        let usage: HashMap<String, usize> = collector.memory_usage().into_iter().collect();
+        let base = std::mem::size_of::<Series>();
+        let per_sample = std::mem::size_of::<Duration>();
+        assert_eq!(usage["a"], base + 64 * per_sample);
+        assert_eq!(usage["bb"], base + 2);
+        assert!(collector.stats_streaming("a").is_none());
+
+        collector.record_duration("a", Duration::from_nanos(1));
+        collector.shrink();
+        let usage: HashMap<String, usize> = collector.memory_usage().into_iter().collect();
+        assert_eq!(usage["a"], base + per_sample);
+
+        let lean = Collector::new().without_samples();
+        lean.reserve("a", 64);
+        assert_eq!(lean.memory_usage(), vec![(String::from("a"), base)]);
+    }
+
+    #[test]
+    fn test_ndjson_export_and_drain() {
+        let collector = Collector::new().with_timestamps();
+        collector.record(&Measurement::new("a\"b\n", Duration::from_nanos(5), 7));
+        collector.record(&Measurement::new("a\"b\n", Duration::from_nanos(6), 8));
+        collector.reserve("empty", 4);
+
+        let mut out = Vec::new();
+        assert_eq!(collector.export_ndjson(&mut out).unwrap(), 2);
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(
+            text,
+            "{\"name\":\"a\\\"b\\u000a\",\"ns\":5,\"ts\":7}\n\
+             {\"name\":\"a\\\"b\\u000a\",\"ns\":6,\"ts\":8}\n"
+        );
+
+        collector.record_duration(String::from("c"), Duration::from_nanos(1));
+        let mut drained = Vec::new();
+        assert_eq!(collector.drain_ndjson(&mut drained).unwrap(), 3);
+        assert_eq!(String::from_utf8(drained).unwrap().lines().count(), 3);
+        let mut empty = Vec::new();
+        assert_eq!(collector.drain_ndjson(&mut empty).unwrap(), 0);
+        assert!(empty.is_empty());
+
+        let plain = Collector::new();
+        plain.record(&Measurement::new("p", Duration::from_nanos(3), 9));
+        plain.reserve("p", 8);
+        let mut out = Vec::new();
+        assert_eq!(plain.export_ndjson(&mut out).unwrap(), 1);
+        assert_eq!(out, b"{\"name\":\"p\",\"ns\":3}\n");
+        let map = plain.measurements.read().unwrap();
+        assert_eq!(map["p"].timestamps.capacity(), 0);
+    }
+
     #[test]
     fn test_collector_thread_safety() {
         use std::thread;
@@ -376,4 +1032,61 @@ mod tests {
         collector.clear();
         assert!(collector.stats("test").is_none());
     }
+
+    #[test]
+    fn test_poisoned_lock_keeps_recording() {
+        let collector = Collector::new();
+        collector.record_duration("test", Duration::from_nanos(1000));
+        let c = collector.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = c.measurements.write().unwrap();
+            panic!("poison the collector lock");
+        })
+        .join();
+        assert!(collector.measurements.is_poisoned());
+
+        collector.record_duration("test", Duration::from_nanos(3000));
+        assert_eq!(collector.stats("test").unwrap().count, 2);
+        assert_eq!(collector.drain().len(), 1);
+        collector.clear();
+        assert!(collector.all_stats().is_empty());
+    }
+
+    #[test]
+    fn test_collector_drain_concurrent() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::thread;
+
+        let collector = Collector::new();
+        let done = Arc::new(AtomicBool::new(false));
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let c = collector.clone();
+                thread::spawn(move || {
+                    for _ in 0..1_000 {
+                        c.record_duration("w", Duration::from_nanos(1));
+                    }
+                })
+            })
+            .collect();
+
+        let drainer = {
+            let c = collector.clone();
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                let mut seen = 0usize;
+                while !done.load(Ordering::Acquire) {
+                    seen += c.drain().get("w").map_or(0, Vec::len);
+                }
+                seen
+            })
+        };
+        for w in writers {
+            w.join().unwrap();
+        }
+        done.store(true, Ordering::Release);
+        let total_drained = drainer.join().unwrap();
+        let rest = collector.take_samples("w").map_or(0, |v| v.len());
+        assert_eq!(total_drained + rest, 4_000);
+    }
 }
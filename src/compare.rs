@@ -0,0 +1,323 @@
+#![cfg(feature = "collector")]
+//! Before/after comparison of collected statistics.
+//!
+//! [`compare`] pairs metrics by name across two snapshots (Collector stats or
+//! Watch snapshots) and reports absolute and relative changes, e.g. to A/B a
+//! configuration change in staging. [`CompareTable`] renders the result.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::BuildHasher;
+
+use crate::collector::Stats;
+use crate::Duration;
+#[cfg(feature = "metrics")]
+use crate::WatchStats;
+
+/// Statistics (ns) compared by [`compare`]. Percentiles are `None` for
+/// sources that do not track them (e.g. `Collector` stats).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetricSummary {
+    /// Number of samples.
+    pub count: u64,
+    /// Arithmetic mean.
+    pub mean: f64,
+    /// Minimum.
+    pub min: f64,
+    /// Maximum.
+    pub max: f64,
+    /// Median.
+    pub p50: Option<f64>,
+    /// 90th percentile.
+    pub p90: Option<f64>,
+    /// 99th percentile.
+    pub p99: Option<f64>,
+}
+
+#[allow(clippy::cast_precision_loss)]
+impl From<&Stats> for MetricSummary {
+    fn from(s: &Stats) -> Self {
+        Self {
+            count: s.count,
+            mean: s.mean.as_nanos() as f64,
+            min: s.min.as_nanos() as f64,
+            max: s.max.as_nanos() as f64,
+            p50: None,
+            p90: None,
+            p99: None,
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+#[allow(clippy::cast_precision_loss)]
+impl From<&WatchStats> for MetricSummary {
+    fn from(s: &WatchStats) -> Self {
+        Self {
+            count: s.count,
+            mean: s.mean,
+            min: s.min as f64,
+            max: s.max as f64,
+            p50: Some(s.p50 as f64),
+            p90: Some(s.p90 as f64),
+            p99: Some(s.p99 as f64),
+        }
+    }
+}
+
+/// A set of named statistics that can be passed to [`compare`].
+pub trait StatsSource {
+    /// Named summaries of every metric in the source.
+    fn summaries(&self) -> Vec<(String, MetricSummary)>;
+}
+
+impl StatsSource for [(String, Stats)] {
+    fn summaries(&self) -> Vec<(String, MetricSummary)> {
+        self.iter().map(|(k, v)| (k.clone(), v.into())).collect()
+    }
+}
+
+impl StatsSource for Vec<(String, Stats)> {
+    fn summaries(&self) -> Vec<(String, MetricSummary)> {
+        self.as_slice().summaries()
+    }
+}
+
+impl<H: BuildHasher> StatsSource for HashMap<String, Stats, H> {
+    fn summaries(&self) -> Vec<(String, MetricSummary)> {
+        self.iter().map(|(k, v)| (k.clone(), v.into())).collect()
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl<H: BuildHasher> StatsSource for HashMap<String, WatchStats, H> {
+    fn summaries(&self) -> Vec<(String, MetricSummary)> {
+        self.iter().map(|(k, v)| (k.clone(), v.into())).collect()
+    }
+}
+
+/// A before/after pair of one statistic (ns).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Change {
+    /// Value in the baseline.
+    pub before: f64,
+    /// Value in the candidate.
+    pub after: f64,
+}
+
+impl Change {
+    /// Absolute change (`after - before`).
+    pub fn diff(&self) -> f64 {
+        self.after - self.before
+    }
+
+    /// Relative change (`0.25` = 25% slower); `None` if `before` is 0.
+    pub fn relative(&self) -> Option<f64> {
+        (self.before != 0.0).then(|| self.diff() / self.before)
+    }
+
+    fn pair(before: Option<f64>, after: Option<f64>) -> Option<Self> {
+        Some(Self {
+            before: before?,
+            after: after?,
+        })
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", fmt_ns(self.before), fmt_ns(self.after))?;
+        match self.relative() {
+            Some(r) => write!(f, " ({:+.1}%)", r * 100.0),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Changes for one metric present in both inputs of [`compare`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricDelta {
+    /// Metric name.
+    pub name: String,
+    /// Sample counts `(before, after)`.
+    pub count: (u64, u64),
+    /// Mean.
+    pub mean: Change,
+    /// Minimum.
+    pub min: Change,
+    /// Maximum.
+    pub max: Change,
+    /// Median, if both sides track percentiles.
+    pub p50: Option<Change>,
+    /// 90th percentile, if both sides track percentiles.
+    pub p90: Option<Change>,
+    /// 99th percentile, if both sides track percentiles.
+    pub p99: Option<Change>,
+}
+
+/// Compare two sets of statistics, metric by metric.
+///
+/// Only metrics present in both inputs are reported, sorted by name.
+///
+/// # Examples
+/// ```
+/// use benchmark::{compare, Collector, CompareTable, Duration};
+/// let before = Collector::new();
+/// let after = Collector::new();
+/// before.record_duration("query", Duration::from_nanos(1_000));
+/// after.record_duration("query", Duration::from_nanos(1_250));
+/// let deltas = compare(&before.all_stats(), &after.all_stats());
+/// assert_eq!(deltas[0].mean.relative(), Some(0.25));
+/// println!("{}", CompareTable(&deltas));
+/// ```
+pub fn compare<S: StatsSource + ?Sized>(before: &S, after: &S) -> Vec<MetricDelta> {
+    let base: HashMap<String, MetricSummary> = before.summaries().into_iter().collect();
+    let mut out: Vec<MetricDelta> = after
+        .summaries()
+        .into_iter()
+        .filter_map(|(name, a)| {
+            let b = base.get(&name)?;
+            Some(MetricDelta {
+                count: (b.count, a.count),
+                mean: Change {
+                    before: b.mean,
+                    after: a.mean,
+                },
+                min: Change {
+                    before: b.min,
+                    after: a.min,
+                },
+                max: Change {
+                    before: b.max,
+                    after: a.max,
+                },
+                p50: Change::pair(b.p50, a.p50),
+                p90: Change::pair(b.p90, a.p90),
+                p99: Change::pair(b.p99, a.p99),
+                name,
+            })
+        })
+        .collect();
+    out.sort_by(|x, y| x.name.cmp(&y.name));
+    out
+}
+
+/// Renders [`MetricDelta`]s as an aligned text table.
+///
+/// Columns without data on either side (e.g. percentiles for `Collector`
+/// stats) are omitted.
+#[derive(Debug, Clone, Copy)]
+pub struct CompareTable<'a>(pub &'a [MetricDelta]);
+
+impl fmt::Display for CompareTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        type Column = (&'static str, fn(&MetricDelta) -> Option<Change>);
+        let columns: [Column; 5] = [
+            ("mean", |d| Some(d.mean)),
+            ("p50", |d| d.p50),
+            ("p90", |d| d.p90),
+            ("p99", |d| d.p99),
+            ("max", |d| Some(d.max)),
+        ];
+        let columns: Vec<&Column> = columns
+            .iter()
+            .filter(|(_, get)| self.0.iter().any(|d| get(d).is_some()))
+            .collect();
+
+        let mut rows: Vec<Vec<String>> = vec![std::iter::once("metric")
+            .chain(std::iter::once("count"))
+            .chain(columns.iter().map(|(h, _)| *h))
+            .map(str::to_string)
+            .collect()];
+        for d in self.0 {
+            let mut row = vec![d.name.clone(), format!("{} -> {}", d.count.0, d.count.1)];
+            row.extend(
+                columns
+                    .iter()
+                    .map(|(_, get)| get(d).map_or_else(|| "-".to_string(), |c| c.to_string())),
+            );
+            rows.push(row);
+        }
+
+        let widths: Vec<usize> = (0..rows[0].len())
+            .map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0))
+            .collect();
+        for row in &rows {
+            for (i, cell) in row.iter().enumerate() {
+                if i > 0 {
+                    f.write_str("  ")?;
+                }
+                if i + 1 == row.len() {
+                    f.write_str(cell)?;
+                } else {
+                    write!(f, "{cell:<w$}", w = widths[i])?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn fmt_ns(ns: f64) -> Duration {
+    Duration::from_nanos(ns.max(0.0).round() as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Collector;
+
+    #[test]
+    fn test_compare_collectors() {
+        let before = Collector::new();
+        let after = Collector::new();
+        for v in [100, 200, 300] {
+            before.record_duration("a", Duration::from_nanos(v));
+            after.record_duration("a", Duration::from_nanos(v / 2));
+        }
+        before.record_duration("only_before", Duration::from_nanos(1));
+        after.record_duration("only_after", Duration::from_nanos(1));
+
+        let d = compare(&before.all_stats(), &after.all_stats());
+        assert_eq!(d.len(), 1);
+        assert_eq!(d[0].name, "a");
+        assert_eq!(d[0].count, (3, 3));
+        assert!((d[0].mean.diff() + 100.0).abs() < 1e-9);
+        assert_eq!(d[0].mean.relative(), Some(-0.5));
+        assert!(d[0].p50.is_none());
+
+        let table = CompareTable(&d).to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("metric  count"));
+        assert!(!lines[0].contains("p50"));
+        assert!(lines[1].contains("200ns -> 100ns (-50.0%)"));
+    }
+
+    #[test]
+    fn test_change_relative_from_zero() {
+        let c = Change {
+            before: 0.0,
+            after: 5.0,
+        };
+        assert_eq!(c.relative(), None);
+        assert_eq!(c.to_string(), "0ns -> 5ns");
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_compare_watch_snapshots() {
+        let w = crate::Watch::new();
+        w.record("rpc", 1_000);
+        let before = w.snapshot();
+        w.record("rpc", 3_000);
+        let after = w.snapshot();
+        let d = compare(&before, &after);
+        assert_eq!(d[0].count, (1, 2));
+        let p99 = d[0].p99.unwrap();
+        assert!(p99.after > p99.before);
+        assert!(CompareTable(&d).to_string().contains("p99"));
+    }
+}
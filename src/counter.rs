@@ -0,0 +1,65 @@
+#![cfg(feature = "metrics")]
+//! A striped event counter for hot paths.
+//!
+//! Every thread adds to one of a few cache-line-sized stripes, picked once
+//! per thread, so concurrent recorders do not fight over one counter; reads
+//! sum the stripes.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Number of stripes; threads beyond this share them round-robin.
+const STRIPES: usize = 16;
+
+/// Source of per-thread stripe indices.
+static NEXT_STRIPE: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static STRIPE: usize = NEXT_STRIPE.fetch_add(1, Ordering::Relaxed) % STRIPES;
+}
+
+#[repr(align(64))]
+#[derive(Default)]
+struct Stripe(AtomicU64);
+
+/// A relaxed counter spread over per-thread stripes.
+#[derive(Default)]
+pub(crate) struct StripedCounter {
+    stripes: [Stripe; STRIPES],
+}
+
+impl StripedCounter {
+    #[inline]
+    pub(crate) fn add(&self, n: u64) {
+        let i = STRIPE.try_with(|i| *i).unwrap_or(0);
+        self.stripes[i].0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Sum over all stripes; concurrent adds may or may not be included.
+    pub(crate) fn sum(&self) -> u64 {
+        self.stripes
+            .iter()
+            .map(|s| s.0.load(Ordering::Relaxed))
+            .fold(0, u64::wrapping_add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sums_adds_from_all_threads() {
+        let c = StripedCounter::default();
+        std::thread::scope(|s| {
+            for _ in 0..20 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        c.add(1);
+                    }
+                });
+            }
+        });
+        c.add(5);
+        assert_eq!(c.sum(), 2_005);
+    }
+}
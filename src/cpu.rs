@@ -0,0 +1,362 @@
+#![cfg(feature = "cpu-time")]
+//! CPU time alongside wall time.
+//!
+//! Wall-clock time alone cannot tell "slow because blocked" (I/O, locks,
+//! sleeping) from "slow because computing". [`measure_cpu`] reads the
+//! process's user and system CPU time before and after the closure
+//! (`getrusage` on Unix, `GetProcessTimes` on Windows) and reports them next
+//! to the wall time: CPU well below wall means the code mostly waited, CPU
+//! above wall means several threads were busy at once.
+//!
+//! Process CPU time covers every thread, so work done concurrently by other
+//! threads is included. The OS accounts it in coarse steps (microseconds to
+//! milliseconds), which makes it meaningful for regions of a millisecond or
+//! more. On other platforms the CPU figures read as zero.
+//!
+//! [`ThreadCpuClock`] is a [`Clock`] over the calling thread's own CPU time
+//! (`CLOCK_THREAD_CPUTIME_ID`, `GetThreadTimes` on Windows). It excludes time
+//! the thread spent blocked or preempted, and backs
+//! [`Timer::new_thread_cpu`](crate::Timer::new_thread_cpu).
+
+use std::sync::OnceLock;
+
+use crate::clock::{system_now_ns, Clock};
+use crate::Duration;
+
+/// User and system CPU time consumed by the process so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuTimes {
+    /// Time spent executing the program's own code.
+    pub user: Duration,
+    /// Time spent in the kernel on the program's behalf.
+    pub system: Duration,
+}
+
+/// Wall time and process CPU time of one measured region, from [`measure_cpu`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WallAndCpu {
+    /// Elapsed wall-clock time.
+    pub wall: Duration,
+    /// User CPU time consumed by the process meanwhile.
+    pub user: Duration,
+    /// System CPU time consumed by the process meanwhile.
+    pub system: Duration,
+}
+
+impl WallAndCpu {
+    /// User plus system CPU time.
+    pub fn cpu(&self) -> Duration {
+        Duration::from_nanos(self.user.as_nanos() + self.system.as_nanos())
+    }
+
+    /// CPU time divided by wall time: near 0 when blocked, near 1 when one
+    /// thread computed throughout, above 1 when several did. `0.0` for an
+    /// empty wall time.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn cpu_ratio(&self) -> f64 {
+        let wall = self.wall.as_nanos();
+        if wall == 0 {
+            return 0.0;
+        }
+        self.cpu().as_nanos() as f64 / wall as f64
+    }
+}
+
+/// CPU time consumed by the whole process so far, or `None` on platforms
+/// without a supported source.
+///
+/// # Examples
+/// ```
+/// let before = benchmark::process_cpu_times();
+/// # #[cfg(any(unix, windows))]
+/// assert!(before.is_some());
+/// ```
+pub fn process_cpu_times() -> Option<CpuTimes> {
+    sys::process_times()
+}
+
+/// Run `f`, returning its result with the wall and process CPU time it took
+/// (see the [module docs](self)).
+///
+/// # Examples
+/// ```
+/// use benchmark::measure_cpu;
+/// let (sum, t) = measure_cpu(|| (0..1_000_000u64).map(std::hint::black_box).sum::<u64>());
+/// assert_eq!(sum, 499_999_500_000);
+/// assert!(t.cpu_ratio() >= 0.0);
+///
+/// let ((), t) = measure_cpu(|| std::thread::sleep(std::time::Duration::from_millis(20)));
+/// assert!(t.wall.as_millis() >= 20);
+/// assert!(t.cpu() < t.wall); // blocked, not computing
+/// ```
+pub fn measure_cpu<T, F: FnOnce() -> T>(f: F) -> (T, WallAndCpu) {
+    let cpu_start = sys::process_times().unwrap_or_default();
+    let wall_start = crate::clock::__now_ns();
+    let out = f();
+    let wall = crate::clock::__elapsed_since(wall_start);
+    let cpu_end = sys::process_times().unwrap_or_default();
+    let since = |end: Duration, start: Duration| {
+        Duration::from_nanos(end.as_nanos().saturating_sub(start.as_nanos()))
+    };
+    let times = WallAndCpu {
+        wall,
+        user: since(cpu_end.user, cpu_start.user),
+        system: since(cpu_end.system, cpu_start.system),
+    };
+    (out, times)
+}
+
+/// A [`Clock`] reading the CPU time consumed by the calling thread.
+///
+/// Readings only advance while the thread runs, so the difference of two
+/// readings taken on the same thread is its on-CPU time in between; readings
+/// from different threads are unrelated. Where no per-thread clock is
+/// available it falls back to wall time (see [`is_supported`](Self::is_supported)).
+///
+/// # Examples
+/// ```
+/// use benchmark::{measure_with, ThreadCpuClock};
+/// let ((), busy) = measure_with(&ThreadCpuClock, || std::thread::sleep(std::time::Duration::from_millis(20)));
+/// # #[cfg(feature = "benchmark")]
+/// if ThreadCpuClock::is_supported() {
+///     assert!(busy.as_millis() < 20); // sleeping costs no CPU
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadCpuClock;
+
+impl ThreadCpuClock {
+    /// Whether this platform has a per-thread CPU clock; when it does not,
+    /// readings are wall time.
+    pub fn is_supported() -> bool {
+        static SUPPORTED: OnceLock<bool> = OnceLock::new();
+        *SUPPORTED.get_or_init(|| sys::thread_time_ns().is_some())
+    }
+
+    /// A shared handle, as `Timer` stores its clock.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn shared() -> std::sync::Arc<dyn Clock> {
+        static SHARED: OnceLock<std::sync::Arc<dyn Clock>> = OnceLock::new();
+        std::sync::Arc::clone(SHARED.get_or_init(|| std::sync::Arc::new(ThreadCpuClock)))
+    }
+}
+
+impl Clock for ThreadCpuClock {
+    #[inline]
+    fn now_ns(&self) -> u64 {
+        if Self::is_supported() {
+            sys::thread_time_ns().unwrap_or(0)
+        } else {
+            system_now_ns()
+        }
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use super::CpuTimes;
+    use crate::Duration;
+
+    fn from_timeval(tv: libc::timeval) -> Duration {
+        let secs = u128::try_from(tv.tv_sec).unwrap_or(0);
+        let micros = u128::try_from(tv.tv_usec).unwrap_or(0);
+        Duration::from_nanos(secs * 1_000_000_000 + micros * 1_000)
+    }
+
+    #[allow(unsafe_code)]
+    pub(super) fn process_times() -> Option<CpuTimes> {
+        let mut usage = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+        // SAFETY: `usage` is a valid, writable `rusage`; `getrusage` fully
+        // initializes it when it returns 0.
+        let usage = unsafe {
+            if libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) != 0 {
+                return None;
+            }
+            usage.assume_init()
+        };
+        Some(CpuTimes {
+            user: from_timeval(usage.ru_utime),
+            system: from_timeval(usage.ru_stime),
+        })
+    }
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    ))]
+    #[allow(unsafe_code)]
+    pub(super) fn thread_time_ns() -> Option<u64> {
+        let mut ts = std::mem::MaybeUninit::<libc::timespec>::zeroed();
+        // SAFETY: `ts` is a valid, writable `timespec`; `clock_gettime` fully
+        // initializes it when it returns 0.
+        let ts = unsafe {
+            if libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, ts.as_mut_ptr()) != 0 {
+                return None;
+            }
+            ts.assume_init()
+        };
+        let secs = u64::try_from(ts.tv_sec).ok()?;
+        let nanos = u64::try_from(ts.tv_nsec).ok()?;
+        Some(secs.saturating_mul(1_000_000_000).saturating_add(nanos))
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    )))]
+    pub(super) fn thread_time_ns() -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::CpuTimes;
+    use crate::Duration;
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct FileTime {
+        low: u32,
+        high: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentProcess() -> *mut c_void;
+        fn GetCurrentThread() -> *mut c_void;
+        fn GetProcessTimes(
+            process: *mut c_void,
+            creation: *mut FileTime,
+            exit: *mut FileTime,
+            kernel: *mut FileTime,
+            user: *mut FileTime,
+        ) -> i32;
+        fn GetThreadTimes(
+            thread: *mut c_void,
+            creation: *mut FileTime,
+            exit: *mut FileTime,
+            kernel: *mut FileTime,
+            user: *mut FileTime,
+        ) -> i32;
+    }
+
+    /// A `FILETIME` span counts 100ns intervals.
+    fn from_filetime(t: &FileTime) -> Duration {
+        Duration::from_nanos(((u128::from(t.high) << 32) | u128::from(t.low)) * 100)
+    }
+
+    #[allow(unsafe_code)]
+    pub(super) fn process_times() -> Option<CpuTimes> {
+        let (mut creation, mut exit) = (FileTime::default(), FileTime::default());
+        let (mut kernel, mut user) = (FileTime::default(), FileTime::default());
+        // SAFETY: the pseudo-handle from `GetCurrentProcess` needs no closing
+        // and every out-pointer refers to a live `FILETIME`.
+        let ok = unsafe {
+            GetProcessTimes(
+                GetCurrentProcess(),
+                &mut creation,
+                &mut exit,
+                &mut kernel,
+                &mut user,
+            )
+        };
+        (ok != 0).then(|| CpuTimes {
+            user: from_filetime(&user),
+            system: from_filetime(&kernel),
+        })
+    }
+
+    /// User plus kernel time of the calling thread. Windows accounts it per
+    /// scheduler tick, so short regions may read as zero.
+    #[allow(unsafe_code)]
+    pub(super) fn thread_time_ns() -> Option<u64> {
+        let (mut creation, mut exit) = (FileTime::default(), FileTime::default());
+        let (mut kernel, mut user) = (FileTime::default(), FileTime::default());
+        // SAFETY: as for `GetProcessTimes`, with the thread pseudo-handle.
+        let ok = unsafe {
+            GetThreadTimes(
+                GetCurrentThread(),
+                &mut creation,
+                &mut exit,
+                &mut kernel,
+                &mut user,
+            )
+        };
+        let ns = from_filetime(&kernel).as_nanos() + from_filetime(&user).as_nanos();
+        (ok != 0).then(|| u64::try_from(ns).unwrap_or(u64::MAX))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use super::CpuTimes;
+
+    #[allow(clippy::unnecessary_wraps)]
+    pub(super) fn process_times() -> Option<CpuTimes> {
+        None
+    }
+
+    #[allow(clippy::unnecessary_wraps)]
+    pub(super) fn thread_time_ns() -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busy_loop_consumes_cpu() {
+        let (_, t) = measure_cpu(|| {
+            let start = std::time::Instant::now();
+            let mut x = 0u64;
+            while start.elapsed() < std::time::Duration::from_millis(50) {
+                x = std::hint::black_box(x.wrapping_add(1));
+            }
+            x
+        });
+        assert!(t.wall.as_millis() >= 50);
+        #[cfg(any(unix, windows))]
+        assert!(t.cpu().as_millis() >= 10, "{t:?}");
+    }
+
+    #[test]
+    fn test_thread_cpu_clock_ignores_sleep() {
+        let clock = ThreadCpuClock;
+        let start = clock.now_ns();
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        let slept = clock.now_ns() - start;
+        #[cfg(any(target_os = "linux", target_os = "macos", windows))]
+        assert!(ThreadCpuClock::is_supported());
+        if ThreadCpuClock::is_supported() {
+            assert!(slept < 20_000_000, "{slept}");
+        } else {
+            assert!(slept >= 30_000_000);
+        }
+    }
+
+    #[test]
+    fn test_cpu_ratio_of_empty_wall_is_zero() {
+        let t = WallAndCpu {
+            user: Duration::from_millis(3),
+            system: Duration::from_millis(1),
+            ..WallAndCpu::default()
+        };
+        assert_eq!(t.cpu().as_nanos(), 4_000_000);
+        assert!(t.cpu_ratio().abs() < f64::EPSILON);
+        let t = WallAndCpu {
+            wall: Duration::from_millis(8),
+            ..t
+        };
+        assert!((t.cpu_ratio() - 0.5).abs() < 1e-9);
+    }
+}
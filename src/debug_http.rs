@@ -0,0 +1,336 @@
+#![cfg(feature = "debug-http")]
+//! Minimal HTTP debug endpoint for `Watch` metrics, built on `std::net`.
+//!
+//! [`Watch::serve_debug`] binds a listener and answers requests one at a time
+//! on a background thread, with no web framework involved:
+//! - `GET /metrics`: Prometheus text format, one `summary` per metric with
+//!   p50/p90/p99/p99.9 quantiles, `_sum` and `_count` (in seconds).
+//! - `GET /snapshot.json`: every [`WatchStats`](crate::WatchStats) field of
+//!   every metric, keyed by name (see [`Snapshot::FIELD_NAMES`]).
+//!
+//! Intended for small tools and local debugging: it serves one connection
+//! at a time, ignores request bodies and keep-alive, and has no TLS or auth.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read as _, Write as _};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration as StdDuration;
+
+use crate::{Snapshot, Watch, WatchStats};
+
+/// How long a client may take to send its request or read the response.
+const IO_TIMEOUT: StdDuration = StdDuration::from_secs(2);
+/// Longest request head read before giving up on a client.
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+impl Watch {
+    /// Serve this Watch's metrics over HTTP on `addr` from a background
+    /// thread: `/metrics` (Prometheus text) and `/snapshot.json`.
+    ///
+    /// Requests are handled one at a time; each takes a fresh snapshot.
+    /// Bind to port 0 and read [`DebugServer::local_addr`] for an ephemeral
+    /// port. The server stops when the handle is shut down or dropped.
+    ///
+    /// # Errors
+    /// Returns an error if `addr` cannot be resolved or bound.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use benchmark::Watch;
+    ///
+    /// let w = Watch::new();
+    /// let server = w.serve_debug("127.0.0.1:9898")?;
+    /// w.record("db.query", 40_000);
+    /// // curl http://127.0.0.1:9898/metrics
+    /// server.shutdown();
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn serve_debug(&self, addr: impl ToSocketAddrs) -> io::Result<DebugServer> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let watch = self.clone();
+        let flag = Arc::clone(&stop);
+        let thread = std::thread::Builder::new()
+            .name("benchmark-debug-http".into())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if flag.load(Ordering::Acquire) {
+                        break;
+                    }
+                    // A failed accept or client only costs that request.
+                    if let Ok(stream) = stream {
+                        let _ = respond(&watch, stream);
+                    }
+                }
+            })?;
+        Ok(DebugServer {
+            local_addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Handle to the HTTP thread started by [`Watch::serve_debug`].
+///
+/// Dropping the handle stops the server.
+#[derive(Debug)]
+pub struct DebugServer {
+    local_addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DebugServer {
+    /// The address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop serving and wait for the thread to exit.
+    pub fn shutdown(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        self.stop.store(true, Ordering::Release);
+        // Wake the blocking `accept` with a connection of our own.
+        let mut wake = self.local_addr;
+        if wake.ip().is_unspecified() {
+            wake.set_ip(match wake {
+                SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+                SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+            });
+        }
+        if TcpStream::connect_timeout(&wake, IO_TIMEOUT).is_ok() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for DebugServer {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+/// Read one request from `stream` and write the matching response.
+fn respond(watch: &Watch, stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers so the client sees a clean close.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim_end() != "" {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    let path = path.split('?').next().unwrap_or(path);
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => (
+            "200 OK",
+            "text/plain; version=0.0.4",
+            prometheus_text(&watch.snapshot_sorted()),
+        ),
+        ("GET", "/snapshot.json") => (
+            "200 OK",
+            "application/json",
+            snapshot_json(&watch.snapshot_sorted()),
+        ),
+        ("GET", _) => ("404 Not Found", "text/plain", "not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n".to_string(),
+        ),
+    };
+
+    let mut stream = reader.into_inner().into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+/// Render `snap` in the Prometheus text exposition format.
+///
+/// Metrics whose names sanitize to the same family (`a.b` and `a_b`) share
+/// one `# TYPE` block, each series labelled with its original
+/// `metric="..."` name.
+#[allow(clippy::cast_precision_loss)]
+fn prometheus_text(snap: &Snapshot) -> String {
+    let seconds = |ns: u64| ns as f64 / 1e9;
+    let mut families: BTreeMap<String, Vec<(&str, &WatchStats)>> = BTreeMap::new();
+    for (name, s) in snap {
+        families
+            .entry(prometheus_name(name))
+            .or_default()
+            .push((name, s));
+    }
+    let mut out = String::new();
+    for (family, members) in &families {
+        let _ = writeln!(out, "# TYPE {family} summary");
+        for &(name, s) in members {
+            // `(prefix for the quantile labels, label set for _sum/_count)`.
+            let (prefix, labels) = if members.len() > 1 {
+                let mut label = String::from("metric=\"");
+                push_label_escaped(&mut label, name);
+                label.push('"');
+                (format!("{label},"), format!("{{{label}}}"))
+            } else {
+                (String::new(), String::new())
+            };
+            for (q, ns) in [
+                ("0.5", s.p50),
+                ("0.9", s.p90),
+                ("0.99", s.p99),
+                ("0.999", s.p999),
+            ] {
+                let _ = writeln!(out, "{family}{{{prefix}quantile=\"{q}\"}} {}", seconds(ns));
+            }
+            let _ = writeln!(out, "{family}_sum{labels} {}", seconds(s.total_ns));
+            let _ = writeln!(out, "{family}_count{labels} {}", s.count);
+        }
+    }
+    out
+}
+
+/// Append `value` escaped for a Prometheus label value.
+fn push_label_escaped(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// `name` with every character Prometheus does not allow replaced by `_`.
+fn prometheus_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !out.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == ':') {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Render `snap` as `{"metric":{"count":1,...},...}`; non-finite values
+/// become `null`.
+fn snapshot_json(snap: &Snapshot) -> String {
+    let mut out = String::from("{");
+    let mut current: Option<&str> = None;
+    for (name, field, value) in snap.fields() {
+        if current == Some(name) {
+            out.push(',');
+        } else {
+            if current.is_some() {
+                out.push_str("},");
+            }
+            out.push('"');
+            crate::collector::push_json_escaped(&mut out, name);
+            out.push_str("\":{");
+            current = Some(name);
+        }
+        if value.is_finite() {
+            let _ = write!(out, "\"{field}\":{value}");
+        } else {
+            let _ = write!(out, "\"{field}\":null");
+        }
+    }
+    if current.is_some() {
+        out.push('}');
+    }
+    out.push('}');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(addr: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_serves_prometheus_and_json() {
+        let w = Watch::new();
+        w.record("db.query", 2_000_000);
+        w.record("db.query", 4_000_000);
+        let server = w.serve_debug("127.0.0.1:0").unwrap();
+        let addr = server.local_addr();
+
+        let metrics = get(addr, "GET /metrics HTTP/1.1\r\nHost: x\r\n\r\n");
+        assert!(metrics.starts_with("HTTP/1.1 200 OK\r\n"), "{metrics}");
+        assert!(metrics.contains("# TYPE db_query summary\n"));
+        assert!(metrics.contains("db_query_count 2\n"));
+        assert!(metrics.contains("db_query_sum 0.006\n"));
+
+        let json = get(addr, "GET /snapshot.json?pretty=0 HTTP/1.0\r\n\r\n");
+        assert!(json.contains("Content-Type: application/json\r\n"));
+        let body = json.split("\r\n\r\n").nth(1).unwrap();
+        assert!(body.starts_with("{\"db.query\":{\"count\":2,"), "{body}");
+        assert!(body.ends_with("}}"));
+
+        assert!(get(addr, "GET /nope HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 404"));
+        assert!(get(addr, "POST /metrics HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 405"));
+        server.shutdown();
+        assert!(TcpStream::connect_timeout(&addr, StdDuration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn test_colliding_names_share_one_family() {
+        let w = Watch::new();
+        w.record("a.b", 1_000_000);
+        w.record("a_b", 2_000_000);
+        w.record("a_b", 2_000_000);
+        let text = prometheus_text(&w.snapshot_sorted());
+        assert_eq!(text.matches("# TYPE a_b summary").count(), 1, "{text}");
+        assert!(text.contains("a_b{metric=\"a.b\",quantile=\"0.5\"} "));
+        assert!(text.contains("a_b_count{metric=\"a.b\"} 1\n"));
+        assert!(text.contains("a_b_count{metric=\"a_b\"} 2\n"));
+
+        let mut escaped = String::new();
+        push_label_escaped(&mut escaped, "x\"y\\z\n");
+        assert_eq!(escaped, r#"x\"y\\z\n"#);
+    }
+
+    #[test]
+    fn test_names_and_empty_snapshot() {
+        assert_eq!(prometheus_name("http.get /users"), "http_get__users");
+        assert_eq!(prometheus_name("9lives"), "_9lives");
+        assert_eq!(snapshot_json(&Snapshot::new()), "{}");
+        assert_eq!(prometheus_text(&Snapshot::new()), "");
+    }
+}
@@ -1,6 +1,7 @@
 //! Duration type for representing time measurements.
 
 use core::fmt;
+use core::str::FromStr;
 
 /// A duration represented in nanoseconds.
 ///
@@ -18,7 +19,7 @@ use core::fmt;
 /// assert!((d.as_secs_f64() - 1.234_567_89).abs() < 1e-9);
 /// assert_eq!(d.to_string(), "1.23s");
 /// ```
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Duration {
     pub(crate) nanos: u128,
 }
@@ -33,6 +34,66 @@ impl Duration {
         Self { nanos }
     }
 
+    /// Creates a new Duration from microseconds.
+    ///
+    /// Like the other integer constructors this is `const`, so thresholds
+    /// can be declared as constants.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Duration;
+    /// const SLO: Duration = Duration::from_millis(250);
+    /// assert_eq!(SLO.as_nanos(), 250_000_000);
+    /// assert_eq!(Duration::from_micros(3), Duration::from_nanos(3_000));
+    /// ```
+    #[inline]
+    #[allow(clippy::cast_lossless)] // `u128::from` is not const
+    pub const fn from_micros(micros: u64) -> Self {
+        Self::from_nanos(micros as u128 * 1_000)
+    }
+
+    /// Creates a new Duration from milliseconds.
+    #[inline]
+    #[allow(clippy::cast_lossless)]
+    pub const fn from_millis(millis: u64) -> Self {
+        Self::from_nanos(millis as u128 * 1_000_000)
+    }
+
+    /// Creates a new Duration from whole seconds.
+    #[inline]
+    #[allow(clippy::cast_lossless)]
+    pub const fn from_secs(secs: u64) -> Self {
+        Self::from_nanos(secs as u128 * 1_000_000_000)
+    }
+
+    /// Creates a new Duration from seconds as a float, rounded to the
+    /// nearest nanosecond.
+    ///
+    /// # Panics
+    /// Panics if `secs` is negative, NaN or too large, like
+    /// `std::time::Duration::from_secs_f64`.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Duration;
+    /// assert_eq!(Duration::from_secs_f64(1.5).as_nanos(), 1_500_000_000);
+    /// assert_eq!(Duration::from_secs_f64(2.5e-9).as_nanos(), 3);
+    /// ```
+    #[inline]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn from_secs_f64(secs: f64) -> Self {
+        let nanos = secs * 1_000_000_000.0 + 0.5;
+        assert!(
+            nanos >= 0.5 && nanos < u128::MAX as f64,
+            "Duration::from_secs_f64: value is negative, NaN or overflows"
+        );
+        Self::from_nanos(nanos as u128)
+    }
+
     /// Returns the number of nanoseconds.
     #[inline]
     pub const fn as_nanos(&self) -> u128 {
@@ -64,6 +125,253 @@ impl Duration {
     pub fn as_secs_f32(&self) -> f32 {
         self.nanos as f32 / 1_000_000_000.0
     }
+
+    /// Formats the exact nanosecond count (e.g. `"1234567ns"`).
+    ///
+    /// Unlike `Display`, this is lossless: parsing the output yields the
+    /// identical value, which makes it suitable for snapshot files.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Duration;
+    /// let d = Duration::from_nanos(1_234_567);
+    /// assert_eq!(d.to_string_precise(), "1234567ns");
+    /// assert_eq!(d.to_string_precise().parse::<Duration>().unwrap(), d);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_string_precise(self) -> String {
+        format!("{}ns", self.nanos)
+    }
+
+    /// Formats in a fixed `unit` with exactly `precision` decimals, rounding
+    /// half up (e.g. `"1500.00µs"`), so table columns line up.
+    ///
+    /// The conversion is exact integer arithmetic, and the output parses
+    /// back with [`parse`](Self::parse). Pad with the usual width specifiers,
+    /// e.g. `format!("{:>12}", d.format(Unit::Millis, 3))`.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Duration, Unit};
+    /// let d = Duration::from_nanos(1_500_000);
+    /// assert_eq!(d.format(Unit::Micros, 2), "1500.00µs");
+    /// assert_eq!(d.format(Unit::Millis, 0), "2ms");
+    /// assert_eq!(d.format(Unit::Secs, 4), "0.0015s");
+    /// assert_eq!(Duration::parse(&d.format(Unit::Millis, 3)), Ok(d));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn format(self, unit: Unit, precision: usize) -> String {
+        // Digits beyond 18 are always zero for a nanosecond count.
+        let digits = precision.min(18);
+        let scale = 10u128.pow(u32::try_from(digits).unwrap_or(18));
+        let per = unit.nanos();
+        let mut whole = self.nanos / per;
+        let mut frac = (self.nanos % per * scale * 2 + per) / (per * 2);
+        if frac == scale {
+            whole += 1;
+            frac = 0;
+        }
+        let mut out = if precision > 0 {
+            format!("{whole}.{frac:0digits$}")
+        } else {
+            whole.to_string()
+        };
+        out.extend(core::iter::repeat('0').take(precision - digits));
+        out.push_str(unit.suffix());
+        out
+    }
+
+    /// Parses a human-readable duration such as `"1.5ms"` or `"1m 5s"`; the
+    /// same as [`str::parse`] (see the [`FromStr`] impl for the grammar).
+    ///
+    /// # Errors
+    /// Returns a [`ParseDurationError`] describing the first invalid component.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Duration;
+    /// assert_eq!(Duration::parse("250us").unwrap().as_nanos(), 250_000);
+    /// ```
+    #[inline]
+    pub fn parse(s: &str) -> Result<Self, ParseDurationError> {
+        s.parse()
+    }
+}
+
+/// A fixed display unit for [`Duration::format`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Unit {
+    /// Nanoseconds (`ns`).
+    Nanos,
+    /// Microseconds (`µs`).
+    Micros,
+    /// Milliseconds (`ms`).
+    Millis,
+    /// Seconds (`s`).
+    Secs,
+}
+
+impl Unit {
+    /// Nanoseconds in one of this unit.
+    #[inline]
+    pub const fn nanos(self) -> u128 {
+        match self {
+            Self::Nanos => 1,
+            Self::Micros => 1_000,
+            Self::Millis => 1_000_000,
+            Self::Secs => 1_000_000_000,
+        }
+    }
+
+    /// Suffix used by `Display` and accepted by the parser (`"µs"` for micros).
+    #[inline]
+    pub const fn suffix(self) -> &'static str {
+        match self {
+            Self::Nanos => "ns",
+            Self::Micros => "\u{b5}s",
+            Self::Millis => "ms",
+            Self::Secs => "s",
+        }
+    }
+}
+
+/// Lossless: every `core::time::Duration` fits in `u128` nanoseconds.
+///
+/// # Examples
+/// ```
+/// use benchmark::Duration;
+/// let d: Duration = std::time::Duration::from_millis(3).into();
+/// assert_eq!(d.as_nanos(), 3_000_000);
+/// ```
+impl From<core::time::Duration> for Duration {
+    #[inline]
+    fn from(d: core::time::Duration) -> Self {
+        Self::from_nanos(d.as_nanos())
+    }
+}
+
+/// Error returned when parsing a [`Duration`] from a string fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseDurationError {
+    /// The input was empty or only whitespace.
+    Empty,
+    /// A component was not a valid (decimal) number.
+    InvalidNumber,
+    /// A unit was missing or not recognised.
+    InvalidUnit,
+    /// The value does not fit in a `u128` nanosecond count.
+    Overflow,
+}
+
+impl fmt::Display for ParseDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Empty => "empty duration string",
+            Self::InvalidNumber => "invalid number in duration",
+            Self::InvalidUnit => {
+                "missing or unknown duration unit (expected ns, us, µs, ms, s, m, h)"
+            }
+            Self::Overflow => "duration too large",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseDurationError {}
+
+/// Parses the formats produced by `Display` and `to_string_precise`.
+///
+/// Accepts one or more whitespace-separated `<number><unit>` components
+/// (`"1m 5s"`), with optional space between number and unit. Numbers may have
+/// a decimal fraction; units are `ns`, `us`/`µs`, `ms`, `s`, `m`/`min`, `h`.
+/// Parsing is locale-independent and exact: fractional parts are converted
+/// with integer arithmetic and rounded to the nearest nanosecond, so every
+/// `Display` output parses back to within one unit of its last printed digit.
+///
+/// # Examples
+/// ```
+/// use benchmark::Duration;
+/// let d: Duration = "1.50ms".parse().unwrap();
+/// assert_eq!(d.as_nanos(), 1_500_000);
+/// assert_eq!("1m 5s".parse::<Duration>().unwrap().as_nanos(), 65_000_000_000);
+/// assert!("12 parsecs".parse::<Duration>().is_err());
+/// ```
+impl FromStr for Duration {
+    type Err = ParseDurationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split_whitespace();
+        let mut total: u128 = 0;
+        let mut any = false;
+        while let Some(token) = tokens.next() {
+            let split = token
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(token.len());
+            let (number, unit) = token.split_at(split);
+            let unit = if unit.is_empty() {
+                tokens.next().ok_or(ParseDurationError::InvalidUnit)?
+            } else {
+                unit
+            };
+            let nanos = parse_component(number, unit_nanos(unit)?)?;
+            total = total
+                .checked_add(nanos)
+                .ok_or(ParseDurationError::Overflow)?;
+            any = true;
+        }
+        if any {
+            Ok(Self::from_nanos(total))
+        } else {
+            Err(ParseDurationError::Empty)
+        }
+    }
+}
+
+fn unit_nanos(unit: &str) -> Result<u128, ParseDurationError> {
+    Ok(match unit {
+        "ns" => 1,
+        "us" | "\u{b5}s" | "\u{3bc}s" => 1_000,
+        "ms" => 1_000_000,
+        "s" => 1_000_000_000,
+        "m" | "min" => 60_000_000_000,
+        "h" => 3_600_000_000_000,
+        _ => return Err(ParseDurationError::InvalidUnit),
+    })
+}
+
+/// Convert `"<int>[.<frac>]"` times `unit` nanoseconds, rounding to nearest.
+fn parse_component(number: &str, unit: u128) -> Result<u128, ParseDurationError> {
+    let (int, frac) = number.split_once('.').unwrap_or((number, ""));
+    if int.is_empty() && frac.is_empty() {
+        return Err(ParseDurationError::InvalidNumber);
+    }
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(int) || !digits(frac) {
+        return Err(ParseDurationError::InvalidNumber);
+    }
+
+    let mut whole: u128 = 0;
+    for b in int.bytes() {
+        whole = whole
+            .checked_mul(10)
+            .and_then(|v| v.checked_add(u128::from(b - b'0')))
+            .ok_or(ParseDurationError::Overflow)?;
+    }
+    let whole = whole
+        .checked_mul(unit)
+        .ok_or(ParseDurationError::Overflow)?;
+
+    // Units are at most 3.6e12 ns, so 18 fractional digits already resolve
+    // far below one nanosecond; extra digits cannot change the rounding.
+    let (mut num, mut den) = (0u128, 1u128);
+    for b in frac.bytes().take(18) {
+        num = num * 10 + u128::from(b - b'0');
+        den *= 10;
+    }
+    let frac_nanos = (num * unit + den / 2) / den;
+    whole
+        .checked_add(frac_nanos)
+        .ok_or(ParseDurationError::Overflow)
 }
 
 impl fmt::Display for Duration {
@@ -109,6 +417,8 @@ mod tests {
         assert_eq!(d.as_micros(), 1_234_567);
         assert_eq!(d.as_millis(), 1_234);
         assert!((d.as_secs_f64() - 1.234_567_89).abs() < 0.000_000_1);
+        let max = Duration::from(core::time::Duration::MAX);
+        assert_eq!(max.as_nanos(), core::time::Duration::MAX.as_nanos());
     }
 
     #[test]
@@ -122,6 +432,113 @@ mod tests {
         assert_eq!(Duration::from_nanos(65_000_000_000).to_string(), "1m 5s");
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_display_round_trip_within_one_ulp() {
+        let mut n: u128 = 0;
+        while n < 10_000_000_000_000 {
+            let d = Duration::from_nanos(n);
+            let shown = d.to_string();
+            let back: Duration = shown.parse().unwrap();
+            let ulp = if n < 1_000 {
+                0
+            } else if n < 1_000_000 {
+                10
+            } else if n < 1_000_000_000 {
+                10_000
+            } else if n < 60_000_000_000 {
+                10_000_000
+            } else {
+                1_000_000_000
+            };
+            assert!(
+                back.as_nanos().abs_diff(n) <= ulp,
+                "{n} -> {shown} -> {back:?}"
+            );
+            assert_eq!(d.to_string_precise().parse::<Duration>().unwrap(), d);
+            n = n * 3 / 2 + 1;
+        }
+    }
+
+    #[test]
+    fn test_duration_parse() {
+        let p = |s: &str| s.parse::<Duration>().map(|d| d.as_nanos());
+        assert_eq!(p("0ns"), Ok(0));
+        assert_eq!(p(" 1.5 us "), Ok(1_500));
+        assert_eq!(p("2µs"), Ok(2_000));
+        assert_eq!(p("0.0000005s"), Ok(500));
+        assert_eq!(p("1h 2m 3.5s"), Ok(3_723_500_000_000));
+        assert_eq!(p(".25ms"), Ok(250_000));
+        assert_eq!(p(""), Err(ParseDurationError::Empty));
+        assert_eq!(p("5"), Err(ParseDurationError::InvalidUnit));
+        assert_eq!(p("5 weeks"), Err(ParseDurationError::InvalidUnit));
+        assert_eq!(p("1.2.3s"), Err(ParseDurationError::InvalidNumber));
+        assert_eq!(p(".s"), Err(ParseDurationError::InvalidNumber));
+        assert_eq!(
+            p("999999999999999999999999999999999999999h"),
+            Err(ParseDurationError::Overflow)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_duration_format_fixed_unit() {
+        let d = Duration::from_nanos(1_234_567);
+        assert_eq!(d.format(Unit::Nanos, 0), "1234567ns");
+        assert_eq!(d.format(Unit::Nanos, 2), "1234567.00ns");
+        assert_eq!(d.format(Unit::Micros, 1), "1234.6µs");
+        assert_eq!(d.format(Unit::Millis, 3), "1.235ms");
+        assert_eq!(d.format(Unit::Millis, 6), "1.234567ms");
+        assert_eq!(d.format(Unit::Secs, 2), "0.00s");
+        // Rounding carries into the whole part.
+        assert_eq!(
+            Duration::from_nanos(999_999).format(Unit::Millis, 2),
+            "1.00ms"
+        );
+        assert_eq!(Duration::ZERO.format(Unit::Secs, 1), "0.0s");
+        // Precision beyond nanoseconds pads with zeros.
+        let long = Duration::from_nanos(1).format(Unit::Secs, 20);
+        assert_eq!(long, "0.00000000100000000000s");
+        let max = Duration::from_nanos(u128::MAX);
+        assert_eq!(Duration::parse(&max.format(Unit::Nanos, 0)), Ok(max));
+        for unit in [Unit::Nanos, Unit::Micros, Unit::Millis, Unit::Secs] {
+            assert_eq!(Duration::parse(&d.format(unit, 9)), Ok(d), "{unit:?}");
+        }
+    }
+
+    #[test]
+    fn test_const_constructors() {
+        const T: Duration = Duration::from_secs(2);
+        assert_eq!(T.as_nanos(), 2_000_000_000);
+        assert_eq!(Duration::from_millis(7).as_micros(), 7_000);
+        assert_eq!(
+            Duration::from_micros(u64::MAX).as_nanos(),
+            u128::from(u64::MAX) * 1_000
+        );
+        assert_eq!(
+            Duration::from(core::time::Duration::from_millis(9)),
+            Duration::from_millis(9)
+        );
+        assert_eq!(Duration::from_secs_f64(0.0), Duration::ZERO);
+        assert_eq!(
+            Duration::from_secs_f64(0.001_5),
+            Duration::from_micros(1_500)
+        );
+        assert_eq!(Duration::from_secs_f64(-0.0), Duration::ZERO);
+    }
+
+    #[test]
+    #[should_panic(expected = "from_secs_f64")]
+    fn test_from_secs_f64_rejects_negative() {
+        let _ = Duration::from_secs_f64(-1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "from_secs_f64")]
+    fn test_from_secs_f64_rejects_nan() {
+        let _ = Duration::from_secs_f64(f64::NAN);
+    }
+
     #[test]
     fn test_duration_ord() {
         let d1 = Duration::from_nanos(100);
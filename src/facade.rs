@@ -0,0 +1,287 @@
+#![cfg(feature = "metrics-facade")]
+//! A [`metrics`](https://docs.rs/metrics) facade recorder backed by [`Watch`].
+//!
+//! Libraries instrumented with the `metrics` crate's `histogram!`,
+//! `counter!` and `gauge!` macros emit into this crate once a
+//! [`FacadeRecorder`] is installed:
+//!
+//! - histograms record into the Watch. Values are taken as seconds, the
+//!   `metrics` convention for durations, and stored as nanoseconds; a
+//!   `describe_histogram!` unit of milliseconds, microseconds or nanoseconds
+//!   is honoured, and any other unit (bytes, counts, ...) is recorded as-is.
+//! - counters and gauges have no Watch equivalent; the recorder keeps them in
+//!   atomics, readable through [`FacadeRecorder::counters`] and
+//!   [`FacadeRecorder::gauges`].
+//!
+//! Labelled keys are flattened to `name{k=v,k2=v2}` in label order.
+//!
+//! # Examples
+//! ```
+//! use benchmark::{facade::FacadeRecorder, Watch};
+//! let w = Watch::new();
+//! let recorder = FacadeRecorder::new(w.clone());
+//! // `metrics_rs` is the `metrics` crate; your code just uses `metrics::`.
+//! metrics_rs::with_local_recorder(&recorder, || {
+//!     metrics_rs::histogram!("db.query").record(0.000_250);
+//!     metrics_rs::counter!("db.calls", "table" => "users").increment(1);
+//! });
+//! assert_eq!(w.snapshot()["db.query"].total_ns, 250_000);
+//! assert_eq!(recorder.counters()["db.calls{table=users}"], 1);
+//! // In a binary: `recorder.install().unwrap();`
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use metrics_rs::atomics::AtomicU64;
+use metrics_rs::{
+    Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SetRecorderError,
+    SharedString, Unit,
+};
+
+use crate::{MetricId, Watch};
+
+/// A `metrics::Recorder` that feeds histograms into a [`Watch`] (see the
+/// [module docs](self)).
+///
+/// Clones share the same Watch, counters and gauges.
+#[derive(Clone)]
+pub struct FacadeRecorder {
+    inner: Arc<Shared>,
+}
+
+struct Shared {
+    watch: Watch,
+    /// Histogram units from `describe_histogram!`, by metric name.
+    units: Mutex<HashMap<String, Unit>>,
+    histograms: RwLock<HashMap<Key, Arc<WatchHistogram>>>,
+    counters: RwLock<HashMap<Key, Arc<AtomicU64>>>,
+    gauges: RwLock<HashMap<Key, Arc<AtomicU64>>>,
+}
+
+impl fmt::Debug for FacadeRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FacadeRecorder")
+            .field("watch", &self.inner.watch)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FacadeRecorder {
+    /// Create a recorder that records histograms into `watch`.
+    pub fn new(watch: Watch) -> Self {
+        Self {
+            inner: Arc::new(Shared {
+                watch,
+                units: Mutex::new(HashMap::new()),
+                histograms: RwLock::new(HashMap::new()),
+                counters: RwLock::new(HashMap::new()),
+                gauges: RwLock::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Install a clone of this recorder as the process-wide `metrics` recorder.
+    ///
+    /// # Errors
+    /// Fails if a global recorder was already installed.
+    pub fn install(&self) -> Result<(), SetRecorderError<Self>> {
+        metrics_rs::set_global_recorder(self.clone())
+    }
+
+    /// The Watch histograms are recorded into.
+    pub fn watch(&self) -> &Watch {
+        &self.inner.watch
+    }
+
+    /// Current value of every counter, by flattened key.
+    pub fn counters(&self) -> HashMap<String, u64> {
+        read(&self.inner.counters)
+            .iter()
+            .map(|(k, v)| (flatten(k), v.load(Ordering::Acquire)))
+            .collect()
+    }
+
+    /// Current value of every gauge, by flattened key.
+    pub fn gauges(&self) -> HashMap<String, f64> {
+        read(&self.inner.gauges)
+            .iter()
+            .map(|(k, v)| (flatten(k), f64::from_bits(v.load(Ordering::Acquire))))
+            .collect()
+    }
+}
+
+/// Histogram handle: one Watch metric with a fixed value scale.
+struct WatchHistogram {
+    watch: Watch,
+    id: MetricId,
+    scale: f64,
+}
+
+impl HistogramFn for WatchHistogram {
+    fn record(&self, value: f64) {
+        self.watch.record_id(&self.id, to_u64(value * self.scale));
+    }
+}
+
+/// Nanoseconds per unit for duration units; other units are recorded as-is.
+fn scale_of(unit: Option<Unit>) -> f64 {
+    match unit {
+        None | Some(Unit::Seconds) => 1e9,
+        Some(Unit::Milliseconds) => 1e6,
+        Some(Unit::Microseconds) => 1e3,
+        Some(_) => 1.0,
+    }
+}
+
+/// Round a scaled value to `u64`, mapping negatives and NaN to 0 and saturating.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn to_u64(v: f64) -> u64 {
+    if v.is_nan() || v <= 0.0 {
+        0
+    } else if v >= u64::MAX as f64 {
+        u64::MAX
+    } else {
+        v.round() as u64
+    }
+}
+
+/// `name` or `name{k=v,...}`.
+fn flatten(key: &Key) -> String {
+    let mut labels = key.labels().peekable();
+    if labels.peek().is_none() {
+        return key.name().to_owned();
+    }
+    let labels: Vec<String> = labels
+        .map(|l| format!("{}={}", l.key(), l.value()))
+        .collect();
+    format!("{}{{{}}}", key.name(), labels.join(","))
+}
+
+fn read<K, V>(lock: &RwLock<HashMap<K, V>>) -> std::sync::RwLockReadGuard<'_, HashMap<K, V>> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Look `key` up under the read lock, inserting `make()` under the write lock.
+fn get_or_insert<V: Clone>(
+    lock: &RwLock<HashMap<Key, V>>,
+    key: &Key,
+    make: impl FnOnce() -> V,
+) -> V {
+    if let Some(v) = read(lock).get(key) {
+        return v.clone();
+    }
+    lock.write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(key.clone())
+        .or_insert_with(make)
+        .clone()
+}
+
+impl Recorder for FacadeRecorder {
+    fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_histogram(&self, key: KeyName, unit: Option<Unit>, _description: SharedString) {
+        if let Some(unit) = unit {
+            self.inner
+                .units
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(key.as_str().to_owned(), unit);
+        }
+    }
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        Counter::from_arc(get_or_insert(&self.inner.counters, key, || {
+            Arc::new(AtomicU64::new(0))
+        }))
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(get_or_insert(&self.inner.gauges, key, || {
+            Arc::new(AtomicU64::new(0.0_f64.to_bits()))
+        }))
+    }
+
+    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(get_or_insert(&self.inner.histograms, key, || {
+            let unit = self
+                .inner
+                .units
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(key.name())
+                .copied();
+            Arc::new(WatchHistogram {
+                watch: self.inner.watch.clone(),
+                id: self.inner.watch.register(&flatten(key)),
+                scale: scale_of(unit),
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_rs::{counter, describe_histogram, gauge, histogram, with_local_recorder};
+    use std::time::Duration;
+
+    #[test]
+    fn test_histograms_record_seconds_as_ns() {
+        let w = Watch::new();
+        let r = FacadeRecorder::new(w.clone());
+        with_local_recorder(&r, || {
+            histogram!("rpc").record(Duration::from_micros(3));
+            histogram!("rpc").record(0.000_005);
+            histogram!("rpc", "peer" => "a", "code" => "ok").record(-1.0);
+            histogram!("rpc").record(f64::NAN);
+        });
+        let s = w.snapshot();
+        assert_eq!(s["rpc"].count, 3);
+        // NaN maps to 0, which the Watch clamps to its lowest bound.
+        assert_eq!(s["rpc"].min, 1);
+        assert_eq!(s["rpc"].total_ns, 8_001);
+        assert_eq!(s["rpc{peer=a,code=ok}"].count, 1);
+    }
+
+    #[test]
+    fn test_described_units_change_scale() {
+        let w = Watch::new();
+        let r = FacadeRecorder::new(w.clone());
+        with_local_recorder(&r, || {
+            describe_histogram!("ms", Unit::Milliseconds, "latency");
+            describe_histogram!("bytes", Unit::Bytes, "payload");
+            histogram!("ms").record(2.0);
+            histogram!("bytes").record(512.0);
+        });
+        let s = w.snapshot();
+        assert_eq!(s["ms"].total_ns, 2_000_000);
+        assert_eq!(s["bytes"].total_ns, 512);
+    }
+
+    #[test]
+    fn test_counters_and_gauges_are_shared_across_registrations() {
+        let r = FacadeRecorder::new(Watch::new());
+        with_local_recorder(&r, || {
+            counter!("hits").increment(2);
+            counter!("hits").increment(3);
+            counter!("hits", "kind" => "miss").absolute(9);
+            gauge!("queue").set(4.0);
+            gauge!("queue").decrement(1.5);
+        });
+        let c = r.counters();
+        assert_eq!(c["hits"], 5);
+        assert_eq!(c["hits{kind=miss}"], 9);
+        assert!((r.gauges()["queue"] - 2.5).abs() < f64::EPSILON);
+        assert!(r.watch().snapshot().is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "metrics")]
+//! Process-wide runtime filter selecting which metrics `Watch` records.
+//!
+//! [`enable_only`] takes a comma-separated list of patterns, in the spirit
+//! of `env_logger` filters: `db` enables `db` and every `db.*` metric,
+//! `*` matches any run of characters (`http.*.latency`). Records for other
+//! names are dropped. Each metric evaluates the filter once and caches the
+//! answer until the filter changes again, so a set filter costs two atomic
+//! loads per record and an unset one costs one.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{PoisonError, RwLock};
+
+/// The active filter; `None` enables every metric.
+static FILTER: RwLock<Option<Filter>> = RwLock::new(None);
+/// Bumped on every filter change; 0 until a filter is first set.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Record only metrics matching one of the comma-separated `patterns`, in
+/// every `Watch` of the process.
+///
+/// A pattern without `*` matches that name and the names below it
+/// (`"db"` matches `db` and `db.query`, not `dbx`); `*` matches any run of
+/// characters, dots included. An empty list disables every metric. Replaces
+/// any previous filter; metrics re-evaluate it on their next record.
+///
+/// Samples are only dropped from now on: filtered-out metrics keep what
+/// they already recorded and may still appear in snapshots.
+///
+/// # Examples
+/// ```
+/// use benchmark::{enable_all, enable_only, is_enabled};
+/// enable_only("db.*, cache");
+/// assert!(is_enabled("db.query"));
+/// assert!(is_enabled("cache.hit"));
+/// assert!(!is_enabled("http.get"));
+/// enable_all();
+/// assert!(is_enabled("http.get"));
+/// ```
+pub fn enable_only(patterns: &str) {
+    set(Some(Filter::parse(patterns)));
+}
+
+/// Remove the filter set by [`enable_only`]; every metric records again.
+pub fn enable_all() {
+    set(None);
+}
+
+/// True if the current filter lets `name` record.
+///
+/// Evaluates the patterns on every call; usable as the condition of
+/// [`stopwatch_if!`](crate::stopwatch_if) to skip the clock reads too.
+pub fn is_enabled(name: &str) -> bool {
+    FILTER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map_or(true, |f| f.allows(name))
+}
+
+fn set(filter: Option<Filter>) {
+    let mut current = FILTER.write().unwrap_or_else(PoisonError::into_inner);
+    *current = filter;
+    // Bumped under the lock so a reader seeing the new generation also
+    // sees the new filter.
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+/// Parsed [`enable_only`] patterns.
+#[derive(Debug)]
+struct Filter {
+    patterns: Vec<String>,
+}
+
+impl Filter {
+    fn parse(patterns: &str) -> Self {
+        Self {
+            patterns: patterns
+                .split(',')
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    fn allows(&self, name: &str) -> bool {
+        self.patterns.iter().any(|p| {
+            if p.contains('*') {
+                glob_match(p.as_bytes(), name.as_bytes())
+            } else {
+                name.strip_prefix(p.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            }
+        })
+    }
+}
+
+/// `*`-only glob match, backtracking to the last star on a mismatch.
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    p = sp + 1;
+                    n = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// A metric's cached filter decision, stamped with the filter generation
+/// it was made under.
+#[derive(Debug, Default)]
+pub(crate) struct FilterCache(AtomicU64);
+
+impl FilterCache {
+    /// True if metric `name` may record under the current filter.
+    #[inline]
+    pub(crate) fn allows(&self, name: &str) -> bool {
+        let generation = GENERATION.load(Ordering::Acquire);
+        if generation == 0 {
+            return true;
+        }
+        let stamp = self.0.load(Ordering::Relaxed);
+        if stamp >> 1 == generation {
+            return stamp & 1 == 1;
+        }
+        let on = is_enabled(name);
+        self.0
+            .store(generation << 1 | u64::from(on), Ordering::Relaxed);
+        on
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_match_names_and_children() {
+        let f = Filter::parse(" db , http.*.latency,,*.slow");
+        assert!(f.allows("db"));
+        assert!(f.allows("db.query"));
+        assert!(!f.allows("dbx"));
+        assert!(f.allows("http.get.latency"));
+        assert!(f.allows("http.a.b.latency"));
+        assert!(!f.allows("http.get"));
+        assert!(f.allows("render.slow"));
+        assert!(!Filter::parse("").allows("db"));
+    }
+
+    #[test]
+    fn test_glob_backtracks_across_stars() {
+        assert!(glob_match(b"*", b""));
+        assert!(glob_match(b"a*b*c", b"axxbyybzc"));
+        assert!(glob_match(b"*.q", b"a.b.q"));
+        assert!(!glob_match(b"a*b", b"acbd"));
+        assert!(!glob_match(b"abc", b"ab"));
+    }
+}
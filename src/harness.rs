@@ -0,0 +1,354 @@
+#![cfg(feature = "benchmark")]
+//! A `cargo bench` harness built on [`Runner`].
+//!
+//! With `harness = false` on a `[[bench]]` target, a `benches/*.rs` file only
+//! needs bench functions and a [`benchmark_main!`](crate::benchmark_main)
+//! line listing them:
+//!
+//! ```no_run
+//! use benchmark::{benchmark_main, Bencher};
+//!
+//! fn parse(b: &mut Bencher) {
+//!     let input = "12345".to_string(); // setup, not timed
+//!     b.iter(move || input.parse::<u64>().unwrap());
+//! }
+//!
+//! fn format(b: &mut Bencher) {
+//!     b.iter(|| 12345u64.to_string());
+//! }
+//!
+//! benchmark_main!(parse, format);
+//! ```
+//!
+//! The listed functions are the registration: attribute-style registration
+//! (`#[bench_fn]`) would need a proc-macro crate, which this zero-dependency
+//! crate avoids.
+//!
+//! Command-line flags (after `cargo bench --bench <target> --`):
+//!
+//! - everything [`Runner::args`] accepts, notably `--filter <s>` (or a bare
+//!   `<s>`), `--iters <n>`, `--warmup <n>`, `--warmup-ms <ms>`, `--seed <n>`
+//!   and `--tags <a,b>`
+//! - `--save-baseline <name>`: store each benchmark's mean and median
+//! - `--baseline <name>`: compare against a stored baseline
+//! - `--baseline-dir <dir>`: where baselines live (default
+//!   `$CARGO_TARGET_DIR/benchmark`, else `target/benchmark`)
+//!
+//! The `BENCHMARK_ITERS`, `BENCHMARK_WARMUP_MS` and `BENCHMARK_FILTER`
+//! environment variables set the same options for CI; flags take precedence.
+//!
+//! Baselines are small tab-separated files (`<dir>/<name>.tsv`).
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::runner::flag_value;
+use crate::{BenchResult, Duration, Runner};
+
+/// Passed to each bench function to register the code to time.
+pub struct Bencher {
+    f: Option<Box<dyn FnMut()>>,
+    tags: Vec<String>,
+}
+
+impl std::fmt::Debug for Bencher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Bencher")
+            .field("registered", &self.f.is_some())
+            .field("tags", &self.tags)
+            .finish()
+    }
+}
+
+impl Bencher {
+    /// Time `routine` once per iteration. Its output is passed through
+    /// [`black_box`](crate::black_box); anything done before this call is
+    /// setup and not timed.
+    // Named after the familiar `Bencher::iter` of other harnesses.
+    #[allow(clippy::iter_not_returning_iterator)]
+    pub fn iter<T, F>(&mut self, mut routine: F)
+    where
+        F: FnMut() -> T + 'static,
+    {
+        self.f = Some(Box::new(move || {
+            crate::black_box(routine());
+        }));
+    }
+
+    /// Tag the benchmark for `--tags` / `--skip-tags` selection.
+    pub fn tags<I, S>(&mut self, tags: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// A bench function as listed in `benchmark_main!`.
+type BenchFn = fn(&mut Bencher);
+
+/// Registers bench functions, runs them, prints results and manages
+/// baselines. Usually created by [`benchmark_main!`](crate::benchmark_main).
+#[derive(Debug)]
+pub struct Harness {
+    runner: Runner,
+    benches: Vec<(&'static str, BenchFn)>,
+    save_baseline: Option<String>,
+    baseline: Option<String>,
+    baseline_dir: PathBuf,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    /// Create a harness with a default [`Runner`] and no flags applied.
+    pub fn new() -> Self {
+        let target =
+            std::env::var_os("CARGO_TARGET_DIR").map_or_else(|| "target".into(), PathBuf::from);
+        Self {
+            runner: Runner::new(),
+            benches: Vec::new(),
+            save_baseline: None,
+            baseline: None,
+            baseline_dir: target.join("benchmark"),
+        }
+    }
+
+    /// Create a harness configured from the environment (see
+    /// [`Runner::env`]) and then the process command line.
+    pub fn from_args() -> Self {
+        Self::new()
+            .with_runner(Runner::new().env())
+            .args(std::env::args().skip(1))
+    }
+
+    /// Apply the baseline flags and pass everything else to [`Runner::args`].
+    #[must_use]
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut rest: Vec<String> = Vec::new();
+        let mut it = args.into_iter();
+        while let Some(arg) = it.next() {
+            let arg = arg.as_ref();
+            if let Some(v) = flag_value(arg, "--save-baseline", &mut it) {
+                self.save_baseline = Some(v);
+            } else if let Some(v) = flag_value(arg, "--baseline-dir", &mut it) {
+                self.baseline_dir = PathBuf::from(v);
+            } else if let Some(v) = flag_value(arg, "--baseline", &mut it) {
+                self.baseline = Some(v);
+            } else {
+                rest.push(arg.to_string());
+            }
+        }
+        self.runner = std::mem::take(&mut self.runner).args(rest);
+        self
+    }
+
+    /// Replace the runner (e.g. to set iterations in code); call before
+    /// [`args`](Self::args) so flags still override it.
+    #[must_use]
+    pub fn with_runner(mut self, runner: Runner) -> Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Register bench function `f` as `name`.
+    #[must_use]
+    pub fn bench(mut self, name: &'static str, f: BenchFn) -> Self {
+        self.benches.push((name, f));
+        self
+    }
+
+    /// Run the benchmarks, print a result line per benchmark (with the
+    /// change against `--baseline`, if given) and save `--save-baseline`.
+    ///
+    /// Baseline I/O errors are reported on stderr and do not abort the run.
+    pub fn run(&mut self) -> Vec<BenchResult> {
+        let mut runner = std::mem::take(&mut self.runner);
+        for &(name, f) in &self.benches {
+            let mut b = Bencher {
+                f: None,
+                tags: Vec::new(),
+            };
+            f(&mut b);
+            if let Some(routine) = b.f {
+                runner = runner.bench_tagged(name, b.tags, routine);
+            } else {
+                eprintln!("benchmark harness: '{name}' never called Bencher::iter; skipped");
+            }
+        }
+        let results = runner.run();
+        self.runner = runner;
+
+        let baseline = self.baseline.as_ref().and_then(|name| {
+            let path = self.baseline_path(name);
+            match load_baseline(&path) {
+                Ok(b) => Some(b),
+                Err(e) => {
+                    eprintln!(
+                        "benchmark harness: cannot read baseline {}: {e}",
+                        path.display()
+                    );
+                    None
+                }
+            }
+        });
+        print!("{}", render(&results, baseline.as_ref()));
+
+        if let Some(name) = &self.save_baseline {
+            let path = self.baseline_path(name);
+            match save_baseline(&path, &results) {
+                Ok(()) => println!("baseline saved to {}", path.display()),
+                Err(e) => eprintln!(
+                    "benchmark harness: cannot save baseline {}: {e}",
+                    path.display()
+                ),
+            }
+        }
+        results
+    }
+
+    fn baseline_path(&self, name: &str) -> PathBuf {
+        self.baseline_dir.join(format!("{name}.tsv"))
+    }
+}
+
+/// One line per result, aligned, with the mean's change against `baseline`.
+#[allow(clippy::cast_precision_loss)]
+fn render(results: &[BenchResult], baseline: Option<&HashMap<String, u128>>) -> String {
+    let width = results
+        .iter()
+        .map(|r| r.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for r in results {
+        let _ = write!(
+            out,
+            "{:<width$}  mean {:>9}  median {:>9}  min {:>9}  max {:>9}",
+            r.name,
+            r.mean.to_string(),
+            r.median.to_string(),
+            r.min.to_string(),
+            r.max.to_string()
+        );
+        match baseline.map(|b| b.get(&r.name)) {
+            Some(Some(&before)) => {
+                let change = (r.mean.as_nanos() as f64 - before as f64) / before.max(1) as f64;
+                let _ = write!(
+                    out,
+                    "  {:+.1}% vs baseline ({})",
+                    change * 100.0,
+                    Duration::from_nanos(before)
+                );
+            }
+            Some(None) => out.push_str("  (new)"),
+            None => {}
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn save_baseline(path: &Path, results: &[BenchResult]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut out = String::from("# name\tmean_ns\tmedian_ns\n");
+    for r in results {
+        let _ = writeln!(
+            out,
+            "{}\t{}\t{}",
+            r.name,
+            r.mean.as_nanos(),
+            r.median.as_nanos()
+        );
+    }
+    fs::write(path, out)
+}
+
+/// Mean (ns) per benchmark name; malformed lines are skipped.
+fn load_baseline(path: &Path) -> io::Result<HashMap<String, u128>> {
+    let text = fs::read_to_string(path)?;
+    Ok(text
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| {
+            let mut cols = l.split('\t');
+            let name = cols.next()?;
+            let mean = cols.next()?.parse().ok()?;
+            Some((name.to_string(), mean))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spin(b: &mut Bencher) {
+        b.tags(["cpu"]).iter(|| (0..64u64).sum::<u64>());
+    }
+
+    fn idle(b: &mut Bencher) {
+        b.iter(|| ());
+    }
+
+    fn forgot(_b: &mut Bencher) {}
+
+    #[test]
+    fn test_flags_and_baseline_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("benchmark-harness-{}", std::process::id()));
+        let dir_arg = format!("--baseline-dir={}", dir.display());
+        let mut h = Harness::new()
+            .args(["--iters", "3", "--save-baseline", "main", dir_arg.as_str()])
+            .bench("spin", spin)
+            .bench("idle", idle)
+            .bench("forgot", forgot);
+        let results = h.run();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].tags, ["cpu"]);
+        assert!(results.iter().all(|r| r.samples.len() == 3));
+
+        let saved = load_baseline(&dir.join("main.tsv")).unwrap();
+        assert_eq!(saved.len(), 2);
+        assert_eq!(saved["spin"], results[0].mean.as_nanos());
+
+        let mut h = Harness::new()
+            .args(["spin", "--iters=1", "--baseline", "main", dir_arg.as_str()])
+            .bench("spin", spin)
+            .bench("idle", idle);
+        let results = h.run();
+        assert_eq!(results.len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_render_against_baseline() {
+        let mut r = Runner::new()
+            .iterations(1)
+            .bench("a", || {})
+            .bench("bb", || {})
+            .run();
+        r[0].mean = Duration::from_nanos(150);
+        let baseline: HashMap<String, u128> = [("a".to_string(), 100)].into_iter().collect();
+        let text = render(&r, Some(&baseline));
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("a   mean     150ns"));
+        assert!(lines[0].ends_with("+50.0% vs baseline (100ns)"));
+        assert!(lines[1].ends_with("(new)"));
+        assert!(!render(&r, None).contains("baseline"));
+    }
+}
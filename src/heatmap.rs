@@ -0,0 +1,142 @@
+#![cfg(all(feature = "std", feature = "metrics"))]
+//! Latency heatmaps over `Watch` history.
+//!
+//! Every history capture also keeps the bucket counts recorded since the
+//! previous capture. A [`Heatmap`] lays those out as a time × latency matrix:
+//! columns are fixed-width time slots ending now, rows are power-of-two
+//! latency bands, and each cell counts the samples captured in that slot
+//! that fell in that band. This is the shape Grafana-style heatmap panels
+//! expect.
+
+use crate::histogram::HistogramSnapshot;
+
+/// A time × latency matrix of sample counts, from [`Watch::heatmap`](crate::Watch::heatmap).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Heatmap {
+    /// Start of the first column (ns since UNIX epoch).
+    pub start_ns: u128,
+    /// Width of each column (ns).
+    pub resolution_ns: u64,
+    /// Latency band of each row as `[low, high)` in ns, ascending. Bands are
+    /// powers of two; only the range between the lowest and highest band
+    /// with samples is included.
+    pub buckets: Vec<(u64, u64)>,
+    /// Sample counts indexed `[column][row]`, oldest column first.
+    pub counts: Vec<Vec<u64>>,
+}
+
+impl Heatmap {
+    /// Start of column `i` (ns since UNIX epoch).
+    pub fn column_start_ns(&self, i: usize) -> u128 {
+        self.start_ns + u128::from(self.resolution_ns) * i as u128
+    }
+
+    /// Total samples in the matrix.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().flatten().sum()
+    }
+}
+
+/// Power-of-two band of `value`: 0 for 0, else `k + 1` for `[2^k, 2^(k+1))`.
+fn band(value: u64) -> u32 {
+    64 - value.leading_zeros()
+}
+
+/// `[low, high)` of band `b`, saturating at `u64::MAX`.
+fn band_bounds(b: u32) -> (u64, u64) {
+    match b {
+        0 => (0, 1),
+        64 => (1 << 63, u64::MAX),
+        b => (1 << (b - 1), 1 << b),
+    }
+}
+
+/// Lay per-capture `(epoch_ns, delta)` intervals out over the `window_ns`
+/// ending at `end_ns`, in columns of `resolution_ns`.
+pub(crate) fn build(
+    intervals: &[(u128, HistogramSnapshot)],
+    end_ns: u128,
+    resolution_ns: u64,
+    window_ns: u64,
+) -> Heatmap {
+    let resolution_ns = resolution_ns.max(1);
+    let columns = (window_ns / resolution_ns + u64::from(window_ns % resolution_ns != 0)).max(1);
+    let start_ns = end_ns.saturating_sub(u128::from(resolution_ns) * u128::from(columns));
+    let in_window: Vec<(usize, &HistogramSnapshot)> = intervals
+        .iter()
+        .filter(|(ts, _)| (start_ns..=end_ns).contains(ts))
+        .map(|(ts, delta)| {
+            let col = (ts - start_ns) / u128::from(resolution_ns);
+            #[allow(clippy::cast_possible_truncation)]
+            let col = col.min(u128::from(columns - 1)) as usize;
+            (col, delta)
+        })
+        .collect();
+    let bands = in_window
+        .iter()
+        .flat_map(|(_, delta)| delta.buckets().map(|(low, _, _)| band(low)));
+    let (lo, hi) = bands.fold((u32::MAX, 0), |(lo, hi), b| (lo.min(b), hi.max(b)));
+    #[allow(clippy::cast_possible_truncation)]
+    let mut counts = vec![Vec::new(); columns as usize];
+    if lo > hi {
+        return Heatmap {
+            start_ns,
+            resolution_ns,
+            buckets: Vec::new(),
+            counts,
+        };
+    }
+    let rows = (hi - lo + 1) as usize;
+    for column in &mut counts {
+        column.resize(rows, 0);
+    }
+    for (col, delta) in in_window {
+        for (low, _, count) in delta.buckets() {
+            counts[col][(band(low) - lo) as usize] += count;
+        }
+    }
+    Heatmap {
+        start_ns,
+        resolution_ns,
+        buckets: (lo..=hi).map(band_bounds).collect(),
+        counts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::histogram::Histogram;
+
+    fn delta(values: &[u64]) -> HistogramSnapshot {
+        let h = Histogram::new();
+        for &v in values {
+            h.record(v);
+        }
+        h.snapshot()
+    }
+
+    #[test]
+    fn test_columns_and_power_of_two_rows() {
+        let intervals = [
+            (100, delta(&[5])),
+            (1_050, delta(&[3, 6, 7])),
+            (1_900, delta(&[40])),
+            (2_500, delta(&[1_000])),
+        ];
+        let map = build(&intervals, 2_000, 1_000, 1_500);
+        assert_eq!(map.start_ns, 0);
+        assert_eq!(map.column_start_ns(1), 1_000);
+        assert_eq!(map.buckets, [(2, 4), (4, 8), (8, 16), (16, 32), (32, 64)]);
+        assert_eq!(map.counts, [[0, 1, 0, 0, 0], [1, 2, 0, 0, 1]]);
+        assert_eq!(map.total(), 5);
+    }
+
+    #[test]
+    fn test_empty_window_has_no_rows() {
+        let map = build(&[(10, delta(&[1]))], 5_000, 1_000, 2_000);
+        assert!(map.buckets.is_empty());
+        assert_eq!(map.counts, [Vec::<u64>::new(), Vec::new()]);
+        assert_eq!(band_bounds(64), (1 << 63, u64::MAX));
+    }
+}
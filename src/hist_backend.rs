@@ -12,18 +12,58 @@
 ///
 /// This is public because `Watch` and `WatchBuilder` are public generic aliases
 /// bound by this trait, and Rust requires public items' bounds to be public.
-pub trait HistBackend {
+pub trait HistBackend: Send + Sync + 'static {
+    /// Largest value recorded without clamping once
+    /// [`with_auto_resize`](Self::with_auto_resize) is applied.
+    const HIGHEST: u64 = u64::MAX;
+
     fn new() -> Self
     where
         Self: Sized;
 
+    /// Create a histogram with `n` sub-buckets per power of two where the
+    /// backend supports it; others ignore `n`.
+    fn with_sub_buckets(_n: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new()
+    }
+
+    /// Keep per-bucket sums where the backend supports it; others ignore it.
+    #[must_use]
+    fn with_bucket_sums(self) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+
+    /// Grow the trackable range on demand where the backend clamps; others
+    /// ignore it.
+    #[must_use]
+    fn with_auto_resize(self) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+
     fn record(&self, value_ns: u64);
+    /// Record `value_ns` and report whether the backend clamped it and the
+    /// bucket it landed in.
+    fn record_returning(&self, value_ns: u64) -> crate::histogram::RecordInfo;
     fn record_duration(&self, duration: core::time::Duration);
 
     fn min(&self) -> Option<u64>;
     fn max(&self) -> Option<u64>;
     fn mean(&self) -> Option<f64>;
+    fn sum(&self) -> u64;
+    fn variance(&self) -> Option<f64>;
+    fn stddev(&self) -> Option<f64>;
     fn count(&self) -> u64;
+    /// Samples clamped into the trackable range.
+    fn saturated(&self) -> u64;
     fn is_empty(&self) -> bool;
 
     fn percentile(&self, p: f64) -> Option<u64>;
@@ -32,6 +72,12 @@ pub trait HistBackend {
     fn percentile_duration(&self, p: f64) -> Option<core::time::Duration>;
     fn percentiles(&self, ps: &[f64]) -> Vec<Option<u64>>;
 
+    fn snapshot(&self) -> crate::histogram::HistogramSnapshot;
+
+    /// Move the contents into a snapshot and leave the histogram empty,
+    /// without losing records that race with it.
+    fn take_snapshot(&self) -> crate::histogram::HistogramSnapshot;
+
     fn reset(&self);
 }
 
@@ -43,11 +89,26 @@ impl HistBackend for crate::histogram::FastHistogram {
         <crate::histogram::FastHistogram>::new()
     }
 
+    #[inline]
+    fn with_sub_buckets(n: usize) -> Self {
+        crate::histogram::FastHistogram::with_sub_buckets(n)
+    }
+
+    #[inline]
+    fn with_bucket_sums(self) -> Self {
+        crate::histogram::FastHistogram::with_bucket_sums(self)
+    }
+
     #[inline]
     fn record(&self, value_ns: u64) {
         crate::histogram::FastHistogram::record(self, value_ns);
     }
 
+    #[inline]
+    fn record_returning(&self, value_ns: u64) -> crate::histogram::RecordInfo {
+        crate::histogram::FastHistogram::record_returning(self, value_ns)
+    }
+
     #[inline]
     fn record_duration(&self, duration: core::time::Duration) {
         crate::histogram::FastHistogram::record_duration(self, duration);
@@ -68,11 +129,31 @@ impl HistBackend for crate::histogram::FastHistogram {
         crate::histogram::FastHistogram::mean(self)
     }
 
+    #[inline]
+    fn sum(&self) -> u64 {
+        crate::histogram::FastHistogram::sum(self)
+    }
+
+    #[inline]
+    fn variance(&self) -> Option<f64> {
+        crate::histogram::FastHistogram::variance(self)
+    }
+
+    #[inline]
+    fn stddev(&self) -> Option<f64> {
+        crate::histogram::FastHistogram::stddev(self)
+    }
+
     #[inline]
     fn count(&self) -> u64 {
         crate::histogram::FastHistogram::count(self)
     }
 
+    #[inline]
+    fn saturated(&self) -> u64 {
+        crate::histogram::FastHistogram::saturated(self)
+    }
+
     #[inline]
     fn is_empty(&self) -> bool {
         crate::histogram::FastHistogram::is_empty(self)
@@ -103,6 +184,16 @@ impl HistBackend for crate::histogram::FastHistogram {
         crate::histogram::FastHistogram::percentiles(self, ps)
     }
 
+    #[inline]
+    fn snapshot(&self) -> crate::histogram::HistogramSnapshot {
+        crate::histogram::FastHistogram::snapshot(self)
+    }
+
+    #[inline]
+    fn take_snapshot(&self) -> crate::histogram::HistogramSnapshot {
+        crate::histogram::FastHistogram::take_snapshot(self)
+    }
+
     #[inline]
     fn reset(&self) {
         crate::histogram::FastHistogram::reset(self);
@@ -112,16 +203,28 @@ impl HistBackend for crate::histogram::FastHistogram {
 // Implement for the HDR backend when enabled
 #[cfg(feature = "hdr")]
 impl HistBackend for crate::hist_hdr::Histogram {
+    const HIGHEST: u64 = crate::hist_hdr::AUTO_HIGHEST;
+
     #[inline]
     fn new() -> Self {
         <crate::hist_hdr::Histogram>::new()
     }
 
+    #[inline]
+    fn with_auto_resize(self) -> Self {
+        crate::hist_hdr::Histogram::with_auto_resize(self)
+    }
+
     #[inline]
     fn record(&self, value_ns: u64) {
         crate::hist_hdr::Histogram::record(self, value_ns);
     }
 
+    #[inline]
+    fn record_returning(&self, value_ns: u64) -> crate::histogram::RecordInfo {
+        crate::hist_hdr::Histogram::record_returning(self, value_ns)
+    }
+
     #[inline]
     fn record_duration(&self, duration: core::time::Duration) {
         crate::hist_hdr::Histogram::record_duration(self, duration);
@@ -142,11 +245,31 @@ impl HistBackend for crate::hist_hdr::Histogram {
         crate::hist_hdr::Histogram::mean(self)
     }
 
+    #[inline]
+    fn sum(&self) -> u64 {
+        crate::hist_hdr::Histogram::sum(self)
+    }
+
+    #[inline]
+    fn variance(&self) -> Option<f64> {
+        crate::hist_hdr::Histogram::variance(self)
+    }
+
+    #[inline]
+    fn stddev(&self) -> Option<f64> {
+        crate::hist_hdr::Histogram::stddev(self)
+    }
+
     #[inline]
     fn count(&self) -> u64 {
         crate::hist_hdr::Histogram::count(self)
     }
 
+    #[inline]
+    fn saturated(&self) -> u64 {
+        crate::hist_hdr::Histogram::saturated(self)
+    }
+
     #[inline]
     fn is_empty(&self) -> bool {
         crate::hist_hdr::Histogram::is_empty(self)
@@ -177,6 +300,16 @@ impl HistBackend for crate::hist_hdr::Histogram {
         crate::hist_hdr::Histogram::percentiles(self, ps)
     }
 
+    #[inline]
+    fn snapshot(&self) -> crate::histogram::HistogramSnapshot {
+        crate::hist_hdr::Histogram::snapshot(self)
+    }
+
+    #[inline]
+    fn take_snapshot(&self) -> crate::histogram::HistogramSnapshot {
+        crate::hist_hdr::Histogram::take_snapshot(self)
+    }
+
     #[inline]
     fn reset(&self) {
         crate::hist_hdr::Histogram::reset(self);
@@ -5,7 +5,18 @@
 //! with a thread-safe `RwLock` and mirrors the public API of the fast default
 //! histogram for seamless swapping.
 
-use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::histogram::RecordInfo;
+
+/// Trackable range shared by construction and clamping.
+const LOWEST: u64 = 1;
+const HIGHEST: u64 = 3_600_000_000_000;
+/// Ceiling of an auto-resizing histogram, the largest range `hdrhistogram` grows to.
+pub(crate) const AUTO_HIGHEST: u64 = u64::MAX / 2;
+/// `log2` of the sub-bucket count for 3 significant figures (2048 sub-buckets).
+const SUB_BUCKET_MAGNITUDE: u32 = 11;
 
 /// HDR-backed histogram adapter.
 ///
@@ -14,6 +25,9 @@ use std::sync::RwLock;
 #[derive(Debug)]
 pub struct Histogram {
     inner: RwLock<hdrhistogram::Histogram<u64>>, // values are nanoseconds
+    sum: AtomicU64,                              // exact sum of recorded (clamped) values
+    saturated: AtomicU64,                        // samples clamped into the trackable range
+    auto_resize: bool,
 }
 
 impl Default for Histogram {
@@ -27,28 +41,72 @@ impl Histogram {
     /// Creates a new HDR-backed histogram with 1ns..~1h bounds and 3 sigfigs.
     pub fn new() -> Self {
         // 1ns .. ~1h, 3 significant figures by default to match Watch defaults.
-        let h = hdrhistogram::Histogram::new_with_bounds(1, 3_600_000_000_000u64, 3)
-            .unwrap_or_else(|e| {
-                // Bounds are compile-time constants and valid. If construction fails,
-                // avoid panicking in release: log via debug assertion and fall back
-                // to a histogram with default dynamic max using the same sigfigs.
-                debug_assert!(false, "HDR bounds init failed: {e}");
-                hdrhistogram::Histogram::new(3).unwrap_or_else(|_| {
-                    hdrhistogram::Histogram::new_with_max(3_600_000_000_000u64, 3).unwrap()
-                })
-            });
+        let h = hdrhistogram::Histogram::new_with_bounds(LOWEST, HIGHEST, 3).unwrap_or_else(|e| {
+            // Bounds are compile-time constants and valid. If construction fails,
+            // avoid panicking in release: log via debug assertion and fall back
+            // to a histogram with default dynamic max using the same sigfigs.
+            debug_assert!(false, "HDR bounds init failed: {e}");
+            hdrhistogram::Histogram::new(3)
+                .unwrap_or_else(|_| hdrhistogram::Histogram::new_with_max(HIGHEST, 3).unwrap())
+        });
         Self {
             inner: RwLock::new(h),
+            sum: AtomicU64::new(0),
+            saturated: AtomicU64::new(0),
+            auto_resize: false,
         }
     }
 
+    /// Grow the trackable range on demand instead of clamping at 1h, like
+    /// `hdrhistogram`'s `auto(true)`. Values up to `u64::MAX / 2` are then
+    /// recorded as they are, at the cost of a counts-array resize (under the
+    /// write lock) the first time a larger power of two shows up.
+    #[must_use]
+    pub fn with_auto_resize(mut self) -> Self {
+        self.inner
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .auto(true);
+        self.auto_resize = true;
+        self
+    }
+
+    #[inline]
+    /// Saturate `value_ns` to the trackable range: `[1ns, 1h]`, or up to
+    /// `u64::MAX / 2` when auto-resizing.
+    fn clamp(&self, value_ns: u64) -> u64 {
+        let highest = if self.auto_resize {
+            AUTO_HIGHEST
+        } else {
+            HIGHEST
+        };
+        value_ns.clamp(LOWEST, highest)
+    }
+
     #[inline]
     /// Record a value in nanoseconds.
     pub fn record(&self, value_ns: u64) {
-        // Saturate to configured bounds [1ns, 1h]
-        let v = value_ns.clamp(1, 3_600_000_000_000u64);
-        if let Ok(mut h) = self.inner.write() {
-            let _ = h.record(v);
+        self.record_returning(value_ns);
+    }
+
+    #[inline]
+    /// Record a value and report its bucket index and whether it was clamped
+    /// to the trackable range.
+    pub fn record_returning(&self, value_ns: u64) -> RecordInfo {
+        let v = self.clamp(value_ns);
+        let clamped = v != value_ns;
+        let mut h = self.write();
+        if h.record(v).is_ok() {
+            // Still under the lock, so snapshots see count and sum together.
+            self.sum.fetch_add(v, Ordering::Relaxed);
+            if clamped {
+                self.saturated.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        drop(h);
+        RecordInfo {
+            clamped,
+            bucket_index: counts_index(v),
         }
     }
 
@@ -68,34 +126,53 @@ impl Histogram {
     #[inline]
     /// Minimum recorded value, if any.
     pub fn min(&self) -> Option<u64> {
-        self.inner
-            .read()
-            .ok()
-            .and_then(|h| if h.is_empty() { None } else { Some(h.min()) })
+        let h = self.read();
+        (!h.is_empty()).then(|| h.min())
     }
 
     #[inline]
     /// Maximum recorded value, if any.
     pub fn max(&self) -> Option<u64> {
-        self.inner
-            .read()
-            .ok()
-            .and_then(|h| if h.is_empty() { None } else { Some(h.max()) })
+        let h = self.read();
+        (!h.is_empty()).then(|| h.max())
     }
 
     #[inline]
     /// Mean of recorded values, if any.
     pub fn mean(&self) -> Option<f64> {
-        self.inner
-            .read()
-            .ok()
-            .and_then(|h| if h.is_empty() { None } else { Some(h.mean()) })
+        let h = self.read();
+        (!h.is_empty()).then(|| h.mean())
+    }
+
+    #[inline]
+    /// Exact sum of recorded values in nanoseconds (as clamped to the trackable range).
+    pub fn sum(&self) -> u64 {
+        self.sum.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    /// Number of recorded samples that were clamped into the trackable range.
+    pub fn saturated(&self) -> u64 {
+        self.saturated.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    /// Population variance of recorded values (ns²), if any.
+    pub fn variance(&self) -> Option<f64> {
+        self.stddev().map(|sd| sd * sd)
+    }
+
+    #[inline]
+    /// Population standard deviation of recorded values (ns), if any.
+    pub fn stddev(&self) -> Option<f64> {
+        let h = self.read();
+        (!h.is_empty()).then(|| h.stdev())
     }
 
     #[inline]
     /// Number of samples recorded.
     pub fn count(&self) -> u64 {
-        self.inner.read().map(|h| h.len()).unwrap_or(0)
+        self.read().len()
     }
 
     #[inline]
@@ -108,13 +185,8 @@ impl Histogram {
     /// Value at the given percentile in [0.0, 1.0].
     pub fn percentile(&self, percentile: f64) -> Option<u64> {
         let p = percentile.clamp(0.0, 1.0) * 100.0;
-        self.inner.read().ok().and_then(|h| {
-            if h.is_empty() {
-                None
-            } else {
-                Some(h.value_at_percentile(p))
-            }
-        })
+        let h = self.read();
+        (!h.is_empty()).then(|| h.value_at_percentile(p))
     }
 
     #[inline]
@@ -138,9 +210,7 @@ impl Histogram {
     #[inline]
     /// Batch percentile queries.
     pub fn percentiles(&self, ps: &[f64]) -> Vec<Option<u64>> {
-        let Ok(guard) = self.inner.read() else {
-            return vec![None; ps.len()];
-        };
+        let guard = self.read();
         if guard.is_empty() {
             return vec![None; ps.len()];
         }
@@ -152,11 +222,70 @@ impl Histogram {
             .collect()
     }
 
+    /// Capture a point-in-time copy of the recorded values.
+    pub fn snapshot(&self) -> crate::histogram::HistogramSnapshot {
+        snapshot_of(&self.read(), self.sum.load(Ordering::Relaxed))
+    }
+
+    /// Move the contents into a snapshot and reset, under one write lock.
+    pub fn take_snapshot(&self) -> crate::histogram::HistogramSnapshot {
+        let mut h = self.write();
+        let snap = snapshot_of(&h, self.sum.swap(0, Ordering::Relaxed));
+        self.saturated.store(0, Ordering::Relaxed);
+        h.reset();
+        snap
+    }
+
     #[inline]
     /// Reset the histogram to empty state.
     pub fn reset(&self) {
-        if let Ok(mut h) = self.inner.write() {
-            h.reset();
-        }
+        let mut h = self.write();
+        h.reset();
+        self.sum.store(0, Ordering::Relaxed);
+        self.saturated.store(0, Ordering::Relaxed);
+    }
+
+    /// Shared access to the HDR histogram. A panic while it was held cannot
+    /// leave the counts torn, so poisoning is ignored.
+    fn read(&self) -> RwLockReadGuard<'_, hdrhistogram::Histogram<u64>> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Exclusive access to the HDR histogram, ignoring poisoning like [`read`](Self::read).
+    fn write(&self) -> RwLockWriteGuard<'_, hdrhistogram::Histogram<u64>> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Backend-independent copy of `h`, whose exact sum is tracked separately.
+fn snapshot_of(h: &hdrhistogram::Histogram<u64>, sum: u64) -> crate::histogram::HistogramSnapshot {
+    use crate::histogram::{Bin, HistogramSnapshot};
+    if h.is_empty() {
+        return HistogramSnapshot::default();
     }
+    let bins: Vec<Bin> = h
+        .iter_recorded()
+        .map(|v| {
+            let value = v.value_iterated_to();
+            Bin {
+                low: h.lowest_equivalent(value),
+                high: h.highest_equivalent(value).saturating_add(1),
+                count: v.count_at_value(),
+                sum: None,
+            }
+        })
+        .collect();
+    HistogramSnapshot::from_bins(bins, sum, Some(h.min()), Some(h.max()))
+}
+
+/// HDR counts-array index for `value` (unit magnitude 0, 3 significant figures),
+/// mirroring `hdrhistogram`'s private layout so values in the same equivalence
+/// range share an index.
+fn counts_index(value: u64) -> usize {
+    let sub_bucket_mask = (1u64 << SUB_BUCKET_MAGNITUDE) - 1;
+    let half_magnitude = SUB_BUCKET_MAGNITUDE - 1;
+    let bucket = 64 - SUB_BUCKET_MAGNITUDE - (value | sub_bucket_mask).leading_zeros();
+    let sub_bucket = value >> bucket;
+    let index = (u64::from(bucket + 1) << half_magnitude) + sub_bucket - (1u64 << half_magnitude);
+    usize::try_from(index).unwrap_or(usize::MAX)
 }
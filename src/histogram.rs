@@ -8,7 +8,8 @@
 //! - **Zero Dependencies**: Pure Rust implementation
 //! - **Thread-Safe**: Lock-free atomic operations for maximum concurrency
 //! - **High Performance**: O(1) record operations, optimized for CPU cache efficiency
-//! - **Memory Efficient**: Fixed ~5KB footprint, no heap allocations after initialization
+//! - **Memory Efficient**: Fixed ~8.5KB footprint by default, no heap allocations
+//!   after initialization
 //! - **Cross-Platform**: Works on all Rust-supported platforms
 //! - **Secure**: Overflow protection and comprehensive input validation
 //!
@@ -30,6 +31,8 @@
 //! println!("Mean: {:?}", histogram.mean());
 //! ```
 
+#[cfg(not(feature = "hdr"))]
+use std::sync::atomic::fence;
 #[cfg(not(feature = "hdr"))]
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
@@ -38,28 +41,54 @@ use std::time::Duration;
 #[cfg(not(feature = "hdr"))]
 const LINEAR_BUCKETS: usize = 1024;
 
-/// Maximum number of logarithmic buckets (covers up to 2^63 nanoseconds)
+/// Number of powers of two covered by logarithmic buckets (up to 2^63 nanoseconds)
 #[cfg(not(feature = "hdr"))]
 const LOG_BUCKETS: usize = 64;
 
+/// Largest sub-bucket count per power of two: at 1024 the 2^10 range is
+/// split into 1ns-wide buckets, continuing the linear range exactly
+#[cfg(not(feature = "hdr"))]
+const MAX_SUB_BUCKETS: usize = LINEAR_BUCKETS;
+
 /// Memory ordering for atomic operations - optimized for performance while ensuring correctness
 #[cfg(not(feature = "hdr"))]
 const MEMORY_ORDER: Ordering = Ordering::Relaxed;
 
+/// Copies `snapshot` attempts before settling for one that raced a writer
+#[cfg(not(feature = "hdr"))]
+const SNAPSHOT_ATTEMPTS: usize = 4;
+
+/// Values from here up share the open-ended top bucket and saturate the sum
+#[cfg(not(feature = "hdr"))]
+const SATURATION_NS: u64 = 1 << 63;
+
 /// A high-performance, thread-safe histogram optimized for timing measurements.
 ///
 /// Uses a hybrid bucketing strategy:
 /// - Linear buckets (0-1023ns) for sub-microsecond precision
-/// - Logarithmic buckets (1024ns+) for efficient wide-range coverage
+/// - Logarithmic buckets (1024ns+) for efficient wide-range coverage, each
+///   power of two optionally split into equal-width sub-buckets
 ///
 /// All operations are lock-free and thread-safe using atomic operations.
 ///
 /// # Memory Layout
 ///
 /// - Linear buckets: 1024 × 8 bytes = 8KB
-/// - Logarithmic buckets: 64 × 8 bytes = 512 bytes  
-/// - Statistics: 4 × 8 bytes = 32 bytes
-/// - **Total: ~8.5KB fixed memory footprint**
+/// - Logarithmic buckets: 64 × `sub_buckets` × 8 bytes (512 bytes by default)
+/// - Statistics: 6 × 8 bytes = 48 bytes
+/// - Per-bucket sums (opt-in, [`with_bucket_sums`](Self::with_bucket_sums)):
+///   the same size again as the logarithmic buckets
+/// - **Total: ~8.5KB fixed memory footprint** by default
+///
+/// # Precision
+///
+/// With the default single bucket per power of two, values above 1µs can
+/// be reported up to ~2x off. [`with_sub_buckets`](Self::with_sub_buckets)
+/// trades memory for accuracy, similar to HDR significant figures: 64
+/// sub-buckets (+32KB) bound the relative bucket width at ~1.6%, 128
+/// (+64KB) at ~0.8%. [`with_bucket_sums`](Self::with_bucket_sums) instead
+/// (or as well) keeps the sum of each logarithmic bucket so interpolation
+/// centres on the bucket's real mean rather than its midpoint.
 ///
 /// # Performance Characteristics
 ///
@@ -75,8 +104,14 @@ pub struct FastHistogram {
     linear_buckets: [AtomicU64; LINEAR_BUCKETS],
 
     /// Logarithmic buckets for values >= 1024 nanoseconds
-    /// Bucket i covers range [2^i, 2^(i+1))
-    log_buckets: [AtomicU64; LOG_BUCKETS],
+    /// Bucket `k * sub_buckets + s` covers the `s`-th equal slice of [2^k, 2^(k+1))
+    log_buckets: Box<[AtomicU64]>,
+
+    /// Sum of the values in each logarithmic bucket, when enabled
+    log_sums: Option<Box<[AtomicU64]>>,
+
+    /// log2 of the sub-bucket count per power of two
+    sub_bits: u32,
 
     /// Minimum recorded value (nanoseconds)
     min_value: AtomicU64,
@@ -89,6 +124,13 @@ pub struct FastHistogram {
 
     /// Sum of all recorded values (with overflow protection)
     sum: AtomicU64,
+
+    /// Sum of squared values as a 128-bit integer split across two words
+    sum_sq_lo: AtomicU64,
+    sum_sq_hi: AtomicU64,
+
+    /// Count of recorded values at or above 2^63 nanoseconds
+    saturated: AtomicU64,
 }
 
 #[cfg(not(feature = "hdr"))]
@@ -108,16 +150,94 @@ impl FastHistogram {
     /// assert!(histogram.is_empty());
     /// ```
     pub fn new() -> Self {
+        Self::with_sub_buckets(1)
+    }
+
+    /// Creates a new empty histogram that splits each power of two above
+    /// 1023ns into `sub_buckets` equal-width buckets.
+    ///
+    /// `sub_buckets` is rounded up to a power of two and capped at 1024; the
+    /// relative width of a bucket is at most `1 / sub_buckets`. See the
+    /// type-level docs for the memory cost.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use benchmark::histogram::FastHistogram;
+    /// let coarse = FastHistogram::new();
+    /// let fine = FastHistogram::with_sub_buckets(128);
+    /// for h in [&coarse, &fine] {
+    ///     for v in [1_100_000, 1_500_000, 1_900_000] {
+    ///         h.record(v);
+    ///     }
+    /// }
+    /// // All samples share the coarse [2^20, 2^21) bucket; p50 is interpolated.
+    /// assert_eq!(coarse.percentile(0.5), Some(1_398_101));
+    /// let p50 = fine.percentile(0.5).unwrap();
+    /// assert!(p50.abs_diff(1_500_000) <= 15_000, "p50={p50}");
+    /// ```
+    pub fn with_sub_buckets(sub_buckets: usize) -> Self {
+        let sub = sub_buckets.clamp(1, MAX_SUB_BUCKETS).next_power_of_two();
         Self {
             linear_buckets: std::array::from_fn(|_| AtomicU64::new(0)),
-            log_buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            log_buckets: (0..LOG_BUCKETS * sub).map(|_| AtomicU64::new(0)).collect(),
+            log_sums: None,
+            sub_bits: sub.trailing_zeros(),
             min_value: AtomicU64::new(u64::MAX),
             max_value: AtomicU64::new(0),
             total_count: AtomicU64::new(0),
             sum: AtomicU64::new(0),
+            sum_sq_lo: AtomicU64::new(0),
+            sum_sq_hi: AtomicU64::new(0),
+            saturated: AtomicU64::new(0),
         }
     }
 
+    /// Returns the number of sub-buckets per power of two.
+    #[inline]
+    pub fn sub_buckets(&self) -> usize {
+        1 << self.sub_bits
+    }
+
+    /// Also keep the sum of the values in each logarithmic bucket.
+    ///
+    /// Percentiles falling in a wide bucket are then spread around the
+    /// bucket's actual mean instead of across its whole range, which removes
+    /// most of the interpolation error when a bucket's samples cluster (the
+    /// common case for latencies) at the cost of one more atomic add per
+    /// record above 1023ns. [`HistogramSnapshot`]s carry the sums too and
+    /// interpolate the same way.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use benchmark::histogram::FastHistogram;
+    /// let plain = FastHistogram::new();
+    /// let summed = FastHistogram::new().with_bucket_sums();
+    /// for h in [&plain, &summed] {
+    ///     for v in [1_500_000, 1_510_000, 1_520_000] {
+    ///         h.record(v);
+    ///     }
+    /// }
+    /// // All three share the [2^20, 2^21) bucket.
+    /// assert_eq!(plain.percentile(0.5), Some(1_500_000)); // clamped to min
+    /// assert_eq!(summed.percentile(0.5), Some(1_510_000));
+    /// ```
+    #[must_use]
+    pub fn with_bucket_sums(mut self) -> Self {
+        let sums = (0..self.log_buckets.len())
+            .map(|_| AtomicU64::new(0))
+            .collect();
+        self.log_sums = Some(sums);
+        self
+    }
+
+    /// True if per-bucket sums are kept (see [`with_bucket_sums`](Self::with_bucket_sums)).
+    #[inline]
+    pub fn has_bucket_sums(&self) -> bool {
+        self.log_sums.is_some()
+    }
+
     /// Records a timing value in nanoseconds.
     ///
     /// This is the core hot-path method optimized for maximum performance.
@@ -136,6 +256,7 @@ impl FastHistogram {
     /// # Overflow Behavior
     ///
     /// - Values >= 2^63 nanoseconds (~292 years) are clamped to the highest bucket
+    ///   and counted by [`saturated`](Self::saturated)
     /// - Sum overflow is handled via saturation (won't panic)
     /// - Count overflow is extremely unlikely (2^64 samples) but handled gracefully
     ///
@@ -149,29 +270,98 @@ impl FastHistogram {
     /// ```
     #[inline]
     pub fn record(&self, value_ns: u64) {
+        // The total is bumped first and the bucket last, so `snapshot` can
+        // tell a record that is still in flight (see `read_consistent`).
+        self.total_count.fetch_add(1, MEMORY_ORDER);
+        fence(Ordering::Release);
+
         // Update statistics atomically
         self.update_min(value_ns);
         self.update_max(value_ns);
-        self.total_count.fetch_add(1, MEMORY_ORDER);
         self.sum
             .fetch_add(value_ns.min(u64::MAX - 1000), MEMORY_ORDER); // Overflow protection
+        self.add_square(value_ns);
 
         // Record in appropriate bucket
         if value_ns < LINEAR_BUCKETS as u64 {
             // High-precision linear bucket
             #[allow(clippy::cast_possible_truncation)]
             {
-                self.linear_buckets[value_ns as usize].fetch_add(1, MEMORY_ORDER);
+                self.linear_buckets[value_ns as usize].fetch_add(1, Ordering::Release);
             }
         } else {
             // Logarithmic bucket - find the highest bit position
-            let bucket_index = Self::log_bucket_index(value_ns);
-            if bucket_index < LOG_BUCKETS {
-                self.log_buckets[bucket_index].fetch_add(1, MEMORY_ORDER);
+            if value_ns >= SATURATION_NS {
+                self.saturated.fetch_add(1, MEMORY_ORDER);
+            }
+            let bucket_index = self.log_bucket_index(value_ns);
+            if bucket_index < self.log_buckets.len() {
+                if let Some(sums) = &self.log_sums {
+                    sums[bucket_index].fetch_add(value_ns, MEMORY_ORDER);
+                }
+                self.log_buckets[bucket_index].fetch_add(1, Ordering::Release);
+            }
+        }
+    }
+
+    /// Records a value and reports where it landed.
+    ///
+    /// Costs the same as [`record`](Self::record) plus returning the bucket
+    /// index. This backend spans the full `u64` range, so `clamped` is only
+    /// set for values of 2^63ns and up (see [`saturated`](Self::saturated)).
+    /// Linear buckets use indices `0..1024` (the value itself); the
+    /// logarithmic bucket `[2^k, 2^(k+1))` has index `1024 + k` (with
+    /// sub-buckets, `1024 + k * sub_buckets + s` for the `s`-th slice).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use benchmark::histogram::Histogram;
+    /// let histogram = Histogram::new();
+    /// let a = histogram.record_returning(1_500);
+    /// let b = histogram.record_returning(1_900);
+    /// assert_eq!(a.bucket_index, b.bucket_index); // same power-of-two bucket
+    /// assert_eq!(histogram.count(), 2);
+    /// ```
+    #[inline]
+    pub fn record_returning(&self, value_ns: u64) -> RecordInfo {
+        self.record(value_ns);
+        let bucket_index = if value_ns < LINEAR_BUCKETS as u64 {
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                value_ns as usize
             }
+        } else {
+            LINEAR_BUCKETS + self.log_bucket_index(value_ns)
+        };
+        RecordInfo {
+            clamped: value_ns >= SATURATION_NS,
+            bucket_index,
         }
     }
 
+    /// Returns how many recorded values were saturated.
+    ///
+    /// Values of 2^63 nanoseconds (~292 years) and up, including durations
+    /// beyond `u64::MAX` nanoseconds passed to
+    /// [`record_duration`](Self::record_duration), share the top bucket and
+    /// have their sum contribution capped. A non-zero count almost always
+    /// means a bogus input such as an underflowed subtraction.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use benchmark::histogram::FastHistogram;
+    /// let histogram = FastHistogram::new();
+    /// histogram.record(1_000);
+    /// histogram.record(0u64.wrapping_sub(1)); // an underflowed `end - start`
+    /// assert_eq!(histogram.saturated(), 1);
+    /// ```
+    #[inline]
+    pub fn saturated(&self) -> u64 {
+        self.saturated.load(MEMORY_ORDER)
+    }
+
     /// Records a Duration value.
     ///
     /// Convenience method that converts Duration to nanoseconds and records it.
@@ -269,6 +459,83 @@ impl FastHistogram {
         }
     }
 
+    /// Returns the sum of all recorded values in nanoseconds.
+    ///
+    /// This is the exact total time accumulated by the metric, unlike
+    /// `mean() * count()` which loses precision for large counts.
+    ///
+    /// # Performance
+    ///
+    /// O(1) - single atomic read
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use benchmark::histogram::Histogram;
+    /// let histogram = Histogram::new();
+    /// histogram.record(1_000);
+    /// histogram.record(2_500);
+    /// assert_eq!(histogram.sum(), 3_500);
+    /// ```
+    #[inline]
+    pub fn sum(&self) -> u64 {
+        self.sum.load(MEMORY_ORDER)
+    }
+
+    /// Returns the population variance of recorded values (ns²).
+    ///
+    /// Computed from the running sum and an exact 128-bit sum of squares, so
+    /// it needs no bucket scan and is not affected by bucket resolution.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(variance)` if values have been recorded
+    /// - `None` if histogram is empty
+    ///
+    /// # Performance
+    ///
+    /// O(1) - four atomic reads
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use benchmark::histogram::Histogram;
+    /// let histogram = Histogram::new();
+    /// for v in [2, 4, 4, 4, 5, 5, 7, 9] {
+    ///     histogram.record(v);
+    /// }
+    /// assert_eq!(histogram.variance(), Some(4.0));
+    /// assert_eq!(histogram.stddev(), Some(2.0));
+    /// histogram.reset();
+    /// assert_eq!(histogram.stddev(), None);
+    /// ```
+    #[inline]
+    pub fn variance(&self) -> Option<f64> {
+        let count = self.total_count.load(MEMORY_ORDER);
+        if count == 0 {
+            return None;
+        }
+        let sum = u128::from(self.sum.load(MEMORY_ORDER));
+        let n = u128::from(count);
+        // n·Var = Σx² − (Σx)²/n; saturate in case a concurrent record was
+        // only partially observed.
+        let spread = self.sum_sq().saturating_sub(sum * sum / n);
+        #[allow(clippy::cast_precision_loss)]
+        {
+            Some(spread as f64 / count as f64)
+        }
+    }
+
+    /// Returns the population standard deviation of recorded values (ns).
+    ///
+    /// # Performance
+    ///
+    /// O(1) - see [`variance`](Self::variance)
+    #[inline]
+    pub fn stddev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
     /// Returns the total number of recorded values.
     ///
     /// # Performance
@@ -396,25 +663,11 @@ impl FastHistogram {
                 continue;
             }
 
-            let bucket_start = Self::bucket_start(bucket_idx);
-            let bucket_end = Self::bucket_end(bucket_idx);
-
             if current_count + count >= target_count {
                 // Target percentile is within this bucket - interpolate
                 let position_in_bucket = target_count.saturating_sub(current_count);
-                let bucket_width = bucket_end.saturating_sub(bucket_start);
-
-                if count > 0 && bucket_width > 0 {
-                    // Use 1-based rank inside the bucket: offset should be (k-1)
-                    // Compute with u128 to avoid intermediate overflow
-                    let num = (u128::from(position_in_bucket.saturating_sub(1)))
-                        * u128::from(bucket_width);
-                    let den = u128::from(count);
-                    let interpolated_offset = u64::try_from(num / den).unwrap_or(u64::MAX);
-                    let v = bucket_start.saturating_add(interpolated_offset);
-                    return Some(v.clamp(min_v, max_v));
-                }
-                return Some(bucket_start.clamp(min_v, max_v));
+                let v = self.interpolate(bucket_idx, count, position_in_bucket);
+                return Some(v.clamp(min_v, max_v));
             }
 
             current_count += count;
@@ -570,24 +823,9 @@ impl FastHistogram {
                 continue;
             }
 
-            let bucket_start = Self::bucket_start(bucket_idx);
-            let bucket_end = Self::bucket_end(bucket_idx);
-
             while target_idx < targets.len() && current_count + count >= targets[target_idx].1 {
                 let position_in_bucket = targets[target_idx].1.saturating_sub(current_count);
-                let bucket_width = bucket_end.saturating_sub(bucket_start);
-
-                let interpolated_value = if count > 0 && bucket_width > 0 {
-                    // 1-based rank; use (k-1) for offset. Do math in u128 to avoid overflow.
-                    let num = (u128::from(position_in_bucket.saturating_sub(1)))
-                        * u128::from(bucket_width);
-                    let den = u128::from(count);
-                    let interpolated_offset = u64::try_from(num / den).unwrap_or(u64::MAX);
-                    bucket_start.saturating_add(interpolated_offset)
-                } else {
-                    bucket_start
-                };
-
+                let interpolated_value = self.interpolate(bucket_idx, count, position_in_bucket);
                 let v = interpolated_value.clamp(min_v.unwrap(), max_v.unwrap());
                 results[targets[target_idx].0] = Some(v);
                 target_idx += 1;
@@ -613,15 +851,152 @@ impl FastHistogram {
         results
     }
 
+    /// Captures a consistent point-in-time copy of the bucket counts and
+    /// statistics.
+    ///
+    /// Writers are never blocked. The copy is retried while a record is in
+    /// flight, so its count, sum, min and max describe the same set of
+    /// samples. Under sustained contention it gives up after a few attempts
+    /// and returns the last copy, which may then be off by the samples that
+    /// landed mid-copy; bucket counts are never torn.
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let mut attempt = self.read_consistent();
+        for _ in 1..SNAPSHOT_ATTEMPTS {
+            if let Ok(snapshot) = attempt {
+                return snapshot;
+            }
+            std::hint::spin_loop();
+            attempt = self.read_consistent();
+        }
+        attempt.unwrap_or_else(|torn| torn)
+    }
+
+    /// One seqlock-style copy: `Err` carries the copy when a record started
+    /// or was still in flight while it was taken.
+    ///
+    /// `record` bumps `total_count` before its other writes and increments
+    /// its bucket last (with release ordering), so a copy whose bucket
+    /// counts add up to an unchanged total includes every write of every
+    /// sample it counts, and nothing else.
+    fn read_consistent(&self) -> Result<HistogramSnapshot, HistogramSnapshot> {
+        let before = self.total_count.load(Ordering::Acquire);
+        let mut bins = Vec::new();
+        for (value, bucket) in self.linear_buckets.iter().enumerate() {
+            let count = bucket.load(Ordering::Acquire);
+            if count > 0 {
+                let low = value as u64;
+                bins.push(Bin {
+                    low,
+                    high: low + 1,
+                    count,
+                    sum: None,
+                });
+            }
+        }
+        for (bucket_idx, bucket) in self.log_buckets.iter().enumerate() {
+            let count = bucket.load(Ordering::Acquire);
+            if count > 0 {
+                bins.push(Bin {
+                    low: self.bucket_start(bucket_idx),
+                    high: self.bucket_end(bucket_idx),
+                    count,
+                    sum: self
+                        .log_sums
+                        .as_ref()
+                        .map(|sums| sums[bucket_idx].load(MEMORY_ORDER)),
+                });
+            }
+        }
+        let snapshot =
+            HistogramSnapshot::from_bins(bins, self.sum.load(MEMORY_ORDER), self.min(), self.max())
+                .with_sum_sq(Some(self.sum_sq()));
+        fence(Ordering::Acquire);
+        let after = self.total_count.load(MEMORY_ORDER);
+        if before == after && snapshot.count() == after {
+            Ok(snapshot)
+        } else {
+            Err(snapshot)
+        }
+    }
+
+    /// Moves the current contents into a snapshot, leaving the histogram empty.
+    ///
+    /// Every bucket is emptied with an atomic swap, so a concurrent `record`
+    /// lands either in the returned snapshot or in the histogram afterwards:
+    /// no sample is lost or counted twice. The sum of a record racing with
+    /// the take may be attributed to the adjacent interval; min and max are
+    /// kept within each side's buckets.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use benchmark::histogram::Histogram;
+    /// let histogram = Histogram::new();
+    /// histogram.record(100);
+    /// histogram.record(5_000);
+    /// let interval = histogram.take_snapshot();
+    /// assert_eq!((interval.count(), interval.sum()), (2, 5_100));
+    /// assert!(histogram.is_empty());
+    /// ```
+    pub fn take_snapshot(&self) -> HistogramSnapshot {
+        let mut bins = Vec::new();
+        let mut taken = 0u64;
+        for (value, bucket) in self.linear_buckets.iter().enumerate() {
+            let count = bucket.swap(0, MEMORY_ORDER);
+            if count > 0 {
+                let low = value as u64;
+                bins.push(Bin {
+                    low,
+                    high: low + 1,
+                    count,
+                    sum: None,
+                });
+                taken += count;
+            }
+        }
+        for (bucket_idx, bucket) in self.log_buckets.iter().enumerate() {
+            let count = bucket.swap(0, MEMORY_ORDER);
+            if count > 0 {
+                bins.push(Bin {
+                    low: self.bucket_start(bucket_idx),
+                    high: self.bucket_end(bucket_idx),
+                    count,
+                    sum: self
+                        .log_sums
+                        .as_ref()
+                        .map(|sums| sums[bucket_idx].swap(0, MEMORY_ORDER)),
+                });
+                taken += count;
+            }
+        }
+        if taken == 0 {
+            // Leave the sum and extremes of any in-flight record in place.
+            return HistogramSnapshot::default();
+        }
+        // `record` bumps the total before its bucket, so every taken sample
+        // is already counted there.
+        self.total_count.fetch_sub(taken, MEMORY_ORDER);
+        let sum = self.sum.swap(0, MEMORY_ORDER);
+        let sum_sq_lo = self.sum_sq_lo.swap(0, MEMORY_ORDER);
+        let sum_sq_hi = self.sum_sq_hi.swap(0, MEMORY_ORDER);
+        self.saturated.swap(0, MEMORY_ORDER);
+        let min = self.min_value.swap(u64::MAX, MEMORY_ORDER);
+        let max = self.max_value.swap(0, MEMORY_ORDER);
+        HistogramSnapshot::from_bins(bins, sum, (min != u64::MAX).then_some(min), Some(max))
+            .with_sum_sq(Some((u128::from(sum_sq_hi) << 64) | u128::from(sum_sq_lo)))
+    }
+
     /// Resets the histogram to empty state.
     ///
-    /// **Warning**: This operation is NOT atomic. If called concurrently with
-    /// `record()` operations, the histogram may be left in an inconsistent state.
-    /// Ensure exclusive access when calling this method.
+    /// Safe to call while other threads record: this is
+    /// [`take_snapshot`](Self::take_snapshot) with the result discarded, so
+    /// buckets are swapped out rather than overwritten. A record racing with
+    /// the reset is either dropped by it or kept whole afterwards; the count
+    /// never falls below the bucket totals.
     ///
     /// # Performance
     ///
-    /// O(1) - resets fixed number of atomic values (~1μs typical)
+    /// O(1) - swaps a fixed number of atomic values (~1μs typical)
     ///
     /// # Example
     ///
@@ -635,23 +1010,36 @@ impl FastHistogram {
     /// assert!(histogram.is_empty());
     /// ```
     pub fn reset(&self) {
-        // Reset all buckets
-        for bucket in &self.linear_buckets {
-            bucket.store(0, MEMORY_ORDER);
-        }
-        for bucket in &self.log_buckets {
-            bucket.store(0, MEMORY_ORDER);
-        }
-
-        // Reset statistics
-        self.min_value.store(u64::MAX, MEMORY_ORDER);
-        self.max_value.store(0, MEMORY_ORDER);
-        self.total_count.store(0, MEMORY_ORDER);
-        self.sum.store(0, MEMORY_ORDER);
+        self.take_snapshot();
     }
 
     // Private helper methods
 
+    /// Adds `value²` to the 128-bit sum of squares, carrying into the high word
+    #[inline]
+    fn add_square(&self, value: u64) {
+        let sq = u128::from(value) * u128::from(value);
+        #[allow(clippy::cast_possible_truncation)]
+        let (lo, hi) = (sq as u64, (sq >> 64) as u64);
+        let prev = self.sum_sq_lo.fetch_add(lo, MEMORY_ORDER);
+        let carry = u64::from(prev.checked_add(lo).is_none());
+        if hi | carry != 0 {
+            self.sum_sq_hi.fetch_add(hi + carry, MEMORY_ORDER);
+        }
+    }
+
+    /// Reads the 128-bit sum of squares, retrying if the high word moved
+    #[inline]
+    fn sum_sq(&self) -> u128 {
+        loop {
+            let hi = self.sum_sq_hi.load(MEMORY_ORDER);
+            let lo = self.sum_sq_lo.load(MEMORY_ORDER);
+            if self.sum_sq_hi.load(MEMORY_ORDER) == hi {
+                return (u128::from(hi) << 64) | u128::from(lo);
+            }
+        }
+    }
+
     /// Atomically updates minimum value using compare-and-swap loop
     #[inline]
     fn update_min(&self, value: u64) {
@@ -688,34 +1076,65 @@ impl FastHistogram {
 
     /// Calculates the logarithmic bucket index for a given value
     #[inline]
-    fn log_bucket_index(value: u64) -> usize {
+    fn log_bucket_index(&self, value: u64) -> usize {
         if value < LINEAR_BUCKETS as u64 {
             0 // Should not happen, but safe fallback
         } else {
             // Find the position of the highest set bit
-            // This gives us log2(value) which determines the bucket
-            63 - value.leading_zeros() as usize
+            // This gives us log2(value) which determines the power of two;
+            // the next `sub_bits` bits select the sub-bucket within it
+            let power = value.ilog2();
+            let sub = (value >> (power - self.sub_bits)) & ((1 << self.sub_bits) - 1);
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                ((power as usize) << self.sub_bits) | sub as usize
+            }
         }
     }
 
+    /// Value of the `rank`-th (1-based) of `count` samples in a logarithmic bucket.
+    ///
+    /// Samples are assumed evenly spread: over the whole bucket by default, or
+    /// with bucket sums over the widest range centred on the bucket's mean
+    /// that still fits inside it, so the interpolated values keep that mean.
+    #[inline]
+    fn interpolate(&self, bucket_idx: usize, count: u64, rank: u64) -> u64 {
+        let bin = Bin {
+            low: self.bucket_start(bucket_idx),
+            high: self.bucket_end(bucket_idx),
+            count,
+            sum: self
+                .log_sums
+                .as_ref()
+                .map(|sums| sums[bucket_idx].load(MEMORY_ORDER)),
+        };
+        bin.interpolate(rank)
+    }
+
     /// Returns the start value for a logarithmic bucket
     #[inline]
-    fn bucket_start(bucket_idx: usize) -> u64 {
-        if bucket_idx == 0 {
-            LINEAR_BUCKETS as u64
-        } else {
-            (1u64 << bucket_idx).max(LINEAR_BUCKETS as u64)
-        }
+    fn bucket_start(&self, bucket_idx: usize) -> u64 {
+        let (start, _) = self.bucket_range(bucket_idx);
+        u64::try_from(start)
+            .unwrap_or(u64::MAX)
+            .max(LINEAR_BUCKETS as u64)
     }
 
     /// Returns the end value for a logarithmic bucket (exclusive)
     #[inline]
-    fn bucket_end(bucket_idx: usize) -> u64 {
-        if bucket_idx >= 63 {
-            u64::MAX
-        } else {
-            1u64 << (bucket_idx + 1)
-        }
+    fn bucket_end(&self, bucket_idx: usize) -> u64 {
+        let (start, width) = self.bucket_range(bucket_idx);
+        u64::try_from(start + width).unwrap_or(u64::MAX)
+    }
+
+    /// Unclamped start and width of a logarithmic bucket
+    #[inline]
+    fn bucket_range(&self, bucket_idx: usize) -> (u128, u128) {
+        let power = bucket_idx >> self.sub_bits;
+        let sub = bucket_idx & ((1 << self.sub_bits) - 1);
+        let base = 1u128 << power;
+        let width = (base >> self.sub_bits).max(1);
+        (base + sub as u128 * width, width)
     }
 }
 
@@ -747,12 +1166,55 @@ impl Histogram {
         }
     }
 
+    /// Grows the trackable range on demand instead of clamping samples to it.
+    ///
+    /// The HDR backend (feature `hdr`) otherwise clamps at 1 hour; with this
+    /// it resizes like `hdrhistogram`'s `auto(true)`, up to `u64::MAX / 2`
+    /// nanoseconds. The default backend already spans the full `u64` range,
+    /// so there this is a no-op.
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let h = Histogram::new().with_auto_resize();
+    /// h.record(2 * 3_600_000_000_000); // 2 hours
+    /// assert_eq!(h.saturated(), 0);
+    /// assert!(h.max().unwrap() >= 7_000_000_000_000);
+    /// ```
+    #[must_use]
+    pub fn with_auto_resize(self) -> Self {
+        #[cfg(feature = "hdr")]
+        let inner = self.inner.with_auto_resize();
+        #[cfg(not(feature = "hdr"))]
+        let inner = self.inner;
+        Self { inner }
+    }
+
     /// Records a timing value in nanoseconds.
     #[inline]
     pub fn record(&self, value_ns: u64) {
         self.inner.record(value_ns);
     }
 
+    /// Records a value and reports its bucket placement and whether it was
+    /// clamped into the backend's trackable range.
+    ///
+    /// Intended for diagnostics, e.g. noticing that a large share of samples
+    /// lands in the top bucket because bounds are misconfigured.
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let h = Histogram::new();
+    /// let info = h.record_returning(250);
+    /// assert!(!info.clamped);
+    /// assert_eq!(h.record_returning(250).bucket_index, info.bucket_index);
+    /// ```
+    #[inline]
+    pub fn record_returning(&self, value_ns: u64) -> RecordInfo {
+        self.inner.record_returning(value_ns)
+    }
+
     /// Records a Duration value.
     #[inline]
     pub fn record_duration(&self, duration: Duration) {
@@ -777,12 +1239,50 @@ impl Histogram {
         self.inner.mean()
     }
 
+    /// Returns the sum of all recorded values in nanoseconds.
+    #[inline]
+    pub fn sum(&self) -> u64 {
+        self.inner.sum()
+    }
+
+    /// Returns the population variance of recorded values (ns²).
+    #[inline]
+    pub fn variance(&self) -> Option<f64> {
+        self.inner.variance()
+    }
+
+    /// Returns the population standard deviation of recorded values (ns).
+    #[inline]
+    pub fn stddev(&self) -> Option<f64> {
+        self.inner.stddev()
+    }
+
     /// Returns the total number of recorded values.
     #[inline]
     pub fn count(&self) -> u64 {
         self.inner.count()
     }
 
+    /// Returns how many recorded values were clamped into the backend's
+    /// trackable range, so lost range shows up instead of silently
+    /// distorting percentiles. Cleared by [`reset`](Self::reset) and
+    /// [`take_snapshot`](Self::take_snapshot).
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let h = Histogram::new();
+    /// h.record(1_000);
+    /// h.record(u64::MAX);
+    /// assert_eq!(h.saturated(), 1);
+    /// h.reset();
+    /// assert_eq!(h.saturated(), 0);
+    /// ```
+    #[inline]
+    pub fn saturated(&self) -> u64 {
+        self.inner.saturated()
+    }
+
     /// Returns true if no values have been recorded.
     #[inline]
     pub fn is_empty(&self) -> bool {
@@ -819,42 +1319,826 @@ impl Histogram {
         self.inner.percentiles(percentiles)
     }
 
-    /// Resets the histogram to empty state.
-    pub fn reset(&self) {
-        self.inner.reset();
+    /// Number of samples at or below `value_ns` (see
+    /// [`HistogramSnapshot::count_below`]). Takes a snapshot, so prefer one
+    /// snapshot when asking several questions at once.
+    #[inline]
+    pub fn count_below(&self, value_ns: u64) -> u64 {
+        self.snapshot().count_below(value_ns)
     }
-}
 
-impl Default for Histogram {
-    fn default() -> Self {
-        Self::new()
+    /// Fraction of samples at or below `value_ns`, in `[0.0, 1.0]` (see
+    /// [`HistogramSnapshot::percentile_rank`]).
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let h = Histogram::new();
+    /// for v in 1..=1_000 {
+    ///     h.record(v);
+    /// }
+    /// assert_eq!(h.count_below(200), 200);
+    /// assert!((h.percentile_rank(200) - 0.2).abs() < 1e-9);
+    /// ```
+    #[inline]
+    pub fn percentile_rank(&self, value_ns: u64) -> f64 {
+        self.snapshot().percentile_rank(value_ns)
     }
-}
 
-// `Histogram` is composed entirely of atomic primitives and thus is `Send` and `Sync`
-// by default. No explicit unsafe impls are required.
+    /// Geometric mean in nanoseconds (see [`HistogramSnapshot::geometric_mean`]).
+    #[inline]
+    pub fn geometric_mean(&self) -> Option<f64> {
+        self.snapshot().geometric_mean()
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    #[cfg(not(feature = "hdr"))]
-    use std::sync::Arc;
-    #[cfg(not(feature = "hdr"))]
-    use std::thread;
+    /// Midpoint of the densest bucket (see [`HistogramSnapshot::mode`]).
+    #[inline]
+    pub fn mode(&self) -> Option<u64> {
+        self.snapshot().mode()
+    }
 
+    /// Skewness estimate (see [`HistogramSnapshot::skewness`]).
     #[inline]
-    fn perf_enabled() -> bool {
-        // Opt-in perf tests via environment to avoid CI/host variance
-        std::env::var_os("PERF_TESTS").is_some()
+    pub fn skewness(&self) -> Option<f64> {
+        self.snapshot().skewness()
     }
 
-    #[test]
-    fn test_empty_histogram() {
-        let hist = Histogram::new();
-        assert!(hist.is_empty());
-        assert_eq!(hist.count(), 0);
-        assert_eq!(hist.min(), None);
-        assert_eq!(hist.max(), None);
+    /// Excess kurtosis estimate (see [`HistogramSnapshot::kurtosis`]).
+    #[inline]
+    pub fn kurtosis(&self) -> Option<f64> {
+        self.snapshot().kurtosis()
+    }
+
+    /// Captures a point-in-time copy of the bucket counts and statistics.
+    ///
+    /// Subtract an earlier snapshot with [`HistogramSnapshot::delta_since`] to
+    /// get what was recorded in between.
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let h = Histogram::new();
+    /// h.record(100);
+    /// let before = h.snapshot();
+    /// h.record(200);
+    /// h.record(300);
+    /// let delta = h.snapshot().delta_since(&before);
+    /// assert_eq!(delta.count(), 2);
+    /// assert_eq!(h.count(), 3);
+    /// ```
+    #[inline]
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        self.inner.snapshot()
+    }
+
+    /// Moves the contents into a snapshot, leaving the histogram empty,
+    /// without losing records that race with it.
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let h = Histogram::new();
+    /// h.record(100);
+    /// assert_eq!(h.take_snapshot().count(), 1);
+    /// assert_eq!(h.take_snapshot().count(), 0);
+    /// ```
+    #[inline]
+    pub fn take_snapshot(&self) -> HistogramSnapshot {
+        self.inner.take_snapshot()
+    }
+
+    /// What was recorded since `earlier` was taken (see [`HistogramSnapshot::delta_since`]).
+    #[inline]
+    pub fn delta_since(&self, earlier: &HistogramSnapshot) -> HistogramSnapshot {
+        self.snapshot().delta_since(earlier)
+    }
+
+    /// Resets the histogram to empty state.
+    pub fn reset(&self) {
+        self.inner.reset();
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where a sample landed, as returned by `record_returning`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RecordInfo {
+    /// True if the value fell outside the trackable range and was clamped to its edge.
+    pub clamped: bool,
+    /// Backend-specific bucket index; equal values always share an index and
+    /// indices increase with value.
+    pub bucket_index: usize,
+}
+
+/// A single non-empty bucket: `count` samples in `[low, high)`, summing to
+/// `sum` when the backend keeps per-bucket sums.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Bin {
+    pub(crate) low: u64,
+    pub(crate) high: u64,
+    pub(crate) count: u64,
+    pub(crate) sum: Option<u64>,
+}
+
+impl Bin {
+    /// Value of the `rank`-th (1-based) sample: evenly spaced across the
+    /// bucket, or around its mean when the sum is known.
+    fn interpolate(&self, rank: u64) -> u64 {
+        let last = self.high.saturating_sub(1).max(self.low);
+        let pos = u128::from(rank.saturating_sub(1));
+        let count = u128::from(self.count.max(1));
+        if let Some(sum) = self.sum {
+            // Counts and sums are read separately, so clamp a racy mean.
+            let mean = u64::try_from(u128::from(sum) / count)
+                .unwrap_or(u64::MAX)
+                .clamp(self.low, last);
+            let half = (mean - self.low).min(last - mean);
+            // The k-th of n evenly spaced points on [mean - half, mean + half].
+            let offset = u128::from(half) * 2 * (2 * pos + 1) / (2 * count);
+            return (mean - half).saturating_add(u64::try_from(offset).unwrap_or(u64::MAX));
+        }
+        let width = u128::from(last - self.low) + 1;
+        self.low
+            .saturating_add(u64::try_from(pos * width / count).unwrap_or(u64::MAX))
+    }
+}
+
+/// Immutable, backend-independent copy of a histogram's contents.
+///
+/// Snapshots are plain values: cheap to keep between scrapes, comparable, and
+/// subtractable. `earlier` and `later` snapshots of the same histogram yield
+/// the samples recorded in between via [`delta_since`](Self::delta_since) or
+/// `&later - &earlier`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistogramSnapshot {
+    bins: Vec<Bin>,
+    count: u64,
+    sum: u64,
+    sum_sq: Option<u128>,
+    min: Option<u64>,
+    max: Option<u64>,
+}
+
+impl HistogramSnapshot {
+    /// Build from sorted, non-empty bins.
+    ///
+    /// `min` and `max` are clamped into the first and last non-empty bins, so
+    /// extremes written by a record that raced a reset or take never
+    /// contradict the bucket counts.
+    pub(crate) fn from_bins(bins: Vec<Bin>, sum: u64, min: Option<u64>, max: Option<u64>) -> Self {
+        let count = bins.iter().map(|b| b.count).sum();
+        let (Some(first), Some(last)) = (bins.first(), bins.last()) else {
+            return Self::default();
+        };
+        if count == 0 {
+            return Self::default();
+        }
+        let top = |b: &Bin| b.high.saturating_sub(1).max(b.low);
+        let min = min.map_or(first.low, |m| m.clamp(first.low, top(first)));
+        let max = max.map_or(top(last), |m| m.clamp(last.low, top(last)));
+        Self {
+            bins,
+            count,
+            sum,
+            sum_sq: None,
+            min: Some(min),
+            max: Some(max),
+        }
+    }
+
+    /// Attach the exact sum of squared samples, when the backend tracks it.
+    pub(crate) fn with_sum_sq(mut self, sum_sq: Option<u128>) -> Self {
+        if self.count > 0 {
+            self.sum_sq = sum_sq;
+        }
+        self
+    }
+
+    /// Number of samples.
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// True if the snapshot holds no samples.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Sum of all samples in nanoseconds.
+    #[inline]
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    /// Smallest sample (exact for full snapshots, bucket-bounded for deltas).
+    #[inline]
+    pub fn min(&self) -> Option<u64> {
+        self.min
+    }
+
+    /// Largest sample (exact for full snapshots, bucket-bounded for deltas).
+    #[inline]
+    pub fn max(&self) -> Option<u64> {
+        self.max
+    }
+
+    /// Arithmetic mean in nanoseconds.
+    #[inline]
+    pub fn mean(&self) -> Option<f64> {
+        #[allow(clippy::cast_precision_loss)]
+        (self.count > 0).then(|| self.sum as f64 / self.count as f64)
+    }
+
+    /// Population standard deviation in nanoseconds.
+    ///
+    /// Exact for snapshots of the default backend, which carry the sum of
+    /// squares; otherwise (HDR, decoded or merged with such) estimated from
+    /// bucket midpoints around the exact mean.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn stddev(&self) -> Option<f64> {
+        if let Some(sum_sq) = self.sum_sq {
+            let sum = u128::from(self.sum);
+            let spread = sum_sq.saturating_sub(sum * sum / u128::from(self.count));
+            return Some((spread as f64 / self.count as f64).sqrt());
+        }
+        self.moments().map(|(m2, _, _)| m2.sqrt())
+    }
+
+    /// Non-empty buckets as `(low, high_exclusive, count)`, in ascending order.
+    pub fn buckets(&self) -> impl Iterator<Item = (u64, u64, u64)> + '_ {
+        self.bins.iter().map(|b| (b.low, b.high, b.count))
+    }
+
+    /// Value at `percentile` in `[0.0, 1.0]`, using nearest rank with linear
+    /// interpolation inside wide buckets, centred on the bucket's mean when
+    /// per-bucket sums were kept (same method as `Histogram`).
+    pub fn percentile(&self, percentile: f64) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+        let (min_v, max_v) = (self.min?, self.max?);
+        let p = percentile.clamp(0.0, 1.0);
+        if p <= 0.0 {
+            return Some(min_v);
+        }
+        if p >= 1.0 {
+            return Some(max_v);
+        }
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let target = ((p * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0u64;
+        for bin in &self.bins {
+            if seen + bin.count >= target {
+                return Some(bin.interpolate(target - seen).clamp(min_v, max_v));
+            }
+            seen += bin.count;
+        }
+        Some(max_v)
+    }
+
+    /// Number of samples at or below `value_ns`.
+    ///
+    /// The inverse of [`percentile`](Self::percentile): exact where buckets
+    /// are 1ns wide (below 1µs by default), and assuming samples are spread
+    /// evenly inside a wider bucket, narrowed to the observed min/max.
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let h = Histogram::new();
+    /// for v in 1..=1_000 {
+    ///     h.record(v);
+    /// }
+    /// let snap = h.snapshot();
+    /// assert_eq!(snap.count_below(250), 250);
+    /// assert_eq!(snap.count_below(0), 0);
+    /// assert_eq!(snap.count_below(5_000), 1_000);
+    /// ```
+    pub fn count_below(&self, value_ns: u64) -> u64 {
+        let (Some(min_v), Some(max_v)) = (self.min, self.max) else {
+            return 0;
+        };
+        if value_ns < min_v {
+            return 0;
+        }
+        if value_ns >= max_v {
+            return self.count;
+        }
+        let mut below = 0u64;
+        for bin in &self.bins {
+            // Samples in this bin lie in [low, high), narrowed to [min, max].
+            let low = bin.low.max(min_v);
+            let last = bin.high.saturating_sub(1).min(max_v);
+            if value_ns >= last {
+                below = below.saturating_add(bin.count);
+                continue;
+            }
+            if value_ns >= low {
+                let width = u128::from(last - low) + 1;
+                let covered = u128::from(value_ns - low) + 1;
+                let part = u128::from(bin.count) * covered / width;
+                below = below.saturating_add(u64::try_from(part).unwrap_or(u64::MAX));
+            }
+            break;
+        }
+        below.min(self.count)
+    }
+
+    /// Fraction of samples at or below `value_ns`, in `[0.0, 1.0]` (0.0 when
+    /// empty): "what share of requests finished within 200ms".
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let h = Histogram::new();
+    /// for v in [100, 200, 300, 400] {
+    ///     h.record(v);
+    /// }
+    /// assert!((h.snapshot().percentile_rank(300) - 0.75).abs() < 1e-9);
+    /// ```
+    pub fn percentile_rank(&self, value_ns: u64) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let rank = self.count_below(value_ns) as f64 / self.count as f64;
+        rank
+    }
+
+    /// Representative value and count of each bucket: its midpoint, with the
+    /// range narrowed to the observed min/max (so 1ns buckets are exact).
+    #[allow(clippy::cast_precision_loss)]
+    fn midpoints(&self) -> impl Iterator<Item = (f64, u64, u64)> + '_ {
+        let (min_v, max_v) = (self.min.unwrap_or(0), self.max.unwrap_or(u64::MAX));
+        self.bins.iter().map(move |b| {
+            let low = b.low.max(min_v);
+            let last = b.high.saturating_sub(1).min(max_v).max(low);
+            let width = last - low + 1;
+            (low as f64 + (last - low) as f64 / 2.0, b.count, width)
+        })
+    }
+
+    /// Central moments 2..=4 around the exact mean, from bucket midpoints.
+    fn moments(&self) -> Option<(f64, f64, f64)> {
+        let mean = self.mean()?;
+        #[allow(clippy::cast_precision_loss)]
+        let n = self.count as f64;
+        let (mut m2, mut m3, mut m4) = (0.0, 0.0, 0.0);
+        for (mid, count, _) in self.midpoints() {
+            #[allow(clippy::cast_precision_loss)]
+            let c = count as f64;
+            let d = mid - mean;
+            m2 += c * d * d;
+            m3 += c * d * d * d;
+            m4 += c * d * d * d * d;
+        }
+        Some((m2 / n, m3 / n, m4 / n))
+    }
+
+    /// Geometric mean in nanoseconds, from bucket midpoints (zeros count as 1ns).
+    ///
+    /// Less sensitive than the arithmetic mean to a few huge outliers, which
+    /// makes it the better summary for skewed timing distributions.
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let h = Histogram::new();
+    /// for v in [10, 100, 1_000] {
+    ///     h.record(v);
+    /// }
+    /// let g = h.snapshot().geometric_mean().unwrap();
+    /// assert!((g - 100.0).abs() < 1.0, "{g}");
+    /// ```
+    pub fn geometric_mean(&self) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let log_sum: f64 = self
+            .midpoints()
+            .map(|(mid, count, _)| {
+                #[allow(clippy::cast_precision_loss)]
+                let c = count as f64;
+                c * mid.max(1.0).ln()
+            })
+            .sum();
+        #[allow(clippy::cast_precision_loss)]
+        let g = (log_sum / self.count as f64).exp();
+        Some(g)
+    }
+
+    /// Most common value: the midpoint of the densest bucket (most samples
+    /// per nanosecond of width; the lowest on ties).
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let h = Histogram::new();
+    /// for v in [40, 41, 41, 41, 900, 5_000_000] {
+    ///     h.record(v);
+    /// }
+    /// assert_eq!(h.snapshot().mode(), Some(41));
+    /// ```
+    pub fn mode(&self) -> Option<u64> {
+        let mut best: Option<(f64, f64)> = None;
+        for (mid, count, width) in self.midpoints() {
+            #[allow(clippy::cast_precision_loss)]
+            let density = count as f64 / width as f64;
+            if best.map_or(true, |(d, _)| density > d) {
+                best = Some((density, mid));
+            }
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        best.map(|(_, mid)| mid.round() as u64)
+    }
+
+    /// Sample skewness estimated from bucket midpoints: positive when the
+    /// tail is on the slow side, as is typical for latencies. `None` with
+    /// fewer than 3 samples or no spread.
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let h = Histogram::new();
+    /// for v in [100, 101, 102, 103, 104, 900] {
+    ///     h.record(v);
+    /// }
+    /// assert!(h.snapshot().skewness().unwrap() > 1.0);
+    /// ```
+    pub fn skewness(&self) -> Option<f64> {
+        if self.count < 3 {
+            return None;
+        }
+        let (m2, m3, _) = self.moments()?;
+        (m2 > 0.0).then(|| m3 / m2.powf(1.5))
+    }
+
+    /// Excess kurtosis estimated from bucket midpoints (0 for a normal
+    /// distribution; larger means heavier tails). `None` with fewer than 4
+    /// samples or no spread.
+    pub fn kurtosis(&self) -> Option<f64> {
+        if self.count < 4 {
+            return None;
+        }
+        let (m2, _, m4) = self.moments()?;
+        (m2 > 0.0).then(|| m4 / (m2 * m2) - 3.0)
+    }
+
+    /// Samples recorded after `earlier` was captured.
+    ///
+    /// Bucket counts, count and sum are subtracted exactly. Min/max of the
+    /// delta are bounded by the lowest/highest bucket that gained samples. If
+    /// any count went backwards (the histogram was reset in between), the
+    /// whole of `self` is returned.
+    #[must_use]
+    pub fn delta_since(&self, earlier: &HistogramSnapshot) -> HistogramSnapshot {
+        if earlier.count > self.count {
+            return self.clone();
+        }
+        let mut bins = Vec::with_capacity(self.bins.len());
+        let mut prev = earlier.bins.iter().peekable();
+        for bin in &self.bins {
+            let (mut before, mut before_sum) = (0u64, Some(0u64));
+            while let Some(p) = prev.peek() {
+                if p.low < bin.low {
+                    if p.count > 0 {
+                        // Bucket vanished: histogram was reset.
+                        return self.clone();
+                    }
+                    prev.next();
+                } else {
+                    if p.low == bin.low {
+                        (before, before_sum) = (p.count, p.sum);
+                        prev.next();
+                    }
+                    break;
+                }
+            }
+            if before > bin.count {
+                return self.clone();
+            }
+            if bin.count > before {
+                bins.push(Bin {
+                    count: bin.count - before,
+                    sum: bin.sum.zip(before_sum).map(|(s, b)| s.saturating_sub(b)),
+                    ..*bin
+                });
+            }
+        }
+        if prev.next().is_some() {
+            return self.clone();
+        }
+        let min = match (bins.first(), self.min) {
+            (Some(b), Some(m)) => Some(b.low.max(m)),
+            _ => None,
+        };
+        let max = match (bins.last(), self.max) {
+            (Some(b), Some(m)) => Some(b.high.saturating_sub(1).min(m)),
+            _ => None,
+        };
+        let sum_sq = self
+            .sum_sq
+            .zip(earlier.sum_sq)
+            .map(|(s, e)| s.saturating_sub(e));
+        HistogramSnapshot::from_bins(bins, self.sum.saturating_sub(earlier.sum), min, max)
+            .with_sum_sq(sum_sq)
+    }
+}
+
+impl HistogramSnapshot {
+    /// Combine two snapshots, e.g. of the same metric in different processes.
+    ///
+    /// Buckets with the same range are added; count and sum add exactly and
+    /// min/max are the overall extremes. Merge snapshots taken with the same
+    /// backend and precision: differently sized buckets are kept side by
+    /// side and percentiles across them are less precise.
+    ///
+    /// # Examples
+    /// ```
+    /// # use benchmark::histogram::Histogram;
+    /// let a = Histogram::new();
+    /// let b = Histogram::new();
+    /// a.record(100);
+    /// b.record(300);
+    /// b.record(500);
+    /// let both = a.snapshot().merge(&b.snapshot());
+    /// assert_eq!(both.count(), 3);
+    /// assert_eq!(both.sum(), 900);
+    /// assert_eq!(both.min(), Some(100));
+    /// ```
+    #[must_use]
+    pub fn merge(&self, other: &HistogramSnapshot) -> HistogramSnapshot {
+        let mut bins = Vec::with_capacity(self.bins.len() + other.bins.len());
+        let (mut a, mut b) = (self.bins.iter().peekable(), other.bins.iter().peekable());
+        loop {
+            let from_a = match (a.peek(), b.peek()) {
+                (None, None) => break,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (Some(x), Some(y)) if (x.low, x.high) == (y.low, y.high) => {
+                    bins.push(Bin {
+                        count: x.count.saturating_add(y.count),
+                        sum: x.sum.zip(y.sum).map(|(a, b)| a.saturating_add(b)),
+                        ..**x
+                    });
+                    a.next();
+                    b.next();
+                    continue;
+                }
+                (Some(x), Some(y)) => (x.low, x.high) < (y.low, y.high),
+            };
+            let side = if from_a { &mut a } else { &mut b };
+            bins.extend(side.next().copied());
+        }
+        let pick = |x: Option<u64>, y: Option<u64>, f: fn(u64, u64) -> u64| match (x, y) {
+            (Some(x), Some(y)) => Some(f(x, y)),
+            (x, y) => x.or(y),
+        };
+        let sum_sq = match (self.is_empty(), other.is_empty()) {
+            (true, _) => other.sum_sq,
+            (_, true) => self.sum_sq,
+            _ => self
+                .sum_sq
+                .zip(other.sum_sq)
+                .map(|(a, b)| a.saturating_add(b)),
+        };
+        HistogramSnapshot::from_bins(
+            bins,
+            self.sum.saturating_add(other.sum),
+            pick(self.min, other.min, u64::min),
+            pick(self.max, other.max, u64::max),
+        )
+        .with_sum_sq(sum_sq)
+    }
+}
+
+impl core::ops::Sub<&HistogramSnapshot> for &HistogramSnapshot {
+    type Output = HistogramSnapshot;
+
+    fn sub(self, earlier: &HistogramSnapshot) -> HistogramSnapshot {
+        self.delta_since(earlier)
+    }
+}
+
+// `Histogram` is composed entirely of atomic primitives and thus is `Send` and `Sync`
+// by default. No explicit unsafe impls are required.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(not(feature = "hdr"))]
+    use std::sync::Arc;
+    #[cfg(not(feature = "hdr"))]
+    use std::thread;
+
+    #[inline]
+    fn perf_enabled() -> bool {
+        // Opt-in perf tests via environment to avoid CI/host variance
+        std::env::var_os("PERF_TESTS").is_some()
+    }
+
+    #[test]
+    fn test_snapshot_delta() {
+        let hist = Histogram::new();
+        for v in [10, 20, 5_000] {
+            hist.record(v);
+        }
+        let first = hist.snapshot();
+        assert_eq!(first.count(), 3);
+        assert_eq!(first.min(), Some(10));
+        assert_eq!(first.max(), hist.max());
+
+        hist.record(20);
+        hist.record(700);
+        let second = hist.snapshot();
+        let delta = &second - &first;
+        assert_eq!(delta.count(), 2);
+        assert_eq!(delta.sum(), second.sum() - first.sum());
+        let (lo, hi) = (delta.min().unwrap(), delta.max().unwrap());
+        assert!(lo <= 20 && hi >= 700, "{lo}..{hi}");
+        assert!(delta.percentile(0.5).unwrap() <= hi);
+        assert!(second.delta_since(&second).is_empty());
+
+        // A reset in between yields the full later snapshot.
+        hist.reset();
+        hist.record(1);
+        let after_reset = hist.snapshot();
+        assert_eq!(after_reset.delta_since(&second), after_reset);
+    }
+
+    #[test]
+    fn test_snapshot_merge() {
+        let a = Histogram::new();
+        let b = Histogram::new();
+        for v in [10, 20, 5_000] {
+            a.record(v);
+        }
+        for v in [20, 9_000_000] {
+            b.record(v);
+        }
+        let (sa, sb) = (a.snapshot(), b.snapshot());
+        let m = sa.merge(&sb);
+        assert_eq!(m.count(), 5);
+        assert_eq!(m.sum(), sa.sum() + sb.sum());
+        assert_eq!((m.min(), m.max()), (sa.min(), sb.max()));
+        assert_eq!(m.buckets().map(|b| b.2).sum::<u64>(), 5);
+        assert!(m.buckets().zip(m.buckets().skip(1)).all(|(x, y)| x.0 < y.0));
+        assert_eq!(m, sb.merge(&sa));
+        assert_eq!(sa.merge(&HistogramSnapshot::default()), sa);
+    }
+
+    #[test]
+    fn test_count_below_and_percentile_rank() {
+        let empty = Histogram::new();
+        assert_eq!(empty.count_below(100), 0);
+        assert!(empty.percentile_rank(100).abs() < f64::EPSILON);
+
+        // 900 fast samples and 100 slow ones spread over wide buckets.
+        let h = Histogram::new();
+        for v in 0..900 {
+            h.record(v % 500);
+        }
+        for v in 0..100 {
+            h.record(150_000_000 + v * 1_000_000);
+        }
+        let snap = h.snapshot();
+        assert_eq!(snap.count_below(499), 900);
+        assert_eq!(snap.count_below(1_000_000), 900);
+        assert_eq!(snap.count_below(u64::MAX), 1_000);
+        // Within the slow tail the answer is interpolated, but monotonic and
+        // close to the inverse of `percentile`.
+        let p95 = snap.percentile(0.95).unwrap();
+        let rank = snap.percentile_rank(p95);
+        assert!((0.93..=0.97).contains(&rank), "p95={p95} rank={rank}");
+        let mut prev = 0;
+        for v in (150_000_000..=250_000_000).step_by(5_000_000) {
+            let n = snap.count_below(v);
+            assert!(n >= prev);
+            prev = n;
+        }
+    }
+
+    #[test]
+    fn test_shape_statistics() {
+        let empty = HistogramSnapshot::default();
+        assert_eq!(empty.geometric_mean(), None);
+        assert_eq!(empty.mode(), None);
+        assert_eq!(empty.skewness(), None);
+
+        let flat = Histogram::new();
+        for _ in 0..10 {
+            flat.record(500);
+        }
+        assert_eq!(flat.mode(), Some(500));
+        assert!((flat.geometric_mean().unwrap() - 500.0).abs() < 1e-6);
+        assert_eq!(flat.skewness(), None);
+        assert_eq!(flat.kurtosis(), None);
+
+        // Symmetric and exact (1ns buckets): no skew, flat-topped.
+        let uniform = Histogram::new();
+        for v in 100..=200 {
+            uniform.record(v);
+        }
+        let snap = uniform.snapshot();
+        assert!(snap.skewness().unwrap().abs() < 1e-9);
+        assert!((snap.kurtosis().unwrap() + 1.2).abs() < 0.01);
+        let g = snap.geometric_mean().unwrap();
+        assert!(g < 150.0 && g > 145.0, "{g}");
+
+        // A heavy slow tail pulls the mean far more than the geometric mean.
+        let tailed = Histogram::new();
+        for _ in 0..95 {
+            tailed.record(200);
+        }
+        for _ in 0..5 {
+            tailed.record(20_000_000);
+        }
+        let snap = tailed.snapshot();
+        assert_eq!(snap.mode(), Some(200));
+        assert!(snap.skewness().unwrap() > 3.0);
+        assert!(snap.kurtosis().unwrap() > 10.0);
+        assert!(snap.geometric_mean().unwrap() * 100.0 < snap.mean().unwrap());
+    }
+
+    #[test]
+    fn test_record_returning() {
+        let hist = Histogram::new();
+        let small = hist.record_returning(10);
+        let mid = hist.record_returning(100_000);
+        let mid2 = hist.record_returning(100_000);
+        let big = hist.record_returning(1_000_000_000);
+        assert_eq!(hist.count(), 4);
+        assert!(small.bucket_index < mid.bucket_index);
+        assert!(mid.bucket_index < big.bucket_index);
+        assert_eq!(mid, mid2);
+        assert!(!small.clamped && !big.clamped);
+    }
+
+    #[cfg(feature = "hdr")]
+    #[test]
+    fn test_record_returning_clamps_out_of_range() {
+        let hist = Histogram::new();
+        assert!(hist.record_returning(0).clamped);
+        let top = hist.record_returning(u64::MAX);
+        assert!(top.clamped);
+        let edge = hist.record_returning(3_600_000_000_000);
+        assert!(!edge.clamped);
+        assert_eq!(top.bucket_index, edge.bucket_index);
+        // 3 significant figures: exact below 2048, width-2 ranges above.
+        let at = |v| hist.record_returning(v).bucket_index;
+        assert_eq!(at(2047) + 1, at(2048));
+        assert_eq!(at(2048), at(2049));
+    }
+
+    #[test]
+    fn test_saturated_counts_clamped_samples() {
+        let hist = Histogram::new();
+        hist.record(1_000);
+        assert_eq!(hist.saturated(), 0);
+        assert!(hist.record_returning(u64::MAX).clamped);
+        hist.record_duration(Duration::MAX);
+        assert_eq!(hist.saturated(), 2);
+        assert_eq!(hist.count(), 3);
+        assert_eq!(hist.take_snapshot().count(), 3);
+        assert_eq!(hist.saturated(), 0);
+    }
+
+    #[cfg(feature = "hdr")]
+    #[test]
+    fn test_auto_resize_grows_past_one_hour() {
+        let two_hours = 7_200_000_000_000;
+        let fixed = Histogram::new();
+        let auto = Histogram::new().with_auto_resize();
+        for h in [&fixed, &auto] {
+            h.record(1_000);
+            h.record(two_hours);
+        }
+        assert_eq!(fixed.saturated(), 1);
+        assert!(fixed.max().unwrap() < two_hours);
+        assert_eq!(auto.saturated(), 0);
+        assert!(auto.max().unwrap().abs_diff(two_hours) <= two_hours / 1000);
+        assert!(auto.record_returning(u64::MAX).clamped);
+        assert_eq!(auto.saturated(), 1);
+    }
+
+    #[test]
+    fn test_empty_histogram() {
+        let hist = Histogram::new();
+        assert!(hist.is_empty());
+        assert_eq!(hist.count(), 0);
+        assert_eq!(hist.min(), None);
+        assert_eq!(hist.max(), None);
         assert_eq!(hist.mean(), None);
         assert_eq!(hist.percentile(0.5), None);
         assert_eq!(hist.median(), None);
@@ -876,6 +2160,50 @@ mod tests {
         assert_eq!(hist.median(), Some(200));
     }
 
+    #[test]
+    fn test_sum_matches_recorded_total() {
+        let hist = Histogram::new();
+        assert_eq!(hist.sum(), 0);
+        for v in [100, 200, 300] {
+            hist.record(v);
+        }
+        assert_eq!(hist.sum(), 600);
+        assert_eq!(hist.snapshot().sum(), 600);
+        hist.reset();
+        assert_eq!(hist.sum(), 0);
+    }
+
+    #[test]
+    fn test_variance_and_stddev() {
+        let hist = Histogram::new();
+        assert_eq!(hist.variance(), None);
+        for v in [2, 4, 4, 4, 5, 5, 7, 9] {
+            hist.record(v);
+        }
+        assert!((hist.variance().unwrap() - 4.0).abs() < 1e-9);
+        assert!((hist.stddev().unwrap() - 2.0).abs() < 1e-9);
+
+        let snap = hist.snapshot();
+        assert!((snap.stddev().unwrap() - 2.0).abs() < 1.0);
+    }
+
+    #[cfg(not(feature = "hdr"))]
+    #[test]
+    fn test_variance_exact_for_large_values() {
+        // Squares exceed u64 and the spread is tiny relative to the magnitude.
+        let hist = Histogram::new();
+        let base = 1u64 << 40;
+        for _ in 0..1_000 {
+            hist.record(base - 3);
+            hist.record(base + 3);
+        }
+        assert_eq!(hist.variance(), Some(9.0));
+        assert_eq!(hist.stddev(), Some(3.0));
+        hist.reset();
+        hist.record(u64::MAX / 2);
+        assert_eq!(hist.variance(), Some(0.0));
+    }
+
     #[cfg(not(feature = "hdr"))]
     #[test]
     fn test_percentiles() {
@@ -944,7 +2272,7 @@ mod tests {
         assert_eq!(hist.max(), Some(1_000_000));
 
         let median_duration = hist.median_duration().unwrap();
-        assert_eq!(median_duration, Duration::from_nanos(1000));
+        assert_eq!(median_duration, Duration::from_micros(1));
     }
 
     #[test]
@@ -1007,6 +2335,68 @@ mod tests {
         assert_eq!(hist.max(), Some(9_999));
     }
 
+    #[cfg(not(feature = "hdr"))]
+    #[test]
+    fn test_consistent_snapshot_never_mixes_in_flight_records() {
+        let hist = Arc::new(Histogram::new());
+        let writers: Vec<_> = [700, 70_000]
+            .into_iter()
+            .map(|v| {
+                let hist = Arc::clone(&hist);
+                thread::spawn(move || {
+                    for _ in 0..20_000 {
+                        hist.record(v);
+                    }
+                })
+            })
+            .collect();
+        while !writers.iter().all(thread::JoinHandle::is_finished) {
+            if let Ok(s) = hist.inner.read_consistent() {
+                let low = s.count_below(701);
+                assert_eq!(s.sum(), low * 700 + (s.count() - low) * 70_000);
+                if low > 0 {
+                    assert_eq!(s.min(), Some(700));
+                }
+            }
+        }
+        for t in writers {
+            t.join().unwrap();
+        }
+        let s = hist.inner.read_consistent().unwrap();
+        assert_eq!((s.count(), s.sum()), (40_000, 20_000 * 70_700));
+    }
+
+    #[cfg(not(feature = "hdr"))]
+    #[test]
+    fn test_reset_racing_records_keeps_count_and_extremes_consistent() {
+        let hist = Arc::new(Histogram::new());
+        let writers: Vec<_> = [500, 50_000]
+            .into_iter()
+            .map(|v| {
+                let hist = Arc::clone(&hist);
+                thread::spawn(move || {
+                    for _ in 0..20_000 {
+                        hist.record(v);
+                    }
+                })
+            })
+            .collect();
+        while !writers.iter().all(thread::JoinHandle::is_finished) {
+            hist.reset();
+            let s = hist.snapshot();
+            if let (Some(min), Some(max)) = (s.min(), s.max()) {
+                assert!(min <= max);
+                assert!(min == 500 || s.count_below(501) == 0);
+            }
+        }
+        for t in writers {
+            t.join().unwrap();
+        }
+        assert_eq!(hist.count(), hist.snapshot().count());
+        hist.reset();
+        assert_eq!((hist.count(), hist.sum(), hist.min()), (0, 0, None));
+    }
+
     #[cfg(not(feature = "hdr"))]
     #[test]
     fn test_concurrent_statistics() {
@@ -1101,6 +2491,109 @@ mod tests {
         assert!((50_000..=150_000).contains(&median));
     }
 
+    #[cfg(not(feature = "hdr"))]
+    #[test]
+    fn test_bucket_sums_center_interpolation_on_mean() {
+        let plain = FastHistogram::new();
+        let summed = FastHistogram::new().with_bucket_sums();
+        assert!(!plain.has_bucket_sums() && summed.has_bucket_sums());
+        // A tight cluster near the top of [2^20, 2^21) plus a few fast samples.
+        let values: Vec<u64> = (0..1_000).map(|i| 1_900_000 + (i % 100) * 1_000).collect();
+        for h in [&plain, &summed] {
+            for &v in &values {
+                h.record(v);
+            }
+            for v in [10, 20, 30] {
+                h.record(v);
+            }
+        }
+        let exact = 1_949_000;
+        let (p, q) = (plain.median().unwrap(), summed.median().unwrap());
+        assert!(q.abs_diff(exact) < 5_000, "summed p50={q}");
+        assert!(q.abs_diff(exact) * 10 < p.abs_diff(exact), "plain p50={p}");
+        assert_eq!(summed.percentiles(&[0.5])[0], Some(q));
+        // Linear buckets are exact either way; extremes stay exact.
+        assert_eq!(summed.percentile(0.001), Some(20));
+        assert_eq!(summed.percentile(1.0), Some(1_999_000));
+
+        // A lone sample in a wide bucket is reported exactly.
+        summed.reset();
+        summed.record(3_333_333);
+        summed.record(10);
+        assert_eq!(summed.percentile(0.9), Some(3_333_333));
+        assert_eq!(
+            FastHistogram::with_sub_buckets(8)
+                .with_bucket_sums()
+                .sub_buckets(),
+            8
+        );
+    }
+
+    #[cfg(not(feature = "hdr"))]
+    #[test]
+    fn test_snapshot_matches_live_stats_with_bucket_sums() {
+        let h = FastHistogram::new().with_bucket_sums();
+        for i in 0..1_000u64 {
+            h.record(1_900_000 + (i % 100) * 1_000);
+            h.record(i % 7);
+        }
+        let snap = h.snapshot();
+        for p in [0.1, 0.5, 0.9, 0.99, 0.999] {
+            assert_eq!(snap.percentile(p), h.percentile(p), "p={p}");
+        }
+        assert_eq!(snap.stddev(), h.stddev());
+
+        let earlier = h.snapshot();
+        for v in [1_950_000, 1_960_000, 5] {
+            h.record(v);
+        }
+        let delta = h.snapshot().delta_since(&earlier);
+        assert_eq!((delta.count(), delta.sum()), (3, 3_910_005));
+        let fresh = FastHistogram::new().with_bucket_sums();
+        for v in [1_950_000, 1_960_000, 5] {
+            fresh.record(v);
+        }
+        assert_eq!(delta.stddev(), fresh.stddev());
+        let taken = h.take_snapshot();
+        assert_eq!(taken.stddev(), h.snapshot().merge(&taken).stddev());
+    }
+
+    #[cfg(not(feature = "hdr"))]
+    #[test]
+    fn test_sub_buckets_precision() {
+        assert_eq!(FastHistogram::new().sub_buckets(), 1);
+        assert_eq!(FastHistogram::with_sub_buckets(0).sub_buckets(), 1);
+        assert_eq!(FastHistogram::with_sub_buckets(100).sub_buckets(), 128);
+        assert_eq!(FastHistogram::with_sub_buckets(1 << 20).sub_buckets(), 1024);
+
+        let hist = FastHistogram::with_sub_buckets(128);
+        for v in [1_500u64, 37_000, 999_999, 12_345_678_901, u64::MAX / 3] {
+            hist.reset();
+            hist.record(v);
+            hist.record(v);
+            let p = hist.percentile(0.5).unwrap();
+            assert!(p.abs_diff(v) <= v / 100, "v={v} p={p}");
+        }
+
+        // Buckets tile the range without gaps, continuing the linear range.
+        let snap = {
+            hist.reset();
+            for v in (1_000..5_000).step_by(7) {
+                hist.record(v);
+            }
+            hist.snapshot()
+        };
+        let bins: Vec<(u64, u64, u64)> = snap.buckets().collect();
+        assert!(bins.windows(2).all(|w| w[0].1 <= w[1].0));
+        assert_eq!(bins.iter().map(|b| b.2).sum::<u64>(), hist.count());
+        let first_log = bins.iter().find(|b| b.0 >= 1024).unwrap();
+        assert_eq!((first_log.0, first_log.1), (1024, 1032));
+        assert_eq!(
+            hist.record_returning(1_031).bucket_index + 1,
+            hist.record_returning(1_032).bucket_index
+        );
+    }
+
     #[cfg(not(feature = "hdr"))]
     #[test]
     fn test_edge_cases() {
@@ -0,0 +1,284 @@
+#![cfg(feature = "std")]
+//! `Read`/`Write` adapters that record I/O costs into a [`Watch`].
+//!
+//! [`TimedReader`] and [`TimedWriter`] wrap any reader or writer (files,
+//! sockets, pipes) and record, per successful call:
+//!
+//! | adapter       | metrics                                                  |
+//! |---------------|----------------------------------------------------------|
+//! | `TimedReader` | `<name>.read` (ns), `<name>.read.bytes`                   |
+//! | `TimedWriter` | `<name>.write` (ns), `<name>.write.bytes`, `<name>.flush` (ns) |
+//!
+//! The `.bytes` metrics hold the size of each call, so their `total_ns`
+//! field is the cumulative byte count, and throughput is
+//! `bytes.total_ns / latency.total_ns` bytes per nanosecond. Zero-byte calls
+//! (EOF) record latency only. Failed calls (including `WouldBlock` and
+//! `Interrupted`) are not recorded.
+//!
+//! Timestamps come from the Watch's clock. With `metrics` disabled the
+//! adapters still pass I/O through and record nothing.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::Watch;
+
+/// Time `op`, recording its latency and byte count on success.
+fn timed_io(
+    watch: &Watch,
+    latency: &str,
+    bytes: &str,
+    op: impl FnOnce() -> io::Result<usize>,
+) -> io::Result<usize> {
+    let start = watch.now_ns();
+    let n = op()?;
+    watch.record(latency, watch.now_ns().saturating_sub(start));
+    // Zero-byte calls (EOF) would be clamped up to the Watch's lowest bound.
+    if n > 0 {
+        watch.record(bytes, n as u64);
+    }
+    Ok(n)
+}
+
+/// A reader that records per-call latency and bytes read into a `Watch`.
+///
+/// # Examples
+/// ```
+/// use std::io::Read;
+/// use benchmark::io::TimedReader;
+/// use benchmark::Watch;
+///
+/// let w = Watch::new();
+/// let mut r = TimedReader::new(&b"hello world"[..], &w, "file");
+/// let mut buf = String::new();
+/// r.read_to_string(&mut buf).unwrap();
+/// assert_eq!(r.bytes_read(), 11);
+/// # #[cfg(feature = "metrics")]
+/// assert_eq!(w.snapshot()["file.read.bytes"].total_ns, 11);
+/// ```
+pub struct TimedReader<R> {
+    inner: R,
+    watch: Watch,
+    latency: String,
+    bytes: String,
+    total: u64,
+}
+
+impl<R: Read> TimedReader<R> {
+    /// Wrap `inner`, recording into `watch` under `<name>.read` / `<name>.read.bytes`.
+    pub fn new(inner: R, watch: &Watch, name: &str) -> Self {
+        Self {
+            inner,
+            watch: watch.clone(),
+            latency: format!("{name}.read"),
+            bytes: format!("{name}.read.bytes"),
+            total: 0,
+        }
+    }
+}
+
+impl<R> TimedReader<R> {
+    /// Total bytes read through this adapter.
+    pub fn bytes_read(&self) -> u64 {
+        self.total
+    }
+
+    /// Shared access to the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Mutable access to the wrapped reader (reads through it are not timed).
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap the reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for TimedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        let n = timed_io(&self.watch, &self.latency, &self.bytes, || inner.read(buf))?;
+        self.total += n as u64;
+        Ok(n)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        let n = timed_io(&self.watch, &self.latency, &self.bytes, || {
+            inner.read_vectored(bufs)
+        })?;
+        self.total += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for TimedReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimedReader")
+            .field("name", &self.latency.trim_end_matches(".read"))
+            .field("inner", &self.inner)
+            .field("bytes_read", &self.total)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A writer that records per-call latency and bytes written into a `Watch`.
+///
+/// # Examples
+/// ```
+/// use std::io::Write;
+/// use benchmark::io::TimedWriter;
+/// use benchmark::Watch;
+///
+/// let w = Watch::new();
+/// let mut out = TimedWriter::new(Vec::new(), &w, "sock");
+/// out.write_all(b"ping").unwrap();
+/// out.flush().unwrap();
+/// assert_eq!(out.bytes_written(), 4);
+/// # #[cfg(feature = "metrics")]
+/// # {
+/// let snap = w.snapshot();
+/// assert_eq!(snap["sock.write.bytes"].total_ns, 4);
+/// assert_eq!(snap["sock.flush"].count, 1);
+/// # }
+/// ```
+pub struct TimedWriter<W> {
+    inner: W,
+    watch: Watch,
+    latency: String,
+    bytes: String,
+    flush: String,
+    total: u64,
+}
+
+impl<W: Write> TimedWriter<W> {
+    /// Wrap `inner`, recording into `watch` under `<name>.write`,
+    /// `<name>.write.bytes` and `<name>.flush`.
+    pub fn new(inner: W, watch: &Watch, name: &str) -> Self {
+        Self {
+            inner,
+            watch: watch.clone(),
+            latency: format!("{name}.write"),
+            bytes: format!("{name}.write.bytes"),
+            flush: format!("{name}.flush"),
+            total: 0,
+        }
+    }
+}
+
+impl<W> TimedWriter<W> {
+    /// Total bytes written through this adapter.
+    pub fn bytes_written(&self) -> u64 {
+        self.total
+    }
+
+    /// Shared access to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Mutable access to the wrapped writer (writes through it are not timed).
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap the writer without flushing.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for TimedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        let n = timed_io(&self.watch, &self.latency, &self.bytes, || inner.write(buf))?;
+        self.total += n as u64;
+        Ok(n)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        let n = timed_io(&self.watch, &self.latency, &self.bytes, || {
+            inner.write_vectored(bufs)
+        })?;
+        self.total += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let start = self.watch.now_ns();
+        self.inner.flush()?;
+        self.watch
+            .record(&self.flush, self.watch.now_ns().saturating_sub(start));
+        Ok(())
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for TimedWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimedWriter")
+            .field("name", &self.latency.trim_end_matches(".write"))
+            .field("inner", &self.inner)
+            .field("bytes_written", &self.total)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reader that fails every other call.
+    struct Flaky(bool);
+
+    impl Read for Flaky {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0 = !self.0;
+            if self.0 {
+                Err(io::ErrorKind::WouldBlock.into())
+            } else {
+                buf[0] = 1;
+                Ok(1)
+            }
+        }
+    }
+
+    #[test]
+    fn test_reader_counts_successful_calls_only() {
+        let w = Watch::new();
+        let mut r = TimedReader::new(Flaky(false), &w, "sock");
+        let mut buf = [0u8; 4];
+        assert!(r.read(&mut buf).is_err());
+        assert_eq!(r.read(&mut buf).unwrap(), 1);
+        assert_eq!(r.bytes_read(), 1);
+        #[cfg(feature = "metrics")]
+        {
+            let snap = w.snapshot();
+            assert_eq!(snap["sock.read"].count, 1);
+            assert_eq!(snap["sock.read.bytes"].total_ns, 1);
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_writer_records_latency_with_watch_clock() {
+        use crate::test_util::MockClock;
+
+        let clock = MockClock::new();
+        clock.auto_advance(100);
+        let w = clock.watch();
+        let mut out = TimedWriter::new(Vec::new(), &w, "log");
+        out.write_all(b"abc").unwrap();
+        out.write_all(b"de").unwrap();
+        assert_eq!(out.into_inner(), b"abcde");
+        let snap = w.snapshot();
+        assert_eq!(snap["log.write"].count, 2);
+        assert_eq!(snap["log.write"].max, 100);
+        assert_eq!(snap["log.write.bytes"].total_ns, 5);
+    }
+}
@@ -0,0 +1,295 @@
+#![cfg(feature = "std")]
+//! `JUnit` XML output for performance gates.
+//!
+//! CI systems already render `JUnit` reports for unit tests. [`JUnitReport`]
+//! turns benchmark results into test cases — one per benchmark, failing when
+//! a gate is exceeded, with the timing details in the message — so
+//! performance checks show up in the same UI without custom tooling.
+//!
+//! Cases can come from runner results with a time budget
+//! ([`JUnitReport::bench`], `benchmark` feature), from a before/after
+//! [`compare`](crate::compare()) with a regression tolerance
+//! ([`JUnitReport::delta`], `collector` feature), or be added directly with
+//! [`JUnitReport::case`].
+
+use std::fmt::{self, Write as _};
+use std::io;
+
+#[cfg(feature = "benchmark")]
+use crate::BenchResult;
+use crate::Duration;
+#[cfg(feature = "collector")]
+use crate::MetricDelta;
+
+/// One test case of a [`JUnitReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Case {
+    name: String,
+    time: Duration,
+    failure: Option<String>,
+    details: String,
+}
+
+/// A `JUnit` `<testsuite>` of performance checks.
+///
+/// # Examples
+/// ```
+/// use benchmark::junit::JUnitReport;
+/// use benchmark::Duration;
+///
+/// let mut report = JUnitReport::new("perf");
+/// report.case("parse", Duration::from_nanos(1_200), Ok("mean 1.2µs".to_string()));
+/// report.case("render", Duration::from_nanos(9_000), Err("mean 9µs > budget 5µs".to_string()));
+/// assert_eq!(report.failures(), 1);
+/// let xml = report.to_string();
+/// assert!(xml.contains(r#"<testsuite name="perf" tests="2" failures="1""#));
+/// assert!(xml.contains(r#"<failure message="mean 9µs &gt; budget 5µs"/>"#));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JUnitReport {
+    suite: String,
+    cases: Vec<Case>,
+}
+
+impl JUnitReport {
+    /// Create an empty report for the test suite `suite`.
+    pub fn new(suite: impl Into<String>) -> Self {
+        Self {
+            suite: suite.into(),
+            cases: Vec::new(),
+        }
+    }
+
+    /// Add a case taking `time`; `Ok` passes with the given details, `Err`
+    /// fails with the given message.
+    pub fn case(
+        &mut self,
+        name: impl Into<String>,
+        time: Duration,
+        outcome: Result<String, String>,
+    ) -> &mut Self {
+        let (failure, details) = match outcome {
+            Ok(details) => (None, details),
+            Err(message) => (Some(message), String::new()),
+        };
+        self.cases.push(Case {
+            name: name.into(),
+            time,
+            failure,
+            details,
+        });
+        self
+    }
+
+    /// Add a runner result, failing if its mean exceeds `budget` (if any) or
+    /// if it was flagged `throttled`.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::junit::JUnitReport;
+    /// use benchmark::{Duration, Runner};
+    ///
+    /// let results = Runner::new().iterations(10).bench("noop", || {}).run();
+    /// let mut report = JUnitReport::new("bench");
+    /// report.bench(&results[0], Some(Duration::from_millis(100)));
+    /// assert_eq!(report.failures(), 0);
+    /// ```
+    #[cfg(feature = "benchmark")]
+    pub fn bench(&mut self, result: &BenchResult, budget: Option<Duration>) -> &mut Self {
+        let mut details = format!(
+            "iterations={} mean={} median={} min={} max={}",
+            result.samples.len(),
+            result.mean,
+            result.median,
+            result.min,
+            result.max
+        );
+        if let Some(b) = budget {
+            let _ = write!(details, " budget={b}");
+        }
+        let failure = match budget {
+            Some(b) if result.mean > b => Some(format!("mean {} exceeds budget {b}", result.mean)),
+            _ if result.throttled => Some("reference workload slowed down (throttled)".to_string()),
+            _ => None,
+        };
+        self.cases.push(Case {
+            name: result.name.clone(),
+            time: result.total,
+            failure,
+            details,
+        });
+        self
+    }
+
+    /// Add every runner result with the same `budget`.
+    #[cfg(feature = "benchmark")]
+    pub fn benches(&mut self, results: &[BenchResult], budget: Option<Duration>) -> &mut Self {
+        for r in results {
+            self.bench(r, budget);
+        }
+        self
+    }
+
+    /// Add a before/after comparison, failing if the mean grew by more than
+    /// `tolerance` (e.g. `0.10` = 10% slower).
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::junit::JUnitReport;
+    /// use benchmark::{compare, Collector, Duration};
+    ///
+    /// let (before, after) = (Collector::new(), Collector::new());
+    /// before.record_duration("query", Duration::from_nanos(1_000));
+    /// after.record_duration("query", Duration::from_nanos(1_300));
+    /// let mut report = JUnitReport::new("regressions");
+    /// for d in compare(&before.all_stats(), &after.all_stats()) {
+    ///     report.delta(&d, 0.10);
+    /// }
+    /// assert_eq!(report.failures(), 1);
+    /// ```
+    #[cfg(feature = "collector")]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn delta(&mut self, delta: &MetricDelta, tolerance: f64) -> &mut Self {
+        let details = format!("mean {} (tolerance {:+.1}%)", delta.mean, tolerance * 100.0);
+        let regressed = delta.mean.relative().is_some_and(|r| r > tolerance);
+        let outcome = if regressed {
+            Err(format!("regression: {details}"))
+        } else {
+            Ok(details)
+        };
+        let time = Duration::from_nanos(delta.mean.after.max(0.0).round() as u128);
+        self.case(delta.name.clone(), time, outcome)
+    }
+
+    /// Number of cases.
+    pub fn tests(&self) -> usize {
+        self.cases.len()
+    }
+
+    /// Number of failing cases.
+    pub fn failures(&self) -> usize {
+        self.cases.iter().filter(|c| c.failure.is_some()).count()
+    }
+
+    /// Write the XML document to `out`.
+    ///
+    /// # Errors
+    /// Returns any error from `out`.
+    pub fn write_to<W: io::Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(self.to_string().as_bytes())
+    }
+}
+
+impl fmt::Display for JUnitReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total: u128 = self.cases.iter().map(|c| c.time.as_nanos()).sum();
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            f,
+            r#"<testsuite name="{}" tests="{}" failures="{}" errors="0" skipped="0" time="{}">"#,
+            Escaped(&self.suite),
+            self.tests(),
+            self.failures(),
+            Seconds(Duration::from_nanos(total))
+        )?;
+        for c in &self.cases {
+            write!(
+                f,
+                r#"  <testcase classname="{}" name="{}" time="{}""#,
+                Escaped(&self.suite),
+                Escaped(&c.name),
+                Seconds(c.time)
+            )?;
+            if c.failure.is_none() && c.details.is_empty() {
+                writeln!(f, "/>")?;
+                continue;
+            }
+            writeln!(f, ">")?;
+            if let Some(msg) = &c.failure {
+                writeln!(f, r#"    <failure message="{}"/>"#, Escaped(msg))?;
+            }
+            if !c.details.is_empty() {
+                writeln!(f, "    <system-out>{}</system-out>", Escaped(&c.details))?;
+            }
+            writeln!(f, "  </testcase>")?;
+        }
+        writeln!(f, "</testsuite>")
+    }
+}
+
+/// XML-escapes text and attribute values.
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ch in self.0.chars() {
+            match ch {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                c if c.is_control() && !matches!(c, '\n' | '\t') => {}
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `JUnit` `time` attribute: seconds with nanosecond precision.
+struct Seconds(Duration);
+
+impl fmt::Display for Seconds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ns = self.0.as_nanos();
+        write!(f, "{}.{:09}", ns / 1_000_000_000, ns % 1_000_000_000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_layout_and_escaping() {
+        let mut r = JUnitReport::new("a&b");
+        r.case("ok", Duration::from_millis(1_500), Ok(String::new()));
+        r.case("bad<1>", Duration::from_nanos(5), Err("\"slow\"".into()));
+        let xml = r.to_string();
+        let lines: Vec<&str> = xml.lines().collect();
+        assert_eq!(
+            lines[1],
+            r#"<testsuite name="a&amp;b" tests="2" failures="1" errors="0" skipped="0" time="1.500000005">"#
+        );
+        assert_eq!(
+            lines[2],
+            r#"  <testcase classname="a&amp;b" name="ok" time="1.500000000"/>"#
+        );
+        assert!(lines[3].contains(r#"name="bad&lt;1&gt;" time="0.000000005">"#));
+        assert_eq!(lines[4], r#"    <failure message="&quot;slow&quot;"/>"#);
+        assert_eq!(lines.last(), Some(&"</testsuite>"));
+
+        let mut buf = Vec::new();
+        r.write_to(&mut buf).unwrap();
+        assert_eq!(buf, xml.as_bytes());
+    }
+
+    #[cfg(feature = "benchmark")]
+    #[test]
+    fn test_bench_budget_gate() {
+        let results = crate::Runner::new()
+            .iterations(3)
+            .bench("spin", || {
+                std::hint::black_box((0..1_000u64).sum::<u64>());
+            })
+            .run();
+        let mut r = JUnitReport::new("perf");
+        r.bench(&results[0], Some(Duration::ZERO))
+            .bench(&results[0], None);
+        assert_eq!((r.tests(), r.failures()), (2, 1));
+        let xml = r.to_string();
+        assert!(xml.contains("exceeds budget 0ns"));
+        assert!(xml.contains("<system-out>iterations=3 mean="));
+    }
+}
@@ -54,48 +54,210 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![warn(missing_debug_implementations)]
-#![forbid(unsafe_code)]
+#![cfg_attr(
+    not(any(
+        feature = "tsc",
+        feature = "cpu-time",
+        feature = "alloc-count",
+        feature = "rss",
+        feature = "perf-events",
+        feature = "lock-free"
+    )),
+    forbid(unsafe_code)
+)]
+// The cycle-counter clock, the CPU-time, memory and perf syscalls, the
+// counting allocator and the lock-free map cell need a few audited `unsafe`
+// blocks; everything else stays safe.
+#![cfg_attr(
+    any(
+        feature = "tsc",
+        feature = "cpu-time",
+        feature = "alloc-count",
+        feature = "rss",
+        feature = "perf-events",
+        feature = "lock-free"
+    ),
+    deny(unsafe_code)
+)]
 #![deny(clippy::all)]
 #![warn(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::must_use_candidate)]
 
 // Core modules
+#[cfg(feature = "metrics")]
+pub mod aggregate;
+#[cfg(feature = "alloc-count")]
+pub mod alloc;
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "metrics")]
+mod anomaly;
+#[cfg(feature = "metrics")]
+mod apdex;
+#[cfg(feature = "lock-free")]
+mod arc_cell;
+#[cfg(feature = "std")]
+mod clock;
 #[cfg(feature = "collector")]
 mod collector;
+#[cfg(feature = "collector")]
+mod compare;
+#[cfg(feature = "metrics")]
+mod counter;
+#[cfg(feature = "cpu-time")]
+mod cpu;
+#[cfg(feature = "debug-http")]
+mod debug_http;
 mod duration;
+#[cfg(feature = "metrics-facade")]
+pub mod facade;
+#[cfg(feature = "metrics")]
+mod filter;
+#[cfg(feature = "benchmark")]
+mod harness;
+#[cfg(feature = "metrics")]
+mod heatmap;
 #[cfg(all(feature = "collector", feature = "metrics"))]
 mod hist_backend;
 #[cfg(all(feature = "collector", feature = "hdr"))]
 mod hist_hdr;
 #[cfg(feature = "collector")]
 pub mod histogram;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "std")]
+pub mod junit;
 mod measurement;
+#[cfg(all(feature = "std", not(feature = "metrics")))]
+mod noop;
+#[cfg(feature = "std")]
+mod overhead;
+#[cfg(feature = "perf-events")]
+mod perf;
+#[cfg(feature = "metrics")]
+mod rate;
+#[cfg(feature = "metrics")]
+mod recorder;
+#[cfg(feature = "collector")]
+pub mod report;
+#[cfg(feature = "reporter")]
+mod reporter;
+#[cfg(feature = "rss")]
+mod rss;
+#[cfg(feature = "benchmark")]
+mod runner;
+#[cfg(feature = "metrics")]
+mod sampling;
+#[cfg(feature = "metrics")]
+mod scoped;
+#[cfg(feature = "std")]
+mod self_check;
+#[cfg(feature = "metrics")]
+mod slowest;
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "std")]
+mod snapshot_diff;
+#[cfg(feature = "statsd")]
+mod statsd;
+#[cfg(feature = "benchmark")]
+mod suite;
+#[cfg(feature = "std")]
+pub mod sync;
+#[cfg(feature = "rt-tokio")]
+pub mod task;
+#[cfg(feature = "std")]
+pub mod test_util;
 #[cfg(feature = "metrics")]
 mod timer;
+#[cfg(feature = "tower")]
+pub mod tower;
 #[cfg(feature = "trace")]
 mod trace;
 #[cfg(feature = "metrics")]
 mod watch;
 
 // Public exports
+#[cfg(feature = "metrics")]
+pub use anomaly::Anomaly;
+#[cfg(feature = "wasm")]
+pub use clock::set_now_fn;
+#[cfg(feature = "tsc")]
+pub use clock::TscClock;
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use clock::{__elapsed_since, __now_ns, __unix_now_ns};
+#[cfg(feature = "std")]
+pub use clock::{
+    measure_with, measure_with_clock, raw_unix_now_ns, unix_now_ns, wall_anchor, Clock,
+    ManualClock, SystemClock, WallAnchor,
+};
 #[cfg(feature = "collector")]
-pub use collector::{Collector, Stats};
-pub use duration::Duration;
+pub use collector::{Collector, RecordTo, Stats, StreamingStats};
+#[cfg(feature = "collector")]
+pub use compare::{compare, Change, CompareTable, MetricDelta, MetricSummary, StatsSource};
+#[cfg(feature = "cpu-time")]
+pub use cpu::{measure_cpu, process_cpu_times, CpuTimes, ThreadCpuClock, WallAndCpu};
+#[cfg(feature = "debug-http")]
+pub use debug_http::DebugServer;
+pub use duration::{Duration, ParseDurationError, Unit};
+#[cfg(feature = "metrics")]
+pub use filter::{enable_all, enable_only, is_enabled};
+#[cfg(feature = "benchmark")]
+pub use harness::{Bencher, Harness};
+#[cfg(feature = "metrics")]
+pub use heatmap::Heatmap;
+#[cfg(target_has_atomic = "64")]
+pub use measurement::next_span_id;
 pub use measurement::Measurement;
+#[cfg(all(feature = "std", not(feature = "metrics")))]
+pub use noop::{enable_all, enable_only, is_enabled};
+#[cfg(all(feature = "std", not(feature = "metrics")))]
+pub use noop::{
+    Anomaly, Heatmap, InternalStats, LocalRecorder, MetricConfig, MetricId, MetricTree,
+    PartialSnapshot, Phases, ScopedTimer, ScopedWatch, SlowOp, SlowSample, Timer, Watch,
+    WatchBuilder, WatchStats,
+};
+#[cfg(all(feature = "std", not(feature = "collector")))]
+pub use noop::{Collector, RecordTo, Stats, StreamingStats};
+#[cfg(feature = "std")]
+pub use overhead::{overhead_report, OverheadReport};
+#[cfg(feature = "perf-events")]
+pub use perf::{PerfCounters, PerfCounts, PerfEvent};
+#[cfg(feature = "metrics")]
+pub use recorder::LocalRecorder;
+#[cfg(feature = "reporter")]
+pub use reporter::{Reporter, ReporterHandle};
+#[cfg(feature = "rss")]
+pub use rss::MemoryUsage;
+#[cfg(feature = "benchmark")]
+pub use runner::{BenchResult, Reference, Runner, TagSummary};
 #[cfg(feature = "metrics")]
-pub use timer::Timer;
+pub use scoped::{MetricTree, ScopedWatch};
+#[cfg(feature = "std")]
+pub use self_check::{self_check, SelfCheckReport};
 #[cfg(feature = "metrics")]
-pub use watch::{Watch, WatchBuilder, WatchStats};
+pub use slowest::{SlowOp, SlowSample};
+#[cfg(feature = "std")]
+pub use snapshot::{FrozenSnapshot, Snapshot};
+#[cfg(feature = "std")]
+pub use snapshot_diff::{MetricDiff, Shift, SnapshotDiff};
+#[cfg(feature = "statsd")]
+pub use statsd::{StatsdBuilder, StatsdEmitter, StatsdHandle};
+#[cfg(feature = "benchmark")]
+pub use suite::{Group, GroupResult, Suite};
+#[cfg(feature = "metrics")]
+pub use timer::{Phases, ScopedTimer, Timer};
+#[cfg(feature = "metrics")]
+pub use watch::{
+    InternalStats, MetricConfig, MetricId, PartialSnapshot, Watch, WatchBuilder, WatchStats,
+};
 
 // Re-export macros at crate root
 #[doc(hidden)]
 pub use crate as benchmark;
 
-// Core timing functionality
-#[cfg(feature = "benchmark")]
-use std::time::Instant;
-
 /// Measures the execution time of a function.
 ///
 /// Returns a tuple of (result, duration) where result is the return value
@@ -117,9 +279,9 @@ use std::time::Instant;
 #[cfg(feature = "benchmark")]
 #[inline]
 pub fn measure<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
-    let start = Instant::now();
+    let start = clock::system_now_ns();
     let result = f();
-    let duration = Duration::from_nanos(start.elapsed().as_nanos());
+    let duration = clock::__elapsed_since(start);
     (result, duration)
 }
 
@@ -149,21 +311,18 @@ pub fn measure<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
 #[cfg(all(feature = "benchmark", feature = "std"))]
 #[inline]
 pub fn measure_named<T, F: FnOnce() -> T>(name: &'static str, f: F) -> (T, Measurement) {
-    #[cfg(miri)]
-    let timestamp = 0;
-    #[cfg(not(miri))]
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map_or(0, |d| d.as_nanos());
-
-    let start = Instant::now();
+    let timestamp = clock::__unix_now_ns();
+
+    let start = clock::system_now_ns();
     let result = f();
-    let duration = Duration::from_nanos(start.elapsed().as_nanos());
+    let duration = clock::__elapsed_since(start);
 
     let measurement = Measurement {
         name,
         duration,
         timestamp,
+        span_id: None,
+        parent_id: None,
     };
 
     (result, measurement)
@@ -177,16 +336,198 @@ pub fn measure_named<T, F: FnOnce() -> T>(name: &'static str, f: F) -> (T, Measu
         name,
         duration: Duration::ZERO,
         timestamp: 0,
+        span_id: None,
+        parent_id: None,
     };
     (f(), measurement)
 }
 
+/// Awaits a future and measures how long it took to complete.
+///
+/// The async counterpart of [`measure`]: returns `(output, duration)`. The
+/// clock starts when the returned future is first polled, so the duration
+/// covers the whole await, including time the task spent suspended.
+///
+/// # Examples
+/// ```
+/// use benchmark::measure_async;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let (body, duration) = measure_async(async { "payload" }).await;
+/// assert_eq!(body, "payload");
+/// # let _ = duration;
+/// # });
+/// ```
+#[cfg(feature = "benchmark")]
+pub async fn measure_async<F: core::future::Future>(f: F) -> (F::Output, Duration) {
+    let start = clock::system_now_ns();
+    let result = f.await;
+    (result, clock::__elapsed_since(start))
+}
+
+/// Awaits a future without timing it (disabled version).
+#[cfg(not(feature = "benchmark"))]
+pub async fn measure_async<F: core::future::Future>(f: F) -> (F::Output, Duration) {
+    (f.await, Duration::ZERO)
+}
+
+/// Awaits a future and measures it as a named [`Measurement`].
+///
+/// The async counterpart of [`measure_named`]; timing starts at the first
+/// poll, as with [`measure_async`].
+///
+/// # Examples
+/// ```
+/// use benchmark::measure_named_async;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let (rows, m) = measure_named_async("db.query", async { 3 }).await;
+/// assert_eq!(rows, 3);
+/// assert_eq!(m.name, "db.query");
+/// # });
+/// ```
+#[cfg(all(feature = "benchmark", feature = "std"))]
+pub async fn measure_named_async<F: core::future::Future>(
+    name: &'static str,
+    f: F,
+) -> (F::Output, Measurement) {
+    let timestamp = clock::__unix_now_ns();
+    let (result, duration) = measure_async(f).await;
+    let measurement = Measurement {
+        name,
+        duration,
+        timestamp,
+        span_id: None,
+        parent_id: None,
+    };
+    (result, measurement)
+}
+
+/// Awaits a future without timing it (disabled version).
+#[cfg(not(feature = "benchmark"))]
+pub async fn measure_named_async<F: core::future::Future>(
+    name: &'static str,
+    f: F,
+) -> (F::Output, Measurement) {
+    let measurement = Measurement {
+        name,
+        duration: Duration::ZERO,
+        timestamp: 0,
+        span_id: None,
+        parent_id: None,
+    };
+    (f.await, measurement)
+}
+
+/// Runs `f` `n` times, timing each call separately.
+///
+/// Returns the output of the last call and one [`Duration`] per call, in
+/// call order. `f` always runs at least once, so `n == 0` behaves like
+/// `n == 1`. Each output is passed through [`black_box`] and earlier outputs
+/// are dropped outside the timed region, matching [`benchmark!`].
+///
+/// # Examples
+/// ```
+/// use benchmark::measure_n;
+///
+/// let (last, samples) = measure_n(100, || (1..=10u64).sum::<u64>());
+/// assert_eq!(last, 55);
+/// # #[cfg(feature = "benchmark")]
+/// assert_eq!(samples.len(), 100);
+/// ```
+#[cfg(feature = "benchmark")]
+pub fn measure_n<T, F: FnMut() -> T>(n: usize, mut f: F) -> (T, Vec<Duration>) {
+    let mut samples = Vec::with_capacity(n.max(1));
+    let mut timed = || {
+        let start = clock::system_now_ns();
+        let out = black_box(f());
+        samples.push(clock::__elapsed_since(start));
+        out
+    };
+    let mut last = timed();
+    for _ in 1..n {
+        last = timed();
+    }
+    (last, samples)
+}
+
+/// Runs `f` once and returns no samples (disabled version).
+#[cfg(all(not(feature = "benchmark"), feature = "std"))]
+#[inline]
+pub fn measure_n<T, F: FnMut() -> T>(_n: usize, mut f: F) -> (T, Vec<Duration>) {
+    (f(), Vec::new())
+}
+
+/// Runs `f` repeatedly until at least `min_time` of wall time has passed,
+/// timing each call separately.
+///
+/// Returns the output of the last call and one [`Duration`] per call. `f`
+/// runs at least once; the loop checks the clock between calls, so the
+/// total may overshoot `min_time` by up to one call. Useful when the number
+/// of iterations needed for stable numbers is not known up front.
+///
+/// # Examples
+/// ```
+/// use benchmark::{measure_until, Duration};
+///
+/// let (four, samples) = measure_until(Duration::from_millis(1), || 2 + 2);
+/// assert_eq!(four, 4);
+/// # #[cfg(feature = "benchmark")]
+/// assert!(!samples.is_empty());
+/// ```
+#[cfg(feature = "benchmark")]
+pub fn measure_until<T, F: FnMut() -> T>(min_time: Duration, mut f: F) -> (T, Vec<Duration>) {
+    let begin = clock::system_now_ns();
+    let mut samples = Vec::new();
+    loop {
+        let start = clock::system_now_ns();
+        let out = black_box(f());
+        samples.push(clock::__elapsed_since(start));
+        if clock::__elapsed_since(begin) >= min_time {
+            return (out, samples);
+        }
+    }
+}
+
+/// Runs `f` once and returns no samples (disabled version).
+#[cfg(all(not(feature = "benchmark"), feature = "std"))]
+#[inline]
+pub fn measure_until<T, F: FnMut() -> T>(_min_time: Duration, mut f: F) -> (T, Vec<Duration>) {
+    (f(), Vec::new())
+}
+
+/// An identity function the optimizer must treat as opaque.
+///
+/// Wrap benchmark inputs and outputs in `black_box` so the compiler can
+/// neither constant-fold the work from known inputs nor delete it because
+/// the result is unused; without it, small benchmark bodies are often
+/// measured as taking no time at all. This forwards to
+/// [`core::hint::black_box`] (best effort: it blocks the common
+/// optimizations but is not a formal guarantee) and needs no extra
+/// dependency.
+///
+/// `benchmark!`, `benchmark_block!` and `warmup!` already pass each
+/// iteration's output through `black_box`; use it explicitly for inputs.
+///
+/// # Examples
+/// ```
+/// use benchmark::{black_box, measure};
+///
+/// let (sum, _) = measure(|| (0..black_box(1_000u64)).sum::<u64>());
+/// assert_eq!(black_box(sum), 499_500);
+/// ```
+#[inline]
+pub fn black_box<T>(value: T) -> T {
+    core::hint::black_box(value)
+}
+
 // Macros
 
 /// Times an expression and returns (result, duration).
 ///
 /// When features `benchmark` + `std` are active, the macro inlines timing using
-/// `std::time::Instant` so it can be used inside async contexts (supports `await`).
+/// the crate clock (`std::time::Instant`, or the `wasm` backend) so it can be
+/// used inside async contexts (supports `await`).
 ///
 /// # Examples
 /// ```
@@ -199,9 +540,9 @@ pub fn measure_named<T, F: FnOnce() -> T>(name: &'static str, f: F) -> (T, Measu
 #[macro_export]
 macro_rules! time {
     ($expr:expr $(,)?) => {{
-        let __start = ::std::time::Instant::now();
+        let __start = $crate::__now_ns();
         let __out = { $expr };
-        let __dur = $crate::Duration::from_nanos(__start.elapsed().as_nanos());
+        let __dur = $crate::__elapsed_since(__start);
         (__out, __dur)
     }};
 }
@@ -218,7 +559,8 @@ macro_rules! time {
 /// Times an expression with a name and returns (result, measurement).
 ///
 /// When features `benchmark` + `std` are active, the macro inlines timing using
-/// `std::time::Instant` so it can be used inside async contexts (supports `await`).
+/// the crate clock (`std::time::Instant`, or the `wasm` backend) so it can be
+/// used inside async contexts (supports `await`).
 ///
 /// # Examples
 /// ```
@@ -233,19 +575,16 @@ macro_rules! time {
 macro_rules! time_named {
     ($name:expr, $expr:expr $(,)?) => {{
         let __name: &'static str = $name;
-        let __start = ::std::time::Instant::now();
+        let __start = $crate::__now_ns();
         let __out = { $expr };
-        let __dur = $crate::Duration::from_nanos(__start.elapsed().as_nanos());
-        #[cfg(miri)]
-        let __ts = 0;
-        #[cfg(not(miri))]
-        let __ts = ::std::time::SystemTime::now()
-            .duration_since(::std::time::UNIX_EPOCH)
-            .map_or(0, |d| d.as_nanos());
+        let __dur = $crate::__elapsed_since(__start);
+        let __ts = $crate::__unix_now_ns();
         let __measurement = $crate::Measurement {
             name: __name,
             duration: __dur,
             timestamp: __ts,
+            span_id: None,
+            parent_id: None,
         };
         (__out, __measurement)
     }};
@@ -260,6 +599,8 @@ macro_rules! time_named {
             name: $name,
             duration: $crate::Duration::ZERO,
             timestamp: 0,
+            span_id: None,
+            parent_id: None,
         };
         ($expr, measurement)
     }};
@@ -282,10 +623,128 @@ macro_rules! stopwatch {
 }
 
 /// Disabled version of `stopwatch!` when `metrics` is off.
+///
+/// The watch and name are still evaluated (by reference) so call sites
+/// type-check and produce the same warnings in both modes; no timer is created.
 #[cfg(not(all(feature = "metrics", feature = "std")))]
 #[macro_export]
 macro_rules! stopwatch {
     ($watch:expr, $name:expr, { $($body:tt)* } $(,)?) => {{
+        let _ = (&$watch, &$name);
+        { $($body)* }
+    }};
+}
+
+/// Variant of [`stopwatch!`] that also hands the elapsed time back:
+/// evaluates to `(body_result, Duration)` while recording under `name`.
+///
+/// The body is timed by a `Timer`, so a panic still records. With `metrics`
+/// off nothing is recorded and the duration comes from [`time!`] (zero when
+/// `benchmark` is off as well).
+///
+/// # Examples
+/// ```
+/// use benchmark::{stopwatch_timed, Duration, Watch};
+/// let w = Watch::new();
+/// let (rows, elapsed) = stopwatch_timed!(w, "query", { 3 });
+/// if elapsed > Duration::from_millis(100) {
+///     eprintln!("slow query: {elapsed}");
+/// }
+/// assert_eq!(rows, 3);
+/// # #[cfg(feature = "metrics")]
+/// assert_eq!(w.snapshot()["query"].count, 1);
+/// ```
+#[cfg(feature = "metrics")]
+#[macro_export]
+macro_rules! stopwatch_timed {
+    ($watch:expr, $name:expr, { $($body:tt)* } $(,)?) => {{
+        let __timer = $crate::Timer::new($watch.clone(), $name);
+        let __out = { $($body)* };
+        let __ns = __timer.stop();
+        (__out, $crate::Duration::from_nanos(u128::from(__ns)))
+    }};
+}
+
+/// Disabled version of `stopwatch_timed!` when `metrics` is off.
+#[cfg(not(all(feature = "metrics", feature = "std")))]
+#[macro_export]
+macro_rules! stopwatch_timed {
+    ($watch:expr, $name:expr, { $($body:tt)* } $(,)?) => {{
+        let _ = (&$watch, &$name);
+        $crate::time!({ $($body)* })
+    }};
+}
+
+/// Conditional [`stopwatch!`]: times the body and records under `name`
+/// only when `condition` is true; otherwise the body runs untimed.
+///
+/// `condition` is evaluated once, before the body, so it can come from
+/// runtime configuration (e.g. a flag that turns off an expensive metric
+/// in one deployment) without rebuilding with `metrics` off.
+///
+/// # Examples
+/// ```
+/// use benchmark::{stopwatch_if, Watch};
+/// let w = Watch::new();
+/// let trace_cache = false;
+/// let hit = stopwatch_if!(w, "cache.lookup", trace_cache, { true });
+/// assert!(hit);
+/// assert!(!w.snapshot().contains_key("cache.lookup"));
+/// ```
+#[cfg(feature = "metrics")]
+#[macro_export]
+macro_rules! stopwatch_if {
+    ($watch:expr, $name:expr, $cond:expr, { $($body:tt)* } $(,)?) => {{
+        let __timer = if $cond {
+            ::core::option::Option::Some($crate::Timer::new($watch.clone(), $name))
+        } else {
+            ::core::option::Option::None
+        };
+        { $($body)* }
+    }};
+}
+
+/// Disabled version of `stopwatch_if!` when `metrics` is off.
+#[cfg(not(all(feature = "metrics", feature = "std")))]
+#[macro_export]
+macro_rules! stopwatch_if {
+    ($watch:expr, $name:expr, $cond:expr, { $($body:tt)* } $(,)?) => {{
+        let _ = (&$watch, &$name, &$cond);
+        { $($body)* }
+    }};
+}
+
+/// Nested-scope variant of [`stopwatch!`]: times the body with a
+/// `ScopedTimer`, recording inclusive time under `name` and self time
+/// (excluding nested `stopwatch_scope!`s) under `name.self`.
+///
+/// Disabled path evaluates body with zero overhead.
+///
+/// # Examples
+/// ```
+/// use benchmark::{stopwatch_scope, Watch};
+/// let w = Watch::new();
+/// stopwatch_scope!(w, "parse", {
+///     stopwatch_scope!(w, "tokenize", { /* ... */ });
+/// });
+/// # #[cfg(feature = "metrics")]
+/// assert_eq!(w.snapshot()["tokenize.self"].count, 1);
+/// ```
+#[cfg(feature = "metrics")]
+#[macro_export]
+macro_rules! stopwatch_scope {
+    ($watch:expr, $name:expr, { $($body:tt)* } $(,)?) => {{
+        let __timer = $crate::ScopedTimer::new($watch.clone(), $name);
+        { $($body)* }
+    }};
+}
+
+/// Disabled version of `stopwatch_scope!` when `metrics` is off.
+#[cfg(not(all(feature = "metrics", feature = "std")))]
+#[macro_export]
+macro_rules! stopwatch_scope {
+    ($watch:expr, $name:expr, { $($body:tt)* } $(,)?) => {{
+        let _ = (&$watch, &$name);
         { $($body)* }
     }};
 }
@@ -296,9 +755,20 @@ macro_rules! stopwatch {
 /// - `benchmark_block!({ body })` uses a default of 10,000 iterations
 /// - `benchmark_block!(iters, { body })` runs the block `iters` times
 ///
-/// The block may contain `await` and arbitrary statements. When the `benchmark`
-/// feature is disabled, the block executes once (to preserve side effects) and
-/// the macro returns an empty `Vec` with zero timing overhead.
+/// The block may contain `await` and arbitrary statements. Its value, if
+/// any, is consumed through [`black_box`] each iteration, so a body ending
+/// in an expression is not optimized away. When the `benchmark` feature is
+/// disabled, the block executes once (to preserve side effects) and the
+/// macro returns an empty `Vec` with zero timing overhead.
+///
+/// # Examples
+/// ```
+/// use benchmark::benchmark_block;
+/// let data: Vec<u32> = (0..256).collect();
+/// let samples = benchmark_block!(100, { data.iter().map(|x| x * 2).sum::<u32>() });
+/// # #[cfg(feature = "benchmark")]
+/// assert_eq!(samples.len(), 100);
+/// ```
 #[cfg(feature = "benchmark")]
 #[macro_export]
 macro_rules! benchmark_block {
@@ -310,9 +780,9 @@ macro_rules! benchmark_block {
         let mut __samples: ::std::vec::Vec<$crate::Duration> = ::std::vec::Vec::with_capacity(__iters);
         let mut __i = 0usize;
         while __i < __iters {
-            let __start = ::std::time::Instant::now();
-            { $($body)* }
-            let __dur = $crate::Duration::from_nanos(__start.elapsed().as_nanos());
+            let __start = $crate::__now_ns();
+            $crate::black_box({ $($body)* });
+            let __dur = $crate::__elapsed_since(__start);
             __samples.push(__dur);
             __i += 1;
         }
@@ -325,12 +795,12 @@ macro_rules! benchmark_block {
 #[macro_export]
 macro_rules! benchmark_block {
     ({ $($body:tt)* } $(,)?) => {{
-        { $($body)* }
+        let _ = { $($body)* };
         ::std::vec::Vec::<$crate::Duration>::new()
     }};
     ($iters:expr, { $($body:tt)* } $(,)?) => {{
         let _ = $iters; // keep param unused warnings away
-        { $($body)* }
+        let _ = { $($body)* };
         ::std::vec::Vec::<$crate::Duration>::new()
     }};
 }
@@ -342,38 +812,64 @@ macro_rules! benchmark_block {
 /// - `benchmark!(name, expr)` uses a default of 10,000 iterations
 /// - `benchmark!(name, iters, expr)` runs `expr` `iters` times
 /// - `benchmark!(name, { body })` and `benchmark!(name, iters, { body })` also work
+/// - `benchmark!(name, iters, warmup = n, { body })` (or `benchmark!(name, warmup = n, { body })`)
+///   first runs the body `n` untimed times to warm caches and branch predictors;
+///   warmup outputs are discarded and not part of the returned measurements
 ///
-/// The expression/body may contain `await`. When the `benchmark` feature is
-/// disabled, the expression executes once and the macro returns `(Some(output), vec![])`
-/// with zero timing overhead.
+/// Each iteration's output is passed through [`black_box`] so the work
+/// cannot be optimized away. The expression/body may contain `await`. When
+/// the `benchmark` feature is disabled, the expression executes once (no warmup) and the macro returns
+/// `(Some(output), vec![])` with zero timing overhead.
+///
+/// # Examples
+/// ```
+/// use benchmark::benchmark;
+/// let (last, m) = benchmark!("parse", 100, warmup = 10, { "7".parse::<u8>().unwrap() });
+/// assert_eq!(last, Some(7));
+/// # #[cfg(feature = "benchmark")]
+/// assert_eq!(m.len(), 100);
+/// ```
 #[cfg(feature = "benchmark")]
 #[macro_export]
 macro_rules! benchmark {
-    ($name:expr, { $($body:tt)* } $(,)?) => {
-        $crate::benchmark!($name, 10_000usize, { $($body)* })
+    ($name:expr, warmup = $warmup:expr, { $($body:tt)* } $(,)?) => {
+        $crate::benchmark!($name, 10_000usize, warmup = $warmup, { $($body)* })
     };
-    ($name:expr, $iters:expr, { $($body:tt)* } $(,)?) => {{
+    ($name:expr, $iters:expr, warmup = $warmup:expr, { $($body:tt)* } $(,)?) => {{
         let __name: &'static str = $name;
         let __iters: usize = $iters;
+        let __warmup: usize = $warmup;
+        let mut __i = 0usize;
+        while __i < __warmup {
+            $crate::black_box({ $($body)* });
+            __i += 1;
+        }
         let mut __measurements: ::std::vec::Vec<$crate::Measurement> = ::std::vec::Vec::with_capacity(__iters);
         let mut __last = None;
         let mut __i = 0usize;
         while __i < __iters {
-            let __start = ::std::time::Instant::now();
-            let __out = { $($body)* };
-            let __dur = $crate::Duration::from_nanos(__start.elapsed().as_nanos());
-            #[cfg(miri)]
-            let __ts = 0;
-            #[cfg(not(miri))]
-            let __ts = ::std::time::SystemTime::now()
-                .duration_since(::std::time::UNIX_EPOCH)
-                .map_or(0, |d| d.as_nanos());
-            __measurements.push($crate::Measurement { name: __name, duration: __dur, timestamp: __ts });
+            let __start = $crate::__now_ns();
+            let __out = $crate::black_box({ $($body)* });
+            let __dur = $crate::__elapsed_since(__start);
+            let __ts = $crate::__unix_now_ns();
+            __measurements.push($crate::Measurement::new(__name, __dur, __ts));
             __last = Some(__out);
             __i += 1;
         }
         (__last, __measurements)
     }};
+    ($name:expr, warmup = $warmup:expr, $expr:expr $(,)?) => {
+        $crate::benchmark!($name, 10_000usize, warmup = $warmup, { $expr })
+    };
+    ($name:expr, $iters:expr, warmup = $warmup:expr, $expr:expr $(,)?) => {
+        $crate::benchmark!($name, $iters, warmup = $warmup, { $expr })
+    };
+    ($name:expr, { $($body:tt)* } $(,)?) => {
+        $crate::benchmark!($name, 10_000usize, warmup = 0usize, { $($body)* })
+    };
+    ($name:expr, $iters:expr, { $($body:tt)* } $(,)?) => {
+        $crate::benchmark!($name, $iters, warmup = 0usize, { $($body)* })
+    };
     ($name:expr, $expr:expr $(,)?) => {
         $crate::benchmark!($name, 10_000usize, { $expr })
     };
@@ -386,6 +882,26 @@ macro_rules! benchmark {
 #[cfg(not(feature = "benchmark"))]
 #[macro_export]
 macro_rules! benchmark {
+    ($name:expr, warmup = $warmup:expr, { $($body:tt)* } $(,)?) => {{
+        let _ = ($name, $warmup);
+        let __out = { $($body)* };
+        (Some(__out), ::std::vec::Vec::<$crate::Measurement>::new())
+    }};
+    ($name:expr, $iters:expr, warmup = $warmup:expr, { $($body:tt)* } $(,)?) => {{
+        let _ = ($name, $iters, $warmup);
+        let __out = { $($body)* };
+        (Some(__out), ::std::vec::Vec::<$crate::Measurement>::new())
+    }};
+    ($name:expr, warmup = $warmup:expr, $expr:expr $(,)?) => {{
+        let _ = ($name, $warmup);
+        let __out = $expr;
+        (Some(__out), ::std::vec::Vec::<$crate::Measurement>::new())
+    }};
+    ($name:expr, $iters:expr, warmup = $warmup:expr, $expr:expr $(,)?) => {{
+        let _ = ($name, $iters, $warmup);
+        let __out = $expr;
+        (Some(__out), ::std::vec::Vec::<$crate::Measurement>::new())
+    }};
     ($name:expr, { $($body:tt)* } $(,)?) => {{
         let _ = $name;
         let __out = { $($body)* };
@@ -408,5 +924,133 @@ macro_rules! benchmark {
     }};
 }
 
+/// Run a block untimed to warm caches, branch predictors and lazy state
+/// before measuring it, returning the number of runs.
+///
+/// Forms supported:
+/// - `warmup!(n, { body })` runs the body `n` times
+/// - `warmup!(time = duration, { body })` runs it until `duration`
+///   (a `std::time::Duration`) has elapsed
+///
+/// Outputs are passed through [`black_box`] and discarded. The body may
+/// contain `await`. When the `benchmark` feature is disabled, nothing runs
+/// and the macro returns `0`.
+///
+/// # Examples
+/// ```
+/// use benchmark::{benchmark_block, warmup};
+/// let table: Vec<u64> = (0..1_024).collect();
+/// let runs = warmup!(100, { table.iter().sum::<u64>() });
+/// # #[cfg(feature = "benchmark")]
+/// assert_eq!(runs, 100);
+/// let _ = warmup!(time = std::time::Duration::from_millis(1), { table.iter().sum::<u64>() });
+/// let samples = benchmark_block!(1_000, { table.iter().sum::<u64>() });
+/// # let _ = (runs, samples);
+/// ```
+#[cfg(feature = "benchmark")]
+#[macro_export]
+macro_rules! warmup {
+    (time = $time:expr, { $($body:tt)* } $(,)?) => {{
+        let __time: ::std::time::Duration = $time;
+        let __start = $crate::__now_ns();
+        let mut __runs = 0usize;
+        while $crate::__elapsed_since(__start).as_nanos() < __time.as_nanos() {
+            $crate::black_box({ $($body)* });
+            __runs += 1;
+        }
+        __runs
+    }};
+    ($iters:expr, { $($body:tt)* } $(,)?) => {{
+        let __iters: usize = $iters;
+        let mut __runs = 0usize;
+        while __runs < __iters {
+            $crate::black_box({ $($body)* });
+            __runs += 1;
+        }
+        __runs
+    }};
+}
+
+/// Disabled version of `warmup!` when `benchmark` is off.
+#[cfg(not(feature = "benchmark"))]
+#[macro_export]
+macro_rules! warmup {
+    (time = $time:expr, { $($body:tt)* } $(,)?) => {{
+        let _: ::std::time::Duration = $time;
+        0usize
+    }};
+    ($iters:expr, { $($body:tt)* } $(,)?) => {{
+        let _: usize = $iters;
+        0usize
+    }};
+}
+
+/// Generate `fn main` for a `cargo bench` target (`harness = false`) that
+/// runs the listed bench functions through [`Harness`].
+///
+/// Each function has the signature `fn(&mut Bencher)`; see the `Harness`
+/// docs for the command-line flags (`--filter`, `--iters`,
+/// `--save-baseline`, `--baseline`, ...). With the `benchmark` feature
+/// disabled the generated `main` does nothing.
+///
+/// # Examples
+/// ```no_run
+/// use benchmark::{benchmark_main, Bencher};
+///
+/// fn sum(b: &mut Bencher) {
+///     b.iter(|| (0..1_000u64).sum::<u64>());
+/// }
+///
+/// benchmark_main!(sum);
+/// ```
+#[cfg(feature = "benchmark")]
+#[macro_export]
+macro_rules! benchmark_main {
+    ($($bench:path),+ $(,)?) => {
+        fn main() {
+            $crate::Harness::from_args()
+                $(.bench(::core::stringify!($bench), $bench))+
+                .run();
+        }
+    };
+}
+
+/// Disabled version of `benchmark_main!` when `benchmark` is off.
+#[cfg(not(feature = "benchmark"))]
+#[macro_export]
+macro_rules! benchmark_main {
+    ($($bench:path),+ $(,)?) => {
+        fn main() {
+            $(let _ = $bench;)+
+        }
+    };
+}
+
+/// Like `benchmark!`, but aggregates the per-iteration measurements for you.
+///
+/// Accepts the same forms as `benchmark!` and returns
+/// `(last_output, Option<Stats>)`, where the stats carry min/mean/median/p95/
+/// stddev (see `Stats::from_measurements`). The stats are `None` when no
+/// iterations ran or when the `benchmark` or `collector` feature is disabled.
+///
+/// # Examples
+/// ```
+/// use benchmark::benchmark_stats;
+/// let (last, stats) = benchmark_stats!("parse", 1_000, { "42".parse::<u32>().unwrap() });
+/// assert_eq!(last, Some(42));
+/// if let Some(s) = stats {
+///     assert_eq!(s.count, 1_000);
+///     println!("median {} p95 {} stddev {}", s.median, s.p95, s.stddev);
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! benchmark_stats {
+    ($($args:tt)*) => {{
+        let (__last, __measurements) = $crate::benchmark!($($args)*);
+        (__last, $crate::Stats::from_measurements(&__measurements))
+    }};
+}
+
 // Intentionally no public trace! macro to avoid API surface area.
 // Use internal crate::trace::record_event() behind the `trace` feature.
@@ -1,6 +1,8 @@
 //! Measurement type for representing a timed operation.
 
 use crate::Duration;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicU64, Ordering};
 
 /// A single time measurement.
 ///
@@ -12,7 +14,15 @@ pub struct Measurement {
     /// The duration of the measurement.
     pub duration: Duration,
     /// Timestamp when measurement was taken (nanoseconds since UNIX epoch).
+    ///
+    /// Taken from `benchmark::unix_now_ns` by `measure_named` and
+    /// the timing macros, so timestamps stay ordered even if the system clock
+    /// is stepped.
     pub timestamp: u128,
+    /// Id of the span this measurement covers, if tracked.
+    pub span_id: Option<u64>,
+    /// Id of the enclosing span, if any; links measurements into call trees.
+    pub parent_id: Option<u64>,
 }
 
 impl Measurement {
@@ -31,6 +41,8 @@ impl Measurement {
             name,
             duration,
             timestamp,
+            span_id: None,
+            parent_id: None,
         }
     }
 
@@ -49,8 +61,48 @@ impl Measurement {
             name,
             duration: Duration::ZERO,
             timestamp: 0,
+            span_id: None,
+            parent_id: None,
         }
     }
+
+    /// Tags this measurement with a span id and optional parent span id.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{next_span_id, measure_named};
+    /// let root = next_span_id();
+    /// let (_, m) = measure_named("child", || 1 + 1);
+    /// let m = m.with_span(next_span_id(), Some(root));
+    /// assert_eq!(m.parent_id, Some(root));
+    /// assert_ne!(m.span_id, Some(root));
+    /// ```
+    #[must_use]
+    pub fn with_span(mut self, span_id: u64, parent_id: Option<u64>) -> Self {
+        self.span_id = Some(span_id);
+        self.parent_id = parent_id;
+        self
+    }
+}
+
+/// Returns a new span id, unique within this process and never 0.
+///
+/// Ids come from a relaxed atomic counter, so generating one costs a single
+/// `fetch_add`. They are not unique across processes; pair them with a
+/// process or host identifier when merging traces.
+///
+/// # Examples
+/// ```
+/// use benchmark::next_span_id;
+/// let a = next_span_id();
+/// let b = next_span_id();
+/// assert_ne!(a, b);
+/// assert!(a > 0 && b > 0);
+/// ```
+#[cfg(target_has_atomic = "64")]
+pub fn next_span_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
 }
 
 #[cfg(test)]
@@ -71,5 +123,17 @@ mod tests {
         assert_eq!(m.name, "test");
         assert_eq!(m.duration.as_nanos(), 0);
         assert_eq!(m.timestamp, 0);
+        assert_eq!((m.span_id, m.parent_id), (None, None));
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    #[test]
+    fn test_span_ids_link_measurements() {
+        let parent = Measurement::zero("req").with_span(next_span_id(), None);
+        let child = Measurement::new("db", Duration::from_nanos(5), 1)
+            .with_span(next_span_id(), parent.span_id);
+        assert_eq!(parent.parent_id, None);
+        assert_eq!(child.parent_id, parent.span_id);
+        assert!(child.span_id.unwrap() > parent.span_id.unwrap());
     }
 }
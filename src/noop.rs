@@ -0,0 +1,1132 @@
+#![cfg(all(feature = "std", not(feature = "metrics")))]
+//! No-op stand-ins for the metrics API when its features are disabled.
+//!
+//! Libraries can keep `Watch`/`Timer`/`Collector` instrumentation in their
+//! code unconditionally and let the final binary decide whether it is active.
+//! With `metrics` (or `collector`) off, these types keep the same names and
+//! method signatures but carry no state: recording does nothing, queries
+//! return empty results, and everything inlines away.
+
+// Receivers are kept so signatures match the real types exactly.
+#![allow(clippy::unused_self)]
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration as StdDuration, Instant};
+
+/// Snapshot stats for a single metric (always empty when disabled).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WatchStats {
+    /// Number of recorded samples.
+    pub count: u64,
+    /// Minimum observed value (ns).
+    pub min: u64,
+    /// Maximum observed value (ns).
+    pub max: u64,
+    /// 50th percentile/median (ns).
+    pub p50: u64,
+    /// 90th percentile (ns).
+    pub p90: u64,
+    /// 95th percentile (ns).
+    pub p95: u64,
+    /// 99th percentile (ns).
+    pub p99: u64,
+    /// 99.9th percentile (ns).
+    pub p999: u64,
+    /// Arithmetic mean (ns).
+    pub mean: f64,
+    /// Total of all samples (ns).
+    pub total_ns: u64,
+    /// Population standard deviation (ns).
+    pub stddev: f64,
+    /// Calls in the last complete second.
+    pub rate_1s: f64,
+    /// Mean calls per second over the last 60 complete seconds.
+    pub rate_1m: f64,
+    /// Wall-clock time of the first record (ns since the UNIX epoch).
+    pub first_seen_ns: u128,
+    /// Wall-clock time of the latest record (ns since the UNIX epoch).
+    pub last_seen_ns: u128,
+    /// Apdex target (ns).
+    pub apdex_target_ns: u64,
+    /// Samples at or under the target.
+    pub apdex_satisfied: u64,
+    /// Samples over the target but at most 4x the target.
+    pub apdex_tolerating: u64,
+    /// Samples over 4x the target.
+    pub apdex_frustrated: u64,
+    /// Apdex score in `[0, 1]`.
+    pub apdex: f64,
+    /// Percentage of samples at or under the target.
+    pub under_target_pct: f64,
+    /// Samples raised to the lowest discernible value.
+    pub clamped_low: u64,
+    /// Samples lowered to the highest trackable value.
+    pub clamped_high: u64,
+}
+
+/// Result of `Watch::snapshot_with_budget` (always empty when disabled).
+#[derive(Debug, Clone, Default)]
+pub struct PartialSnapshot {
+    /// Fully computed stats for the metrics that fit in the budget.
+    pub stats: HashMap<String, WatchStats>,
+    /// True if some metrics were skipped because the budget ran out.
+    pub truncated: bool,
+}
+
+/// Watch self-metrics (always zero when disabled).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InternalStats {
+    /// Values passed to the record calls.
+    pub records: u64,
+    /// Records skipped by sampling.
+    pub sampled_out: u64,
+    /// Records that registered their metric.
+    pub slow_path_inserts: u64,
+    /// Times a Watch lock had to be waited for.
+    pub lock_waits: u64,
+    /// Metrics removed by eviction.
+    pub evicted_metrics: u64,
+    /// Samples held by evicted metrics.
+    pub evicted_samples: u64,
+}
+
+/// Per-metric histogram settings (accepted and ignored when disabled).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetricConfig {
+    /// Lowest discernible value (ns).
+    pub lowest: Option<u64>,
+    /// Highest trackable value (ns).
+    pub highest: Option<u64>,
+    /// Histogram sub-buckets per power of two.
+    pub sub_buckets: Option<usize>,
+    /// Apdex / SLO target (ns).
+    pub apdex_target: Option<u64>,
+    /// Keep one record in this many.
+    pub sample_rate: Option<u64>,
+    /// Keep per-bucket sums for more accurate percentiles.
+    pub bucket_sums: bool,
+    /// Keep the last record time current.
+    pub track_last_seen: bool,
+}
+
+/// A pre-registered metric name (carries nothing when disabled).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MetricId {
+    _private: (),
+}
+
+/// One slow sample (never produced when disabled).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlowSample {
+    /// Recorded value (ns).
+    pub value_ns: u64,
+    /// Wall-clock time of the record, in nanoseconds since the UNIX epoch.
+    pub timestamp_ns: u128,
+}
+
+/// A record that exceeded a slow-op threshold (never produced when disabled).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowOp {
+    /// Metric name.
+    pub name: String,
+    /// Recorded value (ns, before clamping to the histogram bounds).
+    pub duration_ns: u64,
+    /// Wall-clock time of the record, in nanoseconds since the UNIX epoch.
+    pub timestamp_ns: u128,
+}
+
+impl std::fmt::Display for SlowOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "slow_op name={:?} duration={} duration_ns={} timestamp_ns={}",
+            self.name,
+            crate::Duration::from_nanos(u128::from(self.duration_ns)),
+            self.duration_ns,
+            self.timestamp_ns
+        )
+    }
+}
+
+/// A flagged latency shift (never produced when disabled).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anomaly {
+    /// Capture timestamp (ns since UNIX epoch).
+    pub timestamp: u128,
+    /// Mean latency during the interval (ns).
+    pub value: f64,
+    /// Median of the baseline (ns).
+    pub median: f64,
+    /// Median absolute deviation of the baseline (ns).
+    pub mad: f64,
+    /// Robust z-score of `value`.
+    pub score: f64,
+}
+
+/// Disabled namespaced `Watch` view: records nothing.
+#[derive(Debug, Clone)]
+pub struct ScopedWatch {
+    watch: Watch,
+    prefix: String,
+}
+
+impl ScopedWatch {
+    /// Namespace prefix, without the trailing separator.
+    pub fn prefix(&self) -> &str {
+        &self.prefix[..self.prefix.len() - 1]
+    }
+
+    /// The underlying `Watch`.
+    pub fn watch(&self) -> &Watch {
+        &self.watch
+    }
+
+    /// Fully-qualified metric name for `name` within this scope.
+    pub fn full_name(&self, name: &str) -> String {
+        let mut s = String::with_capacity(self.prefix.len() + name.len());
+        s.push_str(&self.prefix);
+        s.push_str(name);
+        s
+    }
+
+    /// Create a nested scope.
+    #[must_use]
+    pub fn scoped(&self, name: &str) -> ScopedWatch {
+        self.watch.scoped(&self.full_name(name))
+    }
+
+    /// Record nanoseconds (no-op).
+    #[inline]
+    pub fn record(&self, _name: &str, _duration_ns: u64) {}
+
+    /// Record elapsed time since `start` (no-op, returns 0).
+    #[inline]
+    pub fn record_instant(&self, _name: &str, _start: Instant) -> u64 {
+        0
+    }
+
+    /// Start a disabled `Timer`.
+    #[inline]
+    pub fn timer(&self, _name: &str) -> Timer {
+        Timer { _private: () }
+    }
+
+    /// Snapshot of metrics under this scope (always empty).
+    #[inline]
+    pub fn snapshot(&self) -> HashMap<String, WatchStats> {
+        HashMap::new()
+    }
+}
+
+/// A node in a hierarchical snapshot (always empty when disabled).
+#[derive(Debug, Clone, Default)]
+pub struct MetricTree {
+    /// Stats for the metric whose full name ends at this node.
+    pub stats: Option<WatchStats>,
+    /// Child nodes keyed by name segment.
+    pub children: BTreeMap<String, MetricTree>,
+}
+
+impl MetricTree {
+    /// Look up a node by dotted path.
+    pub fn get(&self, path: &str) -> Option<&MetricTree> {
+        path.split('.')
+            .filter(|s| !s.is_empty())
+            .try_fold(self, |node, seg| node.children.get(seg))
+    }
+
+    /// Total sample count of this node and all descendants (always 0).
+    #[inline]
+    pub fn total_count(&self) -> u64 {
+        0
+    }
+}
+
+/// A time × latency matrix of sample counts (always empty when disabled).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Heatmap {
+    /// Start of the first column (ns since UNIX epoch).
+    pub start_ns: u128,
+    /// Width of each column (ns).
+    pub resolution_ns: u64,
+    /// Latency band of each row as `[low, high)` in ns.
+    pub buckets: Vec<(u64, u64)>,
+    /// Sample counts indexed `[column][row]`.
+    pub counts: Vec<Vec<u64>>,
+}
+
+impl Heatmap {
+    /// Start of column `i` (ns since UNIX epoch).
+    pub fn column_start_ns(&self, i: usize) -> u128 {
+        self.start_ns + u128::from(self.resolution_ns) * i as u128
+    }
+
+    /// Total samples in the matrix (always 0).
+    #[inline]
+    pub fn total(&self) -> u64 {
+        0
+    }
+}
+
+/// Disabled `Watch`: records nothing and always snapshots empty.
+///
+/// # Examples
+/// ```
+/// use benchmark::{Timer, Watch};
+/// let w = Watch::new();
+/// w.record("op", 1_500);
+/// {
+///     let _t = Timer::new(w.clone(), "scope");
+/// }
+/// assert!(w.snapshot().is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Watch {
+    _private: (),
+}
+
+impl Watch {
+    /// Snapshot key reporting evicted metrics (never present when disabled).
+    pub const DROPPED_METRICS: &'static str = "benchmark.dropped_metrics";
+
+    /// Create a new (disabled) Watch.
+    #[inline]
+    pub fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Create a builder; bounds are accepted and ignored.
+    #[inline]
+    pub fn builder() -> WatchBuilder {
+        WatchBuilder::new()
+    }
+
+    /// Create a Watch with explicit bounds (ignored).
+    #[inline]
+    pub fn with_bounds(_lowest_discernible: u64, _highest_trackable: u64) -> Self {
+        Self::new()
+    }
+
+    /// Record nanoseconds for `name` (no-op).
+    #[inline]
+    pub fn record(&self, _name: &str, _duration_ns: u64) {}
+
+    /// Record nanoseconds for `name` (no-op, returns `None`).
+    #[cfg(feature = "collector")]
+    #[inline]
+    pub fn record_returning(
+        &self,
+        _name: &str,
+        _duration_ns: u64,
+    ) -> Option<crate::histogram::RecordInfo> {
+        None
+    }
+
+    /// Record a batch of nanosecond values for `name` (no-op).
+    #[inline]
+    pub fn record_many(&self, _name: &str, _values: &[u64]) {}
+
+    /// Record nanosecond values from an iterator for `name` (no-op; the
+    /// iterator is not consumed).
+    #[inline]
+    pub fn record_iter<I: IntoIterator<Item = u64>>(&self, _name: &str, _values: I) {}
+
+    /// Register `name` for `record_id` (no-op).
+    #[inline]
+    pub fn register(&self, _name: &str) -> MetricId {
+        MetricId { _private: () }
+    }
+
+    /// Record nanoseconds for a registered id (no-op).
+    #[inline]
+    pub fn record_id(&self, _id: &MetricId, _duration_ns: u64) {}
+
+    /// Record a `std` or `benchmark` duration for `name` (no-op).
+    #[inline]
+    pub fn record_duration(&self, _name: &str, _duration: impl Into<crate::Duration>) {}
+
+    /// Record elapsed time since `start` (no-op). Returns 0.
+    #[inline]
+    pub fn record_instant(&self, _name: &str, _start: Instant) -> u64 {
+        0
+    }
+
+    /// Create a (disabled) batching recorder for `name`.
+    #[inline]
+    pub fn recorder(&self, _name: impl Into<Arc<str>>) -> LocalRecorder {
+        LocalRecorder { _private: () }
+    }
+
+    /// Start a (disabled) timer for `name`.
+    #[inline]
+    pub fn start(&self, _name: impl Into<Arc<str>>) -> Timer {
+        Timer { _private: () }
+    }
+
+    /// Start a (disabled) scoped timer for `name`.
+    #[inline]
+    pub fn scope(&self, _name: impl Into<Arc<str>>) -> ScopedTimer {
+        ScopedTimer { _private: () }
+    }
+
+    /// Start a (disabled) phase timer for `name`.
+    #[inline]
+    pub fn phases(&self, _name: impl Into<Arc<str>>) -> Phases {
+        Phases { _private: () }
+    }
+
+    /// Names of all tracked metrics (always empty).
+    #[inline]
+    pub fn metric_names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Whether `name` is tracked (always `false`).
+    #[inline]
+    pub fn contains(&self, _name: &str) -> bool {
+        false
+    }
+
+    /// Keep only matching metrics (nothing to remove; returns 0).
+    #[inline]
+    pub fn retain(&self, _keep: impl FnMut(&str, &WatchStats) -> bool) -> usize {
+        0
+    }
+
+    /// Folded-stack lines for scoped timers (always empty).
+    #[inline]
+    pub fn folded_stacks(&self) -> String {
+        String::new()
+    }
+
+    /// Run `f` and return its output (nothing is recorded).
+    #[inline]
+    pub fn time<T, F: FnOnce() -> T>(&self, _name: &str, f: F) -> T {
+        f()
+    }
+
+    /// Run `f` and return its output (nothing is recorded).
+    #[inline]
+    pub fn measure<T, F: FnOnce() -> T>(&self, _name: &str, f: F) -> T {
+        f()
+    }
+
+    /// Run `f` and return its result (nothing is recorded).
+    ///
+    /// # Errors
+    /// Returns `f`'s error unchanged.
+    #[inline]
+    pub fn measure_result<T, E, F: FnOnce() -> Result<T, E>>(
+        &self,
+        _name: &str,
+        f: F,
+    ) -> Result<T, E> {
+        f()
+    }
+
+    /// Current clock reading (always 0).
+    #[inline]
+    pub fn now_ns(&self) -> u64 {
+        0
+    }
+
+    /// Snapshot all metrics (always empty).
+    #[inline]
+    pub fn snapshot(&self) -> HashMap<String, WatchStats> {
+        HashMap::new()
+    }
+
+    /// Ordered snapshot of all metrics (always empty).
+    #[inline]
+    pub fn snapshot_sorted(&self) -> crate::Snapshot {
+        crate::Snapshot::new()
+    }
+
+    /// Immutable capture for exporters (always empty).
+    #[inline]
+    pub fn freeze(&self) -> crate::FrozenSnapshot {
+        crate::FrozenSnapshot::default()
+    }
+
+    /// Write histograms to `path` for cross-process aggregation (no-op;
+    /// nothing is written).
+    ///
+    /// # Errors
+    /// Never fails when disabled.
+    #[inline]
+    #[allow(clippy::unnecessary_wraps)] // same signature as the real `dump`
+    pub fn dump(&self, _path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    /// Snapshot within a time budget (always empty, never truncated).
+    #[inline]
+    pub fn snapshot_with_budget(&self, _budget: StdDuration) -> PartialSnapshot {
+        PartialSnapshot::default()
+    }
+
+    /// Stats since the previous `snapshot_delta` call (always empty).
+    #[inline]
+    pub fn snapshot_delta(&self) -> HashMap<String, WatchStats> {
+        HashMap::new()
+    }
+
+    /// Stats since the previous `snapshot_and_reset` call (always empty).
+    #[inline]
+    pub fn snapshot_and_reset(&self) -> HashMap<String, WatchStats> {
+        HashMap::new()
+    }
+
+    /// Clear all metrics (no-op).
+    #[inline]
+    pub fn clear(&self) {}
+
+    /// Clear a specific metric (no-op).
+    #[inline]
+    pub fn clear_name(&self, _name: &str) {}
+
+    /// Enable periodic history capture (no-op).
+    #[inline]
+    pub fn enable_history(&self, _interval: StdDuration, _capacity: usize) {}
+
+    /// Capture one history entry per metric (no-op).
+    #[inline]
+    pub fn capture_history(&self) {}
+
+    /// Precompute snapshots in the background (no-op).
+    #[inline]
+    pub fn enable_precompute(&self, _interval: StdDuration) {}
+
+    /// Recompute the precomputed snapshot table (no-op).
+    #[inline]
+    pub fn refresh_precomputed(&self) {}
+
+    /// Evict expired metrics (no-op). Returns 0.
+    #[inline]
+    pub fn evict_expired(&self) -> usize {
+        0
+    }
+
+    /// Number of evicted metrics (always 0).
+    #[inline]
+    pub fn dropped_metrics(&self) -> u64 {
+        0
+    }
+
+    /// Watch self-metrics (always zero).
+    #[inline]
+    pub fn internal_stats(&self) -> InternalStats {
+        InternalStats::default()
+    }
+
+    /// Time since the latest record of `name` (always `None`).
+    #[inline]
+    pub fn idle_for(&self, _name: &str) -> Option<StdDuration> {
+        None
+    }
+
+    /// Slowest recent samples of `name` (always empty).
+    #[inline]
+    pub fn slowest(&self, _name: &str) -> Vec<SlowSample> {
+        Vec::new()
+    }
+
+    /// Slowest recent samples of every metric (always empty).
+    #[inline]
+    pub fn slowest_all(&self) -> HashMap<String, Vec<SlowSample>> {
+        HashMap::new()
+    }
+
+    /// Slowest recent samples as JSON (always `{}`).
+    #[inline]
+    pub fn slowest_json(&self) -> String {
+        String::from("{}")
+    }
+
+    /// Register a threshold callback (never called when disabled).
+    #[inline]
+    pub fn on_threshold<F>(&self, _name: &str, _threshold: impl Into<crate::Duration>, _callback: F)
+    where
+        F: Fn(&str, u64) + Send + Sync + 'static,
+    {
+    }
+
+    /// Register a slow-op callback (never called when disabled).
+    #[inline]
+    pub fn on_slow<F>(&self, _name: &str, _threshold: impl Into<crate::Duration>, _callback: F)
+    where
+        F: Fn(&SlowOp) + Send + Sync + 'static,
+    {
+    }
+
+    /// Log slow records of `name` to stderr (no-op).
+    #[inline]
+    pub fn log_slow(&self, _name: &str, _threshold: impl Into<crate::Duration>) {}
+
+    /// Override histogram settings for `name` (no-op).
+    #[inline]
+    pub fn configure_metric(&self, _name: &str, _config: MetricConfig) {}
+
+    /// Captured history for `name` (always empty).
+    #[inline]
+    pub fn history(&self, _name: &str) -> Vec<(u128, WatchStats)> {
+        Vec::new()
+    }
+
+    /// Namespaced view prefixing names with `"<namespace>."`.
+    #[inline]
+    pub fn scoped(&self, namespace: &str) -> ScopedWatch {
+        let trimmed = namespace.trim_matches('.');
+        let mut prefix = String::with_capacity(trimmed.len() + 1);
+        prefix.push_str(trimmed);
+        prefix.push('.');
+        ScopedWatch {
+            watch: self.clone(),
+            prefix,
+        }
+    }
+
+    /// Snapshot grouped by dotted names (always empty).
+    #[inline]
+    pub fn snapshot_tree(&self) -> MetricTree {
+        MetricTree::default()
+    }
+
+    /// Time × latency matrix of the history of `name` (always empty).
+    #[inline]
+    pub fn heatmap(&self, _name: &str, _resolution: StdDuration, _window: StdDuration) -> Heatmap {
+        Heatmap::default()
+    }
+
+    /// Anomalous intervals in the history of `name` (always empty).
+    #[inline]
+    pub fn detect_anomalies(&self, _name: &str, _sensitivity: f64) -> Vec<Anomaly> {
+        Vec::new()
+    }
+
+    /// Register an anomaly callback (never called when disabled).
+    #[inline]
+    pub fn on_anomaly<F>(&self, _sensitivity: f64, _callback: F)
+    where
+        F: Fn(&str, &Anomaly) + Send + Sync + 'static,
+    {
+    }
+}
+
+/// Builder for the disabled `Watch`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WatchBuilder {
+    _private: (),
+}
+
+impl WatchBuilder {
+    /// Start a builder.
+    #[inline]
+    pub fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Set the lowest discernible value (ignored).
+    #[inline]
+    #[must_use]
+    pub fn lowest(self, _ns: u64) -> Self {
+        self
+    }
+
+    /// Set the highest trackable value (ignored).
+    #[inline]
+    #[must_use]
+    pub fn highest(self, _ns: u64) -> Self {
+        self
+    }
+
+    /// Set the clock (ignored).
+    #[inline]
+    #[must_use]
+    pub fn clock<C: crate::Clock + 'static>(self, _clock: C) -> Self {
+        self
+    }
+
+    /// Cap the number of distinct metrics (ignored).
+    #[inline]
+    #[must_use]
+    pub fn max_metrics(self, _n: usize) -> Self {
+        self
+    }
+
+    /// Evict idle metrics after `ttl` (ignored).
+    #[inline]
+    #[must_use]
+    pub fn ttl(self, _ttl: StdDuration) -> Self {
+        self
+    }
+
+    /// Track per-metric call rates (ignored).
+    #[inline]
+    #[must_use]
+    pub fn rates(self, _enabled: bool) -> Self {
+        self
+    }
+
+    /// Keep last-seen times current (no-op).
+    #[inline]
+    #[must_use]
+    pub fn track_last_seen(self, _enabled: bool) -> Self {
+        self
+    }
+
+    /// Track the slowest recent samples per metric (ignored).
+    #[inline]
+    #[must_use]
+    pub fn slowest(self, _k: usize, _window: StdDuration) -> Self {
+        self
+    }
+
+    /// Record only one in `n` events (ignored).
+    #[inline]
+    #[must_use]
+    pub fn sample_rate(self, _n: u64) -> Self {
+        self
+    }
+
+    /// Build the disabled `Watch`.
+    #[inline]
+    pub fn build(self) -> Watch {
+        Watch::new()
+    }
+}
+
+/// Disabled scope timer: never reads the clock and records nothing.
+#[must_use]
+#[derive(Debug)]
+pub struct Timer {
+    _private: (),
+}
+
+impl Timer {
+    /// Start a (disabled) timer.
+    #[inline]
+    pub fn new(_watch: Watch, _name: impl Into<Arc<str>>) -> Self {
+        Self { _private: () }
+    }
+
+    /// Start a (disabled) timer with an explicit clock.
+    #[inline]
+    pub fn new_with_clock(
+        _watch: Watch,
+        _name: impl Into<Arc<str>>,
+        _clock: Arc<dyn crate::Clock>,
+    ) -> Self {
+        Self { _private: () }
+    }
+
+    /// Start a (disabled) thread CPU-time timer.
+    #[cfg(feature = "cpu-time")]
+    #[inline]
+    pub fn new_thread_cpu(_watch: Watch, _name: impl Into<Arc<str>>) -> Self {
+        Self { _private: () }
+    }
+
+    /// Create a (disabled) timer without starting it.
+    #[inline]
+    pub fn paused(_watch: Watch, _name: impl Into<Arc<str>>) -> Self {
+        Self { _private: () }
+    }
+
+    /// Start the timer (no-op).
+    #[inline]
+    pub fn start(&mut self) {}
+
+    /// Restart the timer (no-op).
+    #[inline]
+    pub fn restart(&mut self) {}
+
+    /// Always false: a disabled timer never runs.
+    #[inline]
+    pub fn is_running(&self) -> bool {
+        false
+    }
+
+    /// Stop the timer. Always returns 0.
+    #[inline]
+    pub fn stop(self) -> u64 {
+        0
+    }
+}
+
+/// Disabled phase timer: measures nothing and records nothing.
+#[must_use]
+#[derive(Debug)]
+pub struct Phases {
+    _private: (),
+}
+
+impl Phases {
+    /// Start a (disabled) phase timer.
+    #[inline]
+    pub fn new(_watch: Watch, _name: impl Into<Arc<str>>) -> Self {
+        Self { _private: () }
+    }
+
+    /// Start the next phase (returns 0).
+    #[inline]
+    pub fn phase(&mut self, _phase: &str) -> u64 {
+        0
+    }
+
+    /// Finish (returns 0).
+    #[inline]
+    pub fn finish(self) -> u64 {
+        0
+    }
+}
+
+/// Disabled nested-scope timer: measures nothing and records nothing.
+#[must_use]
+#[derive(Debug)]
+pub struct ScopedTimer {
+    _private: (),
+}
+
+impl ScopedTimer {
+    /// Start a (disabled) scope.
+    #[inline]
+    pub fn new(_watch: Watch, _name: impl Into<Arc<str>>) -> Self {
+        Self { _private: () }
+    }
+
+    /// Stop the scope. Always returns 0.
+    #[inline]
+    pub fn stop(self) -> u64 {
+        0
+    }
+}
+
+/// Disabled batching recorder: buffers nothing and records nothing.
+#[must_use]
+#[derive(Debug)]
+pub struct LocalRecorder {
+    _private: (),
+}
+
+impl LocalRecorder {
+    /// Create a (disabled) recorder.
+    #[inline]
+    pub fn new(_watch: Watch, _name: impl Into<Arc<str>>) -> Self {
+        Self { _private: () }
+    }
+
+    /// Set the batch size (ignored).
+    #[inline]
+    pub fn batch_size(self, _n: usize) -> Self {
+        self
+    }
+
+    /// Buffer one value (no-op).
+    #[inline]
+    pub fn record(&mut self, _duration_ns: u64) {}
+
+    /// Buffer a `std` or `benchmark` duration (no-op).
+    #[inline]
+    pub fn record_duration(&mut self, _duration: impl Into<crate::Duration>) {}
+
+    /// Flush buffered values (no-op).
+    #[inline]
+    pub fn flush(&mut self) {}
+
+    /// Number of buffered values (always 0).
+    #[inline]
+    pub fn pending(&self) -> usize {
+        0
+    }
+}
+
+/// Set the metric filter (no-op).
+#[inline]
+pub fn enable_only(_patterns: &str) {}
+
+/// Clear the metric filter (no-op).
+#[inline]
+pub fn enable_all() {}
+
+/// Whether `name` may record (always true; nothing records when disabled).
+#[inline]
+pub fn is_enabled(_name: &str) -> bool {
+    true
+}
+
+#[cfg(not(feature = "collector"))]
+pub use self::collector::{Collector, RecordTo, Stats, StreamingStats};
+
+#[cfg(not(feature = "collector"))]
+mod collector {
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+
+    use crate::{Duration, Measurement};
+
+    /// Basic statistics for a set of measurements (never produced when disabled).
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Stats {
+        /// Number of measurements.
+        pub count: u64,
+        /// Total duration of all measurements.
+        pub total: Duration,
+        /// Minimum duration.
+        pub min: Duration,
+        /// Maximum duration.
+        pub max: Duration,
+        /// Mean (average) duration.
+        pub mean: Duration,
+        /// Median (50th percentile, nearest rank).
+        pub median: Duration,
+        /// 95th percentile (nearest rank).
+        pub p95: Duration,
+        /// Population standard deviation.
+        pub stddev: Duration,
+    }
+
+    impl Stats {
+        /// Aggregate raw durations (always `None` when disabled).
+        #[inline]
+        pub fn from_durations(_durations: &[Duration]) -> Option<Self> {
+            None
+        }
+
+        /// Aggregate measurement durations (always `None` when disabled).
+        #[inline]
+        pub fn from_measurements(_measurements: &[Measurement]) -> Option<Self> {
+            None
+        }
+    }
+
+    /// Running aggregates for one name (never produced when disabled).
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct StreamingStats {
+        /// Number of measurements.
+        pub count: u64,
+        /// Total duration of all measurements.
+        pub total: Duration,
+        /// Minimum duration.
+        pub min: Duration,
+        /// Maximum duration.
+        pub max: Duration,
+        /// Mean (average) duration.
+        pub mean: Duration,
+        /// Population standard deviation.
+        pub stddev: Duration,
+    }
+
+    /// Disabled `Collector`: discards every measurement.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Collector, Duration};
+    /// let c = Collector::new();
+    /// c.record_duration("op", Duration::from_nanos(10));
+    /// assert!(c.stats("op").is_none());
+    /// ```
+    #[derive(Clone, Debug, Default)]
+    pub struct Collector {
+        _private: (),
+    }
+
+    impl Collector {
+        /// Creates a new (disabled) collector.
+        #[inline]
+        pub fn new() -> Self {
+            Self { _private: () }
+        }
+
+        /// Creates a new collector; capacity is ignored.
+        #[inline]
+        pub fn with_capacity(_capacity: usize) -> Self {
+            Self::new()
+        }
+
+        /// Keep only running aggregates (no-op).
+        #[inline]
+        #[must_use]
+        pub fn without_samples(self) -> Self {
+            self
+        }
+
+        /// Keep per-sample timestamps (ignored).
+        #[inline]
+        #[must_use]
+        pub fn with_timestamps(self) -> Self {
+            self
+        }
+
+        /// Reserves room for samples under `name` (no-op).
+        #[inline]
+        pub fn reserve(&self, _name: impl Into<Cow<'static, str>>, _additional: usize) {}
+
+        /// Releases spare capacity (no-op).
+        #[inline]
+        pub fn shrink(&self) {}
+
+        /// Approximate bytes held for each name (always empty).
+        #[inline]
+        pub fn memory_usage(&self) -> Vec<(String, usize)> {
+            Vec::new()
+        }
+
+        /// Records a measurement (no-op).
+        #[inline]
+        pub fn record(&self, _measurement: &Measurement) {}
+
+        /// Records a duration directly (no-op).
+        #[inline]
+        pub fn record_duration(&self, _name: impl Into<Cow<'static, str>>, _duration: Duration) {}
+
+        /// Runs `f` and returns its output (nothing is recorded).
+        #[inline]
+        pub fn record_closure<T, F: FnOnce() -> T>(
+            &self,
+            _name: impl Into<Cow<'static, str>>,
+            f: F,
+        ) -> T {
+            f()
+        }
+
+        /// Gets statistics for a named measurement (always `None`).
+        #[inline]
+        pub fn stats(&self, _name: &str) -> Option<Stats> {
+            None
+        }
+
+        /// Gets running aggregates for a named measurement (always `None`).
+        #[inline]
+        pub fn stats_streaming(&self, _name: &str) -> Option<StreamingStats> {
+            None
+        }
+
+        /// Gets statistics for all measurements (always empty).
+        #[inline]
+        pub fn all_stats(&self) -> Vec<(String, Stats)> {
+            Vec::new()
+        }
+
+        /// Clears all measurements (no-op).
+        #[inline]
+        pub fn clear(&self) {}
+
+        /// Clears measurements for a specific name (no-op).
+        #[inline]
+        pub fn clear_name(&self, _name: &str) {}
+
+        /// Moves all samples out (always empty).
+        #[inline]
+        pub fn drain(&self) -> HashMap<Cow<'static, str>, Vec<Duration>> {
+            HashMap::new()
+        }
+
+        /// Moves the samples for `name` out (always `None`).
+        #[inline]
+        pub fn take_samples(&self, _name: &str) -> Option<Vec<Duration>> {
+            None
+        }
+
+        /// Writes every sample as NDJSON (writes nothing).
+        ///
+        /// # Errors
+        /// Never fails.
+        #[inline]
+        #[allow(clippy::unnecessary_wraps)] // same signature as the real method
+        pub fn export_ndjson<W: std::io::Write>(&self, _out: W) -> std::io::Result<usize> {
+            Ok(0)
+        }
+
+        /// Drains every sample as NDJSON (writes nothing).
+        ///
+        /// # Errors
+        /// Never fails.
+        #[inline]
+        #[allow(clippy::unnecessary_wraps)] // same signature as the real method
+        pub fn drain_ndjson<W: std::io::Write>(&self, _out: W) -> std::io::Result<usize> {
+            Ok(0)
+        }
+    }
+
+    /// Pipes measuring results into a disabled `Collector` (records nothing).
+    pub trait RecordTo {
+        /// What remains after recording.
+        type Output;
+
+        /// Record the measurement into `collector` (no-op).
+        fn record_to(self, collector: &Collector) -> Self::Output;
+    }
+
+    impl RecordTo for Measurement {
+        type Output = Measurement;
+
+        #[inline]
+        fn record_to(self, _collector: &Collector) -> Measurement {
+            self
+        }
+    }
+
+    impl<T> RecordTo for (T, Measurement) {
+        type Output = T;
+
+        #[inline]
+        fn record_to(self, _collector: &Collector) -> T {
+            self.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noop_watch_and_timer() {
+        let w = Watch::builder()
+            .lowest(1)
+            .highest(10)
+            .max_metrics(4)
+            .ttl(StdDuration::from_secs(1))
+            .rates(true)
+            .build();
+        w.record("a", 5);
+        assert_eq!(w.record_instant("a", Instant::now()), 0);
+        assert_eq!(Timer::new(w.clone(), "t").stop(), 0);
+        drop(Timer::new(w.clone(), String::from("u")));
+        assert_eq!(w.scope("s").stop(), 0);
+        assert!(w.folded_stacks().is_empty());
+        assert!(w.metric_names().is_empty() && !w.contains("a"));
+        assert_eq!(w.retain(|_, _| false), 0);
+        drop(ScopedTimer::new(w.clone(), "s"));
+        w.enable_history(StdDuration::from_secs(1), 4);
+        w.capture_history();
+        w.enable_precompute(StdDuration::from_secs(1));
+        w.refresh_precomputed();
+        assert!(w.snapshot().is_empty());
+        assert!(!w.snapshot_with_budget(StdDuration::ZERO).truncated);
+        assert!(w.history("a").is_empty());
+        assert_eq!((w.evict_expired(), w.dropped_metrics()), (0, 0));
+        assert_eq!(std::mem::size_of::<Timer>(), 0);
+        assert_eq!(std::mem::size_of::<Watch>(), 0);
+    }
+
+    #[cfg(not(feature = "collector"))]
+    #[test]
+    fn test_noop_collector() {
+        let c = Collector::with_capacity(8);
+        c.record(&crate::Measurement::new("m", crate::Duration::ZERO, 0));
+        c.record_duration("m", crate::Duration::from_nanos(1));
+        assert!(c.stats("m").is_none());
+        assert!(c.stats_streaming("m").is_none());
+        c.reserve("m", 8);
+        c.shrink();
+        assert!(c.memory_usage().is_empty());
+        assert!(c.all_stats().is_empty());
+        assert!(c.drain().is_empty());
+        assert!(c.take_samples("m").is_none());
+        assert_eq!(c.export_ndjson(std::io::sink()).unwrap(), 0);
+        assert_eq!(c.drain_ndjson(std::io::sink()).unwrap(), 0);
+    }
+}
@@ -0,0 +1,132 @@
+#![cfg(feature = "std")]
+//! Startup measurement of the crate's own instrumentation cost.
+//!
+//! [`overhead_report`] times tight loops of the common entry points on the
+//! current machine and with the current feature set, so applications can
+//! log what their instrumentation costs and CI can track it across builds.
+//! Disabled features show up as near-zero costs.
+
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Iterations timed per operation.
+const ITERS: u32 = 20_000;
+/// Rounds per operation; the cheapest round is kept to shed interference.
+const ROUNDS: usize = 5;
+
+/// Per-call cost of the crate's instrumentation, from [`overhead_report`].
+///
+/// Every figure is the mean wall time of one call in nanoseconds, with the
+/// cost of an empty loop iteration subtracted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OverheadReport {
+    /// [`measure`](crate::measure) around an empty closure.
+    pub measure_ns: f64,
+    /// [`time!`](crate::time) around an empty expression.
+    pub time_macro_ns: f64,
+    /// [`Watch::record`](crate::Watch::record) into an existing metric.
+    pub watch_record_ns: f64,
+    /// Starting a [`Timer`](crate::Timer) and dropping it (which records).
+    pub timer_ns: f64,
+    /// [`Histogram::record`](crate::histogram::Histogram::record); `None`
+    /// without the `collector` feature.
+    pub histogram_record_ns: Option<f64>,
+    /// Iterations timed per operation.
+    pub iterations: u32,
+}
+
+impl OverheadReport {
+    /// The most expensive per-call cost in the report (ns).
+    pub fn max_ns(&self) -> f64 {
+        [
+            self.measure_ns,
+            self.time_macro_ns,
+            self.watch_record_ns,
+            self.timer_ns,
+            self.histogram_record_ns.unwrap_or(0.0),
+        ]
+        .into_iter()
+        .fold(0.0, f64::max)
+    }
+}
+
+/// Cheapest mean per-iteration cost (ns) of `f` over `ROUNDS` rounds.
+fn per_call(mut f: impl FnMut(u32)) -> f64 {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            for i in 0..ITERS {
+                f(black_box(i));
+            }
+            start.elapsed().as_secs_f64() * 1e9 / f64::from(ITERS)
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Measure the per-call cost of `measure`, `time!`, `Watch::record`, a
+/// `Timer` and `Histogram::record` on this machine.
+///
+/// Takes a few tens of milliseconds; call it once at startup. Recording
+/// happens on private instances, so no application metrics are touched.
+///
+/// # Examples
+/// ```
+/// let report = benchmark::overhead_report();
+/// println!("instrumentation costs up to {:.0}ns per call", report.max_ns());
+/// assert!(report.watch_record_ns >= 0.0);
+/// ```
+pub fn overhead_report() -> OverheadReport {
+    let baseline = per_call(|i| {
+        black_box(i);
+    });
+    let cost = |f: &mut dyn FnMut(u32)| (per_call(f) - baseline).max(0.0);
+
+    let measure_ns = cost(&mut |i| {
+        black_box(crate::measure(|| black_box(i)));
+    });
+    let time_macro_ns = cost(&mut |i| {
+        black_box(crate::time!(black_box(i)));
+    });
+
+    let watch = crate::Watch::new();
+    watch.record("overhead", 1);
+    let watch_record_ns = cost(&mut |i| watch.record("overhead", u64::from(i)));
+    let name: Arc<str> = Arc::from("overhead.timer");
+    let timer_ns = cost(&mut |_| {
+        drop(crate::Timer::new(watch.clone(), Arc::clone(&name)));
+    });
+
+    #[cfg(feature = "collector")]
+    let histogram_record_ns = {
+        let hist = crate::histogram::Histogram::new();
+        Some(cost(&mut |i| hist.record(u64::from(i) + 1)))
+    };
+    #[cfg(not(feature = "collector"))]
+    let histogram_record_ns = None;
+
+    OverheadReport {
+        measure_ns,
+        time_macro_ns,
+        watch_record_ns,
+        timer_ns,
+        histogram_record_ns,
+        iterations: ITERS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overhead_report_is_finite_and_non_negative() {
+        let r = overhead_report();
+        for ns in [r.measure_ns, r.time_macro_ns, r.watch_record_ns, r.timer_ns] {
+            assert!(ns.is_finite() && ns >= 0.0, "{r:?}");
+        }
+        assert_eq!(r.histogram_record_ns.is_some(), cfg!(feature = "collector"));
+        assert!(r.max_ns() >= r.watch_record_ns);
+        assert_eq!(r.iterations, ITERS);
+    }
+}
@@ -0,0 +1,343 @@
+#![cfg(feature = "perf-events")]
+//! Hardware performance counters for the benchmark runner.
+//!
+//! [`PerfCounters`] counts CPU cycles, retired instructions, cache
+//! references and misses, and branch misses of the calling thread. On Linux
+//! every event is one `perf_event_open` counter restricted to user space, so
+//! it works at the default `perf_event_paranoid` level of 2. Events the CPU
+//! or hypervisor does not expose read `None`; on other platforms
+//! [`PerfCounters::new`] returns `None`.
+
+/// A hardware event counted by [`PerfCounters`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PerfEvent {
+    /// CPU cycles.
+    Cycles,
+    /// Retired instructions.
+    Instructions,
+    /// Last-level cache accesses.
+    CacheReferences,
+    /// Last-level cache misses.
+    CacheMisses,
+    /// Mispredicted branches.
+    BranchMisses,
+}
+
+impl PerfEvent {
+    /// Every event, in declaration order.
+    pub const ALL: [Self; 5] = [
+        Self::Cycles,
+        Self::Instructions,
+        Self::CacheReferences,
+        Self::CacheMisses,
+        Self::BranchMisses,
+    ];
+}
+
+/// Counter totals of one measured region, from [`PerfCounters::stop`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerfCounts {
+    /// Iterations the totals cover.
+    pub iterations: u64,
+    /// CPU cycles.
+    pub cycles: Option<u64>,
+    /// Retired instructions.
+    pub instructions: Option<u64>,
+    /// Last-level cache accesses.
+    pub cache_references: Option<u64>,
+    /// Last-level cache misses.
+    pub cache_misses: Option<u64>,
+    /// Mispredicted branches.
+    pub branch_misses: Option<u64>,
+}
+
+impl PerfCounts {
+    /// Total for `event`, `None` if it was not counted.
+    pub fn get(&self, event: PerfEvent) -> Option<u64> {
+        match event {
+            PerfEvent::Cycles => self.cycles,
+            PerfEvent::Instructions => self.instructions,
+            PerfEvent::CacheReferences => self.cache_references,
+            PerfEvent::CacheMisses => self.cache_misses,
+            PerfEvent::BranchMisses => self.branch_misses,
+        }
+    }
+
+    fn slot(&mut self, event: PerfEvent) -> &mut Option<u64> {
+        match event {
+            PerfEvent::Cycles => &mut self.cycles,
+            PerfEvent::Instructions => &mut self.instructions,
+            PerfEvent::CacheReferences => &mut self.cache_references,
+            PerfEvent::CacheMisses => &mut self.cache_misses,
+            PerfEvent::BranchMisses => &mut self.branch_misses,
+        }
+    }
+
+    /// Mean count of `event` per iteration.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn per_iteration(&self, event: PerfEvent) -> Option<f64> {
+        let total = self.get(event)?;
+        (self.iterations > 0).then(|| total as f64 / self.iterations as f64)
+    }
+
+    /// Instructions retired per cycle (IPC).
+    #[allow(clippy::cast_precision_loss)]
+    pub fn instructions_per_cycle(&self) -> Option<f64> {
+        let cycles = self.cycles.filter(|&c| c > 0)?;
+        Some(self.instructions? as f64 / cycles as f64)
+    }
+
+    /// Fraction of cache references that missed.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn cache_miss_ratio(&self) -> Option<f64> {
+        let refs = self.cache_references.filter(|&r| r > 0)?;
+        Some(self.cache_misses? as f64 / refs as f64)
+    }
+}
+
+/// A set of open hardware counters for the calling thread.
+///
+/// ```
+/// use benchmark::{PerfCounters, PerfEvent};
+///
+/// if let Some(mut counters) = PerfCounters::new() {
+///     counters.start();
+///     let sum: u64 = (0..1_000u64).sum();
+///     let counts = counters.stop(1);
+///     assert_eq!(sum, 499_500);
+///     println!("{:?} instructions", counts.get(PerfEvent::Instructions));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PerfCounters {
+    counters: Vec<(PerfEvent, sys::Counter)>,
+}
+
+impl PerfCounters {
+    /// Open a counter for every supported event; `None` if none could be
+    /// opened (unsupported platform, no PMU access, or a too restrictive
+    /// `perf_event_paranoid`).
+    pub fn new() -> Option<Self> {
+        let counters: Vec<_> = PerfEvent::ALL
+            .iter()
+            .filter_map(|&event| sys::Counter::open(event).map(|c| (event, c)))
+            .collect();
+        (!counters.is_empty()).then_some(Self { counters })
+    }
+
+    /// Events that are being counted.
+    pub fn events(&self) -> impl Iterator<Item = PerfEvent> + '_ {
+        self.counters.iter().map(|(event, _)| *event)
+    }
+
+    /// Zero and start every counter.
+    pub fn start(&mut self) {
+        for (_, c) in &self.counters {
+            c.reset_and_enable();
+        }
+    }
+
+    /// Stop every counter and return the totals since [`start`](Self::start),
+    /// attributed to `iterations` iterations.
+    pub fn stop(&mut self, iterations: u64) -> PerfCounts {
+        for (_, c) in &self.counters {
+            c.disable();
+        }
+        let mut counts = PerfCounts {
+            iterations,
+            ..PerfCounts::default()
+        };
+        for (event, c) in &self.counters {
+            *counts.slot(*event) = c.read();
+        }
+        counts
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sys {
+    use super::PerfEvent;
+    use std::fs::File;
+    use std::io::Read;
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_FORMAT_TOTAL_TIME_ENABLED: u64 = 1;
+    const PERF_FORMAT_TOTAL_TIME_RUNNING: u64 = 1 << 1;
+    const FLAG_DISABLED: u64 = 1;
+    const FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+    const FLAG_EXCLUDE_HV: u64 = 1 << 6;
+    const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+    const PERF_EVENT_IOC_ENABLE: u64 = 0x2400;
+    const PERF_EVENT_IOC_DISABLE: u64 = 0x2401;
+    const PERF_EVENT_IOC_RESET: u64 = 0x2403;
+
+    /// `struct perf_event_attr` up to `PERF_ATTR_SIZE_VER5`; the bitfield
+    /// flags are folded into `flags`.
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        kind: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64,
+        config2: u64,
+        branch_sample_type: u64,
+        sample_regs_user: u64,
+        sample_stack_user: u32,
+        clockid: i32,
+        sample_regs_intr: u64,
+        aux_watermark: u32,
+        sample_max_stack: u16,
+        reserved: u16,
+    }
+
+    #[derive(Debug)]
+    pub(super) struct Counter(File);
+
+    impl Counter {
+        #[allow(unsafe_code)]
+        pub(super) fn open(event: PerfEvent) -> Option<Self> {
+            let config = match event {
+                PerfEvent::Cycles => 0,
+                PerfEvent::Instructions => 1,
+                PerfEvent::CacheReferences => 2,
+                PerfEvent::CacheMisses => 3,
+                PerfEvent::BranchMisses => 5,
+            };
+            #[allow(clippy::cast_possible_truncation)]
+            let attr = PerfEventAttr {
+                kind: PERF_TYPE_HARDWARE,
+                size: std::mem::size_of::<PerfEventAttr>() as u32,
+                config,
+                read_format: PERF_FORMAT_TOTAL_TIME_ENABLED | PERF_FORMAT_TOTAL_TIME_RUNNING,
+                flags: FLAG_DISABLED | FLAG_EXCLUDE_KERNEL | FLAG_EXCLUDE_HV,
+                ..PerfEventAttr::default()
+            };
+            // SAFETY: `attr` is a live, correctly sized `perf_event_attr`
+            // the kernel only reads; pid 0 / cpu -1 counts this thread.
+            let fd = unsafe {
+                libc::syscall(
+                    libc::SYS_perf_event_open,
+                    std::ptr::addr_of!(attr),
+                    0 as libc::pid_t,
+                    -1 as libc::c_int,
+                    -1 as libc::c_int,
+                    PERF_FLAG_FD_CLOEXEC,
+                )
+            };
+            let fd = libc::c_int::try_from(fd).ok().filter(|&fd| fd >= 0)?;
+            // SAFETY: `fd` was just returned by the kernel and nothing else
+            // owns it.
+            Some(Self(unsafe { File::from_raw_fd(fd) }))
+        }
+
+        #[allow(unsafe_code)]
+        fn ioctl(&self, request: u64) {
+            // SAFETY: a perf ioctl without an argument on an fd we own.
+            unsafe {
+                libc::ioctl(self.0.as_raw_fd(), request as _, 0);
+            }
+        }
+
+        pub(super) fn reset_and_enable(&self) {
+            self.ioctl(PERF_EVENT_IOC_RESET);
+            self.ioctl(PERF_EVENT_IOC_ENABLE);
+        }
+
+        pub(super) fn disable(&self) {
+            self.ioctl(PERF_EVENT_IOC_DISABLE);
+        }
+
+        /// The count, scaled up if the kernel multiplexed the counter.
+        pub(super) fn read(&self) -> Option<u64> {
+            let mut buf = [0u8; 24];
+            (&self.0).read_exact(&mut buf).ok()?;
+            let word = |i: usize| {
+                let mut w = [0u8; 8];
+                w.copy_from_slice(&buf[i * 8..i * 8 + 8]);
+                u64::from_ne_bytes(w)
+            };
+            let (value, enabled, running) = (word(0), word(1), word(2));
+            if running == 0 {
+                return None;
+            }
+            if running >= enabled {
+                return Some(value);
+            }
+            let scaled = u128::from(value) * u128::from(enabled) / u128::from(running);
+            Some(u64::try_from(scaled).unwrap_or(u64::MAX))
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+mod sys {
+    use super::PerfEvent;
+
+    #[derive(Debug)]
+    pub(super) enum Counter {}
+
+    impl Counter {
+        pub(super) fn open(_event: PerfEvent) -> Option<Self> {
+            None
+        }
+
+        pub(super) fn reset_and_enable(&self) {
+            match *self {}
+        }
+
+        pub(super) fn disable(&self) {
+            match *self {}
+        }
+
+        pub(super) fn read(&self) -> Option<u64> {
+            match *self {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derived_ratios() {
+        let counts = PerfCounts {
+            iterations: 4,
+            cycles: Some(200),
+            instructions: Some(500),
+            cache_references: Some(10),
+            cache_misses: Some(1),
+            branch_misses: None,
+        };
+        assert_eq!(counts.instructions_per_cycle(), Some(2.5));
+        assert_eq!(counts.cache_miss_ratio(), Some(0.1));
+        assert_eq!(counts.per_iteration(PerfEvent::Cycles), Some(50.0));
+        assert_eq!(counts.per_iteration(PerfEvent::BranchMisses), None);
+        assert_eq!(PerfCounts::default().instructions_per_cycle(), None);
+    }
+
+    #[test]
+    fn test_counts_scale_with_work() {
+        // Hardware counters are often unavailable in containers and VMs.
+        let Some(mut counters) = PerfCounters::new() else {
+            return;
+        };
+        let mut run = |n: u64| {
+            counters.start();
+            std::hint::black_box((0..n).map(std::hint::black_box).sum::<u64>());
+            counters.stop(1)
+        };
+        let (small, large) = (run(1_000), run(1_000_000));
+        if let (Some(s), Some(l)) = (small.instructions, large.instructions) {
+            assert!(l > s * 10, "{s} vs {l}");
+        }
+    }
+}
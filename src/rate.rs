@@ -0,0 +1,122 @@
+#![cfg(feature = "metrics")]
+//! Per-second call counters backing `WatchStats::rate_1s` / `rate_1m`.
+//!
+//! Each metric with rate tracking owns a ring of one counter per second. A
+//! slot packs the second it belongs to (high 32 bits) with its count (low 32
+//! bits), so a stale slot is recognised and restarted with a single CAS and
+//! readers never see counts from a previous lap of the ring.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Whole seconds covered by `rate_1m`.
+const WINDOW_SECS: u64 = 60;
+/// One slot per second of the window (`WINDOW_SECS`) plus the (partial) current second.
+const SLOTS: usize = 61;
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+const COUNT_MASK: u64 = 0xFFFF_FFFF;
+
+/// Ring of per-second call counters.
+pub(crate) struct RateWindow {
+    slots: [AtomicU64; SLOTS],
+}
+
+impl RateWindow {
+    pub(crate) fn new() -> Self {
+        Self {
+            slots: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+
+    /// Count one call at clock reading `now_ns`.
+    #[inline]
+    pub(crate) fn hit(&self, now_ns: u64) {
+        self.hit_n(now_ns, 1);
+    }
+
+    /// Count `n` calls at clock reading `now_ns`.
+    #[inline]
+    pub(crate) fn hit_n(&self, now_ns: u64, n: u64) {
+        let n = n.min(COUNT_MASK);
+        let sec = now_ns / NANOS_PER_SEC;
+        let slot = self.slot(sec);
+        let tag = tag(sec);
+        let mut cur = slot.load(Ordering::Relaxed);
+        loop {
+            if cur & !COUNT_MASK == tag {
+                slot.fetch_add(n, Ordering::Relaxed);
+                return;
+            }
+            match slot.compare_exchange_weak(cur, tag | n, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(v) => cur = v,
+            }
+        }
+    }
+
+    /// `(calls in the last complete second, mean calls/s over the last 60 complete seconds)`.
+    pub(crate) fn rates(&self, now_ns: u64) -> (f64, f64) {
+        let sec = now_ns / NANOS_PER_SEC;
+        let mut last = 0;
+        let mut minute = 0;
+        for back in 1..=WINDOW_SECS.min(sec) {
+            let s = sec - back;
+            let word = self.slot(s).load(Ordering::Relaxed);
+            if word & !COUNT_MASK == tag(s) {
+                let count = word & COUNT_MASK;
+                if back == 1 {
+                    last = count;
+                }
+                minute += count;
+            }
+        }
+        #[allow(clippy::cast_precision_loss)]
+        (last as f64, minute as f64 / WINDOW_SECS as f64)
+    }
+
+    #[inline]
+    fn slot(&self, sec: u64) -> &AtomicU64 {
+        // `sec % SLOTS` is always a valid index.
+        &self.slots[usize::try_from(sec % SLOTS as u64).unwrap_or(0)]
+    }
+}
+
+/// High-bits stamp for `sec`; offset by one so an untouched slot (0) never matches.
+#[inline]
+fn tag(sec: u64) -> u64 {
+    sec.wrapping_add(1) << 32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const S: u64 = NANOS_PER_SEC;
+
+    #[test]
+    fn test_rates_over_complete_seconds() {
+        let r = RateWindow::new();
+        for sec in 100..130 {
+            for _ in 0..10 {
+                r.hit(sec * S + 5);
+            }
+        }
+        r.hit(130 * S); // current, partial second is excluded
+        let (r1, r60) = r.rates(130 * S + S / 2);
+        assert!((r1 - 10.0).abs() < f64::EPSILON);
+        assert!((r60 - 5.0).abs() < f64::EPSILON); // 300 calls / 60s
+    }
+
+    #[test]
+    fn test_stale_slots_are_ignored_and_reused() {
+        let r = RateWindow::new();
+        r.hit(5 * S);
+        r.hit(5 * S);
+        // One lap later the same slot belongs to a new second.
+        let later = (5 + SLOTS as u64) * S;
+        assert_eq!(r.rates(later + S), (0.0, 0.0));
+        r.hit(later);
+        let (r1, _) = r.rates(later + S);
+        assert!((r1 - 1.0).abs() < f64::EPSILON);
+        assert_eq!(r.rates(0), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,180 @@
+#![cfg(all(feature = "std", feature = "metrics"))]
+//! Batched recording for tight loops.
+//!
+//! [`LocalRecorder`] buffers values in a plain `Vec` owned by one thread and
+//! hands them to its `Watch` in batches: one metric lookup and one clock read
+//! per batch instead of per value.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::watch::Watch;
+
+/// Values buffered before an automatic flush, unless configured otherwise.
+const DEFAULT_BATCH: usize = 1024;
+
+/// A per-thread buffer of values for one metric, flushed to its `Watch` when
+/// full, on [`flush`](Self::flush) and on drop.
+///
+/// Buffered values are invisible to snapshots until flushed. Timestamps
+/// (last-seen time, rates, slowest samples) use the flush time.
+///
+/// # Examples
+/// ```
+/// use benchmark::Watch;
+/// let w = Watch::new();
+/// {
+///     let mut rec = w.recorder("parse.token");
+///     for len in 0..10_000u64 {
+///         rec.record(100 + len % 50);
+///     }
+/// } // dropped: the rest is flushed
+/// assert_eq!(w.snapshot()["parse.token"].count, 10_000);
+/// ```
+#[must_use]
+pub struct LocalRecorder {
+    watch: Watch,
+    name: Arc<str>,
+    buf: Vec<u64>,
+    batch: usize,
+}
+
+impl fmt::Debug for LocalRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalRecorder")
+            .field("name", &self.name)
+            .field("pending", &self.buf.len())
+            .field("batch", &self.batch)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LocalRecorder {
+    /// Create a recorder for `name` on `watch`.
+    pub fn new(watch: Watch, name: impl Into<Arc<str>>) -> Self {
+        Self {
+            watch,
+            name: name.into(),
+            buf: Vec::with_capacity(DEFAULT_BATCH),
+            batch: DEFAULT_BATCH,
+        }
+    }
+
+    /// Flush automatically once `n` values (min 1) are buffered.
+    pub fn batch_size(mut self, n: usize) -> Self {
+        self.batch = n.max(1);
+        self.buf.reserve(self.batch.saturating_sub(self.buf.len()));
+        self
+    }
+
+    /// Buffer one value (ns), flushing if the batch is full.
+    #[inline]
+    pub fn record(&mut self, duration_ns: u64) {
+        self.buf.push(duration_ns);
+        if self.buf.len() >= self.batch {
+            self.flush();
+        }
+    }
+
+    /// Buffer a `std` or `benchmark` duration, saturating at `u64::MAX` ns.
+    #[inline]
+    pub fn record_duration(&mut self, duration: impl Into<crate::Duration>) {
+        self.record(u64::try_from(duration.into().as_nanos()).unwrap_or(u64::MAX));
+    }
+
+    /// Record every buffered value into the Watch.
+    pub fn flush(&mut self) {
+        self.watch.record_many(&self.name, &self.buf);
+        self.buf.clear();
+    }
+
+    /// Number of values waiting for the next flush.
+    pub fn pending(&self) -> usize {
+        self.buf.len()
+    }
+}
+
+impl Drop for LocalRecorder {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl Watch {
+    /// Create a [`LocalRecorder`] that batches values for `name`.
+    ///
+    /// Use one per thread in loops that record every iteration; a single
+    /// `record` call stays the better choice for occasional events.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// let mut rec = w.recorder("hot").batch_size(64);
+    /// for v in 0..100 {
+    ///     rec.record(v);
+    /// }
+    /// assert_eq!(rec.pending(), 36);
+    /// assert_eq!(w.snapshot()["hot"].count, 64);
+    /// rec.flush();
+    /// assert_eq!(w.snapshot()["hot"].count, 100);
+    /// ```
+    #[inline]
+    pub fn recorder(&self, name: impl Into<Arc<str>>) -> LocalRecorder {
+        LocalRecorder::new(self.clone(), name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::MockClock;
+    use crate::{MetricConfig, WatchBuilder};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration as StdDuration;
+
+    #[test]
+    fn test_batches_match_single_records() {
+        let clock = MockClock::new();
+        let w = WatchBuilder::new()
+            .clock(clock.clone())
+            .rates(true)
+            .highest(1_000)
+            .build();
+        w.configure_metric(
+            "batched",
+            MetricConfig {
+                apdex_target: Some(100),
+                ..MetricConfig::default()
+            },
+        );
+        let hits = Arc::new(AtomicUsize::new(0));
+        let h = Arc::clone(&hits);
+        w.on_threshold("batched", StdDuration::from_nanos(500), move |_, _| {
+            h.fetch_add(1, Ordering::Relaxed);
+        });
+
+        let mut rec = w.recorder("batched").batch_size(4);
+        for v in [50, 150, 5_000, 80, 90, 2_000] {
+            rec.record(v);
+        }
+        assert_eq!(rec.pending(), 2);
+        assert_eq!(hits.load(Ordering::Relaxed), 1);
+        drop(rec);
+        assert_eq!(hits.load(Ordering::Relaxed), 2);
+
+        clock.advance(1_000_000_000);
+        let s = w.snapshot()["batched"];
+        assert_eq!(s.count, 6);
+        assert_eq!(s.max, 1_000); // clamped like `record`
+        assert!((s.rate_1s - 6.0).abs() < f64::EPSILON);
+        assert_eq!(
+            (s.apdex_satisfied, s.apdex_tolerating, s.apdex_frustrated),
+            (3, 1, 2)
+        );
+
+        // Flushing an empty buffer registers nothing.
+        w.recorder("never").flush();
+        assert!(!w.snapshot().contains_key("never"));
+    }
+}
@@ -0,0 +1,291 @@
+#![cfg(feature = "collector")]
+//! Table rendering for collected statistics.
+//!
+//! [`Table`] turns `Collector` stats or a `Watch` snapshot into rows of
+//! auto-scaled durations (via `Duration`'s `Display`) and renders them either
+//! as an aligned plain-text table for terminals or as a GitHub-flavored
+//! Markdown table for pull requests and issues. [`html`] writes standalone
+//! HTML pages with latency distribution charts.
+
+#[cfg(feature = "metrics")]
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "metrics")]
+use std::hash::BuildHasher;
+
+use crate::collector::Stats;
+#[cfg(feature = "metrics")]
+use crate::{Duration, WatchStats};
+
+pub mod html;
+
+/// A table of named statistics, rendered as text or Markdown.
+///
+/// The first column holds metric names (left-aligned); the others hold
+/// counts and durations (right-aligned).
+///
+/// # Examples
+/// ```
+/// use benchmark::report::Table;
+/// use benchmark::{Collector, Duration};
+///
+/// let c = Collector::new();
+/// c.record_duration("parse", Duration::from_nanos(1_500));
+/// c.record_duration("parse", Duration::from_nanos(2_500));
+/// let table = Table::from_stats(&c.all_stats());
+///
+/// let md = table.to_markdown();
+/// assert!(md.starts_with("| metric | count |"));
+/// assert!(md.contains("| parse  |     2 | 2.00µs |"));
+/// println!("{table}"); // aligned plain text
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Create an empty table with the given column headers.
+    pub fn new<I, S>(header: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            header: header.into_iter().map(Into::into).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Append a row; it is padded with empty cells or truncated to the
+    /// header width.
+    pub fn row<I, S>(&mut self, cells: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut row: Vec<String> = cells.into_iter().map(Into::into).collect();
+        row.resize(self.header.len(), String::new());
+        self.rows.push(row);
+        self
+    }
+
+    /// Columns: metric, count, mean, median, p95, min, max, stddev. Rows
+    /// keep the order of `stats` (`Collector::all_stats` is sorted by name).
+    pub fn from_stats(stats: &[(String, Stats)]) -> Self {
+        let mut table = Self::new([
+            "metric", "count", "mean", "median", "p95", "min", "max", "stddev",
+        ]);
+        for (name, s) in stats {
+            table.row([
+                name.clone(),
+                s.count.to_string(),
+                s.mean.to_string(),
+                s.median.to_string(),
+                s.p95.to_string(),
+                s.min.to_string(),
+                s.max.to_string(),
+                s.stddev.to_string(),
+            ]);
+        }
+        table
+    }
+
+    /// Columns: metric, count, mean, p50, p90, p99, max, stddev. Rows are
+    /// sorted by metric name.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::report::Table;
+    /// use benchmark::Watch;
+    ///
+    /// let w = Watch::new();
+    /// w.record("db.query", 12_000);
+    /// let text = Table::from_snapshot(&w.snapshot()).to_string();
+    /// assert!(text.starts_with("metric    count"));
+    /// ```
+    #[cfg(feature = "metrics")]
+    pub fn from_snapshot<H: BuildHasher>(snapshot: &HashMap<String, WatchStats, H>) -> Self {
+        let mut rows: Vec<(&str, &WatchStats)> =
+            snapshot.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        rows.sort_by(|a, b| a.0.cmp(b.0));
+        Self::from_watch_stats(rows)
+    }
+
+    /// Same columns as [`from_snapshot`](Self::from_snapshot), from a
+    /// [`FrozenSnapshot`](crate::FrozenSnapshot) (rows sorted by name).
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::report::Table;
+    /// use benchmark::Watch;
+    ///
+    /// let w = Watch::new();
+    /// w.record("db.query", 12_000);
+    /// let table = Table::from_frozen(&w.freeze());
+    /// assert_eq!(table.len(), 1);
+    /// ```
+    #[cfg(feature = "metrics")]
+    pub fn from_frozen(frozen: &crate::FrozenSnapshot) -> Self {
+        Self::from_watch_stats(frozen.stats().iter())
+    }
+
+    #[cfg(feature = "metrics")]
+    fn from_watch_stats<'a>(rows: impl IntoIterator<Item = (&'a str, &'a WatchStats)>) -> Self {
+        let mut table = Self::new([
+            "metric", "count", "mean", "p50", "p90", "p99", "max", "stddev",
+        ]);
+        for (name, s) in rows {
+            table.row([
+                name.to_string(),
+                s.count.to_string(),
+                ns_f64(s.mean).to_string(),
+                ns(s.p50).to_string(),
+                ns(s.p90).to_string(),
+                ns(s.p99).to_string(),
+                ns(s.max).to_string(),
+                ns_f64(s.stddev).to_string(),
+            ]);
+        }
+        table
+    }
+
+    /// Number of rows (excluding the header).
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// True if the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Render as a GitHub-flavored Markdown table (`|` in cells is escaped).
+    pub fn to_markdown(&self) -> String {
+        let escape = |c: &String| c.replace('|', "\\|");
+        let header: Vec<String> = self.header.iter().map(escape).collect();
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|r| r.iter().map(escape).collect())
+            .collect();
+        let widths = widths(&header, &rows, 3);
+
+        let mut out = String::new();
+        let mut line = |cells: &[String]| {
+            out.push('|');
+            for (i, cell) in cells.iter().enumerate() {
+                out.push(' ');
+                out.push_str(&pad(cell, widths[i], i > 0));
+                out.push_str(" |");
+            }
+            out.push('\n');
+        };
+        line(&header);
+        let rule: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| {
+                if i == 0 {
+                    format!(":{}", "-".repeat(w - 1))
+                } else {
+                    format!("{}:", "-".repeat(w - 1))
+                }
+            })
+            .collect();
+        line(&rule);
+        for row in &rows {
+            line(row);
+        }
+        out
+    }
+}
+
+/// Aligned plain text, two spaces between columns.
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let widths = widths(&self.header, &self.rows, 0);
+        for row in std::iter::once(&self.header).chain(&self.rows) {
+            let cells: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(i, c)| pad(c, widths[i], i > 0))
+                .collect();
+            writeln!(f, "{}", cells.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Display width of each column, at least `min`.
+fn widths(header: &[String], rows: &[Vec<String>], min: usize) -> Vec<usize> {
+    (0..header.len())
+        .map(|i| {
+            std::iter::once(header)
+                .chain(rows.iter().map(Vec::as_slice))
+                .map(|r| r[i].chars().count())
+                .fold(min, usize::max)
+        })
+        .collect()
+}
+
+/// Pad `cell` to `width` characters, right-aligned if `right`.
+fn pad(cell: &str, width: usize, right: bool) -> String {
+    let fill = " ".repeat(width.saturating_sub(cell.chars().count()));
+    if right {
+        fill + cell
+    } else {
+        format!("{cell}{fill}")
+    }
+}
+
+#[cfg(feature = "metrics")]
+fn ns(v: u64) -> Duration {
+    Duration::from_nanos(u128::from(v))
+}
+
+#[cfg(feature = "metrics")]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn ns_f64(v: f64) -> Duration {
+    Duration::from_nanos(v.max(0.0).round() as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Table {
+        let mut t = Table::new(["metric", "mean"]);
+        t.row(["a|b", "1.50µs"]).row(["long_name", "3ns"]);
+        t
+    }
+
+    #[test]
+    fn test_text_alignment() {
+        let text = sample().to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "metric       mean");
+        assert_eq!(lines[1], "a|b        1.50µs");
+        assert_eq!(lines[2], "long_name     3ns");
+    }
+
+    #[test]
+    fn test_markdown_layout() {
+        let md = sample().to_markdown();
+        let lines: Vec<&str> = md.lines().collect();
+        assert_eq!(lines[0], "| metric    |   mean |");
+        assert_eq!(lines[1], "| :-------- | -----: |");
+        assert_eq!(lines[2], "| a\\|b      | 1.50µs |");
+        assert_eq!(lines[3], "| long_name |    3ns |");
+    }
+
+    #[test]
+    fn test_rows_fit_header() {
+        let mut t = Table::new(["a", "b"]);
+        t.row(["1"]).row(["1", "2", "3"]);
+        assert_eq!(t.len(), 2);
+        assert_eq!(t.to_string().lines().nth(1), Some("1"));
+        assert!(Table::from_stats(&[]).is_empty());
+    }
+}
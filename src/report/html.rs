@@ -0,0 +1,341 @@
+//! Standalone HTML reports with latency distribution charts.
+//!
+//! [`HtmlReport`] writes a single self-contained HTML file: per metric, a
+//! summary table and an SVG chart of the histogram buckets on a logarithmic
+//! time axis, with p50/p90/p99 markers. The SVG is generated in plain Rust;
+//! the page needs no JavaScript, stylesheets or network access to render.
+
+use std::fmt::{self, Write as _};
+use std::io;
+use std::path::Path;
+
+use crate::histogram::HistogramSnapshot;
+use crate::Duration;
+#[cfg(feature = "metrics")]
+use crate::Watch;
+
+/// Chart geometry (SVG user units).
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 220.0;
+const LEFT: f64 = 48.0;
+const RIGHT: f64 = 12.0;
+const TOP: f64 = 22.0;
+const BOTTOM: f64 = 28.0;
+
+/// Percentile markers drawn on each chart.
+const MARKERS: [(&str, f64); 3] = [("p50", 0.50), ("p90", 0.90), ("p99", 0.99)];
+
+/// A standalone HTML report of histogram distributions.
+///
+/// # Examples
+/// ```
+/// use benchmark::histogram::Histogram;
+/// use benchmark::report::html::HtmlReport;
+///
+/// let h = Histogram::new();
+/// for v in [1_200, 1_500, 1_900, 2_400, 8_000] {
+///     h.record(v);
+/// }
+/// let mut report = HtmlReport::new("Parser latency");
+/// report.histogram("parse", h.snapshot());
+/// let html = report.to_string();
+/// assert!(html.starts_with("<!DOCTYPE html>"));
+/// assert!(html.contains("<svg"));
+/// // report.save("target/latency.html")?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HtmlReport {
+    title: String,
+    metrics: Vec<(String, HistogramSnapshot)>,
+}
+
+impl HtmlReport {
+    /// Create an empty report titled `title`.
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            metrics: Vec::new(),
+        }
+    }
+
+    /// Add a metric section for `snapshot`.
+    pub fn histogram(&mut self, name: impl Into<String>, snapshot: HistogramSnapshot) -> &mut Self {
+        self.metrics.push((name.into(), snapshot));
+        self
+    }
+
+    /// Add a section for every metric of `watch`, sorted by name.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::report::html::HtmlReport;
+    /// use benchmark::Watch;
+    ///
+    /// let w = Watch::new();
+    /// w.record("db.query", 40_000);
+    /// w.record("http.get", 900_000);
+    /// let mut report = HtmlReport::new("Service latency");
+    /// report.watch(&w);
+    /// assert_eq!(report.len(), 2);
+    /// ```
+    #[cfg(feature = "metrics")]
+    pub fn watch(&mut self, watch: &Watch) -> &mut Self {
+        self.metrics.extend(watch.histogram_snapshots());
+        self
+    }
+
+    /// Add a section for every histogram of `frozen`, sorted by name.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::report::html::HtmlReport;
+    /// use benchmark::Watch;
+    ///
+    /// let w = Watch::new();
+    /// w.record("db.query", 40_000);
+    /// let mut report = HtmlReport::new("Service latency");
+    /// report.frozen(&w.freeze());
+    /// assert_eq!(report.len(), 1);
+    /// ```
+    #[cfg(feature = "metrics")]
+    pub fn frozen(&mut self, frozen: &crate::FrozenSnapshot) -> &mut Self {
+        self.metrics.extend(
+            frozen
+                .histograms()
+                .map(|(name, h)| (name.to_string(), h.clone())),
+        );
+        self
+    }
+
+    /// Number of metric sections.
+    pub fn len(&self) -> usize {
+        self.metrics.len()
+    }
+
+    /// True if the report has no metric sections.
+    pub fn is_empty(&self) -> bool {
+        self.metrics.is_empty()
+    }
+
+    /// Write the HTML document to `out`.
+    ///
+    /// # Errors
+    /// Returns any error from `out`.
+    pub fn write_to<W: io::Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(self.to_string().as_bytes())
+    }
+
+    /// Write the HTML document to the file at `path`, replacing it.
+    ///
+    /// # Errors
+    /// Returns any error creating or writing the file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for HtmlReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let title = Escaped(&self.title);
+        writeln!(f, "<!DOCTYPE html>")?;
+        writeln!(f, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(f, "<title>{title}</title>")?;
+        writeln!(f, "<style>{STYLE}</style>\n</head>\n<body>")?;
+        writeln!(f, "<h1>{title}</h1>")?;
+        if self.metrics.is_empty() {
+            writeln!(f, "<p>No metrics recorded.</p>")?;
+        }
+        for (name, snap) in &self.metrics {
+            writeln!(f, "<section>\n<h2>{}</h2>", Escaped(name))?;
+            summary(f, snap)?;
+            writeln!(f, "{}", chart(snap))?;
+            writeln!(f, "</section>")?;
+        }
+        writeln!(f, "</body>\n</html>")
+    }
+}
+
+const STYLE: &str =
+    "body{font-family:system-ui,sans-serif;margin:2em auto;max-width:700px;color:#222}\
+h2{font-size:1.1em;margin-top:2em}\
+table{border-collapse:collapse;font-size:.9em}\
+th,td{padding:2px 10px;text-align:right;border-bottom:1px solid #ddd}\
+svg{display:block;margin-top:.5em}\
+.bar{fill:#4a7bd0}.axis{stroke:#888}.mark{stroke:#d04a4a;stroke-dasharray:4 3}\
+text{font-size:10px;fill:#555}.mark-label{fill:#d04a4a}";
+
+/// Summary statistics row for one metric.
+fn summary(f: &mut fmt::Formatter<'_>, snap: &HistogramSnapshot) -> fmt::Result {
+    let ns = |v: Option<u64>| v.map_or_else(|| "-".to_string(), |v| dur(v).to_string());
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let mean = snap.mean().map(|m| m.round() as u64);
+    writeln!(
+        f,
+        "<table><tr><th>count</th><th>min</th><th>mean</th><th>p50</th><th>p90</th><th>p99</th><th>max</th></tr>"
+    )?;
+    writeln!(
+        f,
+        "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr></table>",
+        snap.count(),
+        ns(snap.min()),
+        ns(mean),
+        ns(snap.percentile(0.50)),
+        ns(snap.percentile(0.90)),
+        ns(snap.percentile(0.99)),
+        ns(snap.max()),
+    )
+}
+
+/// SVG bar chart of the bucket counts on a log10 time axis.
+#[allow(clippy::cast_precision_loss)]
+fn chart(snap: &HistogramSnapshot) -> String {
+    let buckets: Vec<(u64, u64, u64)> = snap.buckets().collect();
+    let (Some(first), Some(last)) = (buckets.first(), buckets.last()) else {
+        return "<p>No samples.</p>".to_string();
+    };
+    let lo = (first.0.max(1) as f64).log10();
+    let hi = (last.1.max(first.0 + 1) as f64).log10().max(lo + 1e-3);
+    let plot_w = WIDTH - LEFT - RIGHT;
+    let plot_h = HEIGHT - TOP - BOTTOM;
+    let base = HEIGHT - BOTTOM;
+    let x = |v: u64| LEFT + ((v.max(1) as f64).log10() - lo) / (hi - lo) * plot_w;
+    let peak = buckets.iter().map(|b| b.2).max().unwrap_or(1).max(1) as f64;
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\" viewBox=\"0 0 {WIDTH} {HEIGHT}\">"
+    );
+    for &(low, high, count) in &buckets {
+        let x0 = x(low).max(LEFT);
+        let w = (x(high) - x0).max(1.0);
+        let h = count as f64 / peak * plot_h;
+        let _ = write!(
+            svg,
+            "<rect class=\"bar\" x=\"{x0:.1}\" y=\"{:.1}\" width=\"{w:.1}\" height=\"{h:.1}\"><title>{}–{}: {count}</title></rect>",
+            base - h,
+            dur(low),
+            dur(high),
+        );
+    }
+
+    // Axes, decade ticks and the peak count.
+    let _ = write!(
+        svg,
+        "<line class=\"axis\" x1=\"{LEFT}\" y1=\"{base}\" x2=\"{:.1}\" y2=\"{base}\"/>\
+         <line class=\"axis\" x1=\"{LEFT}\" y1=\"{TOP}\" x2=\"{LEFT}\" y2=\"{base}\"/>\
+         <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+        WIDTH - RIGHT,
+        LEFT - 4.0,
+        TOP + 8.0,
+        peak,
+    );
+    let mut ticks: Vec<u64> = decades(first.0.max(1), last.1);
+    if ticks.is_empty() {
+        ticks = vec![first.0.max(1), last.1];
+    }
+    for t in ticks {
+        let tx = x(t);
+        let _ = write!(
+            svg,
+            "<line class=\"axis\" x1=\"{tx:.1}\" y1=\"{base}\" x2=\"{tx:.1}\" y2=\"{:.1}\"/>\
+             <text x=\"{tx:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+            base + 4.0,
+            base + 16.0,
+            dur(t),
+        );
+    }
+
+    for (label, q) in MARKERS {
+        if let Some(v) = snap.percentile(q) {
+            let mx = x(v).clamp(LEFT, WIDTH - RIGHT);
+            let _ = write!(
+                svg,
+                "<line class=\"mark\" x1=\"{mx:.1}\" y1=\"{TOP}\" x2=\"{mx:.1}\" y2=\"{base}\"/>\
+                 <text class=\"mark-label\" x=\"{mx:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{label}</text>",
+                TOP - 6.0,
+            );
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Powers of ten within `[lo, hi]`.
+fn decades(lo: u64, hi: u64) -> Vec<u64> {
+    let mut out = Vec::new();
+    let mut t: u64 = 1;
+    loop {
+        if t >= lo && t <= hi {
+            out.push(t);
+        }
+        match t.checked_mul(10) {
+            Some(next) if next <= hi => t = next,
+            _ => break,
+        }
+    }
+    out
+}
+
+fn dur(ns: u64) -> Duration {
+    Duration::from_nanos(u128::from(ns))
+}
+
+/// HTML-escapes text and attribute values.
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ch in self.0.chars() {
+            match ch {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&#39;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::histogram::Histogram;
+
+    #[test]
+    fn test_document_structure_and_escaping() {
+        let h = Histogram::new();
+        for v in [100, 150, 1_000, 20_000] {
+            h.record(v);
+        }
+        let mut r = HtmlReport::new("a<b>");
+        r.histogram("x&y", h.snapshot())
+            .histogram("empty", Histogram::new().snapshot());
+        let html = r.to_string();
+        assert!(html.contains("<title>a&lt;b&gt;</title>"));
+        assert!(html.contains("<h2>x&amp;y</h2>"));
+        assert_eq!(html.matches("<svg").count(), 1);
+        assert!(html.contains("<p>No samples.</p>"));
+        assert_eq!(html.matches("class=\"mark\"").count(), 3);
+        // Decade ticks between 100ns and 20µs.
+        for label in [">100ns<", ">1.00µs<", ">10.00µs<"] {
+            assert!(html.contains(label), "missing tick {label}");
+        }
+        assert!(html.trim_end().ends_with("</html>"));
+
+        let mut buf = Vec::new();
+        r.write_to(&mut buf).unwrap();
+        assert_eq!(buf, html.as_bytes());
+    }
+
+    #[test]
+    fn test_decades() {
+        assert_eq!(decades(1, 1), [1]);
+        assert_eq!(decades(5, 2_000), [10, 100, 1_000]);
+        assert!(decades(11, 99).is_empty());
+        assert_eq!(decades(1, u64::MAX).len(), 20);
+    }
+}
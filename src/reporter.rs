@@ -0,0 +1,264 @@
+#![cfg(all(feature = "std", feature = "reporter"))]
+//! Background periodic snapshot reporter for `Watch`.
+//!
+//! A [`Reporter`] owns a `Watch` clone and a sink, and emits a snapshot every
+//! interval from a dedicated thread. Sinks can be stdout, an append-only file,
+//! or any custom closure. Shutdown is graceful: the returned
+//! [`ReporterHandle`] stops the thread after one final report.
+
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration as StdDuration;
+
+use crate::watch::{Watch, WatchStats};
+
+/// Boxed snapshot sink invoked from the reporter thread.
+type Sink = Box<dyn FnMut(&HashMap<String, WatchStats>) + Send + 'static>;
+
+/// Periodic snapshot reporter.
+///
+/// # Examples
+/// ```
+/// use benchmark::{Reporter, Watch};
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+///
+/// let w = Watch::new();
+/// w.record("job", 1_000);
+///
+/// let seen = Arc::new(Mutex::new(0usize));
+/// let seen2 = Arc::clone(&seen);
+/// let handle = Reporter::new(w, Duration::from_secs(60))
+///     .with_sink(move |snap| *seen2.lock().unwrap() += snap.len())
+///     .spawn();
+/// handle.shutdown(); // final report is always emitted
+/// assert_eq!(*seen.lock().unwrap(), 1);
+/// ```
+pub struct Reporter {
+    watch: Watch,
+    interval: StdDuration,
+    sink: Sink,
+}
+
+impl fmt::Debug for Reporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reporter")
+            .field("watch", &self.watch)
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Reporter {
+    /// Create a reporter for `watch` emitting every `interval` to stdout.
+    pub fn new(watch: Watch, interval: StdDuration) -> Self {
+        Self {
+            watch,
+            interval: interval.max(StdDuration::from_millis(1)),
+            sink: Box::new(|snap| {
+                let out = io::stdout();
+                let mut lock = out.lock();
+                let _ = lock.write_all(format_snapshot(snap).as_bytes());
+            }),
+        }
+    }
+
+    /// Emit to stdout (the default).
+    #[must_use]
+    pub fn with_stdout(self) -> Self {
+        let Self {
+            watch, interval, ..
+        } = self;
+        Self::new(watch, interval)
+    }
+
+    /// Append reports to the file at `path`, creating it if needed.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be opened for appending.
+    pub fn with_file(mut self, path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.sink = Box::new(move |snap| {
+            let _ = file.write_all(format_snapshot(snap).as_bytes());
+            let _ = file.flush();
+        });
+        Ok(self)
+    }
+
+    /// Use a custom sink closure receiving each snapshot.
+    #[must_use]
+    pub fn with_sink<F>(mut self, sink: F) -> Self
+    where
+        F: FnMut(&HashMap<String, WatchStats>) + Send + 'static,
+    {
+        self.sink = Box::new(sink);
+        self
+    }
+
+    /// Start the background thread.
+    pub fn spawn(self) -> ReporterHandle {
+        let Self {
+            watch,
+            interval,
+            mut sink,
+        } = self;
+        let (tx, rx) = mpsc::channel::<()>();
+        let thread = std::thread::Builder::new()
+            .name("benchmark-reporter".into())
+            .spawn(move || loop {
+                match rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => sink(&watch.snapshot()),
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => {
+                        sink(&watch.snapshot());
+                        break;
+                    }
+                }
+            })
+            .ok();
+        ReporterHandle {
+            stop: Some(tx),
+            thread,
+        }
+    }
+}
+
+/// Handle to a running [`Reporter`] thread.
+///
+/// Dropping the handle stops the thread after a final report.
+#[derive(Debug)]
+pub struct ReporterHandle {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ReporterHandle {
+    /// Stop the reporter, emitting one final report, and wait for it.
+    pub fn shutdown(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        if let Some(tx) = self.stop.take() {
+            let _ = tx.send(());
+        }
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+    }
+}
+
+impl Drop for ReporterHandle {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+/// Render a snapshot as one logfmt-style line per metric, sorted by name.
+fn format_snapshot(snap: &HashMap<String, WatchStats>) -> String {
+    let ts = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    let mut names: Vec<&String> = snap.keys().collect();
+    names.sort();
+
+    let mut out = String::new();
+    for name in names {
+        let s = &snap[name];
+        let _ = write!(
+            out,
+            "ts_ms={ts} metric={name} count={} min={} p50={} p90={} p99={} max={} mean={:.1}",
+            s.count, s.min, s.p50, s.p90, s.p99, s.max, s.mean
+        );
+        if s.rate_1s > 0.0 || s.rate_1m > 0.0 {
+            let _ = write!(out, " rate_1s={:.1} rate_1m={:.2}", s.rate_1s, s.rate_1m);
+        }
+        if s.apdex_target_ns > 0 {
+            let _ = write!(out, " apdex={:.3}", s.apdex);
+        }
+        if s.clamped_low > 0 || s.clamped_high > 0 {
+            let _ = write!(
+                out,
+                " clamped_low={} clamped_high={}",
+                s.clamped_low, s.clamped_high
+            );
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_format_includes_rates_when_tracked() {
+        let mut snap = HashMap::new();
+        snap.insert("a".to_string(), WatchStats::EMPTY);
+        snap.insert(
+            "b".to_string(),
+            WatchStats {
+                rate_1s: 3.0,
+                rate_1m: 2.5,
+                apdex_target_ns: 1_000,
+                apdex: 0.875,
+                ..WatchStats::EMPTY
+            },
+        );
+        snap.insert(
+            "c".to_string(),
+            WatchStats {
+                clamped_high: 4,
+                ..WatchStats::EMPTY
+            },
+        );
+        let text = format_snapshot(&snap);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(!lines[0].contains("rate_1s"));
+        assert!(!lines[0].contains("clamped"));
+        assert!(lines[1].ends_with("mean=0.0 rate_1s=3.0 rate_1m=2.50 apdex=0.875"));
+        assert!(lines[2].ends_with("mean=0.0 clamped_low=0 clamped_high=4"));
+    }
+
+    #[test]
+    fn test_periodic_reports() {
+        let w = Watch::new();
+        w.record("a", 1);
+        let reports = Arc::new(Mutex::new(0usize));
+        let r = Arc::clone(&reports);
+        let h = Reporter::new(w, StdDuration::from_millis(5))
+            .with_sink(move |_| *r.lock().unwrap() += 1)
+            .spawn();
+        std::thread::sleep(StdDuration::from_millis(50));
+        h.shutdown();
+        assert!(*reports.lock().unwrap() >= 2);
+    }
+
+    #[test]
+    fn test_file_sink_appends_lines() {
+        let path =
+            std::env::temp_dir().join(format!("benchmark-reporter-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let w = Watch::new();
+        w.record("db", 42);
+        w.record("api", 7);
+        drop(
+            Reporter::new(w, StdDuration::from_secs(3600))
+                .with_file(&path)
+                .unwrap()
+                .spawn(),
+        );
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("metric=api count=1"));
+        assert!(lines[1].contains("metric=db count=1"));
+    }
+}
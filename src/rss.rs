@@ -0,0 +1,178 @@
+#![cfg(feature = "rss")]
+//! Resident set size (RSS) probes for the benchmark runner.
+//!
+//! Linux and Android read `/proc/self/status` (`VmRSS`, `VmHWM`) and reset
+//! the peak between benchmarks through `/proc/self/clear_refs`, so the peak
+//! covers one benchmark. Windows reads `GetProcessMemoryInfo`, whose peak
+//! cannot be reset and is the process-wide high-water mark. Elsewhere every
+//! probe returns `None`.
+
+/// Memory footprint of one benchmark, from [`Runner::track_memory`](crate::Runner::track_memory).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Resident set size before warm-up, in bytes.
+    pub rss_before: Option<u64>,
+    /// Resident set size after the last iteration, in bytes.
+    pub rss_after: Option<u64>,
+    /// Peak resident set size while the benchmark ran, in bytes. Where the
+    /// platform cannot reset the peak (Windows) this is the process-wide
+    /// peak so far; `peak_is_local` tells which.
+    pub peak_rss: Option<u64>,
+    /// Whether `peak_rss` was reset before the benchmark started.
+    pub peak_is_local: bool,
+}
+
+impl MemoryUsage {
+    /// `rss_after - rss_before` in bytes; negative when memory was returned
+    /// to the OS.
+    pub fn delta(&self) -> Option<i64> {
+        let (before, after) = (self.rss_before?, self.rss_after?);
+        let signed = |v: u64| i64::try_from(v).unwrap_or(i64::MAX);
+        Some(signed(after) - signed(before))
+    }
+
+    /// Probe before a benchmark: current RSS, resetting the peak if possible.
+    pub(crate) fn start() -> Self {
+        Self {
+            rss_before: sys::current(),
+            peak_is_local: sys::reset_peak(),
+            ..Self::default()
+        }
+    }
+
+    /// Complete a [`start`](Self::start) probe after the benchmark.
+    pub(crate) fn finish(self) -> Self {
+        Self {
+            rss_after: sys::current(),
+            peak_rss: sys::peak(),
+            ..self
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sys {
+    /// A `kB` field of `/proc/self/status`, in bytes.
+    fn status_field(key: &str) -> Option<u64> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|l| l.starts_with(key))?;
+        let kb: u64 = line[key.len()..]
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse()
+            .ok()?;
+        Some(kb * 1024)
+    }
+
+    pub(super) fn current() -> Option<u64> {
+        status_field("VmRSS:")
+    }
+
+    pub(super) fn peak() -> Option<u64> {
+        status_field("VmHWM:")
+    }
+
+    /// Writing `5` resets `VmHWM` to the current RSS (Linux 4.0+).
+    pub(super) fn reset_peak() -> bool {
+        std::fs::write("/proc/self/clear_refs", "5").is_ok()
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ProcessMemoryCounters {
+        cb: u32,
+        page_fault_count: u32,
+        peak_working_set_size: usize,
+        working_set_size: usize,
+        quota_peak_paged_pool_usage: usize,
+        quota_paged_pool_usage: usize,
+        quota_peak_non_paged_pool_usage: usize,
+        quota_non_paged_pool_usage: usize,
+        pagefile_usage: usize,
+        peak_pagefile_usage: usize,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentProcess() -> *mut c_void;
+        fn K32GetProcessMemoryInfo(
+            process: *mut c_void,
+            counters: *mut ProcessMemoryCounters,
+            cb: u32,
+        ) -> i32;
+    }
+
+    #[allow(unsafe_code)]
+    fn counters() -> Option<ProcessMemoryCounters> {
+        let mut c = ProcessMemoryCounters::default();
+        #[allow(clippy::cast_possible_truncation)]
+        let cb = std::mem::size_of::<ProcessMemoryCounters>() as u32;
+        c.cb = cb;
+        // SAFETY: `c` is a live, correctly sized `PROCESS_MEMORY_COUNTERS`
+        // and the pseudo-handle needs no closing.
+        let ok = unsafe { K32GetProcessMemoryInfo(GetCurrentProcess(), &mut c, cb) };
+        (ok != 0).then_some(c)
+    }
+
+    pub(super) fn current() -> Option<u64> {
+        counters().map(|c| c.working_set_size as u64)
+    }
+
+    pub(super) fn peak() -> Option<u64> {
+        counters().map(|c| c.peak_working_set_size as u64)
+    }
+
+    pub(super) fn reset_peak() -> bool {
+        false
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+mod sys {
+    pub(super) fn current() -> Option<u64> {
+        None
+    }
+
+    pub(super) fn peak() -> Option<u64> {
+        None
+    }
+
+    pub(super) fn reset_peak() -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_is_signed() {
+        let m = MemoryUsage {
+            rss_before: Some(10_000),
+            rss_after: Some(4_000),
+            ..MemoryUsage::default()
+        };
+        assert_eq!(m.delta(), Some(-6_000));
+        assert_eq!(MemoryUsage::default().delta(), None);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_peak_sees_a_touched_buffer() {
+        let probe = MemoryUsage::start();
+        let buf = vec![1u8; 32 << 20];
+        std::hint::black_box(&buf);
+        drop(buf);
+        let m = probe.finish();
+        let (before, peak) = (m.rss_before.unwrap(), m.peak_rss.unwrap());
+        assert!(m.rss_after.is_some());
+        assert!(peak >= before + (16 << 20) || !m.peak_is_local, "{m:?}");
+    }
+}
@@ -0,0 +1,1078 @@
+#![cfg(feature = "benchmark")]
+//! Minimal benchmark runner with reproducible run-order randomization.
+//!
+//! Benchmarks registered on a [`Runner`] are executed one after another, each
+//! for a fixed number of iterations. Running them in declaration order lets a
+//! benchmark systematically benefit (or suffer) from the one before it (warm
+//! caches, branch predictors, allocator state). Enabling shuffling randomizes
+//! the order with a seeded PRNG; the seed is printed so any run can be
+//! reproduced with `--seed <n>`.
+//!
+//! Between benchmarks the runner can sleep for a configurable cooldown and
+//! re-measure a fixed reference workload; if the reference has slowed down
+//! beyond a tolerance (CPU frequency scaling, thermal throttling, noisy
+//! neighbours), the affected result is flagged as `throttled`.
+//!
+//! With normalization enabled, the same reference workload is timed at suite
+//! start and end, and each result additionally reports its mean/median in
+//! "reference units" (benchmark time divided by reference time). Normalized
+//! numbers are far more comparable across machines and cloud instances than
+//! absolute nanoseconds.
+//!
+//! Each benchmark can be warmed up before sampling ([`Runner::warmup`],
+//! [`Runner::warmup_time`]): the closure runs untimed so the first samples
+//! don't pay for cold caches, page faults, lazy initialisation or an idle CPU
+//! clock.
+//!
+//! Benchmarks may carry tags (e.g. `io`, `cpu`, `regression-critical`).
+//! [`Runner::only_tags`] / [`Runner::skip_tags`] (or `--tags` / `--skip-tags`
+//! on the command line) select a subset, so different CI stages can run
+//! different slices of one suite, and [`TagSummary`] aggregates results per tag.
+
+use std::fmt;
+use std::hint::black_box;
+use std::time::Duration as StdDuration;
+
+use crate::analysis::SplitMix64;
+use crate::Duration;
+
+/// Default iterations per benchmark.
+const DEFAULT_ITERS: usize = 1_000;
+/// Rounds of the reference workload per probe; the fastest round is kept.
+const REFERENCE_ROUNDS: usize = 5;
+/// Inner loop length of the reference workload (~tens of microseconds).
+const REFERENCE_LOOP: u64 = 20_000;
+
+type BenchFn = Box<dyn FnMut()>;
+
+struct Bench {
+    name: String,
+    tags: Vec<String>,
+    f: BenchFn,
+}
+
+/// Aggregated result of one benchmark.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    /// Benchmark name.
+    pub name: String,
+    /// Tags declared with [`Runner::bench_tagged`].
+    pub tags: Vec<String>,
+    /// Zero-based position in the executed (possibly shuffled) order.
+    pub order: usize,
+    /// Raw per-iteration samples.
+    pub samples: Vec<Duration>,
+    /// Total time across all iterations.
+    pub total: Duration,
+    /// Fastest iteration.
+    pub min: Duration,
+    /// Slowest iteration.
+    pub max: Duration,
+    /// Mean iteration time.
+    pub mean: Duration,
+    /// Median iteration time (nearest rank, as in `Stats`).
+    pub median: Duration,
+    /// Geometric mean iteration time (zero samples count as 1ns); less
+    /// swayed than `mean` by a few slow outliers.
+    pub geometric_mean: Duration,
+    /// Reference workload time after this benchmark divided by the suite
+    /// baseline (`1.0` = unchanged). `None` unless the thermal guard is enabled.
+    pub reference_drift: Option<f64>,
+    /// True if the reference workload slowed beyond the configured tolerance,
+    /// suggesting the result was collected on a throttled machine.
+    pub throttled: bool,
+    /// Mean divided by the suite's reference time. `None` unless normalization is enabled.
+    pub normalized_mean: Option<f64>,
+    /// Median divided by the suite's reference time. `None` unless normalization is enabled.
+    pub normalized_median: Option<f64>,
+    /// Process memory before, after and at peak. `None` unless
+    /// [`Runner::track_memory`] is enabled.
+    #[cfg(feature = "rss")]
+    pub memory: Option<crate::MemoryUsage>,
+    /// Hardware counter totals over one extra, untimed pass of the measured
+    /// iterations. `None` unless [`Runner::perf_counters`] is enabled and
+    /// counters are available.
+    #[cfg(feature = "perf-events")]
+    pub perf: Option<crate::PerfCounts>,
+}
+
+impl BenchResult {
+    fn from_samples(name: String, order: usize, samples: Vec<Duration>) -> Self {
+        let mut sorted = samples.clone();
+        sorted.sort_unstable();
+        let total: u128 = sorted.iter().map(Duration::as_nanos).sum();
+        let n = sorted.len();
+        let (min, max, mean, median, geometric_mean) = if n == 0 {
+            (
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::ZERO,
+            )
+        } else {
+            #[allow(clippy::cast_precision_loss)]
+            let log_mean = sorted
+                .iter()
+                .map(|d| (d.as_nanos().max(1) as f64).ln())
+                .sum::<f64>()
+                / n as f64;
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let geometric_mean = Duration::from_nanos(log_mean.exp().round() as u128);
+            (
+                sorted[0],
+                sorted[n - 1],
+                Duration::from_nanos(total / n as u128),
+                crate::analysis::nearest_rank(&sorted, 50),
+                geometric_mean,
+            )
+        };
+        Self {
+            name,
+            tags: Vec::new(),
+            order,
+            samples,
+            total: Duration::from_nanos(total),
+            min,
+            max,
+            mean,
+            median,
+            geometric_mean,
+            reference_drift: None,
+            throttled: false,
+            normalized_mean: None,
+            normalized_median: None,
+            #[cfg(feature = "rss")]
+            memory: None,
+            #[cfg(feature = "perf-events")]
+            perf: None,
+        }
+    }
+}
+
+/// Aggregate of all results sharing one tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagSummary {
+    /// The tag.
+    pub tag: String,
+    /// Number of benchmarks carrying the tag.
+    pub benchmarks: usize,
+    /// Number of iterations across those benchmarks.
+    pub samples: usize,
+    /// Total time across all their iterations.
+    pub total: Duration,
+    /// Mean iteration time across all their iterations.
+    pub mean: Duration,
+    /// How many of them were flagged `throttled`.
+    pub throttled: usize,
+}
+
+impl TagSummary {
+    /// Summarize `results` per tag, sorted by tag name. Untagged results are
+    /// not included.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Runner, TagSummary};
+    ///
+    /// let results = Runner::new()
+    ///     .iterations(5)
+    ///     .bench_tagged("read", ["io"], || {})
+    ///     .bench_tagged("write", ["io", "slow"], || {})
+    ///     .bench("sum", || {})
+    ///     .run();
+    /// let tags = TagSummary::from_results(&results);
+    /// assert_eq!(tags[0].tag, "io");
+    /// assert_eq!((tags[0].benchmarks, tags[0].samples), (2, 10));
+    /// assert_eq!(tags[1].tag, "slow");
+    /// ```
+    pub fn from_results(results: &[BenchResult]) -> Vec<TagSummary> {
+        let mut by_tag: std::collections::BTreeMap<&str, TagSummary> =
+            std::collections::BTreeMap::new();
+        for r in results {
+            for tag in &r.tags {
+                let s = by_tag.entry(tag).or_insert_with(|| TagSummary {
+                    tag: tag.clone(),
+                    benchmarks: 0,
+                    samples: 0,
+                    total: Duration::ZERO,
+                    mean: Duration::ZERO,
+                    throttled: 0,
+                });
+                s.benchmarks += 1;
+                s.samples += r.samples.len();
+                s.total = Duration::from_nanos(s.total.as_nanos() + r.total.as_nanos());
+                s.throttled += usize::from(r.throttled);
+            }
+        }
+        by_tag
+            .into_values()
+            .map(|mut s| {
+                s.mean = Duration::from_nanos(s.total.as_nanos() / (s.samples.max(1) as u128));
+                s
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for TagSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {} benchmarks, {} iterations, total {}, mean {}",
+            self.tag, self.benchmarks, self.samples, self.total, self.mean
+        )?;
+        if self.throttled > 0 {
+            write!(f, ", {} throttled", self.throttled)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reference workload timings bracketing a normalized suite run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reference {
+    /// Reference time measured before the first benchmark.
+    pub start: Duration,
+    /// Reference time measured after the last benchmark.
+    pub end: Duration,
+}
+
+impl Reference {
+    /// Mean of the start and end measurements; the normalization divisor.
+    pub fn mean(&self) -> Duration {
+        Duration::from_nanos((self.start.as_nanos() + self.end.as_nanos()) / 2)
+    }
+
+    /// Relative change from start to end (`end / start`); values far from
+    /// `1.0` mean the machine's speed changed during the suite.
+    pub fn drift(&self) -> f64 {
+        ratio(self.end, self.start)
+    }
+}
+
+/// Sequential benchmark runner.
+///
+/// # Examples
+/// ```
+/// use benchmark::Runner;
+///
+/// let mut runner = Runner::new()
+///     .iterations(10)
+///     .seed(42) // shuffle with a fixed, reproducible seed
+///     .bench("sum", || {
+///         let _ = (0..100u64).sum::<u64>();
+///     })
+///     .bench("product", || {
+///         let _ = (1..10u64).product::<u64>();
+///     });
+/// let results = runner.run();
+/// assert_eq!(results.len(), 2);
+/// assert_eq!(results[0].name, "sum"); // results keep declaration order
+/// assert_eq!(runner.last_seed(), Some(42));
+/// ```
+#[allow(clippy::struct_excessive_bools)]
+pub struct Runner {
+    benches: Vec<Bench>,
+    iters: usize,
+    shuffle: bool,
+    seed: Option<u64>,
+    last_seed: Option<u64>,
+    cooldown: StdDuration,
+    warmup: usize,
+    warmup_time: StdDuration,
+    thermal_tolerance: Option<f64>,
+    normalize: bool,
+    last_reference: Option<Reference>,
+    only_tags: Vec<String>,
+    skip_tags: Vec<String>,
+    filter: Option<String>,
+    #[cfg(feature = "rss")]
+    track_memory: bool,
+    #[cfg(feature = "perf-events")]
+    perf_counters: bool,
+}
+
+impl fmt::Debug for Runner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.benches.iter().map(|b| b.name.as_str()).collect();
+        let mut d = f.debug_struct("Runner");
+        d.field("benches", &names)
+            .field("iters", &self.iters)
+            .field("shuffle", &self.shuffle)
+            .field("seed", &self.seed)
+            .field("cooldown", &self.cooldown)
+            .field("warmup", &self.warmup)
+            .field("warmup_time", &self.warmup_time)
+            .field("thermal_tolerance", &self.thermal_tolerance)
+            .field("normalize", &self.normalize)
+            .field("only_tags", &self.only_tags)
+            .field("skip_tags", &self.skip_tags)
+            .field("filter", &self.filter);
+        #[cfg(feature = "rss")]
+        d.field("track_memory", &self.track_memory);
+        #[cfg(feature = "perf-events")]
+        d.field("perf_counters", &self.perf_counters);
+        d.finish_non_exhaustive()
+    }
+}
+
+impl Default for Runner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runner {
+    /// Create an empty runner (1,000 iterations per benchmark, no shuffling).
+    pub fn new() -> Self {
+        Self {
+            benches: Vec::new(),
+            iters: DEFAULT_ITERS,
+            shuffle: false,
+            seed: None,
+            last_seed: None,
+            cooldown: StdDuration::ZERO,
+            warmup: 0,
+            warmup_time: StdDuration::ZERO,
+            thermal_tolerance: None,
+            normalize: false,
+            last_reference: None,
+            only_tags: Vec::new(),
+            skip_tags: Vec::new(),
+            filter: None,
+            #[cfg(feature = "rss")]
+            track_memory: false,
+            #[cfg(feature = "perf-events")]
+            perf_counters: false,
+        }
+    }
+
+    /// Create a runner configured from the environment and then the process
+    /// command line, so flags override environment variables.
+    ///
+    /// See [`Runner::env`] and [`Runner::args`] for what is recognized.
+    pub fn from_args() -> Self {
+        Self::new().env().args(std::env::args().skip(1))
+    }
+
+    /// Apply settings from environment variables; unset, empty or
+    /// unparsable values are ignored.
+    ///
+    /// - `BENCHMARK_ITERS=<n>`: iterations per benchmark
+    /// - `BENCHMARK_WARMUP_MS=<ms>`: minimum warmup time per benchmark
+    /// - `BENCHMARK_FILTER=<s>`: only run benchmarks whose name contains `s`
+    ///
+    /// Lets CI tune run length without code changes.
+    #[must_use]
+    pub fn env(self) -> Self {
+        self.env_with(|key| std::env::var(key).ok())
+    }
+
+    fn env_with(mut self, var: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |key: &str| var(key).filter(|v| !v.trim().is_empty());
+        if let Some(n) = var("BENCHMARK_ITERS").and_then(|v| v.trim().parse::<usize>().ok()) {
+            self = self.iterations(n);
+        }
+        if let Some(ms) = var("BENCHMARK_WARMUP_MS").and_then(|v| v.trim().parse::<u64>().ok()) {
+            self = self.warmup_time(StdDuration::from_millis(ms));
+        }
+        if let Some(pattern) = var("BENCHMARK_FILTER") {
+            self = self.filter(pattern);
+        }
+        self
+    }
+
+    /// Apply command-line style flags; unrecognized flags are ignored.
+    ///
+    /// - `--iters <n>` / `--iters=<n>`: iterations per benchmark
+    /// - `--filter <s>` / `--filter=<s>`, or a bare argument: only run
+    ///   benchmarks whose name contains `s` (as passed by `cargo bench -- <s>`)
+    /// - `--seed <n>` / `--seed=<n>`: shuffle with the given seed
+    /// - `--shuffle`: shuffle with a fresh random seed
+    /// - `--tags <a,b>` / `--tags=<a,b>`: only run benchmarks with any of these tags
+    /// - `--skip-tags <a,b>` / `--skip-tags=<a,b>`: skip benchmarks with any of these tags
+    /// - `--warmup <n>` / `--warmup=<n>`: warmup iterations per benchmark
+    /// - `--warmup-ms <ms>` / `--warmup-ms=<ms>`: minimum warmup time per benchmark
+    #[must_use]
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut it = args.into_iter();
+        while let Some(arg) = it.next() {
+            let arg = arg.as_ref();
+            if arg == "--shuffle" {
+                self.shuffle = true;
+            } else if let Some(v) = flag_value(arg, "--seed", &mut it) {
+                if let Ok(seed) = v.parse::<u64>() {
+                    self = self.seed(seed);
+                }
+            } else if let Some(v) = flag_value(arg, "--tags", &mut it) {
+                self = self.only_tags(split_tags(&v));
+            } else if let Some(v) = flag_value(arg, "--skip-tags", &mut it) {
+                self = self.skip_tags(split_tags(&v));
+            } else if let Some(v) = flag_value(arg, "--warmup-ms", &mut it) {
+                if let Ok(ms) = v.parse::<u64>() {
+                    self = self.warmup_time(StdDuration::from_millis(ms));
+                }
+            } else if let Some(v) = flag_value(arg, "--warmup", &mut it) {
+                if let Ok(n) = v.parse::<usize>() {
+                    self = self.warmup(n);
+                }
+            } else if let Some(v) = flag_value(arg, "--iters", &mut it) {
+                if let Ok(n) = v.parse::<usize>() {
+                    self = self.iterations(n);
+                }
+            } else if let Some(v) = flag_value(arg, "--filter", &mut it) {
+                self = self.filter(v);
+            } else if !arg.starts_with('-') && !arg.is_empty() {
+                self = self.filter(arg);
+            }
+        }
+        self
+    }
+
+    /// Set iterations per benchmark (min 1).
+    #[must_use]
+    pub fn iterations(mut self, iters: usize) -> Self {
+        self.iters = iters.max(1);
+        self
+    }
+
+    /// Enable or disable run-order randomization.
+    ///
+    /// Without an explicit [`seed`](Self::seed) a fresh seed is drawn per run.
+    #[must_use]
+    pub fn shuffle(mut self, enabled: bool) -> Self {
+        self.shuffle = enabled;
+        self
+    }
+
+    /// Shuffle the run order using `seed` (implies `shuffle(true)`).
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.shuffle = true;
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sleep for `cooldown` between consecutive benchmarks.
+    ///
+    /// Gives the CPU time to return to its idle clock/temperature so one heavy
+    /// benchmark doesn't penalize the next.
+    #[must_use]
+    pub fn cooldown(mut self, cooldown: StdDuration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Run each benchmark `iters` times, untimed, right before sampling it.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Runner;
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    ///
+    /// let calls = Rc::new(Cell::new(0));
+    /// let counter = Rc::clone(&calls);
+    /// let results = Runner::new()
+    ///     .iterations(10)
+    ///     .warmup(5)
+    ///     .bench("count", move || counter.set(counter.get() + 1))
+    ///     .run();
+    /// assert_eq!(calls.get(), 15);
+    /// assert_eq!(results[0].samples.len(), 10);
+    /// ```
+    #[must_use]
+    pub fn warmup(mut self, iters: usize) -> Self {
+        self.warmup = iters;
+        self
+    }
+
+    /// Keep warming each benchmark until at least `time` has elapsed.
+    ///
+    /// Combines with [`warmup`](Self::warmup): warming stops once both the
+    /// iteration count and the time are reached.
+    #[must_use]
+    pub fn warmup_time(mut self, time: StdDuration) -> Self {
+        self.warmup_time = time;
+        self
+    }
+
+    /// Enable the thermal guard with a relative `tolerance` (e.g. `0.10` = 10%).
+    ///
+    /// A reference workload is timed once before the suite and again after
+    /// each benchmark; results whose post-run reference time exceeds the
+    /// baseline by more than `tolerance` are marked `throttled` and a warning
+    /// is printed to stderr.
+    #[must_use]
+    pub fn thermal_guard(mut self, tolerance: f64) -> Self {
+        self.thermal_tolerance = Some(if tolerance.is_finite() {
+            tolerance.max(0.0)
+        } else {
+            0.0
+        });
+        self
+    }
+
+    /// Only run benchmarks whose name contains `pattern`.
+    #[must_use]
+    pub fn filter(mut self, pattern: impl Into<String>) -> Self {
+        self.filter = Some(pattern.into());
+        self
+    }
+
+    /// Report results normalized against a built-in reference workload.
+    ///
+    /// The reference is timed at suite start and end; each result's
+    /// `normalized_mean`/`normalized_median` is its time divided by the mean
+    /// of the two.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Runner;
+    ///
+    /// let mut runner = Runner::new()
+    ///     .iterations(10)
+    ///     .normalize(true)
+    ///     .bench("sum", || {
+    ///         let _ = (0..100u64).sum::<u64>();
+    ///     });
+    /// let results = runner.run();
+    /// assert!(results[0].normalized_mean.is_some());
+    /// assert!(runner.last_reference().is_some());
+    /// ```
+    #[must_use]
+    pub fn normalize(mut self, enabled: bool) -> Self {
+        self.normalize = enabled;
+        self
+    }
+
+    /// Sample process memory around each benchmark into
+    /// [`BenchResult::memory`].
+    ///
+    /// RSS is read before warm-up and after the last iteration. On Linux the
+    /// peak is reset before each benchmark, so `peak_rss` is that benchmark's
+    /// own high-water mark; on Windows it is the process-wide peak so far, and
+    /// on other platforms every field is `None`.
+    #[cfg(feature = "rss")]
+    #[must_use]
+    pub fn track_memory(mut self, enabled: bool) -> Self {
+        self.track_memory = enabled;
+        self
+    }
+
+    /// Count hardware events for each benchmark into [`BenchResult::perf`].
+    ///
+    /// After the timed samples, the benchmark runs its iterations once more
+    /// with the counters on and the clock off, so timer reads do not inflate
+    /// the counts. If no counter can be opened a warning is printed to
+    /// stderr and `perf` stays `None`.
+    #[cfg(feature = "perf-events")]
+    #[must_use]
+    pub fn perf_counters(mut self, enabled: bool) -> Self {
+        self.perf_counters = enabled;
+        self
+    }
+
+    /// Register a benchmark closure executed once per iteration.
+    #[must_use]
+    pub fn bench<F>(self, name: impl Into<String>, f: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.bench_tagged(name, std::iter::empty::<String>(), f)
+    }
+
+    /// Register a benchmark carrying `tags` (e.g. `["io", "regression-critical"]`).
+    #[must_use]
+    pub fn bench_tagged<F, I, S>(mut self, name: impl Into<String>, tags: I, f: F) -> Self
+    where
+        F: FnMut() + 'static,
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.benches.push(Bench {
+            name: name.into(),
+            tags: tags.into_iter().map(Into::into).collect(),
+            f: Box::new(f),
+        });
+        self
+    }
+
+    /// Only run benchmarks carrying at least one of `tags` (added to any
+    /// previously selected tags). Without a selection every benchmark runs.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Runner;
+    ///
+    /// let results = Runner::new()
+    ///     .iterations(1)
+    ///     .bench_tagged("disk", ["io"], || {})
+    ///     .bench_tagged("hash", ["cpu"], || {})
+    ///     .only_tags(["cpu"])
+    ///     .run();
+    /// assert_eq!(results.len(), 1);
+    /// assert_eq!(results[0].name, "hash");
+    /// ```
+    #[must_use]
+    pub fn only_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.only_tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Skip benchmarks carrying any of `tags`; takes precedence over
+    /// [`only_tags`](Self::only_tags).
+    #[must_use]
+    pub fn skip_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.skip_tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Seed used by the most recent shuffled run, if any.
+    pub fn last_seed(&self) -> Option<u64> {
+        self.last_seed
+    }
+
+    /// Reference timings from the most recent normalized run, if any.
+    pub fn last_reference(&self) -> Option<Reference> {
+        self.last_reference
+    }
+
+    /// Execute all selected benchmarks and return results in declaration order.
+    ///
+    /// Benchmarks filtered out by tag selection are not run and have no
+    /// result. When shuffling, the seed is printed to stderr so the order can be
+    /// reproduced with `--seed <n>`.
+    pub fn run(&mut self) -> Vec<BenchResult> {
+        let order = self.execution_order();
+        let baseline = (self.normalize || self.thermal_tolerance.is_some()).then(reference_time);
+        let mut results: Vec<Option<BenchResult>> = vec![None; self.benches.len()];
+        #[cfg(feature = "perf-events")]
+        let mut counters = self.perf_counters.then(open_counters).flatten();
+        for (pos, &idx) in order.iter().enumerate() {
+            if pos > 0 && !self.cooldown.is_zero() {
+                std::thread::sleep(self.cooldown);
+            }
+            let bench = &mut self.benches[idx];
+            #[cfg(feature = "rss")]
+            let memory = self.track_memory.then(crate::MemoryUsage::start);
+            warm_up(&mut bench.f, self.warmup, self.warmup_time);
+            let samples = sample(&mut bench.f, self.iters);
+            let mut result = BenchResult::from_samples(bench.name.clone(), pos, samples);
+            #[cfg(feature = "rss")]
+            {
+                result.memory = memory.map(crate::MemoryUsage::finish);
+            }
+            #[cfg(feature = "perf-events")]
+            if let Some(counters) = counters.as_mut() {
+                counters.start();
+                for _ in 0..self.iters {
+                    (bench.f)();
+                }
+                result.perf = Some(counters.stop(self.iters as u64));
+            }
+            result.tags.clone_from(&bench.tags);
+            if let (Some(tolerance), Some(base)) = (self.thermal_tolerance, baseline) {
+                let drift = ratio(reference_time(), base);
+                result.reference_drift = Some(drift);
+                if drift > 1.0 + tolerance {
+                    result.throttled = true;
+                    eprintln!(
+                        "benchmark runner: '{}' may be throttled (reference workload {:.1}% slower than baseline)",
+                        result.name,
+                        (drift - 1.0) * 100.0
+                    );
+                }
+            }
+            results[idx] = Some(result);
+        }
+        let mut results: Vec<BenchResult> = results.into_iter().flatten().collect();
+
+        self.last_reference = None;
+        if let (true, Some(start)) = (self.normalize, baseline) {
+            let reference = Reference {
+                start,
+                end: reference_time(),
+            };
+            let unit = reference.mean();
+            for r in &mut results {
+                r.normalized_mean = Some(ratio(r.mean, unit));
+                r.normalized_median = Some(ratio(r.median, unit));
+            }
+            self.last_reference = Some(reference);
+        }
+        results
+    }
+
+    fn selected(&self, bench: &Bench) -> bool {
+        let has = |set: &[String]| bench.tags.iter().any(|t| set.contains(t));
+        !has(&self.skip_tags)
+            && (self.only_tags.is_empty() || has(&self.only_tags))
+            && self
+                .filter
+                .as_ref()
+                .map_or(true, |p| bench.name.contains(p.as_str()))
+    }
+
+    fn execution_order(&mut self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.benches.len())
+            .filter(|&i| self.selected(&self.benches[i]))
+            .collect();
+        if !self.shuffle {
+            self.last_seed = None;
+            return order;
+        }
+        let seed = self.seed.unwrap_or_else(random_seed);
+        self.last_seed = Some(seed);
+        eprintln!(
+            "benchmark runner: order shuffled with seed {seed} (reproduce with --seed {seed})"
+        );
+        let mut rng = SplitMix64(seed);
+        // Fisher-Yates
+        for i in (1..order.len()).rev() {
+            #[allow(clippy::cast_possible_truncation)]
+            let j = (rng.next() % (i as u64 + 1)) as usize;
+            order.swap(i, j);
+        }
+        order
+    }
+}
+
+fn sample(f: &mut BenchFn, iters: usize) -> Vec<Duration> {
+    let mut samples = Vec::with_capacity(iters);
+    for _ in 0..iters {
+        let start = crate::clock::system_now_ns();
+        f();
+        samples.push(crate::clock::__elapsed_since(start));
+    }
+    samples
+}
+
+/// Open the hardware counters, warning once if none are available.
+#[cfg(feature = "perf-events")]
+fn open_counters() -> Option<crate::PerfCounters> {
+    let counters = crate::PerfCounters::new();
+    if counters.is_none() {
+        eprintln!("benchmark runner: hardware performance counters unavailable");
+    }
+    counters
+}
+
+/// Run `f` untimed until both `iters` runs and `time` have passed.
+fn warm_up(f: &mut BenchFn, iters: usize, time: StdDuration) {
+    let start = crate::clock::system_now_ns();
+    let mut runs = 0;
+    while runs < iters || crate::clock::__elapsed_since(start) < time.into() {
+        f();
+        runs += 1;
+    }
+}
+
+/// Time a fixed CPU-bound reference workload, keeping the fastest of a few rounds.
+fn reference_time() -> Duration {
+    let mut best = u128::MAX;
+    for _ in 0..REFERENCE_ROUNDS {
+        let start = crate::clock::system_now_ns();
+        let mut x = black_box(0x2545_F491_4F6C_DD1D_u64);
+        for i in 0..REFERENCE_LOOP {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x = x.wrapping_add(i);
+        }
+        black_box(x);
+        best = best.min(crate::clock::__elapsed_since(start).as_nanos());
+    }
+    Duration::from_nanos(best.max(1))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn ratio(a: Duration, b: Duration) -> f64 {
+    a.as_nanos() as f64 / b.as_nanos().max(1) as f64
+}
+
+/// Split a comma-separated tag list, dropping empty entries.
+fn split_tags(v: &str) -> Vec<String> {
+    v.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Extract the value of `--name <v>` or `--name=<v>` style flags.
+pub(crate) fn flag_value<I, S>(arg: &str, name: &str, rest: &mut I) -> Option<String>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    if arg == name {
+        return rest.next().map(|v| v.as_ref().to_string());
+    }
+    arg.strip_prefix(name)
+        .and_then(|r| r.strip_prefix('='))
+        .map(str::to_string)
+}
+
+fn random_seed() -> u64 {
+    let t = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    // Mix in the process id so processes started in the same instant differ.
+    #[allow(clippy::cast_possible_truncation)]
+    let s = (t as u64) ^ (u64::from(std::process::id()) << 32);
+    SplitMix64(s).next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn traced(n: usize, log: &Rc<RefCell<Vec<usize>>>) -> Runner {
+        let mut r = Runner::new().iterations(1);
+        for i in 0..n {
+            let log = Rc::clone(log);
+            r = r.bench(format!("b{i}"), move || log.borrow_mut().push(i));
+        }
+        r
+    }
+
+    #[test]
+    fn test_same_seed_same_order() {
+        let a = Rc::new(RefCell::new(Vec::new()));
+        let b = Rc::new(RefCell::new(Vec::new()));
+        traced(8, &a).seed(7).run();
+        traced(8, &b).seed(7).run();
+        assert_eq!(*a.borrow(), *b.borrow());
+        let mut sorted = a.borrow().clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_unshuffled_runs_in_declaration_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let results = traced(5, &log).run();
+        assert_eq!(*log.borrow(), vec![0, 1, 2, 3, 4]);
+        assert!(results.iter().enumerate().all(|(i, r)| r.order == i));
+    }
+
+    #[test]
+    fn test_seed_flag_parsing() {
+        let r = Runner::new().args(["--bench", "--seed", "99"]);
+        assert_eq!(r.seed, Some(99));
+        let r = Runner::new().args(["--seed=5"]);
+        assert_eq!(r.seed, Some(5));
+        assert!(r.shuffle);
+    }
+
+    #[test]
+    fn test_filter_and_iters_flags() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let results = traced(12, &log).args(["--iters=2", "b1"]).run();
+        let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["b1", "b10", "b11"]);
+        assert!(results.iter().all(|r| r.samples.len() == 2));
+
+        let r = Runner::new().args(["--filter", "parse", "--iters", "x"]);
+        assert_eq!(r.filter.as_deref(), Some("parse"));
+        assert_eq!(r.iters, DEFAULT_ITERS);
+    }
+
+    #[test]
+    fn test_env_vars_then_flags() {
+        let vars = |key: &str| {
+            match key {
+                "BENCHMARK_ITERS" => Some(" 7 "),
+                "BENCHMARK_WARMUP_MS" => Some("250"),
+                "BENCHMARK_FILTER" => Some("parse"),
+                _ => None,
+            }
+            .map(str::to_string)
+        };
+        let r = Runner::new().env_with(vars);
+        assert_eq!(r.iters, 7);
+        assert_eq!(r.warmup_time, StdDuration::from_millis(250));
+        assert_eq!(r.filter.as_deref(), Some("parse"));
+
+        // Flags override the environment; bad or empty values are ignored.
+        let r = r.args(["--iters=3", "--warmup-ms", "5", "--filter=fmt"]);
+        assert_eq!(r.iters, 3);
+        assert_eq!(r.warmup_time, StdDuration::from_millis(5));
+        assert_eq!(r.filter.as_deref(), Some("fmt"));
+        let r = Runner::new().env_with(|key| (key == "BENCHMARK_ITERS").then(|| "many".into()));
+        assert_eq!(r.iters, DEFAULT_ITERS);
+        assert!(Runner::new()
+            .env_with(|_| Some(String::new()))
+            .filter
+            .is_none());
+    }
+
+    #[test]
+    fn test_tag_selection_and_flags() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut r = Runner::new().iterations(1);
+        for (i, tags) in [&["io"][..], &["cpu"], &["io", "slow"], &[]]
+            .iter()
+            .enumerate()
+        {
+            let log = Rc::clone(&log);
+            r = r.bench_tagged(format!("b{i}"), tags.iter().copied(), move || {
+                log.borrow_mut().push(i);
+            });
+        }
+        let results = r.args(["--tags", "io,cpu", "--skip-tags=slow"]).run();
+        assert_eq!(*log.borrow(), vec![0, 1]);
+        let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["b0", "b1"]);
+        assert_eq!(results[0].tags, ["io"]);
+
+        let summary = TagSummary::from_results(&results);
+        assert_eq!(summary.len(), 2);
+        assert_eq!((summary[0].tag.as_str(), summary[0].benchmarks), ("cpu", 1));
+        assert!(summary[1]
+            .to_string()
+            .starts_with("[io] 1 benchmarks, 1 iterations"));
+    }
+
+    #[test]
+    fn test_warmup_runs_before_sampling() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let results = traced(2, &log).args(["--warmup=3"]).run();
+        // Warmup runs precede each benchmark's own samples.
+        assert_eq!(*log.borrow(), vec![0, 0, 0, 0, 1, 1, 1, 1]);
+        assert_eq!(results[0].samples.len(), 1);
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let start = std::time::Instant::now();
+        traced(1, &log)
+            .warmup_time(StdDuration::from_millis(5))
+            .run();
+        assert!(start.elapsed() >= StdDuration::from_millis(5));
+        assert!(log.borrow().len() > 1);
+    }
+
+    #[test]
+    fn test_thermal_guard_reports_drift() {
+        let mut r = Runner::new()
+            .iterations(2)
+            .cooldown(StdDuration::from_millis(1))
+            .thermal_guard(10.0)
+            .bench("a", || {})
+            .bench("b", || {});
+        let results = r.run();
+        for res in &results {
+            let drift = res.reference_drift.unwrap();
+            assert!(drift > 0.0);
+            assert!(!res.throttled, "drift {drift}");
+        }
+        let plain = Runner::new().iterations(1).bench("c", || {}).run();
+        assert!(plain[0].reference_drift.is_none());
+    }
+
+    #[cfg(feature = "rss")]
+    #[test]
+    fn test_track_memory_reports_per_benchmark_usage() {
+        let results = Runner::new()
+            .iterations(2)
+            .track_memory(true)
+            .bench("grow", || {
+                std::hint::black_box(vec![1u8; 8 << 20]);
+            })
+            .run();
+        let memory = results[0].memory.unwrap();
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            assert!(memory.delta().is_some());
+            assert!(memory.peak_rss >= memory.rss_before, "{memory:?}");
+        }
+        let _ = memory;
+        let plain = Runner::new().iterations(1).bench("c", || {}).run();
+        assert!(plain[0].memory.is_none());
+    }
+
+    #[cfg(feature = "perf-events")]
+    #[test]
+    fn test_perf_counters_cover_the_measured_iterations() {
+        let results = Runner::new()
+            .iterations(3)
+            .perf_counters(true)
+            .bench("sum", || {
+                black_box((0..1_000u64).sum::<u64>());
+            })
+            .run();
+        // Counters are often unavailable in containers and VMs.
+        if let Some(perf) = results[0].perf {
+            assert_eq!(perf.iterations, 3);
+        }
+        let plain = Runner::new().iterations(1).bench("c", || {}).run();
+        assert!(plain[0].perf.is_none());
+    }
+
+    #[test]
+    fn test_normalized_results() {
+        let mut r = Runner::new()
+            .iterations(5)
+            .normalize(true)
+            .bench("spin", || {
+                black_box((0..1_000u64).fold(0u64, u64::wrapping_add));
+            });
+        let res = r.run();
+        let reference = r.last_reference().unwrap();
+        assert!(reference.start > Duration::ZERO && reference.end > Duration::ZERO);
+        assert!(reference.drift() > 0.0);
+        let expected = ratio(res[0].mean, reference.mean());
+        assert!((res[0].normalized_mean.unwrap() - expected).abs() < 1e-12);
+        assert!(res[0].normalized_median.unwrap() >= 0.0);
+
+        let mut plain = Runner::new().iterations(1).bench("c", || {});
+        assert!(plain.run()[0].normalized_mean.is_none());
+        assert!(plain.last_reference().is_none());
+    }
+
+    #[test]
+    fn test_result_stats() {
+        let r = BenchResult::from_samples(
+            "x".into(),
+            0,
+            vec![
+                Duration::from_nanos(30),
+                Duration::from_nanos(10),
+                Duration::from_nanos(20),
+            ],
+        );
+        assert_eq!(r.min.as_nanos(), 10);
+        assert_eq!(r.max.as_nanos(), 30);
+        assert_eq!(r.mean.as_nanos(), 20);
+        assert_eq!(r.median.as_nanos(), 20);
+        assert_eq!(r.total.as_nanos(), 60);
+        // (10 * 20 * 30)^(1/3) ≈ 18.2
+        assert_eq!(r.geometric_mean.as_nanos(), 18);
+        let even: Vec<Duration> = [40, 10, 30, 20].map(Duration::from_nanos).to_vec();
+        let r = BenchResult::from_samples("z".into(), 0, even.clone());
+        // Same nearest-rank rule as `Stats`: the lower middle of an even count.
+        assert_eq!(r.median.as_nanos(), 20);
+        #[cfg(feature = "collector")]
+        assert_eq!(
+            crate::Stats::from_durations(&even).unwrap().median,
+            r.median
+        );
+        let empty = BenchResult::from_samples("y".into(), 0, Vec::new());
+        assert_eq!(empty.geometric_mean, Duration::ZERO);
+    }
+}
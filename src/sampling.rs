@@ -0,0 +1,116 @@
+#![cfg(feature = "metrics")]
+//! 1-in-N sampling for hot metrics.
+//!
+//! A sampled metric only feeds its histogram (and rate, slowest and Apdex
+//! trackers) for roughly one record in `N`, chosen by a per-thread xorshift
+//! draw; snapshots scale the counts back up. Min and max stay exact: every
+//! record is compared against the current extremes with a relaxed load and
+//! only a new extreme costs an atomic update.
+
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::WatchStats;
+
+thread_local! {
+    // xorshift64 state; 0 until first use seeds it.
+    static RNG: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Sampling state of one metric.
+pub(crate) struct Sampler {
+    rate: u64,
+    min: AtomicU64,
+    max: AtomicU64,
+}
+
+impl Sampler {
+    /// Keep one record in `rate` (at least 2; 1 means no sampler at all).
+    pub(crate) fn new(rate: u64) -> Self {
+        Self {
+            rate: rate.max(2),
+            min: AtomicU64::new(u64::MAX),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    /// Track `value_ns` for the exact min/max and decide whether the record
+    /// is kept.
+    #[inline]
+    pub(crate) fn offer(&self, value_ns: u64) -> bool {
+        if value_ns < self.min.load(Ordering::Relaxed) {
+            self.min.fetch_min(value_ns, Ordering::Relaxed);
+        }
+        if value_ns > self.max.load(Ordering::Relaxed) {
+            self.max.fetch_max(value_ns, Ordering::Relaxed);
+        }
+        next_random() % self.rate == 0
+    }
+
+    /// Scale the sampled counts in `stats` up to estimated totals.
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn scale(&self, stats: &mut WatchStats) {
+        let n = self.rate;
+        stats.count = stats.count.saturating_mul(n);
+        stats.total_ns = stats.total_ns.saturating_mul(n);
+        stats.rate_1s *= n as f64;
+        stats.rate_1m *= n as f64;
+        stats.apdex_satisfied = stats.apdex_satisfied.saturating_mul(n);
+        stats.apdex_tolerating = stats.apdex_tolerating.saturating_mul(n);
+        stats.apdex_frustrated = stats.apdex_frustrated.saturating_mul(n);
+        stats.clamped_low = stats.clamped_low.saturating_mul(n);
+        stats.clamped_high = stats.clamped_high.saturating_mul(n);
+    }
+
+    /// Exact `(min, max)` over every offered record, if any.
+    pub(crate) fn extremes(&self) -> Option<(u64, u64)> {
+        let min = self.min.load(Ordering::Relaxed);
+        let max = self.max.load(Ordering::Relaxed);
+        (min <= max).then_some((min, max))
+    }
+}
+
+/// Next draw of this thread's xorshift64 generator.
+#[inline]
+fn next_random() -> u64 {
+    RNG.with(|state| {
+        let mut x = state.get();
+        if x == 0 {
+            let mut h = RandomState::new().build_hasher();
+            h.write_u64(0x9E37_79B9_7F4A_7C15);
+            // Avoid the all-zero xorshift state.
+            x = h.finish().max(1);
+        }
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        x
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_about_one_in_n_with_exact_extremes() {
+        let s = Sampler::new(10);
+        assert_eq!(s.extremes(), None);
+        let kept = (1..=100_000u64).filter(|&v| s.offer(v)).count();
+        assert!((8_000..12_000).contains(&kept), "kept {kept}");
+        assert_eq!(s.extremes(), Some((1, 100_000)));
+
+        let mut stats = WatchStats {
+            count: 3,
+            total_ns: 30,
+            rate_1s: 1.5,
+            ..WatchStats::EMPTY
+        };
+        s.scale(&mut stats);
+        assert_eq!((stats.count, stats.total_ns), (30, 300));
+        assert!((stats.rate_1s - 15.0).abs() < 1e-12);
+    }
+}
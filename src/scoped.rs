@@ -0,0 +1,181 @@
+#![cfg(all(feature = "std", feature = "metrics"))]
+//! Hierarchical (dotted) metric namespaces for `Watch`.
+//!
+//! Metric names are flat strings inside a `Watch`; by convention dots separate
+//! levels (`"http.get"`, `"db.users.insert"`). [`ScopedWatch`] prefixes names
+//! automatically so call sites don't concatenate strings by hand, and
+//! [`Watch::snapshot_tree`] regroups a snapshot into a nested [`MetricTree`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+use crate::hist_backend::HistBackend;
+use crate::timer::Timer;
+use crate::watch::{Watch, WatchGeneric, WatchStats};
+
+/// Separator between namespace levels.
+const SEP: char = '.';
+
+/// A `Watch` view that prefixes every metric name with a namespace.
+///
+/// Cheap to clone; records into the same underlying `Watch`.
+///
+/// # Examples
+/// ```
+/// use benchmark::Watch;
+/// let w = Watch::new();
+/// let http = w.scoped("http");
+/// http.record("get", 1_000);
+/// http.scoped("v2").record("post", 2_000);
+/// let snap = w.snapshot();
+/// assert_eq!(snap["http.get"].count, 1);
+/// assert_eq!(snap["http.v2.post"].count, 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScopedWatch {
+    watch: Watch,
+    prefix: String, // always ends with SEP
+}
+
+impl ScopedWatch {
+    /// Namespace prefix, without the trailing separator.
+    pub fn prefix(&self) -> &str {
+        &self.prefix[..self.prefix.len() - 1]
+    }
+
+    /// The underlying `Watch`.
+    pub fn watch(&self) -> &Watch {
+        &self.watch
+    }
+
+    /// Fully-qualified metric name for `name` within this scope.
+    pub fn full_name(&self, name: &str) -> String {
+        let mut s = String::with_capacity(self.prefix.len() + name.len());
+        s.push_str(&self.prefix);
+        s.push_str(name);
+        s
+    }
+
+    /// Create a nested scope (`"<prefix>.<name>"`).
+    #[must_use]
+    pub fn scoped(&self, name: &str) -> ScopedWatch {
+        self.watch.scoped(&self.full_name(name))
+    }
+
+    /// Record nanoseconds for `name` within this scope.
+    pub fn record(&self, name: &str, duration_ns: u64) {
+        self.watch.record(&self.full_name(name), duration_ns);
+    }
+
+    /// Record elapsed time since `start` for `name` within this scope.
+    pub fn record_instant(&self, name: &str, start: Instant) -> u64 {
+        self.watch.record_instant(&self.full_name(name), start)
+    }
+
+    /// Start a `Timer` recording into `name` within this scope.
+    pub fn timer(&self, name: &str) -> Timer {
+        Timer::new(self.watch.clone(), self.full_name(name))
+    }
+
+    /// Snapshot of metrics under this scope, keyed by name relative to it.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// w.record("db.query", 5);
+    /// w.record("http.get", 7);
+    /// let db = w.scoped("db").snapshot();
+    /// assert_eq!(db.len(), 1);
+    /// assert!(db.contains_key("query"));
+    /// ```
+    pub fn snapshot(&self) -> HashMap<String, WatchStats> {
+        self.watch
+            .snapshot()
+            .into_iter()
+            .filter_map(|(k, v)| k.strip_prefix(&self.prefix).map(|r| (r.to_string(), v)))
+            .collect()
+    }
+}
+
+impl Watch {
+    /// Create a namespaced view whose metric names are prefixed with `"<namespace>."`.
+    ///
+    /// Leading/trailing dots in `namespace` are ignored.
+    pub fn scoped(&self, namespace: &str) -> ScopedWatch {
+        let trimmed = namespace.trim_matches(SEP);
+        let mut prefix = String::with_capacity(trimmed.len() + 1);
+        prefix.push_str(trimmed);
+        prefix.push(SEP);
+        ScopedWatch {
+            watch: self.clone(),
+            prefix,
+        }
+    }
+}
+
+/// A node in a hierarchical view of a `Watch` snapshot.
+///
+/// `stats` holds the metric recorded at exactly this path (if any);
+/// `children` are keyed by the next name segment, in sorted order.
+#[derive(Debug, Clone, Default)]
+pub struct MetricTree {
+    /// Stats for the metric whose full name ends at this node.
+    pub stats: Option<WatchStats>,
+    /// Child nodes keyed by name segment.
+    pub children: BTreeMap<String, MetricTree>,
+}
+
+impl MetricTree {
+    /// Look up a node by dotted path (e.g. `"http.get"`).
+    pub fn get(&self, path: &str) -> Option<&MetricTree> {
+        path.split(SEP)
+            .filter(|s| !s.is_empty())
+            .try_fold(self, |node, seg| node.children.get(seg))
+    }
+
+    /// Total sample count of this node and all descendants.
+    pub fn total_count(&self) -> u64 {
+        self.stats.map_or(0, |s| s.count)
+            + self
+                .children
+                .values()
+                .map(MetricTree::total_count)
+                .sum::<u64>()
+    }
+
+    fn insert(&mut self, name: &str, stats: WatchStats) {
+        let node = name
+            .split(SEP)
+            .filter(|s| !s.is_empty())
+            .fold(self, |node, seg| {
+                node.children.entry(seg.to_string()).or_default()
+            });
+        node.stats = Some(stats);
+    }
+}
+
+impl<B: HistBackend> WatchGeneric<B> {
+    /// Snapshot all metrics grouped into a tree by their dotted names.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// w.record("http.get", 10);
+    /// w.record("http.post", 20);
+    /// w.record("db", 30);
+    /// let tree = w.snapshot_tree();
+    /// assert_eq!(tree.get("http").unwrap().children.len(), 2);
+    /// assert_eq!(tree.get("http").unwrap().total_count(), 2);
+    /// assert_eq!(tree.get("http.get").unwrap().stats.unwrap().count, 1);
+    /// assert!(tree.get("db").unwrap().stats.is_some());
+    /// ```
+    pub fn snapshot_tree(&self) -> MetricTree {
+        let mut root = MetricTree::default();
+        for (name, stats) in self.snapshot() {
+            root.insert(&name, stats);
+        }
+        root
+    }
+}
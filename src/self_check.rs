@@ -0,0 +1,158 @@
+#![cfg(feature = "std")]
+//! Startup self-check of the local monotonic clock.
+//!
+//! Virtualized or older hosts may expose a coarse `Instant` (microsecond or
+//! worse ticks) or an expensive one (syscall-backed). [`self_check`] probes
+//! both and returns a [`SelfCheckReport`] so applications can decide whether
+//! sub-microsecond measurements are meaningful on this machine.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+/// Consecutive `Instant::now()` pairs sampled.
+const SAMPLES: usize = 10_000;
+/// Upper bound on spins while waiting for the clock to tick once.
+const MAX_SPINS: usize = 1_000_000;
+/// A region must span this many clock "quanta" to be trusted.
+const TRUST_FACTOR: u64 = 10;
+
+/// Result of [`self_check`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelfCheckReport {
+    /// Smallest observed non-zero difference between two readings (ns).
+    pub resolution_ns: u64,
+    /// Mean cost of one `Instant::now()` call (ns).
+    pub overhead_ns: f64,
+    /// Fraction of back-to-back readings that returned the same value.
+    pub zero_delta_ratio: f64,
+    /// Readings that went backwards (should always be 0).
+    pub non_monotonic: u64,
+    /// Number of reading pairs sampled.
+    pub samples: usize,
+}
+
+impl SelfCheckReport {
+    /// Whether the clock never went backwards.
+    pub fn is_monotonic(&self) -> bool {
+        self.non_monotonic == 0
+    }
+
+    /// Smallest region length (ns) that can be timed with ~10% quantization
+    /// error, given the measured resolution and call overhead.
+    pub fn min_reliable_ns(&self) -> u64 {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let overhead = self.overhead_ns.ceil() as u64;
+        self.resolution_ns
+            .max(overhead)
+            .max(1)
+            .saturating_mul(TRUST_FACTOR)
+    }
+
+    /// Whether a measurement of `ns` nanoseconds can be trusted on this host.
+    ///
+    /// # Examples
+    /// ```
+    /// let report = benchmark::self_check();
+    /// if !report.trustworthy_for(500) {
+    ///     eprintln!("timer too coarse for sub-microsecond regions: {report:?}");
+    /// }
+    /// ```
+    pub fn trustworthy_for(&self, ns: u64) -> bool {
+        self.is_monotonic() && ns >= self.min_reliable_ns()
+    }
+}
+
+/// Probe the resolution, overhead and monotonicity of `Instant`.
+///
+/// Takes a few milliseconds; call it once at startup.
+///
+/// # Examples
+/// ```
+/// let report = benchmark::self_check();
+/// assert!(report.is_monotonic());
+/// assert!(report.resolution_ns > 0);
+/// ```
+pub fn self_check() -> SelfCheckReport {
+    // Overhead: mean cost of a call.
+    let start = Instant::now();
+    for _ in 0..SAMPLES {
+        black_box(Instant::now());
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let overhead_ns = start.elapsed().as_nanos() as f64 / SAMPLES as f64;
+
+    // Back-to-back pairs: zero deltas and ordering.
+    let mut zero = 0usize;
+    let mut non_monotonic = 0u64;
+    let mut prev = Instant::now();
+    for _ in 0..SAMPLES {
+        let now = Instant::now();
+        match now.checked_duration_since(prev) {
+            Some(d) if d.is_zero() => zero += 1,
+            Some(_) => {}
+            None => non_monotonic += 1,
+        }
+        prev = now;
+    }
+
+    // Resolution: smallest observed tick over a few waits for the next tick.
+    let mut resolution = u128::MAX;
+    for _ in 0..32 {
+        let a = Instant::now();
+        let mut spins = 0;
+        let b = loop {
+            let b = Instant::now();
+            if b > a || spins >= MAX_SPINS {
+                break b;
+            }
+            spins += 1;
+        };
+        let d = b.saturating_duration_since(a).as_nanos();
+        if d > 0 {
+            resolution = resolution.min(d);
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    SelfCheckReport {
+        resolution_ns: u64::try_from(resolution).unwrap_or(u64::MAX),
+        overhead_ns,
+        zero_delta_ratio: zero as f64 / SAMPLES as f64,
+        non_monotonic,
+        samples: SAMPLES,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_check_report() {
+        let r = self_check();
+        assert!(r.is_monotonic());
+        assert!(r.resolution_ns > 0 && r.resolution_ns < 1_000_000_000);
+        assert!(r.overhead_ns > 0.0);
+        assert!((0.0..=1.0).contains(&r.zero_delta_ratio));
+        assert!(r.trustworthy_for(u64::MAX / 2));
+        assert!(!r.trustworthy_for(0));
+    }
+
+    #[test]
+    fn test_min_reliable_uses_worst_quantum() {
+        let r = SelfCheckReport {
+            resolution_ns: 1_000,
+            overhead_ns: 25.2,
+            zero_delta_ratio: 0.9,
+            non_monotonic: 0,
+            samples: 1,
+        };
+        assert_eq!(r.min_reliable_ns(), 10_000);
+        assert!(r.trustworthy_for(10_000));
+        assert!(!SelfCheckReport {
+            non_monotonic: 1,
+            ..r
+        }
+        .trustworthy_for(u64::MAX));
+    }
+}
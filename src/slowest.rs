@@ -0,0 +1,225 @@
+#![cfg(feature = "metrics")]
+//! Per-metric tracking of the slowest recent samples.
+//!
+//! Each metric with tracking enabled keeps two top-K buffers, one for the
+//! current window (by Watch clock) and one for the previous, so a query
+//! covers the last one to two windows. A sample only takes the lock when it
+//! beats the current window's K-th slowest value, so the common case is two
+//! atomic loads.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// One slow sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlowSample {
+    /// Recorded value (ns).
+    pub value_ns: u64,
+    /// Wall-clock time of the record, in nanoseconds since the UNIX epoch.
+    pub timestamp_ns: u128,
+}
+
+/// A record that exceeded a slow-op threshold (see [`Watch::on_slow`]).
+///
+/// Displays as one logfmt line, e.g.
+/// `slow_op name="db.query" duration=700.00ms duration_ns=700000000 timestamp_ns=...`.
+///
+/// [`Watch::on_slow`]: crate::Watch::on_slow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowOp {
+    /// Metric name.
+    pub name: String,
+    /// Recorded value (ns, before clamping to the histogram bounds).
+    pub duration_ns: u64,
+    /// Wall-clock time of the record, in nanoseconds since the UNIX epoch.
+    pub timestamp_ns: u128,
+}
+
+impl fmt::Display for SlowOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "slow_op name={:?} duration={} duration_ns={} timestamp_ns={}",
+            self.name,
+            crate::Duration::from_nanos(u128::from(self.duration_ns)),
+            self.duration_ns,
+            self.timestamp_ns
+        )
+    }
+}
+
+struct Slot {
+    // Window index + 1 that `samples` belongs to (0 = unused).
+    epoch: AtomicU64,
+    // Smallest value in `samples` once it holds K entries, else 0.
+    floor: AtomicU64,
+    samples: Mutex<Vec<SlowSample>>,
+}
+
+/// Top-K slowest samples over the current and previous window.
+pub(crate) struct SlowestWindow {
+    k: usize,
+    window_ns: u64,
+    slots: [Slot; 2],
+}
+
+impl SlowestWindow {
+    pub(crate) fn new(k: usize, window_ns: u64) -> Self {
+        let slot = || Slot {
+            epoch: AtomicU64::new(0),
+            floor: AtomicU64::new(0),
+            samples: Mutex::new(Vec::with_capacity(k)),
+        };
+        Self {
+            k: k.max(1),
+            window_ns: window_ns.max(1),
+            slots: [slot(), slot()],
+        }
+    }
+
+    /// Consider `value_ns`, recorded at Watch clock reading `now_ns`.
+    #[inline]
+    pub(crate) fn offer(&self, value_ns: u64, now_ns: u64) {
+        let tag = now_ns / self.window_ns + 1;
+        let slot = &self.slots[usize::from(tag % 2 == 1)];
+        if slot.epoch.load(Ordering::Relaxed) == tag
+            && value_ns <= slot.floor.load(Ordering::Relaxed)
+        {
+            return;
+        }
+        self.insert(slot, tag, value_ns);
+    }
+
+    #[cold]
+    fn insert(&self, slot: &Slot, tag: u64, value_ns: u64) {
+        let mut samples = slot
+            .samples
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if slot.epoch.load(Ordering::Relaxed) != tag {
+            samples.clear();
+            slot.floor.store(0, Ordering::Relaxed);
+            slot.epoch.store(tag, Ordering::Relaxed);
+        }
+        if samples.len() >= self.k {
+            let Some((i, min)) = samples
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, s)| s.value_ns)
+                .map(|(i, s)| (i, s.value_ns))
+            else {
+                return;
+            };
+            if value_ns <= min {
+                return;
+            }
+            samples.swap_remove(i);
+        }
+        samples.push(SlowSample {
+            value_ns,
+            timestamp_ns: crate::watch::now_epoch_nanos(),
+        });
+        if samples.len() >= self.k {
+            let floor = samples.iter().map(|s| s.value_ns).min().unwrap_or(0);
+            slot.floor.store(floor, Ordering::Relaxed);
+        }
+    }
+
+    /// Up to K slowest samples of the current and previous window, slowest first.
+    pub(crate) fn collect(&self, now_ns: u64) -> Vec<SlowSample> {
+        let current = now_ns / self.window_ns + 1;
+        let mut out: Vec<SlowSample> = Vec::with_capacity(self.k * 2);
+        for slot in &self.slots {
+            let samples = slot
+                .samples
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            let epoch = slot.epoch.load(Ordering::Relaxed);
+            if epoch != 0 && (epoch == current || epoch + 1 == current) {
+                out.extend_from_slice(&samples);
+            }
+        }
+        out.sort_unstable_by(|a, b| {
+            b.value_ns
+                .cmp(&a.value_ns)
+                .then(b.timestamp_ns.cmp(&a.timestamp_ns))
+        });
+        out.truncate(self.k);
+        out
+    }
+}
+
+/// Render `(metric, samples)` pairs as a JSON object, keys in the given order.
+pub(crate) fn to_json<'a, I>(metrics: I) -> String
+where
+    I: IntoIterator<Item = (&'a str, &'a [SlowSample])>,
+{
+    use std::fmt::Write as _;
+    let mut out = String::from("{");
+    for (i, (name, samples)) in metrics.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push('"');
+        for ch in name.chars() {
+            match ch {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                c if u32::from(c) < 0x20 => {
+                    let _ = write!(out, "\\u{:04x}", u32::from(c));
+                }
+                c => out.push(c),
+            }
+        }
+        out.push_str("\":[");
+        for (j, s) in samples.iter().enumerate() {
+            if j > 0 {
+                out.push(',');
+            }
+            let _ = write!(
+                out,
+                "{{\"value_ns\":{},\"timestamp_ns\":{}}}",
+                s.value_ns, s.timestamp_ns
+            );
+        }
+        out.push(']');
+    }
+    out.push('}');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_k_slowest_across_two_windows() {
+        let w = SlowestWindow::new(3, 100);
+        for (v, t) in [(5, 0), (9, 10), (1, 20), (7, 30), (8, 40)] {
+            w.offer(v, t);
+        }
+        let vals = |s: Vec<SlowSample>| s.iter().map(|s| s.value_ns).collect::<Vec<_>>();
+        assert_eq!(vals(w.collect(50)), [9, 8, 7]);
+
+        // Next window: the previous one still counts.
+        w.offer(6, 150);
+        assert_eq!(vals(w.collect(150)), [9, 8, 7]);
+        w.offer(20, 160);
+        assert_eq!(vals(w.collect(160)), [20, 9, 8]);
+
+        // Two windows later only window 1 remains.
+        assert_eq!(vals(w.collect(250)), [20, 6]);
+        assert!(w.collect(1_000).is_empty());
+    }
+
+    #[test]
+    fn test_json_escaping() {
+        let s = [SlowSample {
+            value_ns: 7,
+            timestamp_ns: 1,
+        }];
+        let json = to_json([("a\"b", &s[..]), ("c", &[][..])]);
+        assert_eq!(json, r#"{"a\"b":[{"value_ns":7,"timestamp_ns":1}],"c":[]}"#);
+    }
+}
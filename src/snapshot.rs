@@ -0,0 +1,479 @@
+#![cfg(feature = "std")]
+//! Ordered, queryable `Watch` snapshots.
+//!
+//! [`Snapshot`] holds the same per-metric [`WatchStats`] as
+//! `Watch::snapshot()`, but in a deterministic order (by name unless
+//! re-sorted) with helpers for the usual reporting chores: prefix filtering
+//! and "top N by p99" rankings. [`FrozenSnapshot`] is the read-only input
+//! handed to exporters on other threads.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::ops::Index;
+use std::sync::Arc;
+
+#[cfg(feature = "metrics")]
+use crate::histogram::HistogramSnapshot;
+use crate::WatchStats;
+
+/// Per-metric stats in a stable order.
+///
+/// # Examples
+/// ```
+/// use benchmark::Watch;
+///
+/// let w = Watch::new();
+/// w.record("db.query", 40_000);
+/// w.record("http.get", 900_000);
+/// w.record("http.post", 2_000_000);
+///
+/// let snap = w.snapshot_sorted();
+/// let names: Vec<&str> = snap.names().collect();
+/// # #[cfg(feature = "metrics")]
+/// assert_eq!(names, ["db.query", "http.get", "http.post"]);
+///
+/// let http = snap.with_prefix("http.");
+/// # #[cfg(feature = "metrics")]
+/// assert_eq!(http.len(), 2);
+/// for (name, stats) in snap.top_n_by(2, |s| s.p99) {
+///     println!("{name}: p99 {}ns", stats.p99);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    entries: Vec<(String, WatchStats)>,
+}
+
+impl Snapshot {
+    /// Field names yielded by [`fields`](Self::fields), in order; they match
+    /// the [`WatchStats`] field names.
+    pub const FIELD_NAMES: [&'static str; 23] = [
+        "count",
+        "min",
+        "max",
+        "p50",
+        "p90",
+        "p95",
+        "p99",
+        "p999",
+        "mean",
+        "total_ns",
+        "stddev",
+        "rate_1s",
+        "rate_1m",
+        "first_seen_ns",
+        "last_seen_ns",
+        "apdex_target_ns",
+        "apdex_satisfied",
+        "apdex_tolerating",
+        "apdex_frustrated",
+        "apdex",
+        "under_target_pct",
+        "clamped_low",
+        "clamped_high",
+    ];
+
+    /// Create an empty snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of metrics.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// True if the snapshot holds no metrics.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Stats for `name`, if present.
+    pub fn get(&self, name: &str) -> Option<&WatchStats> {
+        self.entries.iter().find(|(n, _)| n == name).map(|(_, s)| s)
+    }
+
+    /// True if `name` is present.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// `(name, stats)` pairs in the current order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &WatchStats)> + '_ {
+        self.entries.iter().map(|(n, s)| (n.as_str(), s))
+    }
+
+    /// Metric names in the current order.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.iter().map(|(n, _)| n.as_str())
+    }
+
+    /// The metrics whose names start with `prefix`, in the current order.
+    #[must_use]
+    pub fn with_prefix(&self, prefix: &str) -> Self {
+        self.filter(|name, _| name.starts_with(prefix))
+    }
+
+    /// The metrics for which `keep(name, stats)` is true, in the current order.
+    #[must_use]
+    pub fn filter(&self, mut keep: impl FnMut(&str, &WatchStats) -> bool) -> Self {
+        Self {
+            entries: self
+                .entries
+                .iter()
+                .filter(|(n, s)| keep(n, s))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Reorder the metrics with `compare`; the sort is stable.
+    pub fn sort_by(&mut self, mut compare: impl FnMut(&WatchStats, &WatchStats) -> Ordering) {
+        self.entries.sort_by(|a, b| compare(&a.1, &b.1));
+    }
+
+    /// Reorder the metrics by name.
+    pub fn sort_by_name(&mut self) {
+        self.entries.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    /// The `n` metrics with the largest `key`, largest first; ties keep the
+    /// current order. Keys that do not compare (NaN) rank last.
+    pub fn top_n_by<K, F>(&self, n: usize, mut key: F) -> Vec<(&str, &WatchStats)>
+    where
+        K: PartialOrd,
+        F: FnMut(&WatchStats) -> K,
+    {
+        let mut ranked: Vec<(K, &str, &WatchStats)> = self
+            .entries
+            .iter()
+            .map(|(name, s)| (key(s), name.as_str(), s))
+            .collect();
+        ranked.sort_by(|a, b| match b.0.partial_cmp(&a.0) {
+            Some(ord) => ord,
+            // Push incomparable keys behind comparable ones.
+            None => a.0.partial_cmp(&a.0).cmp(&b.0.partial_cmp(&b.0)).reverse(),
+        });
+        ranked.truncate(n);
+        ranked.into_iter().map(|(_, n, s)| (n, s)).collect()
+    }
+
+    /// Flattened `(metric, field, value)` rows: every [`WatchStats`] field
+    /// of every metric, metrics in the current order and fields in
+    /// [`FIELD_NAMES`](Self::FIELD_NAMES) order.
+    ///
+    /// One generic loop over this iterator is enough to write CSV, line
+    /// protocol or any other row-based export without serde. Integer fields
+    /// are converted to `f64`, which is exact up to 2^53 (the UNIX-epoch
+    /// timestamps lose sub-microsecond precision).
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    ///
+    /// let w = Watch::new();
+    /// w.record("db.query", 40_000);
+    /// let mut csv = String::from("metric,field,value\n");
+    /// for (metric, field, value) in w.snapshot_sorted().fields() {
+    ///     csv.push_str(&format!("{metric},{field},{value}\n"));
+    /// }
+    /// # #[cfg(feature = "metrics")]
+    /// assert!(csv.contains("db.query,p99,40"));
+    /// ```
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &'static str, f64)> + '_ {
+        self.entries.iter().flat_map(|(name, s)| {
+            Self::FIELD_NAMES
+                .into_iter()
+                .zip(field_values(s))
+                .map(move |(field, value)| (name.as_str(), field, value))
+        })
+    }
+
+    /// How each metric moved since `earlier`: count and percentile shifts,
+    /// plus the metrics added and removed.
+    ///
+    /// The result displays as one line per changed metric, for before/after
+    /// checks around a deploy or an optimisation.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    ///
+    /// let w = Watch::new();
+    /// w.record("api.get", 1_200_000);
+    /// let before = w.snapshot_sorted();
+    /// for _ in 0..99 {
+    ///     w.record("api.get", 3_400_000);
+    /// }
+    /// let diff = w.snapshot_sorted().diff(&before);
+    /// println!("{diff}"); // api.get: count 1 → 100, p50 1.20ms → 3.40ms (+183%), ...
+    /// # #[cfg(feature = "metrics")]
+    /// assert!(diff.get("api.get").unwrap().p99.relative().unwrap() > 1.0);
+    /// ```
+    pub fn diff(&self, earlier: &Snapshot) -> crate::SnapshotDiff {
+        crate::SnapshotDiff::between(earlier, self)
+    }
+
+    /// Convert into the `HashMap` shape returned by `Watch::snapshot()`.
+    pub fn into_map(self) -> HashMap<String, WatchStats> {
+        self.entries.into_iter().collect()
+    }
+}
+
+/// The values of `s` in [`Snapshot::FIELD_NAMES`] order.
+#[allow(clippy::cast_precision_loss)]
+fn field_values(s: &WatchStats) -> [f64; 23] {
+    [
+        s.count as f64,
+        s.min as f64,
+        s.max as f64,
+        s.p50 as f64,
+        s.p90 as f64,
+        s.p95 as f64,
+        s.p99 as f64,
+        s.p999 as f64,
+        s.mean,
+        s.total_ns as f64,
+        s.stddev,
+        s.rate_1s,
+        s.rate_1m,
+        s.first_seen_ns as f64,
+        s.last_seen_ns as f64,
+        s.apdex_target_ns as f64,
+        s.apdex_satisfied as f64,
+        s.apdex_tolerating as f64,
+        s.apdex_frustrated as f64,
+        s.apdex,
+        s.under_target_pct,
+        s.clamped_low as f64,
+        s.clamped_high as f64,
+    ]
+}
+
+/// Sorted by name.
+impl<S: BuildHasher> From<HashMap<String, WatchStats, S>> for Snapshot {
+    fn from(map: HashMap<String, WatchStats, S>) -> Self {
+        let mut snap = Self {
+            entries: map.into_iter().collect(),
+        };
+        snap.sort_by_name();
+        snap
+    }
+}
+
+/// Panics if `name` is not present, like `HashMap` indexing.
+impl Index<&str> for Snapshot {
+    type Output = WatchStats;
+
+    fn index(&self, name: &str) -> &WatchStats {
+        self.get(name)
+            .unwrap_or_else(|| panic!("no metric named {name:?} in snapshot"))
+    }
+}
+
+impl IntoIterator for Snapshot {
+    type Item = (String, WatchStats);
+    type IntoIter = std::vec::IntoIter<(String, WatchStats)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Snapshot {
+    type Item = (&'a str, &'a WatchStats);
+    type IntoIter = std::iter::Map<
+        std::slice::Iter<'a, (String, WatchStats)>,
+        fn(&'a (String, WatchStats)) -> (&'a str, &'a WatchStats),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter().map(|(n, s)| (n.as_str(), s))
+    }
+}
+
+/// An immutable capture of a `Watch`: stats and histograms of every metric
+/// plus the capture time, from `Watch::freeze()`.
+///
+/// Cheap to clone and `Send + Sync`, with no way back to the Watch, so
+/// exporters on other threads can read it without being able to record,
+/// reset or reconfigure anything. It implements [`StatsSource`](crate::StatsSource)
+/// for [`compare`](crate::compare) and feeds [`Table`](crate::report::Table)
+/// and [`HtmlReport`](crate::report::html::HtmlReport).
+///
+/// # Examples
+/// ```
+/// use benchmark::Watch;
+///
+/// let w = Watch::new();
+/// w.record("db.query", 40_000);
+/// let frozen = w.freeze();
+/// w.record("db.query", 90_000); // not part of the capture
+///
+/// let exporter = std::thread::spawn(move || {
+///     frozen.stats().iter().map(|(name, s)| format!("{name} {}", s.count)).collect::<Vec<_>>()
+/// });
+/// # #[cfg(feature = "metrics")]
+/// assert_eq!(exporter.join().unwrap(), ["db.query 1"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FrozenSnapshot {
+    inner: Arc<Frozen>,
+}
+
+#[derive(Debug, Default)]
+struct Frozen {
+    captured_at_ns: u128,
+    stats: Snapshot,
+    #[cfg(feature = "metrics")]
+    histograms: Vec<(String, HistogramSnapshot)>,
+}
+
+impl FrozenSnapshot {
+    #[cfg(feature = "metrics")]
+    pub(crate) fn new(
+        captured_at_ns: u128,
+        stats: Snapshot,
+        histograms: Vec<(String, HistogramSnapshot)>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Frozen {
+                captured_at_ns,
+                stats,
+                histograms,
+            }),
+        }
+    }
+
+    /// Wall-clock capture time (ns since the UNIX epoch; 0 if empty).
+    pub fn captured_at_ns(&self) -> u128 {
+        self.inner.captured_at_ns
+    }
+
+    /// Per-metric stats, sorted by name.
+    pub fn stats(&self) -> &Snapshot {
+        &self.inner.stats
+    }
+
+    /// Stats for `name`, if present.
+    pub fn get(&self, name: &str) -> Option<&WatchStats> {
+        self.inner.stats.get(name)
+    }
+
+    /// Number of metrics.
+    pub fn len(&self) -> usize {
+        self.inner.stats.len()
+    }
+
+    /// True if the capture holds no metrics.
+    pub fn is_empty(&self) -> bool {
+        self.inner.stats.is_empty()
+    }
+
+    /// Histogram of `name`, if present.
+    #[cfg(feature = "metrics")]
+    pub fn histogram(&self, name: &str) -> Option<&HistogramSnapshot> {
+        self.inner
+            .histograms
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, h)| h)
+    }
+
+    /// `(name, histogram)` pairs, sorted by name.
+    #[cfg(feature = "metrics")]
+    pub fn histograms(&self) -> impl Iterator<Item = (&str, &HistogramSnapshot)> + '_ {
+        self.inner.histograms.iter().map(|(n, h)| (n.as_str(), h))
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl crate::StatsSource for FrozenSnapshot {
+    fn summaries(&self) -> Vec<(String, crate::MetricSummary)> {
+        self.stats()
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.into()))
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use crate::Watch;
+
+    #[test]
+    fn test_order_filter_and_ranking() {
+        let w = Watch::new();
+        for (name, v) in [("b.x", 300), ("a", 100), ("b.y", 900), ("c", 500)] {
+            w.record(name, v);
+        }
+        let mut snap = w.snapshot_sorted();
+        assert_eq!(snap.names().collect::<Vec<_>>(), ["a", "b.x", "b.y", "c"]);
+        assert_eq!(snap.with_prefix("b.").len(), 2);
+        assert_eq!(snap["c"].count, 1);
+        assert!(!snap.contains("missing"));
+
+        let top: Vec<&str> = snap
+            .top_n_by(2, |s| s.max)
+            .into_iter()
+            .map(|e| e.0)
+            .collect();
+        assert_eq!(top, ["b.y", "c"]);
+        let by_mean: Vec<&str> = snap.top_n_by(9, |s| s.mean).iter().map(|e| e.0).collect();
+        assert_eq!(by_mean, ["b.y", "c", "b.x", "a"]);
+
+        snap.sort_by(|a, b| b.max.cmp(&a.max));
+        assert_eq!((&snap).into_iter().next().map(|e| e.0), Some("b.y"));
+        assert_eq!(snap.clone().into_map().len(), 4);
+    }
+
+    #[test]
+    fn test_frozen_is_a_consistent_read_only_capture() {
+        let w = Watch::new();
+        w.record("b", 200);
+        w.record("a", 100);
+        let frozen = w.freeze();
+        w.record("a", 300);
+        w.clear();
+
+        assert_eq!(frozen.len(), 2);
+        assert_eq!(frozen.stats().names().collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(frozen.get("a").map(|s| s.count), Some(1));
+        assert_eq!(frozen.histogram("a").unwrap().count(), 1);
+        assert_eq!(frozen.histograms().count(), 2);
+        assert!(frozen.captured_at_ns() > 0 || cfg!(miri));
+
+        let names: Vec<String> = crate::StatsSource::summaries(&frozen)
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_fields_flatten_every_stat_in_order() {
+        let w = Watch::new();
+        w.record("b", 200);
+        w.record("a", 100);
+        let snap = w.snapshot_sorted();
+        let rows: Vec<_> = snap.fields().collect();
+        assert_eq!(rows.len(), 2 * super::Snapshot::FIELD_NAMES.len());
+        assert_eq!(rows[0], ("a", "count", 1.0));
+        assert_eq!(rows[1], ("a", "min", 100.0));
+        assert!(rows.contains(&("b", "p99", 200.0)));
+        assert!(rows.contains(&("b", "total_ns", 200.0)));
+        assert_eq!(rows[super::Snapshot::FIELD_NAMES.len()].0, "b");
+    }
+
+    #[test]
+    fn test_nan_keys_rank_last() {
+        let w = Watch::new();
+        w.record("a", 1);
+        w.record("b", 2);
+        let snap = w.snapshot_sorted();
+        let ranked = snap.top_n_by(2, |s| if s.max == 2 { f64::NAN } else { 1.0 });
+        assert_eq!(ranked[0].0, "a");
+        assert_eq!(ranked[1].0, "b");
+    }
+}
@@ -0,0 +1,263 @@
+#![cfg(feature = "std")]
+//! Human-readable differences between two [`Snapshot`]s.
+//!
+//! [`Snapshot::diff`] pairs metrics by name and reports how their count and
+//! percentiles moved, e.g. to check a canary or an optimisation in an
+//! integration test. [`SnapshotDiff`] displays as one line per changed
+//! metric: `api.get: p99 1.20ms → 3.40ms (+183%)`.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{Duration, Snapshot, WatchStats};
+
+/// A before/after pair of one latency statistic (ns).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shift {
+    /// Value in the earlier snapshot.
+    pub before: u64,
+    /// Value in the later snapshot.
+    pub after: u64,
+}
+
+impl Shift {
+    /// True if the value moved.
+    pub fn changed(&self) -> bool {
+        self.before != self.after
+    }
+
+    /// Relative change (`0.25` = 25% higher); `None` if `before` is 0.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn relative(&self) -> Option<f64> {
+        (self.before != 0).then(|| (self.after as f64 - self.before as f64) / self.before as f64)
+    }
+}
+
+impl fmt::Display for Shift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} → {}",
+            Duration::from_nanos(u128::from(self.before)),
+            Duration::from_nanos(u128::from(self.after))
+        )?;
+        match self.relative() {
+            Some(r) => write!(f, " ({:+.0}%)", r * 100.0),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Changes for one metric present in both snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricDiff {
+    /// Metric name.
+    pub name: String,
+    /// Sample counts `(before, after)`.
+    pub count: (u64, u64),
+    /// Median.
+    pub p50: Shift,
+    /// 90th percentile.
+    pub p90: Shift,
+    /// 99th percentile.
+    pub p99: Shift,
+    /// 99.9th percentile.
+    pub p999: Shift,
+    /// Maximum.
+    pub max: Shift,
+}
+
+impl MetricDiff {
+    fn new(name: &str, before: &WatchStats, after: &WatchStats) -> Self {
+        let shift = |f: fn(&WatchStats) -> u64| Shift {
+            before: f(before),
+            after: f(after),
+        };
+        Self {
+            name: name.to_string(),
+            count: (before.count, after.count),
+            p50: shift(|s| s.p50),
+            p90: shift(|s| s.p90),
+            p99: shift(|s| s.p99),
+            p999: shift(|s| s.p999),
+            max: shift(|s| s.max),
+        }
+    }
+
+    /// The latency statistics with their labels, in display order.
+    pub fn shifts(&self) -> [(&'static str, Shift); 5] {
+        [
+            ("p50", self.p50),
+            ("p90", self.p90),
+            ("p99", self.p99),
+            ("p999", self.p999),
+            ("max", self.max),
+        ]
+    }
+
+    /// True if the count or any percentile moved.
+    pub fn changed(&self) -> bool {
+        self.count.0 != self.count.1 || self.shifts().iter().any(|(_, s)| s.changed())
+    }
+}
+
+/// `name: count 10 → 25, p99 1.20ms → 3.40ms (+183%)`, listing only what
+/// moved (or `name: unchanged`).
+impl fmt::Display for MetricDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.name)?;
+        if !self.changed() {
+            return f.write_str(" unchanged");
+        }
+        let mut sep = " ";
+        if self.count.0 != self.count.1 {
+            write!(f, "{sep}count {} → {}", self.count.0, self.count.1)?;
+            sep = ", ";
+        }
+        for (label, shift) in self.shifts() {
+            if shift.changed() {
+                write!(f, "{sep}{label} {shift}")?;
+                sep = ", ";
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Per-metric changes between two snapshots, from [`Snapshot::diff`].
+///
+/// Metrics are sorted by name. `Display` prints one line per changed metric,
+/// then one per added (`+ name`) and removed (`- name`) metric; metrics that
+/// did not move are left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// Metrics present in both snapshots.
+    pub metrics: Vec<MetricDiff>,
+    /// Metrics only in the later snapshot.
+    pub added: Vec<String>,
+    /// Metrics only in the earlier snapshot.
+    pub removed: Vec<String>,
+}
+
+impl SnapshotDiff {
+    /// Compare `earlier` against `later`.
+    pub(crate) fn between(earlier: &Snapshot, later: &Snapshot) -> Self {
+        let before: HashMap<&str, &WatchStats> = earlier.iter().collect();
+        let mut diff = Self::default();
+        for (name, after) in later {
+            match before.get(name) {
+                Some(b) => diff.metrics.push(MetricDiff::new(name, b, after)),
+                None => diff.added.push(name.to_string()),
+            }
+        }
+        diff.removed = earlier
+            .names()
+            .filter(|name| !later.contains(name))
+            .map(str::to_string)
+            .collect();
+        diff.metrics.sort_by(|a, b| a.name.cmp(&b.name));
+        diff.added.sort();
+        diff.removed.sort();
+        diff
+    }
+
+    /// The changes of `name`, if it is in both snapshots.
+    pub fn get(&self, name: &str) -> Option<&MetricDiff> {
+        self.metrics.iter().find(|m| m.name == name)
+    }
+
+    /// True if no metric moved, appeared or disappeared.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && !self.metrics.iter().any(MetricDiff::changed)
+    }
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for m in self.metrics.iter().filter(|m| m.changed()) {
+            writeln!(f, "{m}")?;
+        }
+        for name in &self.added {
+            writeln!(f, "+ {name}")?;
+        }
+        for name in &self.removed {
+            writeln!(f, "- {name}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+
+    fn stats(count: u64, p99: u64) -> WatchStats {
+        WatchStats {
+            count,
+            p50: 1_000,
+            p90: 1_000,
+            p99,
+            p999: p99,
+            max: p99,
+            ..WatchStats::EMPTY
+        }
+    }
+
+    fn snapshot(entries: &[(&str, WatchStats)]) -> Snapshot {
+        entries
+            .iter()
+            .map(|(n, s)| ((*n).to_string(), *s))
+            .collect::<HashMap<_, _>>()
+            .into()
+    }
+
+    #[test]
+    fn test_diff_reports_moves_additions_and_removals() {
+        let before = snapshot(&[
+            ("api.get", stats(10, 1_200_000)),
+            ("idle", stats(5, 2_000)),
+            ("old", stats(1, 1)),
+        ]);
+        let after = snapshot(&[
+            ("api.get", stats(25, 3_400_000)),
+            ("idle", stats(5, 2_000)),
+            ("new", stats(1, 1)),
+        ]);
+        let diff = after.diff(&before);
+        let get = diff.get("api.get").unwrap();
+        assert_eq!(get.count, (10, 25));
+        assert!((get.p99.relative().unwrap() - 1.833).abs() < 1e-3);
+        assert!(!diff.get("idle").unwrap().changed());
+        assert_eq!(
+            (diff.added.as_slice(), diff.removed.as_slice()),
+            (&["new".to_string()][..], &["old".to_string()][..])
+        );
+
+        let text = diff.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "api.get: count 10 → 25, p99 1.20ms → 3.40ms (+183%), \
+                 p999 1.20ms → 3.40ms (+183%), max 1.20ms → 3.40ms (+183%)",
+                "+ new",
+                "- old",
+            ]
+        );
+        assert!(!diff.is_empty());
+        assert!(after.diff(&after).is_empty());
+        assert_eq!(diff.get("idle").unwrap().to_string(), "idle: unchanged");
+    }
+
+    #[test]
+    fn test_shift_from_zero_has_no_percentage() {
+        let s = Shift {
+            before: 0,
+            after: 500,
+        };
+        assert_eq!(s.relative(), None);
+        assert_eq!(s.to_string(), "0ns → 500ns");
+    }
+}
@@ -0,0 +1,457 @@
+#![cfg(all(feature = "std", feature = "statsd"))]
+//! `StatsD` / `DogStatsD` UDP emitter for `Watch` metrics.
+//!
+//! Two modes are supported:
+//! - **Snapshot flushing**: periodically convert a `Watch` snapshot into
+//!   counter (`|c`) and gauge (`|g`) packets, either manually via
+//!   [`StatsdEmitter::flush`] or from a background thread via
+//!   [`StatsdEmitter::spawn`].
+//! - **Streaming**: send individual timings (`|ms`) as they happen via
+//!   [`StatsdEmitter::timing`], with optional client-side sampling.
+//!
+//! Lines are batched into datagrams no larger than the configured packet size;
+//! a line that cannot fit in one is dropped rather than sent oversized.
+//! Send errors never panic; UDP delivery is best-effort by design.
+
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::Duration as StdDuration;
+
+use crate::watch::{Watch, WatchStats};
+
+/// Default maximum datagram payload (fits a 1500 byte Ethernet MTU).
+const DEFAULT_MAX_PACKET: usize = 1432;
+
+/// Builder for a [`StatsdEmitter`].
+///
+/// # Examples
+/// ```no_run
+/// use benchmark::StatsdEmitter;
+/// let emitter = StatsdEmitter::builder("127.0.0.1:8125")
+///     .prefix("myapp.")
+///     .sample_rate(0.1)
+///     .tag("env:prod")
+///     .build()
+///     .expect("statsd socket");
+/// emitter.timing("db.query", 1_500_000);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StatsdBuilder {
+    addr: String,
+    prefix: String,
+    sample_rate: f64,
+    tags: Vec<String>,
+    max_packet: usize,
+}
+
+impl StatsdBuilder {
+    /// Set a prefix prepended to every metric name (e.g. `"myapp."`).
+    #[must_use]
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Set the sampling rate for streamed timings, clamped to `(0.0, 1.0]`.
+    ///
+    /// Sampled packets carry the `|@rate` suffix so the server scales counts.
+    #[must_use]
+    pub fn sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = if rate.is_finite() && rate > 0.0 {
+            rate.min(1.0)
+        } else {
+            1.0
+        };
+        self
+    }
+
+    /// Add a `DogStatsD` tag (e.g. `"env:prod"`) attached to every line.
+    #[must_use]
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Set the maximum datagram payload in bytes (min 64).
+    #[must_use]
+    pub fn max_packet_size(mut self, bytes: usize) -> Self {
+        self.max_packet = bytes.max(64);
+        self
+    }
+
+    /// Bind a local UDP socket and connect it to the configured server.
+    ///
+    /// # Errors
+    /// Returns an error if the address cannot be resolved or the socket
+    /// cannot be bound/connected.
+    pub fn build(self) -> io::Result<StatsdEmitter> {
+        let addr =
+            self.addr.to_socket_addrs()?.next().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "no address resolved")
+            })?;
+        let bind = if addr.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind)?;
+        socket.connect(addr)?;
+
+        let tags = if self.tags.is_empty() {
+            String::new()
+        } else {
+            format!("|#{}", self.tags.join(","))
+        };
+
+        Ok(StatsdEmitter {
+            socket,
+            prefix: self.prefix,
+            sample_rate: self.sample_rate,
+            tags,
+            max_packet: self.max_packet,
+            rng: AtomicU64::new(seed()),
+            last_counts: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+/// UDP emitter speaking the `StatsD` line protocol (with `DogStatsD` tags).
+///
+/// Cheap to share behind an `Arc`; all methods take `&self`.
+pub struct StatsdEmitter {
+    socket: UdpSocket,
+    prefix: String,
+    sample_rate: f64,
+    tags: String,
+    max_packet: usize,
+    rng: AtomicU64,
+    // Cumulative counts seen at the previous flush, used to emit counter deltas.
+    last_counts: Mutex<HashMap<String, u64>>,
+}
+
+impl fmt::Debug for StatsdEmitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatsdEmitter")
+            .field("peer", &self.socket.peer_addr().ok())
+            .field("prefix", &self.prefix)
+            .field("sample_rate", &self.sample_rate)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StatsdEmitter {
+    /// Start building an emitter targeting `addr` (e.g. `"127.0.0.1:8125"`).
+    pub fn builder(addr: impl Into<String>) -> StatsdBuilder {
+        StatsdBuilder {
+            addr: addr.into(),
+            prefix: String::new(),
+            sample_rate: 1.0,
+            tags: Vec::new(),
+            max_packet: DEFAULT_MAX_PACKET,
+        }
+    }
+
+    /// Stream a single timing sample (nanoseconds) as a `|ms` packet.
+    ///
+    /// Subject to the configured sample rate; skipped samples cost one
+    /// atomic update. Send errors are ignored, and a line longer than the
+    /// maximum packet size is dropped.
+    pub fn timing(&self, name: &str, duration_ns: u64) {
+        if self.sample_rate < 1.0 && !self.sampled() {
+            return;
+        }
+        let mut line = String::with_capacity(self.prefix.len() + name.len() + 32);
+        let _ = write!(line, "{}{}:{}|ms", self.prefix, name, ns_to_ms(duration_ns));
+        if self.sample_rate < 1.0 {
+            let _ = write!(line, "|@{}", self.sample_rate);
+        }
+        line.push_str(&self.tags);
+        if line.len() <= self.max_packet {
+            let _ = self.socket.send(line.as_bytes());
+        }
+    }
+
+    /// Flush a `Watch` snapshot as counter deltas and percentile gauges.
+    ///
+    /// For every metric emits `<name>.count` as a counter (delta since the
+    /// previous flush) and `min`, `max`, `mean`, `p50`, `p90`, `p95`, `p99`,
+    /// `p999` as gauges in milliseconds.
+    ///
+    /// # Errors
+    /// Returns the first socket send error encountered, or an
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) error if a line was
+    /// longer than the maximum packet size (it is dropped; the other lines
+    /// are still sent).
+    pub fn flush(&self, watch: &Watch) -> io::Result<()> {
+        self.flush_snapshot(&watch.snapshot())
+    }
+
+    /// Flush a previously captured snapshot. See [`StatsdEmitter::flush`].
+    ///
+    /// # Errors
+    /// Returns the first socket send error encountered.
+    pub fn flush_snapshot(&self, snapshot: &HashMap<String, WatchStats>) -> io::Result<()> {
+        let mut names: Vec<&String> = snapshot.keys().collect();
+        names.sort();
+
+        let mut lines = Vec::with_capacity(names.len() * 9);
+        {
+            let mut last = self
+                .last_counts
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            for name in names {
+                let s = &snapshot[name];
+                let prev = last.insert(name.clone(), s.count).unwrap_or(0);
+                // A cleared metric restarts from zero; report its full count.
+                let delta = if s.count >= prev {
+                    s.count - prev
+                } else {
+                    s.count
+                };
+                lines.push(format!(
+                    "{}{}.count:{}|c{}",
+                    self.prefix, name, delta, self.tags
+                ));
+                for (field, ns) in [
+                    ("min", s.min),
+                    ("max", s.max),
+                    ("p50", s.p50),
+                    ("p90", s.p90),
+                    ("p95", s.p95),
+                    ("p99", s.p99),
+                    ("p999", s.p999),
+                ] {
+                    lines.push(self.gauge(name, field, ns_to_ms(ns)));
+                }
+                lines.push(self.gauge(name, "mean", s.mean / 1_000_000.0));
+            }
+        }
+        self.send_batched(&lines)
+    }
+
+    /// Spawn a background thread flushing `watch` every `interval`.
+    ///
+    /// The emitter is moved into the thread. A final flush is performed on
+    /// shutdown so samples recorded since the last tick are not lost.
+    ///
+    /// # Errors
+    /// Returns an error if the thread cannot be spawned.
+    pub fn spawn(self, watch: Watch, interval: StdDuration) -> io::Result<StatsdHandle> {
+        let (tx, rx) = mpsc::channel::<()>();
+        let thread = std::thread::Builder::new()
+            .name("benchmark-statsd".into())
+            .spawn(move || loop {
+                match rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => {
+                        let _ = self.flush(&watch);
+                    }
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => {
+                        let _ = self.flush(&watch);
+                        break;
+                    }
+                }
+            })?;
+        Ok(StatsdHandle {
+            stop: Some(tx),
+            thread: Some(thread),
+        })
+    }
+
+    fn gauge(&self, name: &str, field: &str, value_ms: f64) -> String {
+        format!(
+            "{}{}.{}:{}|g{}",
+            self.prefix, name, field, value_ms, self.tags
+        )
+    }
+
+    fn send_batched(&self, lines: &[String]) -> io::Result<()> {
+        let mut packet = String::with_capacity(self.max_packet);
+        let mut oversized = false;
+        for line in lines {
+            if line.len() > self.max_packet {
+                oversized = true;
+                continue;
+            }
+            if !packet.is_empty() && packet.len() + 1 + line.len() > self.max_packet {
+                self.socket.send(packet.as_bytes())?;
+                packet.clear();
+            }
+            if !packet.is_empty() {
+                packet.push('\n');
+            }
+            packet.push_str(line);
+        }
+        if !packet.is_empty() {
+            self.socket.send(packet.as_bytes())?;
+        }
+        if oversized {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "statsd line longer than the maximum packet size",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns true if the current sample should be sent (xorshift64 draw).
+    fn sampled(&self) -> bool {
+        let mut x = self.rng.load(Ordering::Relaxed);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng.store(x, Ordering::Relaxed);
+        #[allow(clippy::cast_precision_loss)]
+        let u = (x >> 11) as f64 / (1u64 << 53) as f64;
+        u < self.sample_rate
+    }
+}
+
+/// Handle to a background `StatsD` flush thread.
+///
+/// Dropping the handle stops the thread after a final flush.
+#[derive(Debug)]
+pub struct StatsdHandle {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl StatsdHandle {
+    /// Stop the background thread, performing a final flush, and wait for it.
+    pub fn shutdown(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        if let Some(tx) = self.stop.take() {
+            let _ = tx.send(());
+        }
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+    }
+}
+
+impl Drop for StatsdHandle {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn ns_to_ms(ns: u64) -> f64 {
+    ns as f64 / 1_000_000.0
+}
+
+fn seed() -> u64 {
+    let t = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    // Truncation is fine for a seed; avoid the all-zero xorshift state.
+    #[allow(clippy::cast_possible_truncation)]
+    let s = (t as u64) ^ 0x9E37_79B9_7F4A_7C15;
+    s.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receiver() -> (UdpSocket, String) {
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        sock.set_read_timeout(Some(StdDuration::from_secs(2)))
+            .unwrap();
+        let addr = sock.local_addr().unwrap().to_string();
+        (sock, addr)
+    }
+
+    fn recv(sock: &UdpSocket) -> String {
+        let mut buf = [0u8; 4096];
+        let n = sock.recv(&mut buf).unwrap();
+        String::from_utf8_lossy(&buf[..n]).into_owned()
+    }
+
+    #[test]
+    fn test_timing_line_format() {
+        let (sock, addr) = receiver();
+        let e = StatsdEmitter::builder(addr)
+            .prefix("app.")
+            .tag("env:test")
+            .build()
+            .unwrap();
+        e.timing("db", 2_500_000);
+        assert_eq!(recv(&sock), "app.db:2.5|ms|#env:test");
+    }
+
+    #[test]
+    fn test_flush_emits_count_deltas() {
+        let (sock, addr) = receiver();
+        let e = StatsdEmitter::builder(addr).build().unwrap();
+        let w = Watch::new();
+        w.record("op", 1_000_000);
+        w.record("op", 2_000_000);
+        e.flush(&w).unwrap();
+        let first = recv(&sock);
+        assert!(first.lines().any(|l| l == "op.count:2|c"), "{first}");
+        assert!(first
+            .lines()
+            .any(|l| l.starts_with("op.max:") && l.ends_with("|g")));
+
+        w.record("op", 3_000_000);
+        e.flush(&w).unwrap();
+        let second = recv(&sock);
+        assert!(second.lines().any(|l| l == "op.count:1|c"), "{second}");
+    }
+
+    #[test]
+    fn test_packets_respect_max_size() {
+        let (sock, addr) = receiver();
+        let e = StatsdEmitter::builder(addr)
+            .max_packet_size(64)
+            .build()
+            .unwrap();
+        let w = Watch::new();
+        w.record("a", 10);
+        e.flush(&w).unwrap();
+        // 9 lines for one metric cannot fit in a single 64-byte packet.
+        let p = recv(&sock);
+        assert!(p.len() <= 64);
+        assert!(p.lines().count() < 9);
+    }
+
+    #[test]
+    fn test_oversized_lines_are_dropped() {
+        let (sock, addr) = receiver();
+        let e = StatsdEmitter::builder(addr)
+            .max_packet_size(64)
+            .build()
+            .unwrap();
+        let long = "x".repeat(80);
+        e.timing(&long, 1);
+        let w = Watch::new();
+        w.record(&long, 1);
+        w.record("a", 1);
+        let err = e.flush(&w).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        // The short metric's lines still arrive; the long ones never do.
+        let p = recv(&sock);
+        assert!(p.len() <= 64 && p.starts_with("a."), "{p}");
+    }
+
+    #[test]
+    fn test_spawn_final_flush_on_shutdown() {
+        let (sock, addr) = receiver();
+        let e = StatsdEmitter::builder(addr).build().unwrap();
+        let w = Watch::new();
+        w.record("bg", 5);
+        let h = e.spawn(w, StdDuration::from_secs(3600)).unwrap();
+        h.shutdown();
+        assert!(recv(&sock).contains("bg.count:1|c"));
+    }
+}
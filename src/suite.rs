@@ -0,0 +1,260 @@
+#![cfg(feature = "benchmark")]
+//! Benchmark suites with named groups.
+//!
+//! A [`Suite`] registers benchmarks under groups (`serde/serialize`,
+//! `serde/deserialize`, ...) on an underlying [`Runner`], so every runner
+//! option (iterations, warmup, shuffling, tags, thermal guard) still applies.
+//! [`Suite::run`] returns one [`GroupResult`] per group, whose `Display`
+//! renders a comparison table relative to the fastest benchmark in the group.
+
+use std::fmt;
+
+use crate::{BenchResult, Runner};
+
+/// Benchmarks organized into named groups.
+///
+/// # Examples
+/// ```
+/// use benchmark::{Runner, Suite};
+///
+/// let mut suite = Suite::with_runner(Runner::new().iterations(20));
+/// suite
+///     .group("sum")
+///     .bench("iter", || {
+///         benchmark::black_box((0..100u64).sum::<u64>());
+///     })
+///     .bench("formula", || {
+///         let n = benchmark::black_box(100u64);
+///         benchmark::black_box(n * (n - 1) / 2);
+///     });
+/// suite.group("alloc").bench("vec", || {
+///     benchmark::black_box(vec![0u8; 64]);
+/// });
+///
+/// let groups = suite.run();
+/// assert_eq!(groups.len(), 2);
+/// assert_eq!(groups[0].name, "sum");
+/// assert_eq!(groups[0].results[1].name, "sum/formula");
+/// println!("{}", groups[0]); // comparison table
+/// ```
+#[derive(Debug, Default)]
+pub struct Suite {
+    runner: Runner,
+    groups: Vec<String>,
+    // Full name and group index of each registered benchmark.
+    members: Vec<(String, usize)>,
+}
+
+impl Suite {
+    /// Create an empty suite on a default [`Runner`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty suite on a pre-configured `runner`.
+    ///
+    /// Benchmarks already registered on `runner` run too but belong to no
+    /// group and are not part of the results.
+    pub fn with_runner(runner: Runner) -> Self {
+        Self {
+            runner,
+            ..Self::default()
+        }
+    }
+
+    /// Open group `name` (created on first use) to add benchmarks to it.
+    pub fn group(&mut self, name: impl Into<String>) -> Group<'_> {
+        let name = name.into();
+        let index = if let Some(i) = self.groups.iter().position(|g| *g == name) {
+            i
+        } else {
+            self.groups.push(name);
+            self.groups.len() - 1
+        };
+        Group { suite: self, index }
+    }
+
+    /// Run every benchmark and return the results grouped, in group
+    /// creation order. Groups whose benchmarks were all filtered out (see
+    /// [`Runner::only_tags`]) are omitted.
+    pub fn run(&mut self) -> Vec<GroupResult> {
+        let mut groups: Vec<GroupResult> = self
+            .groups
+            .iter()
+            .map(|name| GroupResult {
+                name: name.clone(),
+                results: Vec::new(),
+            })
+            .collect();
+        for result in self.runner.run() {
+            if let Some((_, g)) = self.members.iter().find(|(n, _)| *n == result.name) {
+                groups[*g].results.push(result);
+            }
+        }
+        groups.retain(|g| !g.results.is_empty());
+        groups
+    }
+}
+
+/// Handle for adding benchmarks to one group of a [`Suite`].
+#[derive(Debug)]
+pub struct Group<'a> {
+    suite: &'a mut Suite,
+    index: usize,
+}
+
+// Chaining returns the handle; dropping it after the last bench is the norm.
+#[allow(clippy::return_self_not_must_use)]
+impl Group<'_> {
+    /// Register benchmark `name` in this group as `"<group>/<name>"`.
+    pub fn bench<F>(self, name: &str, f: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.bench_tagged(name, std::iter::empty::<String>(), f)
+    }
+
+    /// Register benchmark `name` in this group with `tags`.
+    pub fn bench_tagged<F, I, S>(self, name: &str, tags: I, f: F) -> Self
+    where
+        F: FnMut() + 'static,
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let full = format!("{}/{name}", self.suite.groups[self.index]);
+        let runner = std::mem::take(&mut self.suite.runner);
+        self.suite.runner = runner.bench_tagged(full.clone(), tags, f);
+        self.suite.members.push((full, self.index));
+        self
+    }
+}
+
+/// Results of one group of a [`Suite`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupResult {
+    /// Group name.
+    pub name: String,
+    /// Results in registration order; names are `"<group>/<bench>"`.
+    pub results: Vec<BenchResult>,
+}
+
+impl GroupResult {
+    /// The result with the lowest mean.
+    pub fn fastest(&self) -> Option<&BenchResult> {
+        self.results.iter().min_by_key(|r| r.mean)
+    }
+
+    /// Each benchmark's mean divided by the fastest mean (`1.0` = fastest).
+    #[allow(clippy::cast_precision_loss)]
+    pub fn relative(&self) -> Vec<(&str, f64)> {
+        let best = self.fastest().map_or(1, |r| r.mean.as_nanos().max(1)) as f64;
+        self.results
+            .iter()
+            .map(|r| (self.short_name(r), r.mean.as_nanos() as f64 / best))
+            .collect()
+    }
+
+    fn short_name<'a>(&self, r: &'a BenchResult) -> &'a str {
+        r.name
+            .strip_prefix(self.name.as_str())
+            .and_then(|s| s.strip_prefix('/'))
+            .unwrap_or(&r.name)
+    }
+}
+
+impl fmt::Display for GroupResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<[String; 4]> = self
+            .relative()
+            .into_iter()
+            .zip(&self.results)
+            .map(|((name, rel), r)| {
+                [
+                    name.to_string(),
+                    r.mean.to_string(),
+                    r.median.to_string(),
+                    format!("{rel:.2}x"),
+                ]
+            })
+            .collect();
+        let header = ["bench", "mean", "median", "vs fastest"];
+        let mut widths = header.map(|h| h.chars().count());
+        for row in &rows {
+            for (w, cell) in widths.iter_mut().zip(row) {
+                *w = (*w).max(cell.chars().count());
+            }
+        }
+        writeln!(f, "{}", self.name)?;
+        let line = |f: &mut fmt::Formatter<'_>, cells: [&str; 4]| {
+            writeln!(
+                f,
+                "  {:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}",
+                cells[0],
+                cells[1],
+                cells[2],
+                cells[3],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3]
+            )
+        };
+        line(f, header)?;
+        for row in &rows {
+            line(f, [&row[0], &row[1], &row[2], &row[3]])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Duration;
+
+    #[test]
+    fn test_groups_collect_their_results() {
+        let mut suite = Suite::with_runner(Runner::new().iterations(2));
+        suite.group("a").bench("x", || {}).bench("y", || {});
+        suite.group("b").bench_tagged("z", ["slow"], || {});
+        suite.group("a").bench("w", || {});
+        let groups = suite.run();
+        let names: Vec<Vec<&str>> = groups
+            .iter()
+            .map(|g| g.results.iter().map(|r| r.name.as_str()).collect())
+            .collect();
+        assert_eq!(names, [vec!["a/x", "a/y", "a/w"], vec!["b/z"]]);
+        assert_eq!(groups[1].results[0].tags, ["slow"]);
+
+        // Groups left empty by tag filtering disappear.
+        let mut suite = Suite::with_runner(Runner::new().iterations(1).skip_tags(["slow"]));
+        suite.group("b").bench_tagged("z", ["slow"], || {});
+        assert!(suite.run().is_empty());
+    }
+
+    #[test]
+    fn test_comparison_table() {
+        let result = |name: &str, ns: u128| {
+            let mut r = Runner::new()
+                .iterations(1)
+                .bench(name, || {})
+                .run()
+                .remove(0);
+            r.mean = Duration::from_nanos(ns);
+            r.median = Duration::from_nanos(ns);
+            r
+        };
+        let g = GroupResult {
+            name: "serde".into(),
+            results: vec![result("serde/ser", 1_500), result("serde/de", 500)],
+        };
+        assert_eq!(g.fastest().unwrap().name, "serde/de");
+        assert_eq!(g.relative(), [("ser", 3.0), ("de", 1.0)]);
+        let table = g.to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "serde");
+        assert_eq!(lines[1], "  bench    mean  median  vs fastest");
+        assert_eq!(lines[2], "  ser    1.50µs  1.50µs       3.00x");
+        assert_eq!(lines[3], "  de      500ns   500ns       1.00x");
+    }
+}
@@ -0,0 +1,423 @@
+#![cfg(feature = "std")]
+//! Lock wrappers that record contention into a [`Watch`].
+//!
+//! [`TimedMutex`] and [`TimedRwLock`] behave like their `std::sync`
+//! counterparts but record, per named lock, how long callers waited to
+//! acquire it and how long they held it:
+//!
+//! | lock           | metrics                                                    |
+//! |----------------|------------------------------------------------------------|
+//! | `TimedMutex`   | `<name>.wait`, `<name>.hold`                               |
+//! | `TimedRwLock`  | `<name>.read.wait`, `<name>.read.hold`, `<name>.write.wait`, `<name>.write.hold` |
+//!
+//! Timestamps come from the Watch's clock. With `metrics` disabled the
+//! wrappers still work as plain locks and record nothing.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{
+    LockResult, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    TryLockError, TryLockResult,
+};
+
+use crate::Watch;
+
+/// Map a std lock result onto a wrapped guard, preserving poisoning.
+fn map_lock<G, W>(r: LockResult<G>, wrap: impl FnOnce(G) -> W) -> LockResult<W> {
+    match r {
+        Ok(g) => Ok(wrap(g)),
+        Err(e) => Err(PoisonError::new(wrap(e.into_inner()))),
+    }
+}
+
+/// Map a std try-lock result onto a wrapped guard, preserving poisoning.
+fn map_try_lock<G, W>(r: TryLockResult<G>, wrap: impl FnOnce(G) -> W) -> TryLockResult<W> {
+    match r {
+        Ok(g) => Ok(wrap(g)),
+        Err(TryLockError::Poisoned(e)) => Err(TryLockError::Poisoned(PoisonError::new(wrap(
+            e.into_inner(),
+        )))),
+        Err(TryLockError::WouldBlock) => Err(TryLockError::WouldBlock),
+    }
+}
+
+/// Metric names for one lock mode.
+#[derive(Debug)]
+struct Names {
+    wait: String,
+    hold: String,
+}
+
+impl Names {
+    fn new(prefix: &str) -> Self {
+        Self {
+            wait: format!("{prefix}.wait"),
+            hold: format!("{prefix}.hold"),
+        }
+    }
+}
+
+/// Records hold time when dropped, after the wrapped guard is released.
+struct Hold<'a, G> {
+    guard: Option<G>,
+    watch: &'a Watch,
+    name: &'a str,
+    acquired: u64,
+}
+
+impl<'a, G> Hold<'a, G> {
+    fn new(guard: G, watch: &'a Watch, name: &'a str) -> Self {
+        Self {
+            guard: Some(guard),
+            watch,
+            name,
+            acquired: watch.now_ns(),
+        }
+    }
+
+    #[inline]
+    fn get(&self) -> &G {
+        self.guard.as_ref().expect("guard present until drop")
+    }
+
+    #[inline]
+    fn get_mut(&mut self) -> &mut G {
+        self.guard.as_mut().expect("guard present until drop")
+    }
+}
+
+impl<G> Drop for Hold<'_, G> {
+    fn drop(&mut self) {
+        // Release first so recording does not extend the critical section.
+        drop(self.guard.take());
+        let held = self.watch.now_ns().saturating_sub(self.acquired);
+        self.watch.record(self.name, held);
+    }
+}
+
+/// Run `acquire` and record how long it took under `name`.
+fn timed_wait<R>(watch: &Watch, name: &str, acquire: impl FnOnce() -> R) -> R {
+    let start = watch.now_ns();
+    let r = acquire();
+    watch.record(name, watch.now_ns().saturating_sub(start));
+    r
+}
+
+/// A `Mutex` that records wait and hold times into a `Watch`.
+///
+/// # Examples
+/// ```
+/// use benchmark::sync::TimedMutex;
+/// use benchmark::Watch;
+/// let w = Watch::new();
+/// let cache = TimedMutex::new(Vec::new(), &w, "cache");
+/// cache.lock().unwrap().push(1);
+/// # #[cfg(feature = "metrics")]
+/// # {
+/// let snap = w.snapshot();
+/// assert_eq!(snap["cache.wait"].count, 1);
+/// assert_eq!(snap["cache.hold"].count, 1);
+/// # }
+/// ```
+pub struct TimedMutex<T> {
+    watch: Watch,
+    names: Names,
+    inner: Mutex<T>,
+}
+
+impl<T> TimedMutex<T> {
+    /// Wrap `value`, recording into `watch` under `<name>.wait` / `<name>.hold`.
+    pub fn new(value: T, watch: &Watch, name: &str) -> Self {
+        Self {
+            watch: watch.clone(),
+            names: Names::new(name),
+            inner: Mutex::new(value),
+        }
+    }
+
+    /// Acquire the lock, recording the wait.
+    ///
+    /// # Errors
+    /// Returns the guard wrapped in `PoisonError` if another holder panicked,
+    /// as `Mutex::lock` does.
+    pub fn lock(&self) -> LockResult<TimedMutexGuard<'_, T>> {
+        let r = timed_wait(&self.watch, &self.names.wait, || self.inner.lock());
+        map_lock(r, |g| self.guard(g))
+    }
+
+    /// Try to acquire the lock without blocking (no wait is recorded).
+    ///
+    /// # Errors
+    /// `WouldBlock` if the lock is held; `Poisoned` as for [`lock`](Self::lock).
+    pub fn try_lock(&self) -> TryLockResult<TimedMutexGuard<'_, T>> {
+        map_try_lock(self.inner.try_lock(), |g| self.guard(g))
+    }
+
+    /// Mutable access without locking (no timing recorded).
+    ///
+    /// # Errors
+    /// Returns `PoisonError` if another holder panicked.
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        self.inner.get_mut()
+    }
+
+    /// Consume the wrapper and return the value.
+    ///
+    /// # Errors
+    /// Returns `PoisonError` if another holder panicked.
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+
+    fn guard<'a>(&'a self, g: MutexGuard<'a, T>) -> TimedMutexGuard<'a, T> {
+        TimedMutexGuard(Hold::new(g, &self.watch, &self.names.hold))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TimedMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimedMutex")
+            .field("name", &self.names.wait.trim_end_matches(".wait"))
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Guard for [`TimedMutex`]; records the hold time on drop.
+#[must_use = "if unused the lock is released immediately"]
+pub struct TimedMutexGuard<'a, T>(Hold<'a, MutexGuard<'a, T>>);
+
+impl<T> Deref for TimedMutexGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.0.get()
+    }
+}
+
+impl<T> DerefMut for TimedMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.0.get_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TimedMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// An `RwLock` that records wait and hold times, per mode, into a `Watch`.
+///
+/// # Examples
+/// ```
+/// use benchmark::sync::TimedRwLock;
+/// use benchmark::Watch;
+/// let w = Watch::new();
+/// let config = TimedRwLock::new(1u32, &w, "config");
+/// assert_eq!(*config.read().unwrap(), 1);
+/// *config.write().unwrap() = 2;
+/// # #[cfg(feature = "metrics")]
+/// # {
+/// let snap = w.snapshot();
+/// assert_eq!(snap["config.read.hold"].count, 1);
+/// assert_eq!(snap["config.write.wait"].count, 1);
+/// # }
+/// ```
+pub struct TimedRwLock<T> {
+    watch: Watch,
+    read_names: Names,
+    write_names: Names,
+    inner: RwLock<T>,
+}
+
+impl<T> TimedRwLock<T> {
+    /// Wrap `value`, recording into `watch` under `<name>.read.*` / `<name>.write.*`.
+    pub fn new(value: T, watch: &Watch, name: &str) -> Self {
+        Self {
+            watch: watch.clone(),
+            read_names: Names::new(&format!("{name}.read")),
+            write_names: Names::new(&format!("{name}.write")),
+            inner: RwLock::new(value),
+        }
+    }
+
+    /// Acquire shared access, recording the wait.
+    ///
+    /// # Errors
+    /// Returns the guard wrapped in `PoisonError` if a writer panicked.
+    pub fn read(&self) -> LockResult<TimedReadGuard<'_, T>> {
+        let r = timed_wait(&self.watch, &self.read_names.wait, || self.inner.read());
+        map_lock(r, |g| self.read_guard(g))
+    }
+
+    /// Acquire exclusive access, recording the wait.
+    ///
+    /// # Errors
+    /// Returns the guard wrapped in `PoisonError` if a writer panicked.
+    pub fn write(&self) -> LockResult<TimedWriteGuard<'_, T>> {
+        let r = timed_wait(&self.watch, &self.write_names.wait, || self.inner.write());
+        map_lock(r, |g| self.write_guard(g))
+    }
+
+    /// Try to acquire shared access without blocking (no wait is recorded).
+    ///
+    /// # Errors
+    /// `WouldBlock` if a writer holds the lock; `Poisoned` as for [`read`](Self::read).
+    pub fn try_read(&self) -> TryLockResult<TimedReadGuard<'_, T>> {
+        map_try_lock(self.inner.try_read(), |g| self.read_guard(g))
+    }
+
+    /// Try to acquire exclusive access without blocking (no wait is recorded).
+    ///
+    /// # Errors
+    /// `WouldBlock` if the lock is held; `Poisoned` as for [`write`](Self::write).
+    pub fn try_write(&self) -> TryLockResult<TimedWriteGuard<'_, T>> {
+        map_try_lock(self.inner.try_write(), |g| self.write_guard(g))
+    }
+
+    /// Mutable access without locking (no timing recorded).
+    ///
+    /// # Errors
+    /// Returns `PoisonError` if a writer panicked.
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        self.inner.get_mut()
+    }
+
+    /// Consume the wrapper and return the value.
+    ///
+    /// # Errors
+    /// Returns `PoisonError` if a writer panicked.
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+
+    fn read_guard<'a>(&'a self, g: RwLockReadGuard<'a, T>) -> TimedReadGuard<'a, T> {
+        TimedReadGuard(Hold::new(g, &self.watch, &self.read_names.hold))
+    }
+
+    fn write_guard<'a>(&'a self, g: RwLockWriteGuard<'a, T>) -> TimedWriteGuard<'a, T> {
+        TimedWriteGuard(Hold::new(g, &self.watch, &self.write_names.hold))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TimedRwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimedRwLock")
+            .field("name", &self.read_names.wait.trim_end_matches(".read.wait"))
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Shared guard for [`TimedRwLock`]; records the hold time on drop.
+#[must_use = "if unused the lock is released immediately"]
+pub struct TimedReadGuard<'a, T>(Hold<'a, RwLockReadGuard<'a, T>>);
+
+impl<T> Deref for TimedReadGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.0.get()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TimedReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Exclusive guard for [`TimedRwLock`]; records the hold time on drop.
+#[must_use = "if unused the lock is released immediately"]
+pub struct TimedWriteGuard<'a, T>(Hold<'a, RwLockWriteGuard<'a, T>>);
+
+impl<T> Deref for TimedWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.0.get()
+    }
+}
+
+impl<T> DerefMut for TimedWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.0.get_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TimedWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use crate::test_util::MockClock;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_mutex_hold_time_exact() {
+        let clock = MockClock::new();
+        let w = clock.watch();
+        let m = TimedMutex::new(0u32, &w, "m");
+        {
+            let mut g = m.lock().unwrap();
+            *g += 1;
+            clock.advance(700);
+        }
+        assert!(m.try_lock().is_ok());
+        let snap = w.snapshot();
+        assert_eq!(snap["m.hold"].count, 2);
+        assert_eq!(snap["m.hold"].max, 700);
+        assert_eq!(snap["m.wait"].count, 1);
+        assert_eq!(m.into_inner().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_contended_mutex_records_wait() {
+        let w = Watch::new();
+        let m = Arc::new(TimedMutex::new((), &w, "hot"));
+        let g = m.lock().unwrap();
+        let waiter = std::thread::spawn({
+            let m = Arc::clone(&m);
+            move || drop(m.lock().unwrap())
+        });
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        drop(g);
+        waiter.join().unwrap();
+        let snap = w.snapshot();
+        assert_eq!(snap["hot.wait"].count, 2);
+        assert!(snap["hot.wait"].max >= 10_000_000);
+    }
+
+    #[test]
+    fn test_rwlock_modes_and_poison() {
+        let clock = MockClock::new();
+        let w = clock.watch();
+        let l = Arc::new(TimedRwLock::new(5, &w, "cfg"));
+        {
+            let a = l.read().unwrap();
+            let b = l.try_read().unwrap();
+            assert!(l.try_write().is_err());
+            clock.advance(40);
+            assert_eq!(*a + *b, 10);
+        }
+        *l.write().unwrap() = 6;
+
+        let l2 = Arc::clone(&l);
+        let _ = std::thread::spawn(move || {
+            let _g = l2.write().unwrap();
+            panic!("poison");
+        })
+        .join();
+        let err = l.read().unwrap_err();
+        assert_eq!(**err.get_ref(), 6);
+        drop(err);
+
+        let snap = w.snapshot();
+        assert_eq!(snap["cfg.read.hold"].count, 3);
+        assert_eq!(snap["cfg.read.hold"].max, 40);
+        assert_eq!(snap["cfg.write.hold"].count, 2);
+        assert_eq!(snap["cfg.read.wait"].count, 2);
+    }
+}
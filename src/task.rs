@@ -0,0 +1,168 @@
+#![cfg(feature = "rt-tokio")]
+//! Tokio task instrumentation.
+//!
+//! [`spawn_timed`] spawns a future on the current Tokio runtime and, when the
+//! task completes, records into a [`Watch`]:
+//!
+//! | metric            | value                                               |
+//! |-------------------|-----------------------------------------------------|
+//! | `<name>`          | runtime from first poll to completion (ns)          |
+//! | `<name>.polls`    | number of times the task was polled (a count)       |
+//! | `<name>.max_poll` | longest single poll (ns); long polls block a worker |
+//!
+//! [`Timed`] is the wrapping future on its own, for code that awaits in place
+//! or spawns through something other than `tokio::spawn`. Timestamps come from
+//! the Watch's clock. A task dropped before completing (aborted, or its runtime
+//! shut down) records nothing. With `metrics` disabled the wrappers still run
+//! the future and record nothing.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::Watch;
+
+/// Spawn `future` on the current Tokio runtime, recording its runtime, poll
+/// count and longest poll under `name` (see the [module docs](self)).
+///
+/// # Panics
+/// Panics if called outside a Tokio runtime, like `tokio::spawn`.
+///
+/// # Examples
+/// ```
+/// use benchmark::{task::spawn_timed, Watch};
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let w = Watch::new();
+/// let v = spawn_timed(&w, "job", async { 2 + 2 }).await.unwrap();
+/// assert_eq!(v, 4);
+/// # #[cfg(feature = "metrics")]
+/// assert_eq!(w.snapshot()["job.polls"].max, 1);
+/// # });
+/// ```
+pub fn spawn_timed<F>(watch: &Watch, name: &str, future: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(Timed::new(watch.clone(), name, future))
+}
+
+/// A future that records its runtime, poll count and longest poll on completion.
+///
+/// # Examples
+/// ```
+/// use benchmark::{task::Timed, Watch};
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let w = Watch::new();
+/// let v = Timed::new(w.clone(), "load", async { "done" }).await;
+/// assert_eq!(v, "done");
+/// # #[cfg(feature = "metrics")]
+/// assert_eq!(w.snapshot()["load"].count, 1);
+/// # });
+/// ```
+#[must_use = "futures do nothing unless polled"]
+pub struct Timed<F> {
+    future: Pin<Box<F>>,
+    watch: Watch,
+    name: String,
+    polls_name: String,
+    max_poll_name: String,
+    first_poll: Option<u64>,
+    polls: u64,
+    max_poll: u64,
+}
+
+impl<F> fmt::Debug for Timed<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timed")
+            .field("name", &self.name)
+            .field("polls", &self.polls)
+            .field("max_poll", &self.max_poll)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F: Future> Timed<F> {
+    /// Wrap `future`, recording into `watch` under `name`.
+    pub fn new(watch: Watch, name: &str, future: F) -> Self {
+        Self {
+            future: Box::pin(future),
+            watch,
+            name: name.to_owned(),
+            polls_name: format!("{name}.polls"),
+            max_poll_name: format!("{name}.max_poll"),
+            first_poll: None,
+            polls: 0,
+            max_poll: 0,
+        }
+    }
+}
+
+impl<F: Future> Future for Timed<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let this = &mut *self;
+        let start = this.watch.now_ns();
+        let first = *this.first_poll.get_or_insert(start);
+        let out = this.future.as_mut().poll(cx);
+        let end = this.watch.now_ns();
+        this.polls += 1;
+        this.max_poll = this.max_poll.max(end.saturating_sub(start));
+        if out.is_ready() {
+            this.watch.record(&this.name, end.saturating_sub(first));
+            this.watch.record(&this.polls_name, this.polls);
+            this.watch.record(&this.max_poll_name, this.max_poll);
+        }
+        out
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_spawn_timed_records_runtime_polls_and_max_poll() {
+        let clock = ManualClock::new();
+        let w = Watch::builder().clock(clock.clone()).build();
+        let c = clock.clone();
+        let out = runtime().block_on(async {
+            spawn_timed(&w, "task", async move {
+                c.advance(100);
+                tokio::task::yield_now().await;
+                c.advance(300);
+                7
+            })
+            .await
+            .unwrap()
+        });
+        assert_eq!(out, 7);
+        let s = w.snapshot();
+        assert_eq!(s["task"].count, 1);
+        assert_eq!(s["task"].total_ns, 400);
+        assert_eq!(s["task.polls"].total_ns, 2);
+        assert_eq!(s["task.max_poll"].total_ns, 300);
+    }
+
+    #[test]
+    fn test_timed_dropped_before_completion_records_nothing() {
+        let w = Watch::new();
+        let rt = runtime();
+        rt.block_on(async {
+            let h = spawn_timed(&w, "never", std::future::pending::<()>());
+            tokio::task::yield_now().await;
+            h.abort();
+            assert!(h.await.unwrap_err().is_cancelled());
+        });
+        assert!(w.snapshot().is_empty());
+    }
+}
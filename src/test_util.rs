@@ -0,0 +1,126 @@
+#![cfg(feature = "std")]
+//! Helpers for unit-testing code that is instrumented with this crate.
+//!
+//! Timing assertions against the real clock need sleeps and tolerance ranges.
+//! [`MockClock`] replaces both: wire it into a `Watch`, run the code under
+//! test, advance the clock by exact amounts, and assert exact durations.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use crate::clock::{Clock, ManualClock};
+use crate::Watch;
+
+/// A test clock that moves only when advanced (or by a fixed step per read).
+///
+/// Clones share the same time and step.
+///
+/// # Examples
+/// ```
+/// use benchmark::test_util::MockClock;
+/// use benchmark::{stopwatch, Clock};
+///
+/// let clock = MockClock::new();
+/// let watch = clock.watch();
+/// stopwatch!(watch, "handler", {
+///     clock.advance(1_250);
+/// });
+/// # #[cfg(feature = "metrics")]
+/// assert_eq!(watch.snapshot()["handler"].max, 1_250);
+/// assert_eq!(clock.now_ns(), 1_250);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockClock {
+    time: ManualClock,
+    step: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    /// Create a clock reading 0 that does not advance on its own.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advance by `ns` nanoseconds.
+    pub fn advance(&self, ns: u64) {
+        self.time.advance(ns);
+    }
+
+    /// Advance by a `std::time::Duration` (saturating at `u64::MAX` ns).
+    pub fn advance_by(&self, d: StdDuration) {
+        self.advance(u64::try_from(d.as_nanos()).unwrap_or(u64::MAX));
+    }
+
+    /// Jump to an absolute reading.
+    pub fn set(&self, ns: u64) {
+        self.time.set(ns);
+    }
+
+    /// Advance by `ns` after every reading (0 disables).
+    ///
+    /// Useful when the code under test takes both readings itself, e.g.
+    /// `measure_with(&clock, f)` then reports exactly `ns`.
+    pub fn auto_advance(&self, ns: u64) {
+        self.step.store(ns, Ordering::SeqCst);
+    }
+
+    /// A `Watch` that timestamps with this clock.
+    pub fn watch(&self) -> Watch {
+        Watch::builder().clock(self.clone()).build()
+    }
+}
+
+impl Clock for MockClock {
+    fn now_ns(&self) -> u64 {
+        let now = self.time.now_ns();
+        let step = self.step.load(Ordering::SeqCst);
+        if step != 0 {
+            self.time.advance(step);
+        }
+        now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_advance_and_step() {
+        let c = MockClock::new();
+        c.advance(10);
+        c.advance_by(StdDuration::from_micros(1));
+        assert_eq!(c.now_ns(), 1_010);
+        c.auto_advance(5);
+        assert_eq!(c.clone().now_ns(), 1_010);
+        assert_eq!(c.now_ns(), 1_015);
+        c.set(0);
+        c.auto_advance(0);
+        assert_eq!(c.now_ns(), 0);
+        assert_eq!(c.now_ns(), 0);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_mock_clock_exact_timer_durations() {
+        let c = MockClock::new();
+        let w = c.watch();
+        for ns in [100, 200, 300] {
+            let t = crate::Timer::new(w.clone(), "op");
+            c.advance(ns);
+            assert_eq!(t.stop(), ns);
+        }
+        let s = w.snapshot()["op"];
+        assert_eq!((s.count, s.min, s.max), (3, 100, 300));
+    }
+
+    #[cfg(feature = "benchmark")]
+    #[test]
+    fn test_mock_clock_auto_advance_measure() {
+        let c = MockClock::new();
+        c.auto_advance(40);
+        let (v, d) = crate::measure_with(&c, || 1);
+        assert_eq!((v, d.as_nanos()), (1, 40));
+    }
+}
@@ -1,9 +1,11 @@
 #![cfg(all(feature = "std", feature = "metrics"))]
 
+use std::cell::{Cell, RefCell};
 use std::fmt;
+use std::marker::PhantomData;
 use std::sync::Arc;
-use std::time::Instant;
 
+use crate::clock::Clock;
 use crate::watch::Watch;
 
 /// A lightweight scope timer that records duration to a central `Watch` on drop.
@@ -25,7 +27,8 @@ use crate::watch::Watch;
 pub struct Timer {
     watch: Watch,
     name: Arc<str>,
-    start: Option<Instant>, // guard to prevent double-record
+    clock: Option<Arc<dyn Clock>>, // overrides the watch's clock when set
+    start: Option<u64>,            // clock reading; guard to prevent double-record
 }
 
 impl fmt::Debug for Timer {
@@ -48,17 +51,133 @@ impl Timer {
     /// ```
     #[inline]
     pub fn new(watch: Watch, name: impl Into<Arc<str>>) -> Self {
+        let start = watch.now_ns();
         Self {
             watch,
             name: name.into(),
-            start: Some(Instant::now()),
+            clock: None,
+            start: Some(start),
         }
     }
 
+    /// Start a timer that reads `clock` instead of the Watch's clock.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{ManualClock, Timer, Watch};
+    /// use std::sync::Arc;
+    /// let clock = ManualClock::new();
+    /// let w = Watch::new();
+    /// let t = Timer::new_with_clock(w.clone(), "op", Arc::new(clock.clone()));
+    /// clock.advance(250);
+    /// assert_eq!(t.stop(), 250);
+    /// ```
+    #[inline]
+    pub fn new_with_clock(watch: Watch, name: impl Into<Arc<str>>, clock: Arc<dyn Clock>) -> Self {
+        let start = clock.now_ns();
+        Self {
+            watch,
+            name: name.into(),
+            clock: Some(clock),
+            start: Some(start),
+        }
+    }
+
+    /// Create a timer without starting its clock; nothing is measured or
+    /// recorded until [`start`](Self::start) or [`restart`](Self::restart).
+    ///
+    /// Lets a timer be built early (e.g. stored in a struct) while
+    /// measurement begins at a later point. Dropping a timer that was never
+    /// started records nothing.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::test_util::MockClock;
+    /// use benchmark::Timer;
+    /// let clock = MockClock::new();
+    /// let w = clock.watch();
+    /// let mut t = Timer::paused(w.clone(), "flush");
+    /// clock.advance(100); // not measured
+    /// t.start();
+    /// clock.advance(30);
+    /// assert_eq!(t.stop(), 30);
+    /// ```
+    #[inline]
+    pub fn paused(watch: Watch, name: impl Into<Arc<str>>) -> Self {
+        Self {
+            watch,
+            name: name.into(),
+            clock: None,
+            start: None,
+        }
+    }
+
+    /// Start a timer measuring the CPU time of the current thread rather than
+    /// wall time, so time spent blocked or preempted is excluded.
+    ///
+    /// Reads [`ThreadCpuClock`](crate::ThreadCpuClock), falling back to wall
+    /// time on platforms without a per-thread CPU clock. Stop or drop the
+    /// timer on the thread that started it; readings from another thread
+    /// are unrelated.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Timer, Watch};
+    /// let w = Watch::new();
+    /// let t = Timer::new_thread_cpu(w.clone(), "parse");
+    /// let n: u64 = (0..10_000u64).map(std::hint::black_box).sum();
+    /// t.stop();
+    /// assert_eq!(w.snapshot()["parse"].count, 1);
+    /// # let _ = n;
+    /// ```
+    #[cfg(feature = "cpu-time")]
+    #[inline]
+    pub fn new_thread_cpu(watch: Watch, name: impl Into<Arc<str>>) -> Self {
+        Self::new_with_clock(watch, name, crate::cpu::ThreadCpuClock::shared())
+    }
+
+    /// Start measuring now if the timer is not already running.
+    ///
+    /// A no-op on a running timer; use [`restart`](Self::restart) to move
+    /// its start point.
+    #[inline]
+    pub fn start(&mut self) {
+        if self.start.is_none() {
+            self.start = Some(self.now());
+        }
+    }
+
+    /// Start measuring from now, discarding any interval in progress
+    /// (nothing is recorded for it).
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::test_util::MockClock;
+    /// let clock = MockClock::new();
+    /// let w = clock.watch();
+    /// let mut t = w.start("request");
+    /// clock.advance(50); // setup, not part of the request
+    /// t.restart();
+    /// clock.advance(20);
+    /// assert_eq!(t.stop(), 20);
+    /// ```
+    #[inline]
+    pub fn restart(&mut self) {
+        self.start = Some(self.now());
+    }
+
+    /// True if the timer has started and not yet recorded.
+    #[inline]
+    pub fn is_running(&self) -> bool {
+        self.start.is_some()
+    }
+
     /// Stop the timer early and record the duration once.
     /// Returns the recorded nanoseconds.
     ///
     /// Safe to call at most once; subsequent calls are no-ops returning 0.
+    /// A [`paused`](Self::paused) timer that was never started records
+    /// nothing and returns 0.
     ///
     /// # Examples
     /// ```
@@ -73,17 +192,606 @@ impl Timer {
     #[inline]
     pub fn stop(mut self) -> u64 {
         if let Some(start) = self.start.take() {
-            return self.watch.record_instant(&self.name, start);
+            return self.finish(start);
         }
         0
     }
 }
 
+impl Timer {
+    #[inline]
+    fn now(&self) -> u64 {
+        match &self.clock {
+            Some(c) => c.now_ns(),
+            None => self.watch.now_ns(),
+        }
+    }
+
+    #[inline]
+    fn finish(&self, start: u64) -> u64 {
+        let ns = self.now().saturating_sub(start);
+        self.watch.record(&self.name, ns);
+        ns
+    }
+}
+
+impl Watch {
+    /// Start a [`Timer`] recording into `name` on this Watch.
+    ///
+    /// Shorthand for `Timer::new(watch.clone(), name)`.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// {
+    ///     let _t = w.start("parse");
+    ///     // do work
+    /// }
+    /// assert_eq!(w.snapshot()["parse"].count, 1);
+    /// ```
+    #[inline]
+    pub fn start(&self, name: impl Into<Arc<str>>) -> Timer {
+        Timer::new(self.clone(), name)
+    }
+
+    /// Run `f`, record its duration under `name`, and return its output.
+    ///
+    /// The duration is recorded even if `f` panics.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// let n = w.time("sum", || (1..=10u32).sum::<u32>());
+    /// assert_eq!(n, 55);
+    /// assert_eq!(w.snapshot()["sum"].count, 1);
+    /// ```
+    #[inline]
+    pub fn time<T, F: FnOnce() -> T>(&self, name: &str, f: F) -> T {
+        let _t = self.start(name);
+        f()
+    }
+
+    /// Same as [`time`](Self::time), named after the free [`measure`](crate::measure)
+    /// function for call sites moving from ad-hoc measuring to a Watch.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// let body = w.measure("fetch", || "payload");
+    /// assert_eq!(body, "payload");
+    /// assert_eq!(w.snapshot()["fetch"].count, 1);
+    /// ```
+    #[inline]
+    pub fn measure<T, F: FnOnce() -> T>(&self, name: &str, f: F) -> T {
+        self.time(name, f)
+    }
+
+    /// Run `f` and record its duration under `<name>.ok` or `<name>.err`
+    /// depending on the result, so slow (or fast) failures do not skew the
+    /// success percentiles.
+    ///
+    /// Nothing is recorded if `f` panics.
+    ///
+    /// # Errors
+    /// Returns `f`'s error unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// let ok: Result<u32, String> = w.measure_result("rpc", || Ok(1));
+    /// let err: Result<u32, String> = w.measure_result("rpc", || Err("timeout".into()));
+    /// assert!(ok.is_ok() && err.is_err());
+    /// let s = w.snapshot();
+    /// assert_eq!((s["rpc.ok"].count, s["rpc.err"].count), (1, 1));
+    /// assert!(!s.contains_key("rpc"));
+    /// ```
+    pub fn measure_result<T, E, F: FnOnce() -> Result<T, E>>(
+        &self,
+        name: &str,
+        f: F,
+    ) -> Result<T, E> {
+        let start = self.now_ns();
+        let result = f();
+        let ns = self.now_ns().saturating_sub(start);
+        let status = if result.is_ok() { "ok" } else { "err" };
+        self.record_id(&self.suffixed_id(name, status), ns);
+        result
+    }
+}
+
 impl Drop for Timer {
     #[inline]
     fn drop(&mut self) {
         if let Some(start) = self.start.take() {
-            let _ = self.watch.record_instant(&self.name, start);
+            let _ = self.finish(start);
+        }
+    }
+}
+
+/// One open [`ScopedTimer`] on this thread's scope stack.
+struct Frame {
+    id: u64,
+    name: Arc<str>,
+    /// Inclusive time of already finished direct children.
+    child_ns: u64,
+}
+
+thread_local! {
+    static SCOPES: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+    static NEXT_FRAME: Cell<u64> = const { Cell::new(0) };
+    // Reused buffer for the folded path of the scope being finished.
+    static PATH: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// A timer that attributes time between nested scopes on the same thread.
+///
+/// Each `ScopedTimer` records two metrics when it stops or drops, and adds
+/// its self time to the Watch's [folded stacks](Watch::folded_stacks):
+/// - `<name>`: inclusive time, from start to stop, like [`Timer`];
+/// - `<name>.self`: exclusive time, minus the inclusive time of
+///   `ScopedTimer`s that started and finished inside it.
+///
+/// Nesting is tracked on a thread-local stack, so a `ScopedTimer` cannot
+/// leave its thread. Scopes nested across different Watches are still
+/// attributed to each other; they should share a clock.
+///
+/// # Examples
+/// ```
+/// use benchmark::test_util::MockClock;
+///
+/// let clock = MockClock::new();
+/// let w = clock.watch();
+/// {
+///     let _req = w.scope("request");
+///     clock.advance(10);
+///     {
+///         let _db = w.scope("db");
+///         clock.advance(30);
+///     }
+///     clock.advance(5);
+/// }
+/// let s = w.snapshot();
+/// assert_eq!((s["request"].total_ns, s["request.self"].total_ns), (45, 15));
+/// assert_eq!((s["db"].total_ns, s["db.self"].total_ns), (30, 30));
+/// ```
+#[must_use]
+pub struct ScopedTimer {
+    watch: Watch,
+    name: Arc<str>,
+    frame: u64,
+    start: Option<u64>,
+    // The frame lives on this thread's stack.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl fmt::Debug for ScopedTimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopedTimer")
+            .field("name", &self.name)
+            .field("active", &self.start.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ScopedTimer {
+    /// Start a scope named `name`, nested inside any open scope on this thread.
+    #[inline]
+    pub fn new(watch: Watch, name: impl Into<Arc<str>>) -> Self {
+        let name = name.into();
+        let frame = NEXT_FRAME.with(|next| {
+            let id = next.get();
+            next.set(id.wrapping_add(1));
+            id
+        });
+        SCOPES.with(|stack| {
+            stack.borrow_mut().push(Frame {
+                id: frame,
+                name: Arc::clone(&name),
+                child_ns: 0,
+            });
+        });
+        let start = watch.now_ns();
+        Self {
+            watch,
+            name,
+            frame,
+            start: Some(start),
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Stop the scope early, recording once. Returns the inclusive nanoseconds.
+    #[inline]
+    pub fn stop(mut self) -> u64 {
+        match self.start.take() {
+            Some(start) => self.finish(start),
+            None => 0,
+        }
+    }
+
+    fn finish(&self, start: u64) -> u64 {
+        let inclusive = self.watch.now_ns().saturating_sub(start);
+        // Scopes normally close innermost first; search from the top so one
+        // stopped out of order only detaches itself.
+        let self_ns = PATH.with(|path| {
+            let mut path = path.borrow_mut();
+            path.clear();
+            let child_ns = SCOPES.with(|stack| {
+                let mut stack = stack.borrow_mut();
+                let pos = stack.iter().rposition(|f| f.id == self.frame)?;
+                folded_path(&mut path, &stack[..=pos]);
+                let frame = stack.remove(pos);
+                if let Some(parent) = pos.checked_sub(1).and_then(|p| stack.get_mut(p)) {
+                    parent.child_ns = parent.child_ns.saturating_add(inclusive);
+                }
+                Some(frame.child_ns)
+            });
+            let child_ns = child_ns.unwrap_or_else(|| {
+                push_frame_name(&mut path, &self.name);
+                0
+            });
+            let self_ns = inclusive.saturating_sub(child_ns);
+            self.watch.add_folded(&path, self_ns);
+            self_ns
+        });
+        self.watch.record(&self.name, inclusive);
+        self.watch
+            .record_id(&self.watch.suffixed_id(&self.name, "self"), self_ns);
+        inclusive
+    }
+}
+
+/// Append frame names from the outermost in, joined by `;` (which names
+/// may not contain), to `path`.
+fn folded_path(path: &mut String, frames: &[Frame]) {
+    for (i, frame) in frames.iter().enumerate() {
+        if i > 0 {
+            path.push(';');
+        }
+        push_frame_name(path, &frame.name);
+    }
+}
+
+fn push_frame_name(path: &mut String, name: &str) {
+    path.extend(name.chars().map(|c| if c == ';' { '_' } else { c }));
+}
+
+impl Drop for ScopedTimer {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(start) = self.start.take() {
+            let _ = self.finish(start);
         }
     }
 }
+
+impl Watch {
+    /// Start a [`ScopedTimer`] recording inclusive and self time for `name`.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// {
+    ///     let _outer = w.scope("outer");
+    ///     let _inner = w.scope("inner");
+    /// }
+    /// let s = w.snapshot();
+    /// assert!(s["outer.self"].total_ns <= s["outer"].total_ns);
+    /// ```
+    #[inline]
+    pub fn scope(&self, name: impl Into<Arc<str>>) -> ScopedTimer {
+        ScopedTimer::new(self.clone(), name)
+    }
+}
+
+/// Times consecutive phases of one operation, such as the parse, query and
+/// render steps of a request.
+///
+/// Each [`phase`](Self::phase) call ends the running phase and starts the
+/// next. [`finish`](Self::finish) (or drop) ends the last phase and records
+/// the total under `<name>`; every phase is recorded under
+/// `<name>.<phase>`. Time between [`new`](Self::new) and the first
+/// `phase` call counts towards the total only.
+///
+/// # Examples
+/// ```
+/// use benchmark::test_util::MockClock;
+///
+/// let clock = MockClock::new();
+/// let w = clock.watch();
+/// let mut p = w.phases("request");
+/// p.phase("parse");
+/// clock.advance(10);
+/// p.phase("db");
+/// clock.advance(30);
+/// assert_eq!(p.finish(), 40);
+/// let s = w.snapshot();
+/// assert_eq!(s["request.parse"].total_ns, 10);
+/// assert_eq!(s["request.db"].total_ns, 30);
+/// assert_eq!(s["request"].total_ns, 40);
+/// ```
+#[must_use]
+pub struct Phases {
+    watch: Watch,
+    name: Arc<str>,
+    start: u64,
+    // `(metric name, start)` of the running phase.
+    current: Option<(String, u64)>,
+    finished: bool,
+}
+
+impl fmt::Debug for Phases {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Phases")
+            .field("name", &self.name)
+            .field("phase", &self.current.as_ref().map(|(name, _)| name))
+            .field("active", &!self.finished)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Phases {
+    /// Start timing operation `name`; no phase runs until [`phase`](Self::phase).
+    #[inline]
+    pub fn new(watch: Watch, name: impl Into<Arc<str>>) -> Self {
+        let start = watch.now_ns();
+        Self {
+            watch,
+            name: name.into(),
+            start,
+            current: None,
+            finished: false,
+        }
+    }
+
+    /// End the running phase, if any, and start `phase`. Returns the
+    /// nanoseconds recorded for the phase that ended (0 if none).
+    pub fn phase(&mut self, phase: &str) -> u64 {
+        let now = self.watch.now_ns();
+        let ended = self.end_phase(now);
+        self.current = Some((format!("{}.{phase}", self.name), now));
+        ended
+    }
+
+    /// End the last phase and record the total. Returns the total nanoseconds.
+    pub fn finish(mut self) -> u64 {
+        self.complete()
+    }
+
+    fn end_phase(&mut self, now: u64) -> u64 {
+        match self.current.take() {
+            Some((metric, start)) => {
+                let ns = now.saturating_sub(start);
+                self.watch.record(&metric, ns);
+                ns
+            }
+            None => 0,
+        }
+    }
+
+    fn complete(&mut self) -> u64 {
+        if self.finished {
+            return 0;
+        }
+        self.finished = true;
+        let now = self.watch.now_ns();
+        self.end_phase(now);
+        let total = now.saturating_sub(self.start);
+        self.watch.record(&self.name, total);
+        total
+    }
+}
+
+impl Drop for Phases {
+    #[inline]
+    fn drop(&mut self) {
+        let _ = self.complete();
+    }
+}
+
+impl Watch {
+    /// Start timing the phases of operation `name` (see [`Phases`]).
+    #[inline]
+    pub fn phases(&self, name: impl Into<Arc<str>>) -> Phases {
+        Phases::new(self.clone(), name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::MockClock;
+
+    #[test]
+    fn test_time_records_even_on_panic() {
+        let clock = MockClock::new();
+        let w = clock.watch();
+        let out = w.time("ok", || {
+            clock.advance(40);
+            "done"
+        });
+        assert_eq!(out, "done");
+
+        let caught = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            w.time("boom", || {
+                clock.advance(70);
+                panic!("boom");
+            });
+        }));
+        assert!(caught.is_err());
+        let snap = w.snapshot();
+        assert_eq!((snap["ok"].count, snap["ok"].max), (1, 40));
+        assert_eq!((snap["boom"].count, snap["boom"].max), (1, 70));
+    }
+
+    #[test]
+    fn test_paused_timer_starts_and_restarts_on_demand() {
+        let clock = MockClock::new();
+        let w = clock.watch();
+        drop(super::Timer::paused(w.clone(), "never"));
+        assert!(!w.snapshot().contains_key("never"));
+
+        let mut t = super::Timer::paused(w.clone(), "op");
+        assert!(!t.is_running());
+        clock.advance(100);
+        t.start();
+        clock.advance(10);
+        t.start(); // already running: keeps the first start
+        clock.advance(5);
+        assert!(t.is_running());
+        drop(t);
+        assert_eq!(w.snapshot()["op"].total_ns, 15);
+
+        let mut t = w.start("op");
+        clock.advance(40);
+        t.restart();
+        clock.advance(3);
+        assert_eq!(t.stop(), 3);
+        let s = w.snapshot();
+        assert_eq!((s["op"].count, s["op"].total_ns), (2, 18));
+    }
+
+    #[test]
+    fn test_measure_result_splits_ok_and_err() {
+        let clock = MockClock::new();
+        let w = clock.watch();
+        for i in 0..3u64 {
+            let r: Result<u64, u64> = w.measure_result("rpc", || {
+                clock.advance(10);
+                Ok(i)
+            });
+            assert_eq!(r, Ok(i));
+        }
+        let r: Result<(), &str> = w.measure_result("rpc", || {
+            clock.advance(500);
+            Err("timeout")
+        });
+        assert_eq!(r, Err("timeout"));
+        let s = w.snapshot();
+        assert_eq!((s["rpc.ok"].count, s["rpc.ok"].max), (3, 10));
+        assert_eq!((s["rpc.err"].count, s["rpc.err"].max), (1, 500));
+        assert!(!s.contains_key("rpc"));
+        // The derived ids are cached; after `clear` they register again.
+        let inserts = w.internal_stats().slow_path_inserts;
+        let _: Result<(), ()> = w.measure_result("rpc", || Ok(()));
+        assert_eq!(w.internal_stats().slow_path_inserts, inserts);
+        w.clear();
+        let _: Result<(), ()> = w.measure_result("rpc", || Ok(()));
+        assert_eq!(w.snapshot()["rpc.ok"].count, 1);
+    }
+
+    #[test]
+    fn test_scoped_timers_split_inclusive_and_self_time() {
+        let clock = MockClock::new();
+        let w = clock.watch();
+        {
+            let _a = w.scope("a");
+            clock.advance(1);
+            for _ in 0..2 {
+                let _b = w.scope("b");
+                clock.advance(10);
+                let c = w.scope("c");
+                clock.advance(100);
+                assert_eq!(c.stop(), 100);
+            }
+        }
+        let s = w.snapshot();
+        assert_eq!((s["a"].total_ns, s["a.self"].total_ns), (221, 1));
+        assert_eq!((s["b"].total_ns, s["b.self"].total_ns), (220, 20));
+        assert_eq!((s["c"].count, s["c.self"].total_ns), (2, 200));
+        assert_eq!(w.folded_stacks(), "a 1\na;b 20\na;b;c 200\n");
+        w.clear();
+        assert_eq!(w.folded_stacks(), "");
+    }
+
+    #[test]
+    fn test_folded_stacks_sanitize_and_merge_paths() {
+        let clock = MockClock::new();
+        let w = clock.watch();
+        for _ in 0..3 {
+            let _root = w.scope("x;y");
+            clock.advance(2);
+            let _leaf = w.scope(String::from("leaf fn"));
+            clock.advance(4);
+        }
+        assert_eq!(w.folded_stacks(), "x_y 6\nx_y;leaf fn 12\n");
+    }
+
+    #[test]
+    fn test_scoped_timer_out_of_order_and_across_threads() {
+        let clock = MockClock::new();
+        let w = clock.watch();
+        let outer = w.scope("outer");
+        let inner = w.scope("inner");
+        clock.advance(7);
+        // Stopping the outer scope first leaves the inner one on the stack.
+        assert_eq!(outer.stop(), 7);
+        let w2 = w.clone();
+        std::thread::spawn(move || drop(w2.scope("other")))
+            .join()
+            .unwrap();
+        clock.advance(3);
+        drop(inner);
+        let s = w.snapshot();
+        assert_eq!(s["outer.self"].total_ns, 7);
+        assert_eq!(s["inner.self"].total_ns, 10);
+        assert_eq!(s["other.self"].count, 1);
+        super::SCOPES.with(|stack| assert!(stack.borrow().is_empty()));
+    }
+
+    #[test]
+    fn test_phases_record_each_phase_and_total_on_drop() {
+        let clock = MockClock::new();
+        let w = clock.watch();
+        {
+            let mut p = w.phases("job");
+            clock.advance(5); // before the first phase: total only
+            assert_eq!(p.phase("load"), 0);
+            clock.advance(20);
+            assert_eq!(p.phase("save"), 20);
+            clock.advance(7);
+        }
+        let s = w.snapshot();
+        assert_eq!(s["job.load"].total_ns, 20);
+        assert_eq!(s["job.save"].total_ns, 7);
+        assert_eq!((s["job"].count, s["job"].total_ns), (1, 32));
+
+        let p = w.phases("empty");
+        clock.advance(3);
+        assert_eq!(p.finish(), 3);
+        assert_eq!(w.snapshot()["empty"].count, 1);
+        assert!(!w.snapshot().keys().any(|k| k.starts_with("empty.")));
+    }
+
+    #[test]
+    fn test_stopwatch_if_records_only_when_enabled() {
+        let clock = MockClock::new();
+        let w = clock.watch();
+        for enabled in [true, false, true] {
+            let out = crate::stopwatch_if!(w, "op", enabled, {
+                clock.advance(8);
+                enabled
+            });
+            assert_eq!(out, enabled);
+        }
+        let s = w.snapshot();
+        assert_eq!((s["op"].count, s["op"].total_ns), (2, 16));
+    }
+
+    #[test]
+    fn test_stopwatch_timed_returns_recorded_duration() {
+        let clock = MockClock::new();
+        let w = clock.watch();
+        let (out, elapsed) = crate::stopwatch_timed!(w, "op", {
+            clock.advance(45);
+            "done"
+        });
+        assert_eq!((out, elapsed), ("done", crate::Duration::from_nanos(45)));
+        let s = w.snapshot();
+        assert_eq!((s["op"].count, s["op"].total_ns), (1, 45));
+    }
+}
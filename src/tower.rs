@@ -0,0 +1,242 @@
+#![cfg(feature = "tower")]
+//! Tower middleware that records request latency into a [`Watch`].
+//!
+//! [`WatchLayer`] wraps any `tower::Service<http::Request<B>>` (axum, hyper,
+//! tonic, ...) and records, per request, the time from `call` until the
+//! response future resolves, whether it resolves to `Ok` or `Err`. By default
+//! the metric name is `"<METHOD> <path>"`, e.g. `"GET /users/42"`; use
+//! [`WatchLayer::key_fn`] to group by route template instead of raw path,
+//! which keeps cardinality bounded.
+//!
+//! Timestamps come from the Watch's clock. A response future dropped before
+//! completing (client went away, timeout) records nothing. With `metrics`
+//! disabled the service is a pass-through.
+//!
+//! # Examples
+//! ```
+//! use benchmark::{tower::WatchLayer, Watch};
+//! let w = Watch::new();
+//! let layer = WatchLayer::new(w.clone());
+//! // axum: `Router::new().route(...).layer(layer)`
+//! // tower: `ServiceBuilder::new().layer(layer).service(svc)`
+//! # let _ = layer;
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http::request::Parts;
+use http::Request;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::Watch;
+
+/// Metric-name extractor shared by a layer and every service it builds.
+type KeyFn = Arc<dyn Fn(&Parts) -> String + Send + Sync>;
+
+/// Default key: `"<METHOD> <path>"`, without the query string.
+fn method_path(parts: &Parts) -> String {
+    format!("{} {}", parts.method, parts.uri.path())
+}
+
+/// A [`Layer`] that times requests into a [`Watch`] (see the [module docs](self)).
+#[derive(Clone)]
+pub struct WatchLayer {
+    watch: Watch,
+    key: KeyFn,
+}
+
+impl fmt::Debug for WatchLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WatchLayer").finish_non_exhaustive()
+    }
+}
+
+impl WatchLayer {
+    /// Record into `watch`, keyed by `"<METHOD> <path>"`.
+    pub fn new(watch: Watch) -> Self {
+        Self {
+            watch,
+            key: Arc::new(method_path),
+        }
+    }
+
+    /// Derive the metric name from the request head instead.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{tower::WatchLayer, Watch};
+    /// // Group by method only; a router could read its matched route from
+    /// // `parts.extensions` here.
+    /// let layer = WatchLayer::new(Watch::new()).key_fn(|parts| parts.method.to_string());
+    /// # let _ = layer;
+    /// ```
+    #[must_use]
+    pub fn key_fn<F>(mut self, key: F) -> Self
+    where
+        F: Fn(&Parts) -> String + Send + Sync + 'static,
+    {
+        self.key = Arc::new(key);
+        self
+    }
+}
+
+impl<S> Layer<S> for WatchLayer {
+    type Service = WatchService<S>;
+
+    fn layer(&self, inner: S) -> WatchService<S> {
+        WatchService {
+            inner,
+            watch: self.watch.clone(),
+            key: Arc::clone(&self.key),
+        }
+    }
+}
+
+/// Service produced by [`WatchLayer`].
+#[derive(Clone)]
+pub struct WatchService<S> {
+    inner: S,
+    watch: Watch,
+    key: KeyFn,
+}
+
+impl<S: fmt::Debug> fmt::Debug for WatchService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WatchService")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, B> Service<Request<B>> for WatchService<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let (parts, body) = req.into_parts();
+        let key = (self.key)(&parts);
+        let start = self.watch.now_ns();
+        ResponseFuture {
+            inner: Box::pin(self.inner.call(Request::from_parts(parts, body))),
+            watch: self.watch.clone(),
+            key,
+            start,
+        }
+    }
+}
+
+/// Response future of [`WatchService`]; records the latency when it resolves.
+#[must_use = "futures do nothing unless polled"]
+pub struct ResponseFuture<F> {
+    inner: Pin<Box<F>>,
+    watch: Watch,
+    key: String,
+    start: u64,
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F: Future> Future for ResponseFuture<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let this = &mut *self;
+        let out = this.inner.as_mut().poll(cx);
+        if out.is_ready() {
+            let elapsed = this.watch.now_ns().saturating_sub(this.start);
+            this.watch.record(&this.key, elapsed);
+        }
+        out
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+    use std::convert::Infallible;
+    use std::future::Ready;
+
+    /// Advances the clock by the request's body, then answers with its path.
+    #[derive(Clone)]
+    struct Svc(ManualClock);
+
+    impl Service<Request<u64>> for Svc {
+        type Response = String;
+        type Error = Infallible;
+        type Future = Ready<Result<String, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: Request<u64>) -> Self::Future {
+            self.0.advance(*req.body());
+            std::future::ready(Ok(req.uri().path().to_owned()))
+        }
+    }
+
+    fn request(method: &str, uri: &str, cost: u64) -> Request<u64> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(cost)
+            .unwrap()
+    }
+
+    fn run<F: Future>(f: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(f)
+    }
+
+    #[test]
+    fn test_layer_records_by_method_and_path() {
+        let clock = ManualClock::new();
+        let w = Watch::builder().clock(clock.clone()).build();
+        let mut svc = WatchLayer::new(w.clone()).layer(Svc(clock));
+        assert_eq!(run(svc.call(request("GET", "/a?x=1", 100))).unwrap(), "/a");
+        run(svc.call(request("GET", "/a", 300))).unwrap();
+        run(svc.call(request("POST", "/a", 50))).unwrap();
+        let s = w.snapshot();
+        assert_eq!(s.len(), 2);
+        assert_eq!(s["GET /a"].count, 2);
+        assert_eq!(s["GET /a"].total_ns, 400);
+        assert_eq!(s["POST /a"].max, 50);
+    }
+
+    #[test]
+    fn test_custom_key_and_unpolled_future() {
+        let clock = ManualClock::new();
+        let w = Watch::builder().clock(clock.clone()).build();
+        let mut svc = WatchLayer::new(w.clone())
+            .key_fn(|p| format!("http.{}", p.method.as_str().to_lowercase()))
+            .layer(Svc(clock));
+        run(svc.call(request("DELETE", "/users/7", 10))).unwrap();
+        drop(svc.call(request("DELETE", "/users/8", 10)));
+        let s = w.snapshot();
+        assert_eq!(s.len(), 1);
+        assert_eq!(s["http.delete"].count, 1);
+    }
+}
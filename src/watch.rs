@@ -1,16 +1,25 @@
 #![cfg(all(feature = "std", feature = "metrics"))]
 
 use core::marker::PhantomData;
-#[cfg(feature = "parking-lot-locks")]
-use parking_lot::RwLock;
-use std::collections::HashMap;
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
-use std::sync::Arc;
-#[cfg(not(feature = "parking-lot-locks"))]
-use std::sync::RwLock;
-use std::time::Instant;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration as StdDuration, Instant};
 
+use crate::anomaly::{self, Anomaly};
+use crate::apdex::ApdexCounter;
+use crate::clock::{self, Clock};
+use crate::counter::StripedCounter;
+use crate::filter::FilterCache;
+use crate::heatmap::{self, Heatmap};
 use crate::hist_backend::HistBackend;
+use crate::histogram::{HistogramSnapshot, RecordInfo};
+use crate::rate::RateWindow;
+use crate::sampling::Sampler;
+use crate::slowest::{self, SlowOp, SlowSample, SlowestWindow};
 #[cfg(feature = "trace")]
 use crate::trace;
 
@@ -20,22 +29,213 @@ type Backend = crate::hist_hdr::Histogram;
 #[cfg(not(feature = "hdr"))]
 type Backend = crate::histogram::FastHistogram;
 
-// Normalize guard types across lock backends at module scope
-#[cfg(feature = "parking-lot-locks")]
-type ReadGuard<'a, B> = parking_lot::RwLockReadGuard<'a, HashMap<Arc<str>, Arc<B>>>;
-#[cfg(not(feature = "parking-lot-locks"))]
-type ReadGuard<'a, B> = std::sync::RwLockReadGuard<'a, HashMap<Arc<str>, Arc<B>>>;
+/// Metrics keyed by name.
+type MetricMap<B> = HashMap<Arc<str>, Arc<Metric<B>>>;
 
-#[cfg(feature = "parking-lot-locks")]
-type WriteGuard<'a, B> = parking_lot::RwLockWriteGuard<'a, HashMap<Arc<str>, Arc<B>>>;
-#[cfg(not(feature = "parking-lot-locks"))]
-type WriteGuard<'a, B> = std::sync::RwLockWriteGuard<'a, HashMap<Arc<str>, Arc<B>>>;
+/// Metrics removed by eviction, with their names.
+type Evicted<B> = Vec<(Arc<str>, Arc<Metric<B>>)>;
+
+// Normalize the publish-cell type across lock backends at module scope
+#[cfg(feature = "lock-free")]
+type MapLock<B> = crate::arc_cell::ArcCell<Published<B>>;
+#[cfg(all(feature = "parking-lot-locks", not(feature = "lock-free")))]
+type MapLock<B> = parking_lot::Mutex<Arc<Published<B>>>;
+#[cfg(not(any(feature = "parking-lot-locks", feature = "lock-free")))]
+type MapLock<B> = Mutex<Arc<Published<B>>>;
+
+/// A captured `(epoch_ns, stats)` history entry.
+type HistoryEntry = (u128, WatchStats);
+
+/// Per-metric history rings.
+type HistoryMap = HashMap<Arc<str>, History>;
+
+/// `(suffix, id)` of each derived `<name>.<suffix>` metric, by base name.
+type SuffixedIds = HashMap<Arc<str>, Vec<(&'static str, MetricId)>>;
+
+/// Retained history of one metric.
+#[derive(Default)]
+struct History {
+    /// Cumulative stats at each capture.
+    stats: VecDeque<HistoryEntry>,
+    /// Bucket counts recorded between consecutive captures, for heatmaps.
+    intervals: VecDeque<(u128, HistogramSnapshot)>,
+    /// Contents at the latest capture, to diff the next one against.
+    last: HistogramSnapshot,
+}
+
+impl History {
+    /// Drop the oldest entries until at most `capacity` remain.
+    fn trim(&mut self, capacity: usize) {
+        while self.stats.len() > capacity {
+            self.stats.pop_front();
+        }
+        while self.intervals.len() > capacity {
+            self.intervals.pop_front();
+        }
+    }
+}
+
+/// Precomputed `snapshot()` result shared with readers.
+type StatsTable = Arc<HashMap<String, WatchStats>>;
+
+/// Callback invoked with `(metric_name, anomaly)` when history capture flags a shift.
+type AnomalyHook = Arc<dyn Fn(&str, &Anomaly) + Send + Sync>;
+
+/// Callback invoked with `(metric_name, recorded_ns)` for records over a threshold.
+type ThresholdHook = Arc<dyn Fn(&str, u64) + Send + Sync>;
 
 /// Default lowest discernible value (1ns)
 const DEFAULT_LOWEST: u64 = 1;
 /// Default highest trackable value (~1 hour in ns)
 const DEFAULT_HIGHEST: u64 = 3_600_000_000_000;
-// Note: precision is fixed internally for performance; no configurable sigfig.
+// Precision is the backend default unless a `MetricConfig` overrides it.
+/// Published maps cached per thread (one slot per recently used Watch).
+const MAP_CACHE_SLOTS: usize = 8;
+
+/// Source of unique `Inner` ids used to key the per-thread map cache.
+static NEXT_WATCH_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A registered metric: its histogram plus last-use time for eviction.
+struct Metric<B> {
+    hist: B,
+    // Clamping bounds: the Watch-wide ones unless overridden by a `MetricConfig`.
+    lowest: u64,
+    highest: u64,
+    // Watch clock reading at registration.
+    registered: u64,
+    // Clock readings + 1 (0 while unset) of the first kept sample and of the
+    // latest one; `last_used` is only maintained when `timed`.
+    first_seen: AtomicU64,
+    last_used: AtomicU64,
+    // Read the clock on every kept sample: eviction, rates, slowest tracking
+    // or `track_last_seen` (Watch-wide or per metric) need it.
+    timed: bool,
+    // Per-second call counts; present when rate tracking is enabled.
+    rates: Option<Box<RateWindow>>,
+    // Slowest recent samples; present when slowest tracking is enabled.
+    slowest: Option<Box<SlowestWindow>>,
+    // Apdex zone counts; present when the metric has an Apdex target.
+    apdex: Option<Box<ApdexCounter>>,
+    // 1-in-N sampling state; present when the metric's sample rate is above 1.
+    sampler: Option<Box<Sampler>>,
+    // Kept samples raised to `lowest` / lowered to `highest`.
+    clamped_low: AtomicU64,
+    clamped_high: AtomicU64,
+    // Whether the process-wide `enable_only` filter lets this metric record.
+    filter: FilterCache,
+}
+
+impl<B> Metric<B> {
+    /// Note a kept sample at clock reading `now`.
+    #[inline]
+    fn seen(&self, now: u64) {
+        let stamp = now.saturating_add(1);
+        if self.first_seen.load(Ordering::Relaxed) == 0 {
+            let _ =
+                self.first_seen
+                    .compare_exchange(0, stamp, Ordering::Relaxed, Ordering::Relaxed);
+        }
+        if self.timed {
+            self.last_used.store(stamp, Ordering::Relaxed);
+        }
+    }
+
+    /// Clock reading of the latest record, or of registration if none is
+    /// known; what TTL and LRU eviction compare.
+    #[inline]
+    fn last_active(&self) -> u64 {
+        self.last_used
+            .load(Ordering::Relaxed)
+            .checked_sub(1)
+            .unwrap_or(self.registered)
+    }
+}
+
+/// Immutable published state; replaced wholesale (copy-on-write) when
+/// metrics, ids or configs are added or removed. Derefs to the map by name.
+struct Published<B> {
+    map: MetricMap<B>,
+    // Metric of each `MetricId` index, `None` while that name is not registered.
+    by_id: Vec<Option<Arc<Metric<B>>>>,
+    // Names by `MetricId` index; append-only.
+    id_names: Vec<Arc<str>>,
+    // Per-metric overrides applied when a metric is registered.
+    configs: HashMap<Arc<str>, MetricConfig>,
+    // Matches `Inner::generation` once this table is fully published.
+    generation: u64,
+}
+
+impl<B> Published<B> {
+    fn empty() -> Self {
+        Self {
+            map: HashMap::new(),
+            by_id: Vec::new(),
+            id_names: Vec::new(),
+            configs: HashMap::new(),
+            generation: 0,
+        }
+    }
+
+    /// A mutable copy to build the next generation from.
+    fn draft(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+            by_id: Vec::new(),
+            id_names: self.id_names.clone(),
+            configs: self.configs.clone(),
+            generation: self.generation + 1,
+        }
+    }
+
+    /// Resolve `by_id` against the final map of a draft.
+    fn finish(mut self) -> Self {
+        self.by_id = self
+            .id_names
+            .iter()
+            .map(|name| self.map.get(name).cloned())
+            .collect();
+        self
+    }
+}
+
+impl<B> std::ops::Deref for Published<B> {
+    type Target = MetricMap<B>;
+
+    fn deref(&self) -> &MetricMap<B> {
+        &self.map
+    }
+}
+
+/// A pre-registered metric name for [`Watch::record_id`](WatchGeneric::record_id).
+///
+/// Recording through an id indexes the Watch's metric table directly instead
+/// of hashing the name. Ids stay valid across [`clear`](WatchGeneric::clear)
+/// and eviction (the metric is registered again on the next record); an id
+/// used with a different Watch records by name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MetricId {
+    watch_id: u64,
+    index: usize,
+    name: Arc<str>,
+}
+
+impl MetricId {
+    /// The metric name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A thread's view of one Watch's published map, stamped with its generation.
+struct CachedMap {
+    watch_id: u64,
+    generation: u64,
+    map: Arc<dyn Any + Send + Sync>,
+}
+
+thread_local! {
+    static MAP_CACHE: RefCell<Vec<CachedMap>> = const { RefCell::new(Vec::new()) };
+}
 
 /// Central, thread-safe metrics collector for production timing.
 ///
@@ -43,6 +243,24 @@ const DEFAULT_HIGHEST: u64 = 3_600_000_000_000;
 /// recording and percentile queries via `snapshot()`. Cheap to clone, safe to
 /// share across threads and async tasks.
 ///
+/// # Concurrency
+/// The metric map is immutable once published: registering or clearing a
+/// metric copies it under a small publish lock and bumps a generation
+/// counter. Each thread caches the map it last saw together with that
+/// generation, so `record` and `snapshot` only read an atomic and take no
+/// lock unless a metric was added or removed since the thread's last call.
+/// Registration costs O(metrics); pre-register hot names at startup with
+/// [`register`](Self::register), whose [`MetricId`] also skips hashing the
+/// name on every [`record_id`](Self::record_id). Each thread keeps the last
+/// few maps it used alive until they are superseded or evicted.
+///
+/// With the `lock-free` feature the map is published through an atomic
+/// pointer instead: refreshing a stale cache is a pointer load, and
+/// registering a metric is a compare-and-swap that retries if another
+/// thread published first, so `record` never takes a lock, even for a new
+/// name. (Eviction bookkeeping and threshold hooks still lock their own
+/// side tables.)
+///
 /// # Examples
 /// Basic record and snapshot:
 /// ```
@@ -77,11 +295,153 @@ impl<B: HistBackend> Clone for WatchGeneric<B> {
 }
 
 struct Inner<B: HistBackend> {
-    // Store Arc<Histogram> to allow lock-free record on hot path
+    // Current published map; only accessed to publish a new one or refresh a
+    // stale thread cache (without a lock under the `lock-free` feature).
     // Keyed by Arc<str> to avoid repeated String allocations and enable cheap sharing.
-    hist: RwLock<HashMap<Arc<str>, Arc<B>>>,
+    hist: MapLock<B>,
+    // Raised to each table's generation after publishing it; threads compare
+    // it with their cached copy.
+    generation: AtomicU64,
+    // Key for this Watch's entries in the per-thread map cache.
+    id: u64,
     lowest: u64,
     highest: u64,
+    // Bounded per-metric ring of past summaries; capacity 0 means disabled.
+    history: Mutex<HistoryMap>,
+    history_capacity: AtomicUsize,
+    // Bumped on every `enable_history` call so superseded sampler threads exit.
+    history_generation: AtomicU64,
+    // Registered `(sensitivity, callback)` pairs evaluated on each history capture.
+    anomaly_hooks: Mutex<Vec<(f64, AnomalyHook)>>,
+    // Registered `(metric, threshold_ns, callback)` triples checked by `record`.
+    threshold_hooks: Mutex<Vec<(Arc<str>, u64, ThresholdHook)>>,
+    // Lowest registered threshold (`u64::MAX` if none): records at or below
+    // it skip the hook lookup with a single atomic load.
+    threshold_floor: AtomicU64,
+    // Custom time source for `Timer`s; `None` uses the `Instant`-based default.
+    clock: Option<Arc<dyn Clock>>,
+    // `(epoch_ns, clock_ns)` read together at construction, mapping clock
+    // readings to wall-clock time for `first_seen_ns` / `last_seen_ns`.
+    epoch_anchor: (u128, u64),
+    // Eviction limits; `evicting` caches `max_metrics.is_some() || ttl_ns.is_some()`.
+    max_metrics: Option<usize>,
+    ttl_ns: Option<u64>,
+    evicting: bool,
+    // Metrics removed by LRU/TTL eviction so far, and the samples they held.
+    dropped: AtomicU64,
+    evicted_samples: AtomicU64,
+    // Self-metrics reported by `internal_stats`.
+    records: StripedCounter,
+    sampled_out: StripedCounter,
+    slow_path_inserts: AtomicU64,
+    lock_waits: AtomicU64,
+    // Track per-second call counts for `rate_1s` / `rate_1m`.
+    rates: bool,
+    // Keep `last_seen_ns` current for every metric, not just configured ones.
+    track_last_seen: bool,
+    // `(k, window_ns)` for slowest-sample tracking.
+    slowest: Option<(usize, u64)>,
+    // Keep one record in this many (1 = all) unless a `MetricConfig` overrides it.
+    sample_rate: u64,
+    // Per-metric histogram state at the previous `snapshot_delta` call.
+    last_scrape: Mutex<HashMap<Arc<str>, HistogramSnapshot>>,
+    // Latest background-computed snapshot; `None` means compute on demand.
+    precomputed: Mutex<Option<StatsTable>>,
+    // Bumped on every `enable_precompute` call so superseded refresh threads exit.
+    precompute_generation: AtomicU64,
+    // Where the next `snapshot_with_budget` starts, so truncated scrapes rotate.
+    budget_cursor: AtomicUsize,
+    // Self time per `;`-joined `ScopedTimer` path, for `folded_stacks`.
+    folded: Mutex<HashMap<String, u64>>,
+    // `(suffix, id)` of the derived `<name>.<suffix>` metrics recorded by
+    // `measure_result` and `ScopedTimer`, so they need no formatting per record.
+    suffixed: Mutex<SuffixedIds>,
+}
+
+/// Result of [`Watch::snapshot_with_budget`](WatchGeneric::snapshot_with_budget).
+#[derive(Debug, Clone, Default)]
+pub struct PartialSnapshot {
+    /// Fully computed stats for the metrics that fit in the budget.
+    pub stats: HashMap<String, WatchStats>,
+    /// True if some metrics were skipped because the budget ran out.
+    pub truncated: bool,
+}
+
+/// Health of a Watch's own instrumentation, from
+/// [`Watch::internal_stats`](WatchGeneric::internal_stats).
+///
+/// All counters are cumulative since the Watch was created; `clear` and
+/// resets do not zero them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InternalStats {
+    /// Values passed to `record`, `record_id` and the bulk `record_*` calls.
+    pub records: u64,
+    /// Records skipped by 1-in-N sampling.
+    pub sampled_out: u64,
+    /// Records whose metric was not yet registered and took the slow path
+    /// that publishes a new metric table.
+    pub slow_path_inserts: u64,
+    /// Times a Watch lock was already held and had to be waited for. With
+    /// the `lock-free` feature this also counts table publishes that lost a
+    /// race and were retried.
+    pub lock_waits: u64,
+    /// Metrics removed by LRU/TTL eviction (as [`Watch::dropped_metrics`](WatchGeneric::dropped_metrics)).
+    pub evicted_metrics: u64,
+    /// Samples held by metrics when they were evicted.
+    pub evicted_samples: u64,
+}
+
+/// Per-metric histogram settings for [`Watch::configure_metric`](WatchGeneric::configure_metric).
+///
+/// `None` fields inherit the Watch-wide setting.
+///
+/// # Examples
+/// ```
+/// use benchmark::{MetricConfig, Watch};
+///
+/// let w = Watch::builder().highest(1_000_000_000).build(); // RPCs: up to 1s
+/// w.configure_metric(
+///     "batch.job",
+///     MetricConfig {
+///         highest: Some(600_000_000_000), // up to 10 minutes
+///         ..MetricConfig::default()
+///     },
+/// );
+/// w.record("rpc", 5_000_000_000);
+/// w.record("batch.job", 5_000_000_000);
+/// let s = w.snapshot();
+/// assert!(s["rpc"].max < 2_000_000_000); // clamped to the Watch bound (1s)
+/// assert!(s["batch.job"].max >= 5_000_000_000);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetricConfig {
+    /// Lowest discernible value (ns, min 1); smaller samples are raised to
+    /// it and counted in [`WatchStats::clamped_low`].
+    pub lowest: Option<u64>,
+    /// Highest trackable value (ns); larger samples are clamped to it and
+    /// counted in [`WatchStats::clamped_high`].
+    pub highest: Option<u64>,
+    /// Histogram sub-buckets per power of two (see
+    /// [`FastHistogram::with_sub_buckets`](crate::histogram::FastHistogram::with_sub_buckets));
+    /// ignored by the `hdr` backend, which always keeps 3 significant figures.
+    pub sub_buckets: Option<usize>,
+    /// Apdex / SLO target `T` (ns): samples up to `T` count as satisfied,
+    /// up to `4T` as tolerating and above that as frustrated. Enables the
+    /// `apdex*` and `under_target_pct` fields of [`WatchStats`].
+    pub apdex_target: Option<u64>,
+    /// Keep one record in this many; see
+    /// [`WatchBuilder::sample_rate`](WatchBuilderGeneric::sample_rate).
+    pub sample_rate: Option<u64>,
+    /// Keep the sum of each histogram bucket so percentiles interpolate
+    /// around the bucket's real mean (see
+    /// [`FastHistogram::with_bucket_sums`](crate::histogram::FastHistogram::with_bucket_sums));
+    /// ignored by the `hdr` backend.
+    pub bucket_sums: bool,
+    /// Read the clock on every record to keep [`WatchStats::last_seen_ns`]
+    /// and [`Watch::idle_for`](WatchGeneric::idle_for) current. Implied by
+    /// [`WatchBuilder::track_last_seen`](WatchBuilderGeneric::track_last_seen),
+    /// eviction, rates and slowest tracking; otherwise `last_seen_ns` is 0.
+    pub track_last_seen: bool,
 }
 
 /// Snapshot stats for a single metric.
@@ -105,44 +465,252 @@ pub struct WatchStats {
     pub p999: u64,
     /// Arithmetic mean (ns).
     pub mean: f64,
+    /// Total of all samples (ns), e.g. for time-budget breakdowns.
+    pub total_ns: u64,
+    /// Population standard deviation (ns). Exact for full snapshots,
+    /// estimated from bucket midpoints for interval (delta) stats.
+    pub stddev: f64,
+    /// Calls in the last complete second (0 unless rate tracking is enabled).
+    pub rate_1s: f64,
+    /// Mean calls per second over the last 60 complete seconds (0 unless rate
+    /// tracking is enabled).
+    pub rate_1m: f64,
+    /// Wall-clock time of the metric's first record since it was registered
+    /// (ns since the UNIX epoch, derived from the Watch clock; 0 until it
+    /// records).
+    pub first_seen_ns: u128,
+    /// Wall-clock time of the metric's latest record (ns since the UNIX
+    /// epoch, derived from the Watch clock); 0 until it records, or if the
+    /// metric does not track it. Off by default; see
+    /// [`WatchBuilder::track_last_seen`](WatchBuilderGeneric::track_last_seen).
+    pub last_seen_ns: u128,
+    /// Apdex target `T` (ns) from [`MetricConfig::apdex_target`]; 0 if the
+    /// metric has none, in which case the other Apdex fields are 0 too.
+    pub apdex_target_ns: u64,
+    /// Samples at or under the target.
+    pub apdex_satisfied: u64,
+    /// Samples over the target but at most 4x the target.
+    pub apdex_tolerating: u64,
+    /// Samples over 4x the target.
+    pub apdex_frustrated: u64,
+    /// Apdex score in `[0, 1]`: `(satisfied + tolerating / 2) / samples`.
+    pub apdex: f64,
+    /// Percentage of samples at or under the target (0-100).
+    pub under_target_pct: f64,
+    /// Samples below the metric's lowest discernible value, recorded as that
+    /// value. Cumulative since the metric was registered.
+    pub clamped_low: u64,
+    /// Samples above the metric's highest trackable value, recorded as that
+    /// value; non-zero means the percentiles near the top are understated
+    /// and the bound should be raised. Cumulative since the metric was
+    /// registered.
+    pub clamped_high: u64,
+}
+
+impl Default for WatchStats {
+    /// All-zero stats, as reported for a metric with no samples.
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl WatchStats {
+    pub(crate) const EMPTY: Self = Self {
+        count: 0,
+        min: 0,
+        max: 0,
+        p50: 0,
+        p90: 0,
+        p95: 0,
+        p99: 0,
+        p999: 0,
+        mean: 0.0,
+        total_ns: 0,
+        stddev: 0.0,
+        rate_1s: 0.0,
+        rate_1m: 0.0,
+        first_seen_ns: 0,
+        last_seen_ns: 0,
+        apdex_target_ns: 0,
+        apdex_satisfied: 0,
+        apdex_tolerating: 0,
+        apdex_frustrated: 0,
+        apdex: 0.0,
+        under_target_pct: 0.0,
+        clamped_low: 0,
+        clamped_high: 0,
+    };
+
+    /// Every field comes from one consistent
+    /// [`snapshot`](HistBackend::snapshot), which carries the backend's
+    /// per-bucket sums and sum of squares.
+    fn from_hist<B: HistBackend>(h: &B) -> Self {
+        Self::from_snapshot(&h.snapshot())
+    }
+
+    pub(crate) fn from_snapshot(s: &HistogramSnapshot) -> Self {
+        if s.is_empty() {
+            return Self::EMPTY;
+        }
+        let min = s.min().unwrap_or(0);
+        let max = s.max().unwrap_or(0);
+        Self {
+            count: s.count(),
+            min,
+            max,
+            p50: s.percentile(0.50).unwrap_or(min),
+            p90: s.percentile(0.90).unwrap_or(max),
+            p95: s.percentile(0.95).unwrap_or(max),
+            p99: s.percentile(0.99).unwrap_or(max),
+            p999: s.percentile(0.999).unwrap_or(max),
+            mean: s.mean().unwrap_or(0.0),
+            total_ns: s.sum(),
+            stddev: s.stddev().unwrap_or(0.0),
+            ..Self::EMPTY
+        }
+    }
 }
 
 impl<B: HistBackend> fmt::Debug for WatchGeneric<B> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let len = self.read_hist().len();
+        let len = self.load_map().len();
         f.debug_struct("Watch").field("metrics_len", &len).finish()
     }
 }
 
 impl<B: HistBackend> WatchGeneric<B> {
-    #[cfg(feature = "parking-lot-locks")]
+    /// Snapshot key reporting evicted metrics (in `count`) when
+    /// [`max_metrics`](WatchBuilderGeneric::max_metrics) or
+    /// [`ttl`](WatchBuilderGeneric::ttl) is set.
+    pub const DROPPED_METRICS: &'static str = "benchmark.dropped_metrics";
+
+    /// The latest published table.
+    #[cfg(feature = "lock-free")]
     #[inline]
-    fn read_hist(&self) -> ReadGuard<'_, B> {
-        self.inner.hist.read()
+    fn current(&self) -> Arc<Published<B>> {
+        self.inner.hist.load()
     }
 
-    #[cfg(not(feature = "parking-lot-locks"))]
+    #[cfg(all(feature = "parking-lot-locks", not(feature = "lock-free")))]
     #[inline]
-    fn read_hist(&self) -> ReadGuard<'_, B> {
-        self.inner
-            .hist
-            .read()
-            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    fn current(&self) -> Arc<Published<B>> {
+        Arc::clone(&self.lock_map())
     }
 
-    #[cfg(feature = "parking-lot-locks")]
+    #[cfg(not(any(feature = "parking-lot-locks", feature = "lock-free")))]
     #[inline]
-    fn write_hist(&self) -> WriteGuard<'_, B> {
-        self.inner.hist.write()
+    fn current(&self) -> Arc<Published<B>> {
+        Arc::clone(&self.lock(&self.inner.hist))
+    }
+
+    #[cfg(all(feature = "parking-lot-locks", not(feature = "lock-free")))]
+    fn lock_map(&self) -> parking_lot::MutexGuard<'_, Arc<Published<B>>> {
+        self.inner.hist.try_lock().unwrap_or_else(|| {
+            self.inner.lock_waits.fetch_add(1, Ordering::Relaxed);
+            self.inner.hist.lock()
+        })
     }
 
-    #[cfg(not(feature = "parking-lot-locks"))]
+    /// Lock `m`, counting a lock wait if it is already held; recovers the
+    /// data if a previous holder panicked.
+    fn lock<'a, T>(&self, m: &'a Mutex<T>) -> std::sync::MutexGuard<'a, T> {
+        match m.try_lock() {
+            Ok(guard) => guard,
+            Err(std::sync::TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(std::sync::TryLockError::WouldBlock) => {
+                self.inner.lock_waits.fetch_add(1, Ordering::Relaxed);
+                m.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+            }
+        }
+    }
+
+    /// Run `f` on this thread's cached copy of the published map, refreshing
+    /// it first if a newer generation has been published.
+    ///
+    /// `f` runs after the cache borrow is released, so it may record into
+    /// any Watch (a [`Clock`] read inside a record can, too).
     #[inline]
-    fn write_hist(&self) -> WriteGuard<'_, B> {
+    fn with_map<R>(&self, f: impl FnOnce(&Arc<dyn Any + Send + Sync>) -> R) -> R {
+        let generation = self.inner.generation.load(Ordering::Acquire);
+        let id = self.inner.id;
+        let map = MAP_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let pos = cache.iter().position(|c| c.watch_id == id);
+            let idx = match pos {
+                Some(i) if cache[i].generation == generation => i,
+                _ => {
+                    let map = self.current();
+                    let entry = CachedMap {
+                        watch_id: id,
+                        generation: map.generation,
+                        map: map as Arc<dyn Any + Send + Sync>,
+                    };
+                    if let Some(i) = pos {
+                        cache[i] = entry;
+                        i
+                    } else {
+                        if cache.len() >= MAP_CACHE_SLOTS {
+                            cache.remove(0);
+                        }
+                        cache.push(entry);
+                        cache.len() - 1
+                    }
+                }
+            };
+            Arc::clone(&cache[idx].map)
+        });
+        f(&map)
+    }
+
+    /// The published metric map as seen by this thread.
+    fn load_map(&self) -> Arc<Published<B>> {
+        self.with_map(Arc::clone)
+            .downcast::<Published<B>>()
+            .unwrap_or_else(|_| self.current())
+    }
+
+    /// Publish a modified copy of the metric map.
+    fn update_map<R>(&self, mut f: impl FnMut(&mut MetricMap<B>) -> R) -> R {
+        self.publish(|next| f(&mut next.map))
+    }
+
+    /// Publish a modified copy of the whole table.
+    ///
+    /// Under the `lock-free` feature concurrent publishers race with a
+    /// compare-and-swap and the loser re-runs `f` on the winner's table, so
+    /// `f` must only change the draft it is given.
+    #[cfg(feature = "lock-free")]
+    fn publish<R>(&self, mut f: impl FnMut(&mut Published<B>) -> R) -> R {
+        loop {
+            let current = self.current();
+            let mut next = current.draft();
+            let out = f(&mut next);
+            let next = Arc::new(next.finish());
+            let generation = next.generation;
+            if self.inner.hist.compare_exchange(&current, next).is_ok() {
+                self.inner
+                    .generation
+                    .fetch_max(generation, Ordering::AcqRel);
+                return out;
+            }
+            self.inner.lock_waits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Publish a modified copy of the whole table.
+    #[cfg(not(feature = "lock-free"))]
+    fn publish<R>(&self, mut f: impl FnMut(&mut Published<B>) -> R) -> R {
+        #[cfg(feature = "parking-lot-locks")]
+        let mut guard = self.lock_map();
+        #[cfg(not(feature = "parking-lot-locks"))]
+        let mut guard = self.lock(&self.inner.hist);
+        let mut next = guard.draft();
+        let out = f(&mut next);
+        *guard = Arc::new(next.finish());
         self.inner
-            .hist
-            .write()
-            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .generation
+            .fetch_max(guard.generation, Ordering::AcqRel);
+        out
     }
 
     /// Create a new Watch with sensible defaults.
@@ -183,250 +751,2516 @@ impl<B: HistBackend> WatchGeneric<B> {
     /// let _ = w.snapshot();
     /// ```
     pub fn with_bounds(lowest_discernible: u64, highest_trackable: u64) -> Self {
-        let lowest = lowest_discernible.max(1);
-        let highest = highest_trackable.max(lowest + 1);
+        Self::builder()
+            .lowest(lowest_discernible)
+            .highest(highest_trackable)
+            .build()
+    }
+
+    fn from_builder(b: WatchBuilderGeneric<B>) -> Self {
+        let lowest = b.lowest.max(1);
+        let highest = b.highest.max(lowest + 1);
+        let ttl_ns = b
+            .ttl
+            .map(|d| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX));
+        let clock_now = b
+            .clock
+            .as_ref()
+            .map_or_else(clock::system_now_ns, |c| c.now_ns());
         Self {
             inner: Arc::new(Inner {
-                hist: RwLock::new(HashMap::new()),
+                hist: MapLock::new(Arc::new(Published::empty())),
+                generation: AtomicU64::new(0),
+                id: NEXT_WATCH_ID.fetch_add(1, Ordering::Relaxed),
                 lowest,
                 highest,
+                history: Mutex::new(HashMap::new()),
+                history_capacity: AtomicUsize::new(0),
+                history_generation: AtomicU64::new(0),
+                anomaly_hooks: Mutex::new(Vec::new()),
+                threshold_hooks: Mutex::new(Vec::new()),
+                threshold_floor: AtomicU64::new(u64::MAX),
+                last_scrape: Mutex::new(HashMap::new()),
+                precomputed: Mutex::new(None),
+                precompute_generation: AtomicU64::new(0),
+                budget_cursor: AtomicUsize::new(0),
+                folded: Mutex::new(HashMap::new()),
+                suffixed: Mutex::new(HashMap::new()),
+                clock: b.clock,
+                epoch_anchor: (now_epoch_nanos(), clock_now),
+                max_metrics: b.max_metrics,
+                ttl_ns,
+                evicting: b.max_metrics.is_some() || ttl_ns.is_some(),
+                dropped: AtomicU64::new(0),
+                evicted_samples: AtomicU64::new(0),
+                records: StripedCounter::default(),
+                sampled_out: StripedCounter::default(),
+                slow_path_inserts: AtomicU64::new(0),
+                lock_waits: AtomicU64::new(0),
+                rates: b.rates,
+                track_last_seen: b.track_last_seen,
+                slowest: b
+                    .slowest
+                    .map(|(k, w)| (k, u64::try_from(w.as_nanos()).unwrap_or(u64::MAX))),
+                sample_rate: b.sample_rate,
             }),
         }
     }
 
-    /// Record a duration in nanoseconds for a metric name.
+    /// Register `name` (if needed) and return an id for
+    /// [`record_id`](Self::record_id).
     ///
-    /// Safe, thread-safe, and minimal overhead.
-    ///
-    /// # Poisoning
-    /// If the internal lock has been poisoned due to a prior panic, this method
-    /// will recover the inner data and continue operating to avoid panics in production.
+    /// Registering the same name again returns an equal id.
     ///
     /// # Examples
     /// ```
     /// use benchmark::Watch;
     /// let w = Watch::new();
-    /// w.record("t", 42);
-    /// assert_eq!(w.snapshot()["t"].count, 1);
+    /// let parse = w.register("parse");
+    /// for v in [100, 200, 300] {
+    ///     w.record_id(&parse, v);
+    /// }
+    /// assert_eq!(w.snapshot()["parse"].count, 3);
+    /// assert_eq!(w.register("parse"), parse);
     /// ```
-    pub fn record(&self, name: &str, duration_ns: u64) {
-        // Clamp to histogram range to avoid errors.
-        let ns = duration_ns.clamp(self.inner.lowest, self.inner.highest);
-
-        // Fast path: try obtain Arc without write locking
-        let existing: Option<Arc<B>> = {
-            let map = self.read_hist();
-            map.get(name).cloned()
-        };
-        if let Some(h) = existing {
-            h.record(ns);
-            #[cfg(feature = "trace")]
-            trace::record_event(name, ns);
-            return;
+    pub fn register(&self, name: &str) -> MetricId {
+        let now = self.now_ns();
+        // Publish a table that resolves the id.
+        let (index, name, evicted) = self.publish(|next| {
+            let index = next
+                .id_names
+                .iter()
+                .position(|n| &**n == name)
+                .unwrap_or_else(|| {
+                    next.id_names.push(Arc::from(name));
+                    next.id_names.len() - 1
+                });
+            let (_, evicted) = self.insert_metric(next, name, now);
+            (index, Arc::clone(&next.id_names[index]), evicted)
+        });
+        self.forget(&evicted);
+        MetricId {
+            watch_id: self.inner.id,
+            index,
+            name,
         }
+    }
 
-        // Slow path: create the histogram under write lock if absent
-        let mut map = self.write_hist();
-        let key: Arc<str> = Arc::<str>::from(name);
-        let h = map.entry(key).or_insert_with(|| Arc::new(B::new())).clone();
-        h.record(ns);
-        #[cfg(feature = "trace")]
-        trace::record_event(name, ns);
+    /// Id of the `<name>.<suffix>` metric, registered on first use and cached
+    /// so derived metrics cost no allocation per record.
+    pub(crate) fn suffixed_id(&self, name: &str, suffix: &'static str) -> MetricId {
+        let cached = self
+            .lock_suffixed()
+            .get(name)
+            .and_then(|ids| ids.iter().find(|(s, _)| *s == suffix))
+            .map(|(_, id)| id.clone());
+        cached.unwrap_or_else(|| {
+            let id = self.register(&format!("{name}.{suffix}"));
+            let mut cache = self.lock_suffixed();
+            let ids = cache.entry(Arc::from(name)).or_default();
+            if !ids.iter().any(|(s, _)| *s == suffix) {
+                ids.push((suffix, id.clone()));
+            }
+            id
+        })
     }
 
-    /// Record elapsed time since `start` for a metric name.
+    /// Record a duration in nanoseconds for the metric registered as `id`,
+    /// without hashing its name.
     ///
     /// # Examples
     /// ```
     /// use benchmark::Watch;
-    /// use std::time::Instant;
     /// let w = Watch::new();
-    /// let start = Instant::now();
-    /// // do work
-    /// let ns = w.record_instant("io", start);
-    /// assert!(ns >= 0);
+    /// let id = w.register("io");
+    /// w.record_id(&id, 1_500);
+    /// w.clear();
+    /// w.record_id(&id, 2_500); // registered again
+    /// assert_eq!(w.snapshot()["io"].count, 1);
     /// ```
-    pub fn record_instant(&self, name: &str, start: Instant) -> u64 {
-        let ns_u128 = start.elapsed().as_nanos();
-        // Convert to u64 using infallible saturating via try_from to satisfy clippy
-        let ns_u64 = if ns_u128 > u128::from(u64::MAX) {
-            u64::MAX
-        } else {
-            u64::try_from(ns_u128).unwrap_or(u64::MAX)
-        };
-        self.record(name, ns_u64);
-        ns_u64
+    pub fn record_id(&self, id: &MetricId, duration_ns: u64) {
+        let hit = (id.watch_id == self.inner.id)
+            .then(|| {
+                self.with_map(|m| {
+                    m.downcast_ref::<Published<B>>()
+                        .and_then(|p| p.by_id.get(id.index)?.as_ref())
+                        .map(|metric| {
+                            metric
+                                .filter
+                                .allows(&id.name)
+                                .then(|| self.record_into(metric, duration_ns).0)
+                        })
+                })
+            })
+            .flatten();
+        match hit {
+            Some(Some(ns)) => self.after_record(&id.name, duration_ns, ns),
+            Some(None) => {}
+            None => self.record(&id.name, duration_ns),
+        }
     }
 
-    /// Return a snapshot of all metrics with basic statistics.
+    /// Record a duration in nanoseconds for a metric name.
     ///
-    /// Implementation clones histograms under a read lock, then computes outside the lock
-    /// to minimize lock hold times and contention.
+    /// Safe, thread-safe, and minimal overhead.
     ///
-    /// # Panics
-    /// Panics if the internal lock is poisoned from a prior panic.
+    /// # Poisoning
+    /// If the internal lock has been poisoned due to a prior panic, this method
+    /// will recover the inner data and continue operating to avoid panics in production.
     ///
     /// # Examples
     /// ```
     /// use benchmark::Watch;
     /// let w = Watch::new();
-    /// w.record("rpc", 10);
-    /// w.record("rpc", 20);
-    /// let m = &w.snapshot()["rpc"];
-    /// assert_eq!(m.count, 2);
-    /// assert!(m.min <= m.p50 && m.p50 <= m.max);
+    /// w.record("t", 42);
+    /// assert_eq!(w.snapshot()["t"].count, 1);
     /// ```
-    pub fn snapshot(&self) -> HashMap<String, WatchStats> {
-        let items: Vec<(Arc<str>, Arc<B>)> = {
-            let map = self.read_hist();
-            map.iter()
-                .map(|(k, v)| (Arc::clone(k), Arc::clone(v)))
-                .collect()
-        };
-
-        let mut out = HashMap::with_capacity(items.len());
-        for (name, h) in items {
-            let count = h.count();
-            if count == 0 {
-                out.insert(
-                    name.to_string(),
-                    WatchStats {
-                        count: 0,
-                        min: 0,
-                        max: 0,
-                        p50: 0,
-                        p90: 0,
-                        p95: 0,
-                        p99: 0,
-                        p999: 0,
-                        mean: 0.0,
-                    },
-                );
-                continue;
-            }
-
-            // Safe unwraps since count > 0
-            let min = h.min().unwrap_or(0);
-            let max = h.max().unwrap_or(0);
-            let p50 = h.percentile(0.50).unwrap_or(min);
-            let p90 = h.percentile(0.90).unwrap_or(max);
-            let p95 = h.percentile(0.95).unwrap_or(max);
-            let p99 = h.percentile(0.99).unwrap_or(max);
-            let p999 = h.percentile(0.999).unwrap_or(max);
-            let mean = h.mean().unwrap_or(0.0);
-
-            out.insert(
-                name.to_string(),
-                WatchStats {
-                    count,
-                    min,
-                    max,
-                    p50,
-                    p90,
-                    p95,
-                    p99,
-                    p999,
-                    mean,
-                },
-            );
+    pub fn record(&self, name: &str, duration_ns: u64) {
+        let ns = self.with_metric(name, |metric| {
+            metric
+                .filter
+                .allows(name)
+                .then(|| self.record_into(metric, duration_ns).0)
+        });
+        if let Some(ns) = ns {
+            self.after_record(name, duration_ns, ns);
         }
-        out
     }
 
-    /// Clear all metrics.
+    /// Like [`record`](Self::record), but report where the sample landed.
+    ///
+    /// `clamped` is true when the value fell outside this metric's
+    /// `lowest..=highest` bounds or the histogram's own range. Returns `None`
+    /// when the filter or sampler dropped the sample.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::with_bounds(1, 1_000);
+    /// assert!(!w.record_returning("q", 500).unwrap().clamped);
+    /// assert!(w.record_returning("q", 5_000).unwrap().clamped);
+    /// ```
+    pub fn record_returning(&self, name: &str, duration_ns: u64) -> Option<RecordInfo> {
+        let hit = self.with_metric(name, |metric| {
+            metric
+                .filter
+                .allows(name)
+                .then(|| self.record_into(metric, duration_ns))
+        });
+        let (ns, info) = hit?;
+        self.after_record(name, duration_ns, ns);
+        info
+    }
+
+    /// Record every value of `values` (ns) for `name` with a single metric
+    /// lookup and clock read.
     ///
-    /// # Panics
-    /// Panics if the internal lock is poisoned from a prior panic.
+    /// Much cheaper than one [`record`](Self::record) call per value when
+    /// replaying logs or flushing worker-local buffers. All values share one
+    /// timestamp for idle tracking and rates.
     ///
     /// # Examples
     /// ```
     /// use benchmark::Watch;
     /// let w = Watch::new();
-    /// w.record("a", 1);
-    /// assert!(!w.snapshot().is_empty());
-    /// w.clear();
-    /// assert!(w.snapshot().is_empty());
+    /// w.record_many("replay", &[100, 200, 300]);
+    /// assert_eq!(w.snapshot()["replay"].total_ns, 600);
     /// ```
-    pub fn clear(&self) {
-        let mut map = self.write_hist();
-        map.clear();
+    pub fn record_many(&self, name: &str, values: &[u64]) {
+        self.record_iter(name, values.iter().copied());
     }
 
-    /// Clear a specific metric by name.
+    /// Like [`record_many`](Self::record_many), for values produced by an
+    /// iterator.
     ///
-    /// # Panics
-    /// Panics if the internal lock is poisoned from a prior panic.
+    /// The iterator is drained outside any internal lock, so it may itself
+    /// record into this Watch. An empty batch does not register `name`.
     ///
     /// # Examples
     /// ```
     /// use benchmark::Watch;
     /// let w = Watch::new();
-    /// w.record("x", 1);
-    /// w.clear_name("x");
-    /// assert!(!w.snapshot().contains_key("x"));
+    /// w.record_iter("parsed", "120 80 95".split(' ').filter_map(|v| v.parse().ok()));
+    /// assert_eq!(w.snapshot()["parsed"].count, 3);
     /// ```
-    pub fn clear_name(&self, name: &str) {
-        let mut map = self.write_hist();
-        map.remove(name);
+    pub fn record_iter<I: IntoIterator<Item = u64>>(&self, name: &str, values: I) {
+        let mut values = values.into_iter().peekable();
+        if values.peek().is_none() {
+            return;
+        }
+        let metric = self.metric_handle(name);
+        if !metric.filter.allows(name) {
+            return;
+        }
+        let floor = self.inner.threshold_floor.load(Ordering::Relaxed);
+        let now = self.now_ns();
+        let (mut offered, mut kept) = (0, 0);
+        for v in values {
+            offered += 1;
+            if Self::store_sample(&metric, v, now) {
+                kept += 1;
+            }
+            #[cfg(feature = "trace")]
+            trace::record_event(name, v.clamp(metric.lowest, metric.highest));
+            if v > floor {
+                self.fire_thresholds(name, v);
+            }
+        }
+        self.inner.records.add(offered);
+        if offered > kept {
+            self.inner.sampled_out.add(offered - kept);
+        }
+        if kept > 0 {
+            metric.seen(now);
+            if let Some(r) = &metric.rates {
+                r.hit_n(now, kept);
+            }
+        }
     }
-}
 
-/// Builder for configuring and constructing a `Watch`.
-#[derive(Debug, Clone, Copy)]
-pub struct WatchBuilderGeneric<B: HistBackend> {
-    lowest: u64,
-    highest: u64,
-    _marker: PhantomData<B>,
-}
+    /// The metric registered as `name`, registering it first if needed.
+    fn metric_handle(&self, name: &str) -> Arc<Metric<B>> {
+        let hit = self.with_map(|m| {
+            m.downcast_ref::<Published<B>>()
+                .and_then(|map| map.get(name))
+                .map(Arc::clone)
+        });
+        hit.unwrap_or_else(|| {
+            let existing = self.current().get(name).cloned();
+            existing.unwrap_or_else(|| self.register_metric(name))
+        })
+    }
 
-/// Feature-selected concrete `WatchBuilder` using the active histogram backend.
-pub type WatchBuilder = WatchBuilderGeneric<Backend>;
+    /// Run `f` on the metric registered as `name`, registering it first if
+    /// needed.
+    #[inline]
+    fn with_metric<R>(&self, name: &str, mut f: impl FnMut(&Metric<B>) -> R) -> R {
+        // Fast path: use this thread's cached map, no lock taken
+        let hit = self.with_map(|m| {
+            m.downcast_ref::<Published<B>>()
+                .and_then(|map| map.get(name))
+                .map(|metric| f(metric))
+        });
+        hit.unwrap_or_else(|| {
+            // Slow path: publish a map containing the new histogram (unless
+            // another thread already did)
+            let existing = self.current().get(name).cloned();
+            let metric = existing.unwrap_or_else(|| self.register_metric(name));
+            f(&metric)
+        })
+    }
 
-impl<B: HistBackend> Default for WatchBuilderGeneric<B> {
+    /// Per-record work outside the metric: threshold hooks and tracing.
     #[inline]
-    fn default() -> Self {
-        Self::new()
+    fn after_record(&self, name: &str, duration_ns: u64, ns: u64) {
+        if duration_ns > self.inner.threshold_floor.load(Ordering::Relaxed) {
+            self.fire_thresholds(name, duration_ns);
+        }
+        #[cfg(feature = "trace")]
+        trace::record_event(name, ns);
+        #[cfg(not(feature = "trace"))]
+        let _ = ns;
     }
-}
 
-impl<B: HistBackend> WatchBuilderGeneric<B> {
-    /// Start a builder with default bounds: 1ns..~1h, 3 significant figures.
-    ///
-    /// # Examples
-    /// ```
-    /// use benchmark::WatchBuilder;
-    /// let _w = WatchBuilder::new().lowest(1).highest(1_000_000).build();
-    /// ```
+    /// Run the threshold hooks of `name` exceeded by `duration_ns`, outside
+    /// the hook lock.
+    #[cold]
+    fn fire_thresholds(&self, name: &str, duration_ns: u64) {
+        let hooks: Vec<ThresholdHook> = self
+            .lock_threshold_hooks()
+            .iter()
+            .filter(|(metric, threshold, _)| &**metric == name && duration_ns > *threshold)
+            .map(|(_, _, hook)| Arc::clone(hook))
+            .collect();
+        for hook in hooks {
+            hook(name, duration_ns);
+        }
+    }
+
+    /// Record into `metric`, returning the value after clamping to its bounds
+    /// and, unless the sampler dropped it, where the sample landed.
     #[inline]
-    pub fn new() -> Self {
-        Self {
-            lowest: DEFAULT_LOWEST,
-            highest: DEFAULT_HIGHEST,
-            _marker: PhantomData,
+    fn record_into(&self, metric: &Metric<B>, duration_ns: u64) -> (u64, Option<RecordInfo>) {
+        // Clamp to histogram range to avoid errors.
+        let ns = duration_ns.clamp(metric.lowest, metric.highest);
+        self.inner.records.add(1);
+        if let Some(sampler) = &metric.sampler {
+            if !sampler.offer(ns) {
+                self.inner.sampled_out.add(1);
+                return (ns, None);
+            }
         }
+        let now = self.sample_clock(metric);
+        let info = Self::keep_sample(metric, duration_ns, ns, now);
+        if let Some(r) = &metric.rates {
+            r.hit(now);
+        }
+        (ns, Some(info))
     }
 
-    /// Set the lowest discernible value in nanoseconds (min 1ns).
+    /// Clock reading for a kept sample of `metric`, marking it seen. Only
+    /// `timed` metrics and a metric's first sample read the clock; other
+    /// samples get 0, which nothing reads.
     #[inline]
-    #[must_use]
-    pub fn lowest(mut self, ns: u64) -> Self {
-        self.lowest = ns.max(1);
-        self
+    fn sample_clock(&self, metric: &Metric<B>) -> u64 {
+        if metric.timed || metric.first_seen.load(Ordering::Relaxed) == 0 {
+            let now = self.now_ns();
+            metric.seen(now);
+            now
+        } else {
+            0
+        }
     }
 
-    /// Set the highest trackable value in nanoseconds (must be > lowest).
+    /// Clamp and offer `duration_ns` to the sampler, then store it if kept;
+    /// returns whether it was kept. Leaves `last_used` and rates to the caller.
     #[inline]
-    #[must_use]
-    pub fn highest(mut self, ns: u64) -> Self {
-        self.highest = ns;
-        self
+    fn store_sample(metric: &Metric<B>, duration_ns: u64, now: u64) -> bool {
+        let ns = duration_ns.clamp(metric.lowest, metric.highest);
+        if metric.sampler.as_ref().is_some_and(|s| !s.offer(ns)) {
+            return false;
+        }
+        Self::keep_sample(metric, duration_ns, ns, now);
+        true
     }
 
-    /// Build the `Watch` with the configured settings.
+    /// Feed a kept sample (`ns` is `duration_ns` clamped) to the histogram
+    /// and the slowest / Apdex trackers; `clamped` covers both the metric's
+    /// bounds and the backend's range.
     #[inline]
-    pub fn build(self) -> WatchGeneric<B> {
-        let lowest = self.lowest.max(1);
-        let highest = self.highest.max(lowest + 1);
-        WatchGeneric::<B>::with_bounds(lowest, highest)
+    fn keep_sample(metric: &Metric<B>, duration_ns: u64, ns: u64, now: u64) -> RecordInfo {
+        let info = metric.hist.record_returning(ns);
+        if ns != duration_ns {
+            let side = if duration_ns < ns {
+                &metric.clamped_low
+            } else {
+                &metric.clamped_high
+            };
+            side.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(top) = &metric.slowest {
+            top.offer(ns, now);
+        }
+        if let Some(a) = &metric.apdex {
+            a.hit(duration_ns);
+        }
+        RecordInfo {
+            clamped: info.clamped || ns != duration_ns,
+            ..info
+        }
+    }
+
+    #[inline]
+    fn stats_of(&self, metric: &Metric<B>, now_ns: u64) -> WatchStats {
+        let mut stats = self.annotate(WatchStats::from_hist(&metric.hist), metric, now_ns);
+        if let Some((min, max)) = metric.sampler.as_ref().and_then(|s| s.extremes()) {
+            if stats.count > 0 {
+                (stats.min, stats.max) = (min, max);
+            }
+        }
+        stats
+    }
+
+    /// Fill in the metric-level fields of `stats`: timestamps, clamp counts
+    /// and, if tracked, rates and Apdex counts; then scale sampled counts up.
+    #[inline]
+    fn annotate(&self, mut stats: WatchStats, metric: &Metric<B>, now_ns: u64) -> WatchStats {
+        stats.clamped_low = metric.clamped_low.load(Ordering::Relaxed);
+        stats.clamped_high = metric.clamped_high.load(Ordering::Relaxed);
+        if let Some(r) = &metric.rates {
+            (stats.rate_1s, stats.rate_1m) = r.rates(now_ns);
+        }
+        if let Some(a) = &metric.apdex {
+            a.fill(&mut stats);
+        }
+        if let Some(sampler) = &metric.sampler {
+            sampler.scale(&mut stats);
+        }
+        stats.first_seen_ns = self.epoch_of(metric.first_seen.load(Ordering::Relaxed));
+        stats.last_seen_ns = self.epoch_of(metric.last_used.load(Ordering::Relaxed));
+        stats
+    }
+
+    /// Wall-clock ns since the UNIX epoch for a `first_seen` / `last_used`
+    /// stamp (clock reading + 1); 0 while the stamp is unset.
+    #[inline]
+    fn epoch_of(&self, stamp: u64) -> u128 {
+        let Some(clock_ns) = stamp.checked_sub(1) else {
+            return 0;
+        };
+        let (epoch, at) = self.inner.epoch_anchor;
+        epoch + u128::from(clock_ns.saturating_sub(at))
+    }
+
+    /// Add `name` to the map, evicting expired and then least-recently-used
+    /// metrics to stay within the configured limits.
+    fn register_metric(&self, name: &str) -> Arc<Metric<B>> {
+        self.inner.slow_path_inserts.fetch_add(1, Ordering::Relaxed);
+        let now = self.now_ns();
+        let (metric, evicted) = self.publish(|next| self.insert_metric(next, name, now));
+        self.forget(&evicted);
+        metric
+    }
+
+    /// Add `name` to the draft table `next` unless present, returning its
+    /// metric and the names evicted to make room.
+    fn insert_metric(
+        &self,
+        next: &mut Published<B>,
+        name: &str,
+        now: u64,
+    ) -> (Arc<Metric<B>>, Evicted<B>) {
+        let config = next.configs.get(name).copied().unwrap_or_default();
+        let map = &mut next.map;
+        if let Some(m) = map.get(name) {
+            return (Arc::clone(m), Vec::new());
+        }
+        let mut evicted = self.remove_expired(map, now);
+        if let Some(max) = self.inner.max_metrics {
+            while map.len() >= max {
+                let Some(lru) = map
+                    .iter()
+                    .min_by_key(|(_, m)| m.last_active())
+                    .map(|(k, _)| Arc::clone(k))
+                else {
+                    break;
+                };
+                if let Some(metric) = map.remove(&lru) {
+                    evicted.push((lru, metric));
+                }
+            }
+        }
+        let lowest = config.lowest.unwrap_or(self.inner.lowest).max(1);
+        let highest = config
+            .highest
+            .unwrap_or(self.inner.highest)
+            .max(lowest + 1)
+            .min(B::HIGHEST);
+        let metric = Arc::new(Metric {
+            hist: {
+                let mut hist = config.sub_buckets.map_or_else(B::new, B::with_sub_buckets);
+                if config.bucket_sums {
+                    hist = hist.with_bucket_sums();
+                }
+                // Samples are already clamped to `highest`; let a backend
+                // with a narrower default range grow to it.
+                if highest > DEFAULT_HIGHEST {
+                    hist = hist.with_auto_resize();
+                }
+                hist
+            },
+            lowest,
+            highest,
+            registered: now,
+            first_seen: AtomicU64::new(0),
+            last_used: AtomicU64::new(0),
+            timed: self.inner.evicting
+                || self.inner.rates
+                || self.inner.slowest.is_some()
+                || self.inner.track_last_seen
+                || config.track_last_seen,
+            rates: self.inner.rates.then(|| Box::new(RateWindow::new())),
+            slowest: self
+                .inner
+                .slowest
+                .map(|(k, window)| Box::new(SlowestWindow::new(k, window))),
+            apdex: config.apdex_target.map(|t| Box::new(ApdexCounter::new(t))),
+            sampler: Some(config.sample_rate.unwrap_or(self.inner.sample_rate))
+                .filter(|&n| n > 1)
+                .map(|n| Box::new(Sampler::new(n))),
+            clamped_low: AtomicU64::new(0),
+            clamped_high: AtomicU64::new(0),
+            filter: FilterCache::default(),
+        });
+        map.insert(Arc::<str>::from(name), Arc::clone(&metric));
+        (metric, evicted)
+    }
+
+    /// Remove metrics idle for longer than the TTL from `map`, returning their names.
+    fn remove_expired(&self, map: &mut MetricMap<B>, now: u64) -> Evicted<B> {
+        let Some(ttl) = self.inner.ttl_ns else {
+            return Vec::new();
+        };
+        let expired: Vec<Arc<str>> = map
+            .iter()
+            .filter(|(_, m)| now.saturating_sub(m.last_active()) > ttl)
+            .map(|(k, _)| Arc::clone(k))
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|k| map.remove(&k).map(|m| (k, m)))
+            .collect()
+    }
+
+    /// Count evicted metrics and drop their per-metric side state.
+    fn forget(&self, evicted: &Evicted<B>) {
+        if evicted.is_empty() {
+            return;
+        }
+        self.inner
+            .dropped
+            .fetch_add(evicted.len() as u64, Ordering::Relaxed);
+        let samples = evicted.iter().map(|(_, m)| m.hist.count()).sum::<u64>();
+        self.inner
+            .evicted_samples
+            .fetch_add(samples, Ordering::Relaxed);
+        let mut history = self.lock_history();
+        let mut last = self.lock_last_scrape();
+        for (name, _) in evicted {
+            history.remove(name);
+            last.remove(name);
+        }
+    }
+
+    /// Evict metrics idle for longer than the configured [`ttl`](WatchBuilderGeneric::ttl).
+    ///
+    /// Expired metrics are also evicted when a new metric is registered and on
+    /// every `snapshot()`. Returns the number of metrics evicted.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::test_util::MockClock;
+    /// use benchmark::Watch;
+    /// use std::time::Duration;
+    /// let clock = MockClock::new();
+    /// let w = Watch::builder()
+    ///     .clock(clock.clone())
+    ///     .ttl(Duration::from_secs(60))
+    ///     .build();
+    /// w.record("tenant.42", 1_000);
+    /// clock.advance_by(Duration::from_secs(61));
+    /// assert_eq!(w.evict_expired(), 1);
+    /// assert_eq!(w.dropped_metrics(), 1);
+    /// ```
+    pub fn evict_expired(&self) -> usize {
+        let Some(ttl) = self.inner.ttl_ns else {
+            return 0;
+        };
+        let now = self.now_ns();
+        let any_expired = self
+            .load_map()
+            .values()
+            .any(|m| now.saturating_sub(m.last_active()) > ttl);
+        if !any_expired {
+            return 0;
+        }
+        let evicted = self.update_map(|map| self.remove_expired(map, now));
+        self.forget(&evicted);
+        evicted.len()
+    }
+
+    /// Number of metrics removed by LRU/TTL eviction since creation.
+    ///
+    /// Also reported in snapshots as the `count` of
+    /// [`DROPPED_METRICS`](Self::DROPPED_METRICS) when eviction is configured.
+    pub fn dropped_metrics(&self) -> u64 {
+        self.inner.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Counters describing the Watch's own overhead and health: how many
+    /// records it took, how many were sampled out or evicted, and how often
+    /// it hit a slow path or waited on a lock.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::builder().sample_rate(1_000_000).build();
+    /// w.record("hot", 10);
+    /// w.record_many("hot", &[20, 30]);
+    /// let s = w.internal_stats();
+    /// assert_eq!(s.records, 3);
+    /// assert_eq!(s.slow_path_inserts, 1);
+    /// assert!(s.sampled_out <= 3);
+    /// ```
+    pub fn internal_stats(&self) -> InternalStats {
+        let inner = &self.inner;
+        InternalStats {
+            records: inner.records.sum(),
+            sampled_out: inner.sampled_out.sum(),
+            slow_path_inserts: inner.slow_path_inserts.load(Ordering::Relaxed),
+            lock_waits: inner.lock_waits.load(Ordering::Relaxed),
+            evicted_metrics: inner.dropped.load(Ordering::Relaxed),
+            evicted_samples: inner.evicted_samples.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Time since the latest record of `name`, by the Watch clock; `None`
+    /// if the metric is not registered, has not recorded yet, or does not
+    /// track its last record time (see [`MetricConfig::track_last_seen`]).
+    ///
+    /// Snapshots carry the same information as wall-clock timestamps in
+    /// [`WatchStats::last_seen_ns`] / [`WatchStats::first_seen_ns`].
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::test_util::MockClock;
+    /// use benchmark::Watch;
+    /// use std::time::Duration;
+    /// let clock = MockClock::new();
+    /// let w = Watch::builder().clock(clock.clone()).build();
+    /// w.configure_metric(
+    ///     "cron.job",
+    ///     benchmark::MetricConfig { track_last_seen: true, ..Default::default() },
+    /// );
+    /// w.record("cron.job", 1_000);
+    /// clock.advance_by(Duration::from_secs(300));
+    /// assert_eq!(w.idle_for("cron.job"), Some(Duration::from_secs(300)));
+    /// assert_eq!(w.idle_for("never"), None);
+    /// ```
+    pub fn idle_for(&self, name: &str) -> Option<StdDuration> {
+        let last = self.load_map().get(name)?.last_used.load(Ordering::Relaxed);
+        let last = last.checked_sub(1)?;
+        Some(StdDuration::from_nanos(self.now_ns().saturating_sub(last)))
+    }
+
+    /// The slowest recent samples of `name`, slowest first.
+    ///
+    /// Requires [`slowest`](WatchBuilderGeneric::slowest) tracking; returns an
+    /// empty `Vec` otherwise or if the metric is unknown.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::WatchBuilder;
+    /// use std::time::Duration;
+    /// let w = WatchBuilder::new().slowest(2, Duration::from_secs(60)).build();
+    /// for v in [300, 100, 900, 500] {
+    ///     w.record("rpc", v);
+    /// }
+    /// let worst: Vec<u64> = w.slowest("rpc").iter().map(|s| s.value_ns).collect();
+    /// assert_eq!(worst, [900, 500]);
+    /// ```
+    pub fn slowest(&self, name: &str) -> Vec<SlowSample> {
+        let now = self.now_ns();
+        self.load_map()
+            .get(name)
+            .and_then(|m| m.slowest.as_ref())
+            .map(|top| top.collect(now))
+            .unwrap_or_default()
+    }
+
+    /// The slowest recent samples of every tracked metric.
+    pub fn slowest_all(&self) -> HashMap<String, Vec<SlowSample>> {
+        let now = self.now_ns();
+        self.load_map()
+            .iter()
+            .filter_map(|(name, m)| {
+                let top = m.slowest.as_ref()?;
+                Some((name.to_string(), top.collect(now)))
+            })
+            .collect()
+    }
+
+    /// [`slowest_all`](Self::slowest_all) as a JSON object keyed by metric
+    /// name (sorted), ready to serve from a `/slowest` debug endpoint:
+    /// `{"rpc":[{"value_ns":900,"timestamp_ns":1700000000000000000},...]}`.
+    pub fn slowest_json(&self) -> String {
+        let mut all: Vec<(String, Vec<SlowSample>)> = self.slowest_all().into_iter().collect();
+        all.sort_by(|a, b| a.0.cmp(&b.0));
+        slowest::to_json(all.iter().map(|(k, v)| (k.as_str(), v.as_slice())))
+    }
+
+    /// Self time of every [`ScopedTimer`](crate::ScopedTimer) path, as
+    /// folded-stack lines for `inferno-flamegraph` or `flamegraph.pl`.
+    ///
+    /// One line per distinct scope path, sorted: the scope names from the
+    /// outermost down, joined by `;`, a space, then the total self
+    /// nanoseconds spent in that path. A `;` inside a scope name becomes `_`.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::test_util::MockClock;
+    /// let clock = MockClock::new();
+    /// let w = clock.watch();
+    /// {
+    ///     let _a = w.scope("main");
+    ///     clock.advance(5);
+    ///     let _b = w.scope("parse");
+    ///     clock.advance(20);
+    /// }
+    /// # #[cfg(feature = "metrics")]
+    /// assert_eq!(w.folded_stacks(), "main 5\nmain;parse 20\n");
+    /// ```
+    pub fn folded_stacks(&self) -> String {
+        use std::fmt::Write as _;
+        let mut lines: Vec<(String, u64)> = self
+            .lock_folded()
+            .iter()
+            .map(|(path, ns)| (path.clone(), *ns))
+            .collect();
+        lines.sort_unstable();
+        let mut out = String::new();
+        for (path, ns) in lines {
+            let _ = writeln!(out, "{path} {ns}");
+        }
+        out
+    }
+
+    /// Add `self_ns` to the folded-stack total for `path`.
+    pub(crate) fn add_folded(&self, path: &str, self_ns: u64) {
+        let mut folded = self.lock_folded();
+        match folded.get_mut(path) {
+            Some(total) => *total = total.saturating_add(self_ns),
+            None => {
+                folded.insert(path.to_owned(), self_ns);
+            }
+        }
+    }
+
+    /// Record elapsed time since `start` for a metric name.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// use std::time::Instant;
+    /// let w = Watch::new();
+    /// let start = Instant::now();
+    /// // do work
+    /// let ns = w.record_instant("io", start);
+    /// assert!(ns >= 0);
+    /// ```
+    pub fn record_instant(&self, name: &str, start: Instant) -> u64 {
+        let ns_u128 = start.elapsed().as_nanos();
+        // Convert to u64 using infallible saturating via try_from to satisfy clippy
+        let ns_u64 = if ns_u128 > u128::from(u64::MAX) {
+            u64::MAX
+        } else {
+            u64::try_from(ns_u128).unwrap_or(u64::MAX)
+        };
+        self.record(name, ns_u64);
+        ns_u64
+    }
+
+    /// Record a duration for a metric name.
+    ///
+    /// Accepts `std::time::Duration` and [`benchmark::Duration`](crate::Duration)
+    /// (anything `Into<benchmark::Duration>`); values above `u64::MAX`
+    /// nanoseconds saturate before the usual clamping to the histogram bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{Duration, Watch};
+    /// let w = Watch::new();
+    /// w.record_duration("db", std::time::Duration::from_micros(250));
+    /// w.record_duration("db", Duration::from_nanos(750_000));
+    /// let s = &w.snapshot()["db"];
+    /// assert_eq!((s.count, s.total_ns), (2, 1_000_000));
+    /// ```
+    #[inline]
+    pub fn record_duration(&self, name: &str, duration: impl Into<crate::Duration>) {
+        let ns = u64::try_from(duration.into().as_nanos()).unwrap_or(u64::MAX);
+        self.record(name, ns);
+    }
+
+    /// Current reading of this Watch's clock in nanoseconds (see [`WatchBuilder::clock`]).
+    ///
+    /// Only differences between readings are meaningful.
+    #[inline]
+    pub fn now_ns(&self) -> u64 {
+        match &self.inner.clock {
+            Some(c) => c.now_ns(),
+            None => clock::system_now_ns(),
+        }
+    }
+
+    /// Return a snapshot of all metrics with basic statistics.
+    ///
+    /// Reads the thread's cached metric map (see [Concurrency](Self#concurrency)),
+    /// so it does not block or get blocked by concurrent `record` calls. With
+    /// [`enable_precompute`](Self::enable_precompute) on, returns a copy of the
+    /// latest precomputed table instead of computing percentiles.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// w.record("rpc", 10);
+    /// w.record("rpc", 20);
+    /// let m = &w.snapshot()["rpc"];
+    /// assert_eq!(m.count, 2);
+    /// assert!(m.min <= m.p50 && m.p50 <= m.max);
+    /// ```
+    pub fn snapshot(&self) -> HashMap<String, WatchStats> {
+        if let Some(table) = self.lock_precomputed().clone() {
+            return HashMap::clone(&table);
+        }
+        self.compute_snapshot()
+    }
+
+    /// [`snapshot`](Self::snapshot) as a [`Snapshot`](crate::Snapshot),
+    /// sorted by metric name.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// w.record("b", 20);
+    /// w.record("a", 10);
+    /// let snap = w.snapshot_sorted();
+    /// assert_eq!(snap.names().collect::<Vec<_>>(), ["a", "b"]);
+    /// ```
+    pub fn snapshot_sorted(&self) -> crate::Snapshot {
+        self.snapshot().into()
+    }
+
+    /// Capture stats and histograms of every metric into an immutable
+    /// [`FrozenSnapshot`](crate::FrozenSnapshot) for exporters.
+    ///
+    /// Each metric's histogram is copied right after its stats are computed;
+    /// records landing in between may make the two differ slightly.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// w.record("rpc", 1_000);
+    /// let frozen = w.freeze();
+    /// assert_eq!(frozen.get("rpc").unwrap().count, 1);
+    /// assert_eq!(frozen.histogram("rpc").unwrap().count(), 1);
+    /// ```
+    pub fn freeze(&self) -> crate::FrozenSnapshot {
+        self.evict_expired();
+        let captured_at = now_epoch_nanos();
+        let now = self.now_ns();
+        let map = self.load_map();
+        let mut stats = HashMap::with_capacity(map.len() + 1);
+        let mut histograms = Vec::with_capacity(map.len());
+        for (name, m) in map.iter() {
+            stats.insert(name.to_string(), self.stats_of(m, now));
+            histograms.push((name.to_string(), m.hist.snapshot()));
+        }
+        if self.inner.evicting {
+            stats.insert(
+                Self::DROPPED_METRICS.to_string(),
+                WatchStats {
+                    count: self.dropped_metrics(),
+                    ..WatchStats::EMPTY
+                },
+            );
+        }
+        histograms.sort_by(|a, b| a.0.cmp(&b.0));
+        crate::FrozenSnapshot::new(captured_at, stats.into(), histograms)
+    }
+
+    /// Histogram snapshot of every metric, sorted by name.
+    pub(crate) fn histogram_snapshots(&self) -> Vec<(String, HistogramSnapshot)> {
+        let mut out: Vec<(String, HistogramSnapshot)> = self
+            .load_map()
+            .iter()
+            .map(|(name, m)| (name.to_string(), m.hist.snapshot()))
+            .collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
+
+    /// Snapshot as many metrics as can be computed within `budget`.
+    ///
+    /// Each included metric is complete; the budget is checked between
+    /// metrics, so the call overruns by at most one metric's computation (a
+    /// non-zero budget always yields at least one metric).
+    /// Successive truncated calls resume where the previous one stopped, so
+    /// every metric is eventually reported. Like [`snapshot`](Self::snapshot),
+    /// expired metrics are evicted first and
+    /// [`DROPPED_METRICS`](Self::DROPPED_METRICS) is always included when
+    /// eviction is configured. With precomputed snapshots enabled the full
+    /// table is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// use std::time::Duration;
+    /// let w = Watch::new();
+    /// w.record("a", 1);
+    /// w.record("b", 2);
+    /// let full = w.snapshot_with_budget(Duration::from_secs(1));
+    /// assert!(!full.truncated);
+    /// assert_eq!(full.stats.len(), 2);
+    /// let none = w.snapshot_with_budget(Duration::ZERO);
+    /// assert!(none.truncated && none.stats.is_empty());
+    /// ```
+    pub fn snapshot_with_budget(&self, budget: StdDuration) -> PartialSnapshot {
+        if let Some(table) = self.lock_precomputed().clone() {
+            return PartialSnapshot {
+                stats: HashMap::clone(&table),
+                truncated: false,
+            };
+        }
+        let start = Instant::now();
+        self.evict_expired();
+        let now = self.now_ns();
+        let map = self.load_map();
+        let len = map.len();
+        let first = self.inner.budget_cursor.load(Ordering::Relaxed) % len.max(1);
+        let mut stats = HashMap::new();
+        let mut done = 0;
+        for (name, h) in map.iter().cycle().skip(first).take(len) {
+            // A non-zero budget always covers one metric so repeated calls progress.
+            if (done > 0 || budget.is_zero()) && start.elapsed() >= budget {
+                break;
+            }
+            stats.insert(name.to_string(), self.stats_of(h, now));
+            done += 1;
+        }
+        let truncated = done < len;
+        if truncated {
+            self.inner
+                .budget_cursor
+                .store(first + done, Ordering::Relaxed);
+        }
+        self.insert_dropped_metrics(&mut stats);
+        PartialSnapshot { stats, truncated }
+    }
+
+    /// Compute stats for every metric from the live histograms.
+    fn compute_snapshot(&self) -> HashMap<String, WatchStats> {
+        self.evict_expired();
+        let now = self.now_ns();
+        let map = self.load_map();
+        let mut out = HashMap::with_capacity(map.len() + 1);
+        for (name, h) in map.iter() {
+            out.insert(name.to_string(), self.stats_of(h, now));
+        }
+        self.insert_dropped_metrics(&mut out);
+        out
+    }
+
+    /// Add the [`DROPPED_METRICS`](Self::DROPPED_METRICS) entry to `out` when
+    /// eviction is configured.
+    fn insert_dropped_metrics(&self, out: &mut HashMap<String, WatchStats>) {
+        if self.inner.evicting {
+            out.insert(
+                Self::DROPPED_METRICS.to_string(),
+                WatchStats {
+                    count: self.dropped_metrics(),
+                    ..WatchStats::EMPTY
+                },
+            );
+        }
+    }
+
+    /// Stats for samples recorded since the previous `snapshot_delta` call.
+    ///
+    /// The first call covers everything recorded so far. Metrics with no new
+    /// samples are reported with `count == 0`. Percentiles are computed from
+    /// the bucket-count difference, so they describe only the interval; delta
+    /// min/max are bounded by bucket edges rather than exact. Apdex counts
+    /// are cumulative since the metric was registered.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// w.record("req", 100);
+    /// assert_eq!(w.snapshot_delta()["req"].count, 1);
+    /// w.record("req", 200);
+    /// w.record("req", 300);
+    /// assert_eq!(w.snapshot_delta()["req"].count, 2);
+    /// assert_eq!(w.snapshot_delta()["req"].count, 0);
+    /// assert_eq!(w.snapshot()["req"].count, 3);
+    /// ```
+    pub fn snapshot_delta(&self) -> HashMap<String, WatchStats> {
+        let now = self.now_ns();
+        let map = self.load_map();
+        let mut last = self.lock_last_scrape();
+        let mut next = HashMap::with_capacity(map.len());
+        let mut out = HashMap::with_capacity(map.len());
+        for (name, h) in map.iter() {
+            let current = h.hist.snapshot();
+            let stats = match last.get(name) {
+                Some(prev) => WatchStats::from_snapshot(&current.delta_since(prev)),
+                None => WatchStats::from_snapshot(&current),
+            };
+            let stats = self.annotate(stats, h, now);
+            out.insert(name.to_string(), stats);
+            next.insert(Arc::clone(name), current);
+        }
+        // Dropping entries for cleared metrics keeps this map bounded.
+        *last = next;
+        out
+    }
+
+    /// Stats for samples recorded since the previous `snapshot_and_reset`
+    /// call, emptying every histogram as it is read.
+    ///
+    /// Each histogram is drained with atomic swaps, so a sample recorded
+    /// concurrently is reported by exactly one call: none are lost in the gap
+    /// between reading and resetting. Metrics stay registered and report
+    /// `count == 0` when idle. Apdex counts stay cumulative, as with
+    /// [`snapshot_delta`](Self::snapshot_delta); mixing the two on one Watch
+    /// makes the next delta cover only what this call left behind.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// w.record("req", 100);
+    /// w.record("req", 300);
+    /// let interval = w.snapshot_and_reset();
+    /// assert_eq!((interval["req"].count, interval["req"].total_ns), (2, 400));
+    /// assert_eq!(w.snapshot_and_reset()["req"].count, 0);
+    /// assert_eq!(w.snapshot()["req"].count, 0);
+    /// ```
+    pub fn snapshot_and_reset(&self) -> HashMap<String, WatchStats> {
+        self.evict_expired();
+        let now = self.now_ns();
+        let map = self.load_map();
+        let mut out = HashMap::with_capacity(map.len() + 1);
+        for (name, h) in map.iter() {
+            let stats = WatchStats::from_snapshot(&h.hist.take_snapshot());
+            out.insert(name.to_string(), self.annotate(stats, h, now));
+        }
+        self.lock_last_scrape().clear();
+        self.lock_precomputed().take();
+        if self.inner.evicting {
+            out.insert(
+                Self::DROPPED_METRICS.to_string(),
+                WatchStats {
+                    count: self.dropped_metrics(),
+                    ..WatchStats::EMPTY
+                },
+            );
+        }
+        out
+    }
+
+    /// Clear all metrics.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// w.record("a", 1);
+    /// assert!(!w.snapshot().is_empty());
+    /// w.clear();
+    /// assert!(w.snapshot().is_empty());
+    /// ```
+    pub fn clear(&self) {
+        self.update_map(HashMap::clear);
+        self.lock_history().clear();
+        self.lock_last_scrape().clear();
+        self.lock_folded().clear();
+        self.lock_suffixed().clear();
+        // Fall back to live snapshots until the next refresh.
+        self.lock_precomputed().take();
+    }
+
+    /// Clear a specific metric by name.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// w.record("x", 1);
+    /// w.clear_name("x");
+    /// assert!(!w.snapshot().contains_key("x"));
+    /// ```
+    pub fn clear_name(&self, name: &str) {
+        self.remove_names(&[name]);
+    }
+
+    /// Names of all tracked metrics, sorted.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// w.record("b", 1);
+    /// w.record("a", 1);
+    /// assert_eq!(w.metric_names(), ["a", "b"]);
+    /// ```
+    pub fn metric_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.load_map().keys().map(ToString::to_string).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Whether a metric named `name` is tracked.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// assert!(!w.contains("db"));
+    /// w.record("db", 1);
+    /// assert!(w.contains("db"));
+    /// ```
+    pub fn contains(&self, name: &str) -> bool {
+        self.load_map().contains_key(name)
+    }
+
+    /// Keep only the metrics for which `keep(name, stats)` returns true,
+    /// clearing the rest as [`clear_name`](Self::clear_name) does.
+    ///
+    /// Stats are computed once per metric before `keep` is called, and all
+    /// removals are published together. A metric recorded for the first
+    /// time while `retain` runs is kept. Returns the number removed.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// let w = Watch::new();
+    /// w.record("tenant.acme.rpc", 10);
+    /// w.record("tenant.globex.rpc", 10);
+    /// w.record("db", 10);
+    /// // Tenant "acme" was deleted.
+    /// assert_eq!(w.retain(|name, _| !name.starts_with("tenant.acme.")), 1);
+    /// assert_eq!(w.metric_names(), ["db", "tenant.globex.rpc"]);
+    /// ```
+    pub fn retain(&self, mut keep: impl FnMut(&str, &WatchStats) -> bool) -> usize {
+        let now = self.now_ns();
+        let map = self.load_map();
+        let doomed: Vec<&str> = map
+            .iter()
+            .filter(|(name, m)| !keep(name, &self.stats_of(m, now)))
+            .map(|(name, _)| &**name)
+            .collect();
+        if !doomed.is_empty() {
+            self.remove_names(&doomed);
+        }
+        doomed.len()
+    }
+
+    /// Remove `names` from the metric map and every per-metric side table.
+    fn remove_names(&self, names: &[&str]) {
+        self.update_map(|map| {
+            for name in names {
+                map.remove(*name);
+            }
+        });
+        {
+            let mut history = self.lock_history();
+            let mut last = self.lock_last_scrape();
+            for name in names {
+                history.remove(*name);
+                last.remove(*name);
+            }
+        }
+        let mut table = self.lock_precomputed();
+        if let Some(t) = table.as_mut() {
+            if names.iter().any(|name| t.contains_key(*name)) {
+                let t = Arc::make_mut(t);
+                for name in names {
+                    t.remove(*name);
+                }
+            }
+        }
+    }
+
+    /// Override histogram bounds and precision for metric `name`.
+    ///
+    /// Useful when one Watch mixes very different scales, e.g. microsecond
+    /// RPCs and multi-second batch jobs. Takes effect when the metric is
+    /// next registered: samples already recorded for `name` are discarded.
+    /// See [`MetricConfig`] for an example.
+    pub fn configure_metric(&self, name: &str, config: MetricConfig) {
+        self.publish(|next| {
+            next.configs.insert(Arc::<str>::from(name), config);
+        });
+        self.clear_name(name);
+    }
+
+    /// Precompute `snapshot()` on a background thread every `interval`.
+    ///
+    /// `snapshot()` then returns a copy of the latest table instead of walking
+    /// every histogram, which bounds scrape latency with thousands of metrics
+    /// at the cost of staleness of up to `interval`. The table is computed
+    /// once immediately. The thread holds only a weak reference and exits once
+    /// all `Watch` clones are dropped; calling this again replaces the
+    /// interval, and a zero interval switches back to live snapshots.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// use std::time::Duration;
+    /// let w = Watch::new();
+    /// w.record("api", 1_000);
+    /// w.enable_precompute(Duration::from_secs(60));
+    /// w.record("api", 2_000);
+    /// assert_eq!(w.snapshot()["api"].count, 1); // as of the last refresh
+    /// w.refresh_precomputed();
+    /// assert_eq!(w.snapshot()["api"].count, 2);
+    /// ```
+    pub fn enable_precompute(&self, interval: StdDuration) {
+        let generation = self
+            .inner
+            .precompute_generation
+            .fetch_add(1, Ordering::AcqRel)
+            + 1;
+        if interval.is_zero() {
+            self.lock_precomputed().take();
+            return;
+        }
+        self.refresh_precomputed();
+
+        let weak: Weak<Inner<B>> = Arc::downgrade(&self.inner);
+        let _ = std::thread::Builder::new()
+            .name("benchmark-precompute".into())
+            .spawn(move || loop {
+                std::thread::sleep(interval);
+                let Some(inner) = weak.upgrade() else { break };
+                if inner.precompute_generation.load(Ordering::Acquire) != generation {
+                    break;
+                }
+                WatchGeneric { inner }.refresh_precomputed();
+            });
+    }
+
+    /// Recompute the precomputed snapshot table now.
+    ///
+    /// Also enables precomputed snapshots if they were off; they stay in use
+    /// until [`clear`](Self::clear) or `enable_precompute(Duration::ZERO)`.
+    pub fn refresh_precomputed(&self) {
+        let table = Arc::new(self.compute_snapshot());
+        *self.lock_precomputed() = Some(table);
+    }
+
+    /// Retain a bounded history of per-metric summaries, sampled every `interval`.
+    ///
+    /// Spawns a background thread that appends the current stats of every
+    /// metric to a ring of at most `capacity` entries per metric. The thread
+    /// holds only a weak reference and exits once all `Watch` clones are
+    /// dropped. Calling this again replaces the interval/capacity; a capacity
+    /// of 0 disables history and discards retained entries.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// use std::time::Duration;
+    /// let w = Watch::new();
+    /// w.enable_history(Duration::from_secs(60), 10);
+    /// w.record("api", 1_000);
+    /// w.capture_history(); // sample immediately instead of waiting
+    /// assert_eq!(w.history("api").len(), 1);
+    /// ```
+    pub fn enable_history(&self, interval: StdDuration, capacity: usize)
+    where
+        B: Send + Sync + 'static,
+    {
+        let generation = self.inner.history_generation.fetch_add(1, Ordering::AcqRel) + 1;
+        self.inner
+            .history_capacity
+            .store(capacity, Ordering::Release);
+        if capacity == 0 {
+            self.lock_history().clear();
+            return;
+        }
+        {
+            let mut hist = self.lock_history();
+            for ring in hist.values_mut() {
+                ring.trim(capacity);
+            }
+        }
+
+        let weak: Weak<Inner<B>> = Arc::downgrade(&self.inner);
+        let interval = interval.max(StdDuration::from_millis(1));
+        let _ = std::thread::Builder::new()
+            .name("benchmark-history".into())
+            .spawn(move || loop {
+                std::thread::sleep(interval);
+                let Some(inner) = weak.upgrade() else { break };
+                if inner.history_generation.load(Ordering::Acquire) != generation {
+                    break;
+                }
+                WatchGeneric { inner }.capture_history();
+            });
+    }
+
+    /// Append the current stats of every metric to its history ring now.
+    ///
+    /// No-op unless history has been enabled via [`enable_history`](Self::enable_history).
+    pub fn capture_history(&self) {
+        let capacity = self.inner.history_capacity.load(Ordering::Acquire);
+        if capacity == 0 {
+            return;
+        }
+        let now = self.now_ns();
+        let map = self.load_map();
+        let ts = now_epoch_nanos();
+        let stats: Vec<(Arc<str>, WatchStats, HistogramSnapshot)> = map
+            .iter()
+            .map(|(name, h)| (Arc::clone(name), self.stats_of(h, now), h.hist.snapshot()))
+            .collect();
+
+        let hooks: Vec<(f64, AnomalyHook)> = self.lock(&self.inner.anomaly_hooks).clone();
+
+        let mut updated: Vec<(Arc<str>, Vec<HistoryEntry>)> = Vec::new();
+        {
+            let mut hist = self.lock_history();
+            for (name, s, snap) in stats {
+                let ring = hist.entry(Arc::clone(&name)).or_default();
+                ring.trim(capacity - 1);
+                ring.stats.push_back((ts, s));
+                ring.intervals.push_back((ts, snap.delta_since(&ring.last)));
+                ring.last = snap;
+                if !hooks.is_empty() {
+                    updated.push((name, ring.stats.iter().copied().collect()));
+                }
+            }
+        }
+
+        // Run callbacks outside the history lock.
+        for (name, ring) in updated {
+            let series = anomaly::interval_means(&ring);
+            for (sensitivity, hook) in &hooks {
+                if let Some(a) = anomaly::check_last(&series, *sensitivity) {
+                    hook(&name, &a);
+                }
+            }
+        }
+    }
+
+    /// Flag latency shifts in the retained history of `name`.
+    ///
+    /// Derives the mean latency of each history interval and scores it against
+    /// the rolling median/MAD of the preceding intervals; points scoring above
+    /// `sensitivity` (e.g. `3.0`) are returned, oldest first. Requires history
+    /// to be enabled and at least a handful of captured intervals.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// use std::time::Duration;
+    /// let w = Watch::new();
+    /// w.enable_history(Duration::from_secs(60), 64);
+    /// for _ in 0..10 {
+    ///     w.record("db", 1_000);
+    ///     w.capture_history();
+    /// }
+    /// w.record("db", 50_000);
+    /// w.capture_history();
+    /// let found = w.detect_anomalies("db", 3.0);
+    /// assert_eq!(found.len(), 1);
+    /// ```
+    pub fn detect_anomalies(&self, name: &str, sensitivity: f64) -> Vec<Anomaly> {
+        let series = anomaly::interval_means(&self.history(name));
+        anomaly::detect(&series, sensitivity)
+    }
+
+    /// Register a callback fired when a newly captured history interval is anomalous.
+    ///
+    /// Evaluated after every history capture (background or via
+    /// [`capture_history`](Self::capture_history)) for each metric, outside
+    /// internal locks. Keep callbacks cheap; they run on the capturing thread.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// let w = Watch::new();
+    /// w.enable_history(Duration::from_secs(60), 64);
+    /// let alerts = Arc::new(AtomicUsize::new(0));
+    /// let a = Arc::clone(&alerts);
+    /// w.on_anomaly(3.0, move |_name, _anomaly| {
+    ///     a.fetch_add(1, Ordering::Relaxed);
+    /// });
+    /// for _ in 0..10 {
+    ///     w.record("db", 1_000);
+    ///     w.capture_history();
+    /// }
+    /// w.record("db", 50_000);
+    /// w.capture_history();
+    /// assert_eq!(alerts.load(Ordering::Relaxed), 1);
+    /// ```
+    pub fn on_anomaly<F>(&self, sensitivity: f64, callback: F)
+    where
+        F: Fn(&str, &Anomaly) + Send + Sync + 'static,
+    {
+        self.lock(&self.inner.anomaly_hooks)
+            .push((sensitivity, Arc::new(callback)));
+    }
+
+    /// Register a callback fired when a record of `name` exceeds `threshold`.
+    ///
+    /// The callback receives the metric name and the recorded value (ns,
+    /// before clamping to the histogram bounds). It runs synchronously on the
+    /// recording thread, outside internal locks, so it can log, bump an error
+    /// counter or even record other metrics; keep it cheap. Records below
+    /// every registered threshold cost one extra atomic load. Hooks stay
+    /// registered across [`clear`](Self::clear).
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// let w = Watch::new();
+    /// let slow = Arc::new(AtomicU64::new(0));
+    /// let s = Arc::clone(&slow);
+    /// w.on_threshold("db.query", Duration::from_millis(500), move |_name, ns| {
+    ///     s.store(ns, Ordering::Relaxed);
+    /// });
+    /// w.record("db.query", 2_000_000);
+    /// w.record("db.query", 700_000_000);
+    /// w.record("http.get", 900_000_000);
+    /// assert_eq!(slow.load(Ordering::Relaxed), 700_000_000);
+    /// ```
+    pub fn on_threshold<F>(&self, name: &str, threshold: impl Into<crate::Duration>, callback: F)
+    where
+        F: Fn(&str, u64) + Send + Sync + 'static,
+    {
+        let threshold = u64::try_from(threshold.into().as_nanos()).unwrap_or(u64::MAX);
+        let mut hooks = self.lock_threshold_hooks();
+        hooks.push((Arc::from(name), threshold, Arc::new(callback)));
+        self.inner
+            .threshold_floor
+            .fetch_min(threshold, Ordering::Relaxed);
+    }
+
+    /// Register a slow-op callback: each record of `name` above `threshold`
+    /// is reported as a [`SlowOp`] carrying the name, value and wall-clock time.
+    ///
+    /// Built on [`on_threshold`](Self::on_threshold), with the same cost and
+    /// threading; `SlowOp` displays as a logfmt line for log pipelines. Records
+    /// made through a [`Timer`](crate::Timer) or `stopwatch!` are covered too.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{SlowOp, Watch};
+    /// use std::sync::{Arc, Mutex};
+    /// use std::time::Duration;
+    /// let w = Watch::new();
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    /// let l = Arc::clone(&log);
+    /// w.on_slow("db.query", Duration::from_millis(100), move |op: &SlowOp| {
+    ///     l.lock().unwrap().push(op.to_string());
+    /// });
+    /// w.record("db.query", 5_000_000);
+    /// w.record("db.query", 250_000_000);
+    /// let log = log.lock().unwrap();
+    /// assert_eq!(log.len(), 1);
+    /// assert!(log[0].starts_with("slow_op name=\"db.query\" duration=250.00ms"));
+    /// ```
+    pub fn on_slow<F>(&self, name: &str, threshold: impl Into<crate::Duration>, callback: F)
+    where
+        F: Fn(&SlowOp) + Send + Sync + 'static,
+    {
+        self.on_threshold(name, threshold, move |name, duration_ns| {
+            callback(&SlowOp {
+                name: name.to_owned(),
+                duration_ns,
+                timestamp_ns: now_epoch_nanos(),
+            });
+        });
+    }
+
+    /// Log each record of `name` above `threshold` to stderr as one
+    /// [`SlowOp`] line (a slow-query log).
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// use std::time::Duration;
+    /// let w = Watch::new();
+    /// w.log_slow("checkout", Duration::from_secs(1));
+    /// w.record("checkout", 1_500_000_000); // slow_op name="checkout" duration=1.50s ...
+    /// ```
+    pub fn log_slow(&self, name: &str, threshold: impl Into<crate::Duration>) {
+        self.on_slow(name, threshold, |op| eprintln!("{op}"));
+    }
+
+    /// Return retained history for `name`, oldest first.
+    ///
+    /// Each entry is `(timestamp, stats)` where the timestamp is nanoseconds
+    /// since the UNIX epoch at capture time. Stats are cumulative as of that
+    /// moment. Returns an empty `Vec` if history is disabled or the metric is
+    /// unknown.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// use std::time::Duration;
+    /// let w = Watch::new();
+    /// w.enable_history(Duration::from_secs(60), 2);
+    /// for v in [10, 20, 30] {
+    ///     w.record("rpc", v);
+    ///     w.capture_history();
+    /// }
+    /// let h = w.history("rpc");
+    /// assert_eq!(h.len(), 2); // oldest entry evicted
+    /// assert_eq!(h[1].1.count, 3);
+    /// ```
+    pub fn history(&self, name: &str) -> Vec<(u128, WatchStats)> {
+        self.lock_history()
+            .get(name)
+            .map(|ring| ring.stats.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Time × latency matrix of the samples recorded for `name` over the
+    /// last `window`, in columns of `resolution` (see [`Heatmap`]).
+    ///
+    /// Built from history: each capture contributes the samples recorded
+    /// since the previous one to the column containing its capture time, so
+    /// `resolution` should be a multiple of the history interval. Requires
+    /// [`enable_history`](Self::enable_history); the matrix has no rows when
+    /// history is disabled or `name` is unknown.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::Watch;
+    /// use std::time::Duration;
+    /// let w = Watch::new();
+    /// w.enable_history(Duration::from_secs(10), 60);
+    /// w.record("api", 1_500);
+    /// w.record("api", 1_800);
+    /// w.capture_history();
+    /// w.record("api", 40_000);
+    /// w.capture_history();
+    /// let map = w.heatmap("api", Duration::from_secs(60), Duration::from_secs(600));
+    /// assert_eq!(map.counts.len(), 10);
+    /// assert_eq!(map.buckets.first(), Some(&(1_024, 2_048)));
+    /// assert_eq!(map.total(), 3);
+    /// ```
+    pub fn heatmap(&self, name: &str, resolution: StdDuration, window: StdDuration) -> Heatmap {
+        let nanos = |d: StdDuration| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX);
+        let mut history = self.lock_history();
+        let intervals = history
+            .get_mut(name)
+            .map_or(&[][..], |ring| ring.intervals.make_contiguous());
+        heatmap::build(
+            intervals,
+            now_epoch_nanos(),
+            nanos(resolution),
+            nanos(window),
+        )
+    }
+
+    #[inline]
+    fn lock_history(&self) -> std::sync::MutexGuard<'_, HistoryMap> {
+        self.lock(&self.inner.history)
+    }
+
+    fn lock_threshold_hooks(
+        &self,
+    ) -> std::sync::MutexGuard<'_, Vec<(Arc<str>, u64, ThresholdHook)>> {
+        self.lock(&self.inner.threshold_hooks)
+    }
+
+    fn lock_precomputed(&self) -> std::sync::MutexGuard<'_, Option<StatsTable>> {
+        self.lock(&self.inner.precomputed)
+    }
+
+    fn lock_folded(&self) -> std::sync::MutexGuard<'_, HashMap<String, u64>> {
+        self.lock(&self.inner.folded)
+    }
+
+    fn lock_suffixed(&self) -> std::sync::MutexGuard<'_, SuffixedIds> {
+        self.lock(&self.inner.suffixed)
+    }
+
+    fn lock_last_scrape(&self) -> std::sync::MutexGuard<'_, HashMap<Arc<str>, HistogramSnapshot>> {
+        self.lock(&self.inner.last_scrape)
+    }
+}
+
+/// Current wall-clock time in nanoseconds since the UNIX epoch (0 under miri).
+pub(crate) fn now_epoch_nanos() -> u128 {
+    crate::clock::__unix_now_ns()
+}
+
+/// Builder for configuring and constructing a `Watch`.
+#[derive(Debug, Clone)]
+pub struct WatchBuilderGeneric<B: HistBackend> {
+    lowest: u64,
+    highest: u64,
+    clock: Option<Arc<dyn Clock>>,
+    max_metrics: Option<usize>,
+    ttl: Option<StdDuration>,
+    rates: bool,
+    track_last_seen: bool,
+    slowest: Option<(usize, StdDuration)>,
+    sample_rate: u64,
+    _marker: PhantomData<B>,
+}
+
+/// Feature-selected concrete `WatchBuilder` using the active histogram backend.
+pub type WatchBuilder = WatchBuilderGeneric<Backend>;
+
+impl<B: HistBackend> Default for WatchBuilderGeneric<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: HistBackend> WatchBuilderGeneric<B> {
+    /// Start a builder with default bounds: 1ns..~1h, 3 significant figures.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::WatchBuilder;
+    /// let _w = WatchBuilder::new().lowest(1).highest(1_000_000).build();
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            lowest: DEFAULT_LOWEST,
+            highest: DEFAULT_HIGHEST,
+            clock: None,
+            max_metrics: None,
+            ttl: None,
+            rates: false,
+            track_last_seen: false,
+            slowest: None,
+            sample_rate: 1,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the lowest discernible value in nanoseconds (min 1ns).
+    #[inline]
+    #[must_use]
+    pub fn lowest(mut self, ns: u64) -> Self {
+        self.lowest = ns.max(1);
+        self
+    }
+
+    /// Set the highest trackable value in nanoseconds (must be > lowest).
+    #[inline]
+    #[must_use]
+    pub fn highest(mut self, ns: u64) -> Self {
+        self.highest = ns;
+        self
+    }
+
+    /// Use `clock` as the time source for `Timer`s created on the built Watch.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::{SystemClock, Timer, WatchBuilder};
+    /// let w = WatchBuilder::new().clock(SystemClock).build();
+    /// Timer::new(w.clone(), "op").stop();
+    /// assert_eq!(w.snapshot()["op"].count, 1);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Cap the number of distinct metrics (min 1).
+    ///
+    /// Registering a new metric at the cap evicts the least recently recorded
+    /// one. Evictions are counted in [`Watch::dropped_metrics`](WatchGeneric::dropped_metrics).
+    /// Enabling eviction adds one clock read per `record`.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::WatchBuilder;
+    /// let w = WatchBuilder::new().max_metrics(2).build();
+    /// w.record("a", 1);
+    /// w.record("b", 1);
+    /// w.record("a", 1);
+    /// w.record("c", 1); // evicts "b", the least recently used
+    /// let snap = w.snapshot();
+    /// assert!(snap.contains_key("a") && snap.contains_key("c"));
+    /// assert!(!snap.contains_key("b"));
+    /// assert_eq!(snap[benchmark::Watch::DROPPED_METRICS].count, 1);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn max_metrics(mut self, n: usize) -> Self {
+        self.max_metrics = Some(n.max(1));
+        self
+    }
+
+    /// Evict metrics that have not been recorded for longer than `ttl`.
+    ///
+    /// Eviction is lazy; see [`Watch::evict_expired`](WatchGeneric::evict_expired).
+    #[inline]
+    #[must_use]
+    pub fn ttl(mut self, ttl: StdDuration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Track per-metric call rates, reported as `WatchStats::rate_1s` and `rate_1m`.
+    ///
+    /// Rates come from per-second counters driven by the Watch clock; they
+    /// cost one clock read and one atomic add per `record`, plus ~0.5 KiB per
+    /// metric.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::test_util::MockClock;
+    /// use benchmark::WatchBuilder;
+    /// let clock = MockClock::new();
+    /// let w = WatchBuilder::new().clock(clock.clone()).rates(true).build();
+    /// for _ in 0..120 {
+    ///     w.record("req", 1_000);
+    ///     clock.advance(500_000_000); // 2 calls per second
+    /// }
+    /// let s = w.snapshot()["req"];
+    /// assert_eq!(s.rate_1s, 2.0);
+    /// assert_eq!(s.rate_1m, 2.0);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn rates(mut self, enabled: bool) -> Self {
+        self.rates = enabled;
+        self
+    }
+
+    /// Keep [`WatchStats::last_seen_ns`] and [`Watch::idle_for`](WatchGeneric::idle_for)
+    /// current for every metric, as [`MetricConfig::track_last_seen`] does
+    /// for one.
+    ///
+    /// Off by default: it costs one clock read per kept record. Eviction,
+    /// rates and slowest tracking read the clock anyway and imply it.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::WatchBuilder;
+    /// let w = WatchBuilder::new().track_last_seen(true).build();
+    /// w.record("job", 1_000);
+    /// let s = w.snapshot()["job"];
+    /// assert!(s.last_seen_ns >= s.first_seen_ns && s.first_seen_ns > 0);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn track_last_seen(mut self, enabled: bool) -> Self {
+        self.track_last_seen = enabled;
+        self
+    }
+
+    /// Keep the `k` slowest samples (min 1) of each metric over a sliding
+    /// `window`, with wall-clock timestamps; see [`Watch::slowest`](WatchGeneric::slowest).
+    ///
+    /// Windows follow the Watch clock; queries cover the current and the
+    /// previous window, i.e. the last `window` to `2 × window`. Costs one
+    /// clock read per `record`; only samples slower than the current K-th
+    /// slowest take a lock.
+    #[inline]
+    #[must_use]
+    pub fn slowest(mut self, k: usize, window: StdDuration) -> Self {
+        self.slowest = Some((k.max(1), window));
+        self
+    }
+
+    /// Record only about one in `n` events (min 1, the default: record all)
+    /// for every metric without a [`MetricConfig::sample_rate`] override.
+    ///
+    /// Skipped records cost a random draw and a min/max comparison instead
+    /// of a histogram update. Snapshots scale `count`, `total_ns`, rates and
+    /// Apdex counts by `n`, so they estimate the full stream; percentiles and
+    /// the mean come from the sample, while `min` and `max` stay exact in
+    /// full snapshots. [`snapshot_delta`](WatchGeneric::snapshot_delta)
+    /// reports sampled min/max, and histogram exports carry the raw sampled
+    /// counts. Per-record side effects (slowest samples, last-seen time)
+    /// only see kept records; [`on_threshold`](WatchGeneric::on_threshold)
+    /// hooks see every record.
+    ///
+    /// # Examples
+    /// ```
+    /// use benchmark::WatchBuilder;
+    /// let w = WatchBuilder::new().sample_rate(10).build();
+    /// for v in 1..=10_000 {
+    ///     w.record("hot", v);
+    /// }
+    /// let s = w.snapshot()["hot"];
+    /// assert_eq!(s.count % 10, 0); // an estimate, in steps of 10
+    /// assert_eq!((s.min, s.max), (1, 10_000)); // exact
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn sample_rate(mut self, n: u64) -> Self {
+        self.sample_rate = n.max(1);
+        self
+    }
+
+    /// Build the `Watch` with the configured settings.
+    #[inline]
+    pub fn build(self) -> WatchGeneric<B> {
+        WatchGeneric::<B>::from_builder(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_reports_stddev() {
+        let w = Watch::new();
+        for v in [100, 300] {
+            w.record("op", v);
+        }
+        w.record("flat", 50);
+        let snap = w.snapshot();
+        assert!((snap["op"].stddev - 100.0).abs() < 1.0);
+        assert!(snap["flat"].stddev.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_total_ns_full_and_delta() {
+        let w = Watch::new();
+        w.record("op", 1_000);
+        w.record("op", 2_000);
+        assert_eq!(w.snapshot()["op"].total_ns, 3_000);
+        let _ = w.snapshot_delta();
+        w.record("op", 500);
+        assert_eq!(w.snapshot_delta()["op"].total_ns, 500);
+        assert_eq!(w.snapshot()["op"].total_ns, 3_500);
+    }
+
+    #[test]
+    fn test_snapshot_and_reset_loses_nothing_under_concurrent_records() {
+        let w = Watch::new();
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let w = w.clone();
+                std::thread::spawn(move || {
+                    for v in 1..=10_000u64 {
+                        w.record("op", v);
+                    }
+                })
+            })
+            .collect();
+        let mut count = 0;
+        let mut total = 0;
+        while !writers.iter().all(std::thread::JoinHandle::is_finished) {
+            if let Some(s) = w.snapshot_and_reset().get("op") {
+                count += s.count;
+                total += s.total_ns;
+            }
+        }
+        for t in writers {
+            t.join().unwrap();
+        }
+        let s = &w.snapshot_and_reset()["op"];
+        assert_eq!(count + s.count, 40_000);
+        assert_eq!(total + s.total_ns, 4 * 50_005_000);
+        assert_eq!(w.snapshot()["op"].count, 0);
+    }
+
+    #[test]
+    fn test_concurrent_first_records_register_every_name_once() {
+        let w = Watch::new();
+        let id = w.register("shared");
+        std::thread::scope(|s| {
+            for t in 0..4u64 {
+                let w = &w;
+                let id = &id;
+                s.spawn(move || {
+                    for i in 0..50u64 {
+                        w.record(&format!("m{}", (i + t * 7) % 60), 1);
+                        w.record_id(id, 1);
+                    }
+                });
+            }
+        });
+        let snap = w.snapshot();
+        assert_eq!(snap.len(), 61);
+        assert_eq!(snap.values().map(|s| s.count).sum::<u64>(), 400);
+        assert_eq!(snap["shared"].count, 200);
+        assert_eq!(w.register("shared"), id);
+    }
+
+    #[test]
+    fn test_record_many_and_iter_match_single_records() {
+        let w = Watch::new();
+        let fired = Arc::new(AtomicUsize::new(0));
+        let f = Arc::clone(&fired);
+        w.on_threshold("bulk", StdDuration::from_nanos(250), move |_, _| {
+            f.fetch_add(1, Ordering::Relaxed);
+        });
+        w.record_many("bulk", &[100, 200, 300]);
+        w.record_many("bulk", &[]);
+        let inner = w.clone();
+        w.record_iter(
+            "bulk",
+            [400, 50].into_iter().inspect(|&v| inner.record("seen", v)),
+        );
+        for v in [100, 200, 300, 400, 50] {
+            w.record("single", v);
+        }
+        let s = w.snapshot();
+        assert_eq!(s["bulk"].count, 5);
+        assert_eq!(s["bulk"].total_ns, s["single"].total_ns);
+        assert_eq!(s["seen"].count, 2);
+        assert_eq!(fired.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_cached_map_sees_registration_and_clear() {
+        let w = Watch::new();
+        w.record("a", 10);
+        let other = std::thread::spawn({
+            let w = w.clone();
+            move || {
+                w.record("b", 20);
+                w.clear_name("a");
+            }
+        });
+        other.join().unwrap();
+        // This thread's cached map predates both changes.
+        let snap = w.snapshot();
+        assert!(!snap.contains_key("a"));
+        assert_eq!(snap["b"].count, 1);
+        w.record("a", 30);
+        assert_eq!(w.snapshot()["a"].count, 1);
+    }
+
+    #[test]
+    fn test_more_watches_than_cache_slots() {
+        let watches: Vec<Watch> = (0..MAP_CACHE_SLOTS * 2).map(|_| Watch::new()).collect();
+        for _ in 0..3 {
+            for (i, w) in watches.iter().enumerate() {
+                w.record("op", i as u64 + 1);
+            }
+        }
+        for (i, w) in watches.iter().enumerate() {
+            let s = w.snapshot()["op"];
+            assert_eq!((s.count, s.min), (3, i as u64 + 1));
+        }
+    }
+
+    #[test]
+    fn test_record_under_registration_churn() {
+        let w = Watch::new();
+        let writers: Vec<_> = (0..4)
+            .map(|t| {
+                let w = w.clone();
+                std::thread::spawn(move || {
+                    for i in 0..2_000u64 {
+                        w.record("hot", 100);
+                        w.record(&format!("t{t}.m{}", i % 64), 1);
+                    }
+                })
+            })
+            .collect();
+        for h in writers {
+            h.join().unwrap();
+        }
+        let snap = w.snapshot();
+        assert_eq!(snap["hot"].count, 8_000);
+        assert_eq!(snap.len(), 1 + 4 * 64);
+        assert_eq!(snap.values().map(|s| s.count).sum::<u64>(), 16_000);
+    }
+
+    #[test]
+    fn test_precompute_refreshes_in_background() {
+        let w = Watch::new();
+        w.record("a", 10);
+        w.enable_precompute(StdDuration::from_millis(5));
+        w.record("a", 20);
+        let deadline = Instant::now() + StdDuration::from_secs(5);
+        while w.snapshot()["a"].count != 2 {
+            assert!(Instant::now() < deadline, "table never refreshed");
+            std::thread::sleep(StdDuration::from_millis(1));
+        }
+
+        w.enable_precompute(StdDuration::from_secs(3600));
+        w.record("b", 1);
+        assert!(!w.snapshot().contains_key("b"));
+        w.clear_name("a");
+        assert!(!w.snapshot().contains_key("a"));
+        w.clear();
+        w.record("c", 1);
+        assert_eq!(w.snapshot()["c"].count, 1); // live until next refresh
+
+        w.refresh_precomputed();
+        w.enable_precompute(StdDuration::ZERO);
+        w.record("c", 1);
+        assert_eq!(w.snapshot()["c"].count, 2);
+    }
+
+    #[test]
+    fn test_lru_and_ttl_eviction() {
+        let clock = crate::test_util::MockClock::new();
+        let w = Watch::builder()
+            .clock(clock.clone())
+            .max_metrics(3)
+            .ttl(StdDuration::from_nanos(100))
+            .build();
+        w.enable_history(StdDuration::from_secs(3600), 4);
+        for name in ["a", "b", "c"] {
+            w.record(name, 1);
+            clock.advance(10);
+        }
+        w.capture_history();
+        w.record("a", 1); // "b" is now least recently used
+        w.record("d", 1);
+        assert_eq!(w.dropped_metrics(), 1);
+        assert!(w.history("b").is_empty());
+        assert_eq!(w.history("c").len(), 1);
+
+        clock.advance(95); // "c" (t=20) idle 105ns; "a"/"d" (t=30) idle 95ns
+        let snap = w.snapshot();
+        assert_eq!(w.dropped_metrics(), 2);
+        assert!(!snap.contains_key("c"));
+        assert_eq!(snap.len(), 3);
+        assert_eq!(snap[Watch::DROPPED_METRICS].count, 2);
+        assert_eq!(w.evict_expired(), 0);
+
+        clock.advance(10);
+        assert_eq!(w.evict_expired(), 2);
+        assert_eq!(w.snapshot().len(), 1);
+    }
+
+    #[test]
+    fn test_internal_stats_count_records_sampling_and_eviction() {
+        let w = Watch::builder().max_metrics(2).build();
+        w.configure_metric(
+            "sampled",
+            MetricConfig {
+                sample_rate: Some(4),
+                ..MetricConfig::default()
+            },
+        );
+        for _ in 0..400 {
+            w.record("sampled", 10);
+        }
+        let kept = w.snapshot()["sampled"].count / 4;
+        w.record_many("a", &[1, 2, 3]);
+        w.record("b", 1); // evicts the least recently used metric
+        let s = w.internal_stats();
+        assert_eq!(s.records, 404);
+        assert_eq!(s.sampled_out, 400 - kept);
+        assert_eq!(s.slow_path_inserts, 3);
+        assert_eq!(s.evicted_metrics, 1);
+        assert_eq!(s.evicted_samples, kept);
+
+        let w = Watch::new();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for i in 0..1_000u64 {
+                        w.record(&format!("m{}", i % 10), i);
+                    }
+                });
+            }
+        });
+        let s = w.internal_stats();
+        assert_eq!(s.records, 4_000);
+        assert!((10..=40).contains(&s.slow_path_inserts), "{s:?}");
+        assert_eq!(s.sampled_out, 0);
+    }
+
+    #[test]
+    fn test_unbounded_watch_reports_no_dropped_entry() {
+        let w = Watch::new();
+        for i in 0..100 {
+            w.record(&format!("m{i}"), 1);
+        }
+        let snap = w.snapshot();
+        assert_eq!(snap.len(), 100);
+        assert!(!snap.contains_key(Watch::DROPPED_METRICS));
+        assert_eq!(w.evict_expired(), 0);
+    }
+
+    #[test]
+    fn test_budgeted_snapshots_rotate_until_complete() {
+        let w = Watch::new();
+        for i in 0..50 {
+            w.record(&format!("m{i}"), 1);
+        }
+        let mut seen = std::collections::HashSet::new();
+        // A budget too small for all 50; tiny but non-zero so progress is made.
+        for _ in 0..10_000 {
+            let part = w.snapshot_with_budget(StdDuration::from_nanos(1));
+            seen.extend(part.stats.into_keys());
+            if seen.len() == 50 {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), 50);
+        assert!(!w.snapshot_with_budget(StdDuration::from_secs(5)).truncated);
+    }
+
+    #[test]
+    fn test_budgeted_snapshot_evicts_like_snapshot() {
+        let clock = crate::test_util::MockClock::new();
+        let w = Watch::builder()
+            .clock(clock.clone())
+            .ttl(StdDuration::from_nanos(100))
+            .build();
+        w.record("old", 1);
+        clock.advance(60);
+        w.record("new", 1);
+        clock.advance(60);
+        let part = w.snapshot_with_budget(StdDuration::from_secs(5));
+        assert!(!part.truncated);
+        assert!(!part.stats.contains_key("old"));
+        assert_eq!(part.stats[Watch::DROPPED_METRICS].count, 1);
+        assert_eq!(part.stats.len(), 2);
+        let none = w.snapshot_with_budget(StdDuration::ZERO);
+        assert_eq!(none.stats.len(), 1);
+        assert!(none.truncated);
+    }
+
+    #[test]
+    fn test_slowest_samples_follow_window() {
+        let clock = crate::test_util::MockClock::new();
+        let w = Watch::builder()
+            .clock(clock.clone())
+            .slowest(2, StdDuration::from_micros(1))
+            .build();
+        for v in [50, 700, 20, 300] {
+            w.record("q", v);
+        }
+        w.record("a\"b", 9);
+        let vals = |s: Vec<SlowSample>| s.iter().map(|s| s.value_ns).collect::<Vec<_>>();
+        assert_eq!(vals(w.slowest("q")), [700, 300]);
+        assert!(w.slowest("missing").is_empty());
+        assert_eq!(w.slowest_all().len(), 2);
+        let json = w.slowest_json();
+        assert!(json.starts_with(r#"{"a\"b":[{"value_ns":9,"#), "{json}");
+        assert!(json.contains(r#""q":[{"value_ns":700,"#), "{json}");
+
+        clock.advance(2_500);
+        assert!(w.slowest("q").is_empty());
+        assert!(Watch::new().slowest_all().is_empty());
+    }
+
+    #[test]
+    fn test_metric_config_overrides_bounds_and_precision() {
+        // The hdr backend reports values to 3 significant figures.
+        let near = |a: u64, b: u64| a.abs_diff(b) <= b / 1_000;
+        let w = Watch::with_bounds(100, 1_000_000);
+        w.record("rpc", 5);
+        w.configure_metric(
+            "rpc",
+            MetricConfig {
+                lowest: Some(1),
+                ..MetricConfig::default()
+            },
+        );
+        // Existing samples are discarded; new ones use the override.
+        assert!(!w.snapshot().contains_key("rpc"));
+        w.record("rpc", 5);
+        w.record("rpc", 5_000_000);
+        let s = w.snapshot()["rpc"];
+        assert_eq!(s.min, 5);
+        assert!(near(s.max, 1_000_000));
+
+        w.configure_metric(
+            "batch",
+            MetricConfig {
+                highest: Some(60_000_000_000),
+                sub_buckets: Some(128),
+                ..MetricConfig::default()
+            },
+        );
+        for v in [1_100_000_000, 1_500_000_000, 1_900_000_000] {
+            w.record("batch", v);
+            w.record("other", v);
+        }
+        let snap = w.snapshot();
+        assert!(near(snap["other"].max, 1_000_000));
+        assert!(near(snap["batch"].max, 1_900_000_000));
+        #[cfg(not(feature = "hdr"))]
+        assert!(snap["batch"].p50.abs_diff(1_500_000_000) <= 15_000_000);
+
+        w.configure_metric(
+            "summed",
+            MetricConfig {
+                bucket_sums: true,
+                ..MetricConfig::default()
+            },
+        );
+        for v in [150_000, 151_000, 152_000, 153_000] {
+            w.record("summed", v);
+        }
+        #[cfg(not(feature = "hdr"))]
+        assert!(w.snapshot()["summed"].p50.abs_diff(151_000) <= 1_000);
+
+        // Bounds past the hdr backend's 1h default are tracked, not clamped.
+        w.configure_metric(
+            "nightly",
+            MetricConfig {
+                highest: Some(36_000_000_000_000),
+                ..MetricConfig::default()
+            },
+        );
+        w.record("nightly", 7_200_000_000_000);
+        assert!(near(w.snapshot()["nightly"].max, 7_200_000_000_000));
+    }
+
+    #[test]
+    fn test_clamped_counts_per_side() {
+        let w = Watch::with_bounds(100, 1_000_000);
+        for v in [5, 99, 100, 500, 1_000_000, 1_000_001, u64::MAX] {
+            w.record("rpc", v);
+        }
+        w.record("ok", 500);
+        let snap = w.snapshot();
+        let s = snap["rpc"];
+        assert_eq!((s.clamped_low, s.clamped_high), (2, 2));
+        assert_eq!(s.count, 7);
+        assert_eq!((snap["ok"].clamped_low, snap["ok"].clamped_high), (0, 0));
+        // Cumulative, like the Apdex counts.
+        w.snapshot_and_reset();
+        assert_eq!(w.snapshot()["rpc"].clamped_high, 2);
+    }
+
+    #[test]
+    fn test_record_returning_reports_watch_bounds() {
+        let w = Watch::with_bounds(100, 1_000_000);
+        let low = w.record_returning("rpc", 5).unwrap();
+        let edge = w.record_returning("rpc", 100).unwrap();
+        assert!(low.clamped && !edge.clamped);
+        assert_eq!(low.bucket_index, edge.bucket_index);
+        assert!(w.record_returning("rpc", 1_000_001).unwrap().clamped);
+        assert!(!w.record_returning("rpc", 500).unwrap().clamped);
+        let s = w.snapshot()["rpc"];
+        assert_eq!((s.count, s.clamped_low, s.clamped_high), (4, 1, 1));
+    }
+
+    #[test]
+    fn test_apdex_counts_per_metric() {
+        let w = Watch::new();
+        w.configure_metric(
+            "api",
+            MetricConfig {
+                apdex_target: Some(100_000_000), // 100ms
+                ..MetricConfig::default()
+            },
+        );
+        for ms in [20, 80, 100, 150, 390, 2_000] {
+            w.record("api", ms * 1_000_000);
+            w.record("plain", ms * 1_000_000);
+        }
+        let snap = w.snapshot();
+        let s = snap["api"];
+        assert_eq!(s.apdex_target_ns, 100_000_000);
+        assert_eq!(
+            (s.apdex_satisfied, s.apdex_tolerating, s.apdex_frustrated),
+            (3, 2, 1)
+        );
+        assert!((s.apdex - 4.0 / 6.0).abs() < 1e-12);
+        assert!((s.under_target_pct - 50.0).abs() < 1e-12);
+        assert_eq!(snap["plain"].apdex_target_ns, 0);
+        assert!(snap["plain"].apdex.abs() < f64::EPSILON);
+
+        // Cumulative counts are reported by interval snapshots too.
+        assert_eq!(w.snapshot_delta()["api"].apdex_satisfied, 3);
+        w.clear_name("api");
+        w.record("api", 1);
+        assert_eq!(w.snapshot()["api"].apdex_satisfied, 1);
+    }
+
+    #[test]
+    fn test_on_slow_reports_name_value_and_timestamp() {
+        let w = Watch::new();
+        let ops = Arc::new(Mutex::new(Vec::new()));
+        let o = Arc::clone(&ops);
+        w.on_slow("db", StdDuration::from_millis(1), move |op| {
+            o.lock().unwrap().push(op.clone());
+        });
+        let before = now_epoch_nanos();
+        w.record("db", 999_999);
+        w.record("db", 2_000_000);
+        w.record("other", 2_000_000);
+        let ops = ops.lock().unwrap();
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].name, "db");
+        assert_eq!(ops[0].duration_ns, 2_000_000);
+        assert!(ops[0].timestamp_ns >= before);
+        let line = ops[0].to_string();
+        assert!(
+            line.starts_with(
+                "slow_op name=\"db\" duration=2.00ms duration_ns=2000000 timestamp_ns="
+            ),
+            "{line}"
+        );
+    }
+
+    #[test]
+    fn test_threshold_hooks_fire_outside_locks() {
+        let w = Watch::with_bounds(1, 1_000);
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let f = Arc::clone(&fired);
+        let inner = w.clone();
+        w.on_threshold("db", StdDuration::from_micros(5), move |name, ns| {
+            f.lock().unwrap().push((name.to_string(), ns));
+            // Recording from the hook must not deadlock.
+            inner.record("db.slow", ns);
+        });
+        w.on_threshold("db", crate::Duration::from_nanos(50_000), |_, _| {});
+        for ns in [10, 5_000, 5_001, 80_000] {
+            w.record("db", ns);
+            w.record("other", ns);
+        }
+        // Values are reported before clamping to the Watch bounds.
+        assert_eq!(
+            *fired.lock().unwrap(),
+            [("db".to_string(), 5_001), ("db".to_string(), 80_000)]
+        );
+        assert_eq!(w.snapshot()["db.slow"].count, 2);
+    }
+
+    #[test]
+    fn test_sampling_scales_counts_and_keeps_exact_extremes() {
+        let w = WatchBuilder::new().sample_rate(8).build();
+        w.configure_metric(
+            "all",
+            MetricConfig {
+                sample_rate: Some(1),
+                apdex_target: Some(50_000),
+                ..MetricConfig::default()
+            },
+        );
+        w.configure_metric(
+            "slo",
+            MetricConfig {
+                apdex_target: Some(50_000),
+                ..MetricConfig::default()
+            },
+        );
+        for v in 1..=80_000u64 {
+            w.record("hot", v);
+            w.record("all", v);
+            w.record("slo", v);
+        }
+        let snap = w.snapshot();
+        let hot = snap["hot"];
+        assert_eq!(hot.count % 8, 0);
+        assert!((64_000..96_000).contains(&hot.count), "count {}", hot.count);
+        assert_eq!((hot.min, hot.max), (1, 80_000));
+        assert!((hot.mean - 40_000.0).abs() < 4_000.0);
+        assert!(hot.total_ns.abs_diff(3_200_040_000) < 400_000_000);
+
+        assert_eq!(snap["all"].count, 80_000);
+        let slo = snap["slo"];
+        assert_eq!(
+            slo.apdex_satisfied + slo.apdex_tolerating + slo.apdex_frustrated,
+            slo.count
+        );
+        assert!((slo.under_target_pct - 62.5).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_metric_ids_survive_clear_and_eviction() {
+        let w = WatchBuilder::new().max_metrics(2).build();
+        let a = w.register("a");
+        let b = w.register("b");
+        assert_ne!(a, b);
+        assert_eq!(a.name(), "a");
+        w.record_id(&a, 10);
+        w.record("c", 1); // evicts "b"
+        assert!(!w.snapshot().contains_key("b"));
+        w.record_id(&b, 20);
+        assert_eq!(w.snapshot()["b"].count, 1);
+
+        w.clear_name("a");
+        w.record_id(&a, 30);
+        assert_eq!(w.snapshot()["a"].count, 1);
+
+        // An id from another Watch records by name.
+        let other = Watch::new();
+        other.record_id(&a, 40);
+        assert_eq!(other.snapshot()["a"].count, 1);
+        assert_eq!(w.snapshot()["a"].count, 1);
+    }
+
+    #[test]
+    fn test_retain_filters_by_name_and_stats() {
+        let w = Watch::new();
+        w.record("keep.fast", 10);
+        w.record("drop.slow", 5_000);
+        w.record("keep.slow", 5_000);
+        w.enable_history(std::time::Duration::from_secs(3_600), 4);
+        w.capture_history();
+        assert_eq!(w.metric_names(), ["drop.slow", "keep.fast", "keep.slow"]);
+
+        let removed = w.retain(|name, stats| name.starts_with("keep.") || stats.max < 1_000);
+        assert_eq!(removed, 1);
+        assert!(!w.contains("drop.slow"));
+        assert!(w.history("drop.slow").is_empty());
+        assert_eq!(w.history("keep.slow").len(), 1);
+        assert_eq!(w.retain(|_, stats| stats.max < 1_000), 1);
+        assert_eq!(w.metric_names(), ["keep.fast"]);
+        assert_eq!(w.retain(|_, _| true), 0);
+    }
+
+    #[test]
+    fn test_record_duration_saturates_and_clamps() {
+        let w = Watch::with_bounds(10, 1_000_000);
+        w.record_duration("d", StdDuration::MAX);
+        w.record_duration("d", crate::Duration::from_nanos(u128::MAX));
+        w.record_duration("d", StdDuration::ZERO);
+        let s = w.snapshot()["d"];
+        assert_eq!(s.count, 3);
+        assert!(s.max >= 1_000_000 && s.max < 1_010_000);
+        assert_eq!(s.total_ns, 2_000_010);
+    }
+
+    #[test]
+    fn test_first_and_last_seen_follow_clock() {
+        let clock = crate::test_util::MockClock::new();
+        let w = Watch::builder().clock(clock.clone()).build();
+        w.configure_metric(
+            "job",
+            MetricConfig {
+                track_last_seen: true,
+                ..MetricConfig::default()
+            },
+        );
+        clock.advance(1_000);
+        w.record("job", 5);
+        clock.advance(4_000);
+        w.record("job", 5);
+        clock.advance(2_000);
+        let s = w.snapshot()["job"];
+        assert_eq!(s.last_seen_ns - s.first_seen_ns, 4_000);
+        let now = now_epoch_nanos();
+        assert!(s.first_seen_ns <= now && now - s.first_seen_ns < 60_000_000_000);
+        assert_eq!(w.idle_for("job"), Some(StdDuration::from_micros(2)));
+        assert_eq!(w.snapshot_delta()["job"].last_seen_ns, s.last_seen_ns);
+
+        // Re-registration after a clear starts over.
+        w.clear_name("job");
+        w.record("job", 5);
+        let again = w.snapshot()["job"];
+        assert_eq!(again.first_seen_ns, again.last_seen_ns);
+        assert_eq!(again.first_seen_ns - s.last_seen_ns, 2_000);
+    }
+
+    #[test]
+    fn test_clock_may_record_into_a_watch() {
+        /// Counts its own reads in another Watch.
+        #[derive(Debug)]
+        struct CountingClock(Watch);
+        impl Clock for CountingClock {
+            fn now_ns(&self) -> u64 {
+                self.0.record("clock.reads", 1);
+                clock::system_now_ns()
+            }
+        }
+        let reads = Watch::new();
+        let w = Watch::builder().clock(CountingClock(reads.clone())).build();
+        w.record("job", 5);
+        let id = w.register("io");
+        w.record_id(&id, 5);
+        w.record("job", 5);
+        assert_eq!(w.snapshot()["job"].count, 2);
+        assert_eq!(w.snapshot()["io"].count, 1);
+        assert!(reads.snapshot()["clock.reads"].count > 0);
+    }
+
+    #[test]
+    fn test_seen_timestamps_unset_until_recorded_or_untracked() {
+        let clock = crate::test_util::MockClock::new();
+        let w = Watch::builder().clock(clock.clone()).build();
+        let id = w.register("idle");
+        clock.advance(500);
+        let s = w.snapshot()["idle"];
+        assert_eq!((s.count, s.first_seen_ns, s.last_seen_ns), (0, 0, 0));
+        assert_eq!(w.idle_for("idle"), None);
+
+        // Untracked: the first sample sets `first_seen`, `last_seen` stays unknown.
+        w.record_id(&id, 5);
+        clock.advance(700);
+        w.record_id(&id, 5);
+        let s = w.snapshot()["idle"];
+        assert_ne!(s.first_seen_ns, 0);
+        assert_eq!(s.last_seen_ns, 0);
+        assert_eq!(w.idle_for("idle"), None);
+        w.record_many("batch", &[1, 2]);
+        assert_eq!(w.snapshot()["batch"].first_seen_ns, s.first_seen_ns + 700);
     }
 }
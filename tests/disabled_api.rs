@@ -0,0 +1,11 @@
+//! Compile-time guarantee that the metrics API surface can be named and used
+//! regardless of which features are enabled (real or no-op implementations).
+//! `watch_signatures.rs` additionally coerces methods to `fn` pointers, so a
+//! no-op stub whose signature drifts from the real one fails to compile.
+#![cfg(feature = "std")]
+
+#[test]
+fn metrics_api_compiles_in_every_std_mode() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+}
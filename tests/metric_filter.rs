@@ -0,0 +1,39 @@
+//! The `enable_only` filter is process-wide, so it is exercised in its own
+//! test binary where no other test records concurrently.
+#![cfg(feature = "metrics")]
+
+use benchmark::{enable_all, enable_only, Watch};
+
+#[test]
+fn enable_only_filters_every_record_path_until_cleared() {
+    let w = Watch::new();
+    let id = w.register("http.get");
+    w.record("http.get", 10);
+    w.record("db.query", 10);
+
+    enable_only("db.*");
+    w.record("db.query", 20);
+    w.record("http.get", 20);
+    w.record_id(&id, 20);
+    w.record_many("http.get", &[20, 30]);
+    w.record("cache.hit", 20);
+    let s = w.snapshot();
+    assert_eq!(s["db.query"].count, 2);
+    assert_eq!(s["http.get"].count, 1);
+    // Filtered-out names are still registered, just never recorded.
+    assert_eq!(s["cache.hit"].count, 0);
+
+    // A new filter invalidates every metric's cached decision.
+    enable_only("http");
+    w.record("db.query", 30);
+    w.record_id(&id, 30);
+    w.record("http.get", 30);
+    let s = w.snapshot();
+    assert_eq!((s["db.query"].count, s["http.get"].count), (2, 3));
+
+    enable_all();
+    w.record("db.query", 40);
+    w.record("cache.hit", 40);
+    let s = w.snapshot();
+    assert_eq!((s["db.query"].count, s["cache.hit"].count), (3, 1));
+}
@@ -0,0 +1,154 @@
+// Instrumentation written once, unconditionally, against the public API.
+use benchmark::{
+    stopwatch, stopwatch_if, stopwatch_scope, stopwatch_timed, Collector, Duration, LocalRecorder, Measurement,
+    MetricConfig, MetricId, RecordTo, ScopedTimer, Stats, StreamingStats, Timer, Watch,
+    WatchBuilder, WatchStats,
+};
+
+fn handler(watch: &Watch) -> u32 {
+    let _t = Timer::new(watch.clone(), "handler");
+    stopwatch!(watch, "inner", { 40 + 2 })
+}
+
+fn unused_watch(watch: Watch, name: &'static str) -> u8 {
+    // `watch`/`name` are only referenced by the macro; must not warn when disabled.
+    stopwatch!(watch, name, { 1 })
+}
+
+fn unused_scope(watch: Watch, name: &'static str) -> u8 {
+    stopwatch_scope!(watch, name, { 1 })
+}
+
+fn unused_timed(watch: Watch, name: &'static str) -> (u8, Duration) {
+    stopwatch_timed!(watch, name, { 1 })
+}
+
+fn unused_if(watch: Watch, name: &'static str, enabled: bool) -> u8 {
+    stopwatch_if!(watch, name, enabled, { 1 })
+}
+
+fn main() {
+    let watch: Watch = WatchBuilder::new()
+        .lowest(1)
+        .highest(1_000_000)
+        .sample_rate(1)
+        .track_last_seen(false)
+        .build();
+    assert_eq!(handler(&watch), 42);
+    assert_eq!(unused_watch(Watch::new(), "x"), 1);
+    assert_eq!(unused_timed(Watch::new(), "x").0, 1);
+    assert_eq!(unused_if(Watch::new(), "x", true), 1);
+    watch.configure_metric(
+        "op",
+        MetricConfig {
+            highest: Some(10_000),
+            apdex_target: Some(1_000),
+            sample_rate: Some(2),
+            bucket_sums: true,
+            ..MetricConfig::default()
+        },
+    );
+    watch.on_threshold("op", std::time::Duration::from_millis(500), |_name, _ns| {});
+    watch.on_slow(
+        "op",
+        std::time::Duration::from_millis(500),
+        |op: &benchmark::SlowOp| {
+            let _ = op.to_string();
+        },
+    );
+    watch.log_slow("op", std::time::Duration::from_secs(1));
+    watch.record("op", 10);
+    watch.record_many("op", &[10, 20]);
+    watch.record_iter("op", [30u64].into_iter());
+    let id: MetricId = watch.register("op");
+    watch.record_id(&id, 10);
+    let mut rec: LocalRecorder = watch.recorder("op").batch_size(16);
+    rec.record(10);
+    rec.record_duration(std::time::Duration::from_nanos(10));
+    let _: usize = rec.pending();
+    rec.flush();
+    watch.record_duration("op", std::time::Duration::from_nanos(10));
+    watch.record_duration("op", Duration::from_nanos(10));
+    let _: u64 = watch.record_instant("op", std::time::Instant::now());
+    let _: u64 = Timer::new(watch.clone(), String::from("early")).stop();
+    let _: u64 = watch.start("started").stop();
+    let mut deferred = Timer::paused(watch.clone(), "deferred");
+    deferred.start();
+    deferred.restart();
+    let _: bool = deferred.is_running();
+    let _: u64 = deferred.stop();
+    let _: u64 = watch.scope("scoped").stop();
+    let mut phases: benchmark::Phases = watch.phases("request");
+    let _: u64 = phases.phase("parse");
+    let _: u64 = phases.finish();
+    let _: String = watch.folded_stacks();
+    let _: u64 = ScopedTimer::new(watch.clone(), String::from("scoped")).stop();
+    let _: u8 = unused_scope(watch.clone(), "scoped");
+    let _: u32 = watch.time("closure", || 7);
+    let _: u32 = watch.measure("closure", || 7);
+    let _: Result<u32, String> = watch.measure_result("fallible", || Ok(7));
+    benchmark::enable_only("op, closure, fallible");
+    let _: bool = benchmark::is_enabled("op");
+    benchmark::enable_all();
+    let snap: std::collections::HashMap<String, WatchStats> = watch.snapshot();
+    let _ = snap
+        .get("op")
+        .map(|s| (s.count, s.p99, s.mean, s.last_seen_ns, s.apdex));
+    let _: usize = watch.snapshot_and_reset().len();
+    let internals: benchmark::InternalStats = watch.internal_stats();
+    let _: u64 = internals.records + internals.lock_waits;
+    let _: Option<std::time::Duration> = watch.idle_for("op");
+    let http: benchmark::ScopedWatch = watch.scoped("http");
+    http.record("get", 10);
+    let _: u64 = http.record_instant("get", std::time::Instant::now());
+    let _: u64 = http.scoped("v2").timer("post").stop();
+    let _: (&str, &Watch, String) = (http.prefix(), http.watch(), http.full_name("get"));
+    let _: usize = http.snapshot().len();
+    let tree: benchmark::MetricTree = watch.snapshot_tree();
+    let _ = (tree.get("http.get").and_then(|n| n.stats), tree.total_count(), tree.children.len());
+    let minute = std::time::Duration::from_secs(60);
+    let map: benchmark::Heatmap = watch.heatmap("op", minute, minute * 10);
+    let _ = (map.column_start_ns(0), map.total(), map.buckets.len(), map.counts.len());
+    let anomalies: Vec<benchmark::Anomaly> = watch.detect_anomalies("op", 3.0);
+    let _ = anomalies.first().map(|a| (a.timestamp, a.value, a.score));
+    watch.on_anomaly(3.0, |_name: &str, _anomaly: &benchmark::Anomaly| {});
+    let sorted: benchmark::Snapshot = watch.snapshot_sorted();
+    let _: Vec<(&str, &WatchStats)> = sorted.with_prefix("o").top_n_by(3, |s| s.p99);
+    let diff: benchmark::SnapshotDiff = watch.snapshot_sorted().diff(&sorted);
+    let _: Option<f64> = diff.get("op").and_then(|m| m.p99.relative());
+    let frozen: benchmark::FrozenSnapshot = watch.freeze();
+    let _ = (
+        frozen.captured_at_ns(),
+        frozen.stats().len(),
+        frozen.get("op"),
+    );
+    let _dump = |path: &std::path::Path| -> std::io::Result<()> { watch.dump(path) };
+    let _: Vec<String> = watch.metric_names();
+    let _: bool = watch.contains("op");
+    let _: usize = watch.retain(|name: &str, stats: &WatchStats| name != "x" && stats.count > 0);
+    watch.clear_name("op");
+    watch.clear();
+
+    let c = Collector::new();
+    c.record(&Measurement::new("m", Duration::ZERO, 0));
+    c.record_duration("m", Duration::from_nanos(1));
+    c.record_duration(String::from("m"), Duration::from_nanos(1));
+    let _: u8 = c.record_closure("m", || 1);
+    let _: Measurement = Measurement::zero("m").record_to(&c);
+    let _: &str = ("out", Measurement::zero("m")).record_to(&c);
+    let (_, _): (u32, Vec<Duration>) = benchmark::measure_n(3, || 7);
+    let (_, _): (u32, Vec<Duration>) = benchmark::measure_until(Duration::from_millis(1), || 7);
+    drop(benchmark::measure_async(async { 7 }));
+    drop(benchmark::measure_named_async("m", async { 7 }));
+    let _: Option<Stats> = c.stats("m");
+    let _: Option<StreamingStats> = c.stats_streaming("m");
+    let _: Collector = Collector::new().without_samples();
+    let _: Collector = Collector::new().with_timestamps();
+    c.reserve("m", 16);
+    c.shrink();
+    let _: Vec<(String, usize)> = c.memory_usage();
+    let _: std::io::Result<usize> = c.export_ndjson(std::io::sink());
+    let _: std::io::Result<usize> = c.drain_ndjson(Vec::new());
+    let _: Vec<(String, Stats)> = c.all_stats();
+    let _: std::collections::HashMap<std::borrow::Cow<'static, str>, Vec<Duration>> = c.drain();
+}
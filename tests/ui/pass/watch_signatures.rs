@@ -0,0 +1,59 @@
+// Pins the exact signatures of the non-generic `Watch` API so the no-op
+// stand-ins cannot drift from the real implementation unnoticed.
+use benchmark::{
+    Anomaly, Heatmap, InternalStats, MetricConfig, MetricId, MetricTree, PartialSnapshot,
+    ScopedWatch, SlowSample, Timer, Watch, WatchBuilder, WatchStats,
+};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+fn main() {
+    let _: fn() -> Watch = Watch::new;
+    let _: fn() -> WatchBuilder = Watch::builder;
+    let _: fn(u64, u64) -> Watch = Watch::with_bounds;
+    let _: fn(&Watch, &str, u64) = Watch::record;
+    let _: fn(&Watch, &str, &[u64]) = Watch::record_many;
+    let _: fn(&Watch, &str) -> MetricId = Watch::register;
+    let _: fn(&Watch, &MetricId, u64) = Watch::record_id;
+    let _: fn(&Watch, &str, Instant) -> u64 = Watch::record_instant;
+    let _: fn(&Watch) -> Vec<String> = Watch::metric_names;
+    let _: fn(&Watch, &str) -> bool = Watch::contains;
+    let _: fn(&Watch) -> String = Watch::folded_stacks;
+    let _: fn(&Watch) -> u64 = Watch::now_ns;
+    let _: fn(&Watch) -> HashMap<String, WatchStats> = Watch::snapshot;
+    let _: fn(&Watch, Duration) -> PartialSnapshot = Watch::snapshot_with_budget;
+    let _: fn(&Watch) -> HashMap<String, WatchStats> = Watch::snapshot_delta;
+    let _: fn(&Watch) -> HashMap<String, WatchStats> = Watch::snapshot_and_reset;
+    let _: fn(&Watch) = Watch::clear;
+    let _: fn(&Watch, &str) = Watch::clear_name;
+    let _: fn(&Watch, Duration, usize) = Watch::enable_history;
+    let _: fn(&Watch) = Watch::capture_history;
+    let _: fn(&Watch, Duration) = Watch::enable_precompute;
+    let _: fn(&Watch) = Watch::refresh_precomputed;
+    let _: fn(&Watch) -> usize = Watch::evict_expired;
+    let _: fn(&Watch) -> u64 = Watch::dropped_metrics;
+    let _: fn(&Watch) -> InternalStats = Watch::internal_stats;
+    let _: fn(&Watch, &str) -> Option<Duration> = Watch::idle_for;
+    let _: fn(&Watch, &str) -> Vec<SlowSample> = Watch::slowest;
+    let _: fn(&Watch) -> HashMap<String, Vec<SlowSample>> = Watch::slowest_all;
+    let _: fn(&Watch) -> String = Watch::slowest_json;
+    let _: fn(&Watch, &str, MetricConfig) = Watch::configure_metric;
+    let _: fn(&Watch, &str) -> Vec<(u128, WatchStats)> = Watch::history;
+    let _: fn(&Watch, &str) -> ScopedWatch = Watch::scoped;
+    let _: fn(&Watch) -> MetricTree = Watch::snapshot_tree;
+    let _: fn(&Watch, &str, Duration, Duration) -> Heatmap = Watch::heatmap;
+    let _: fn(&Watch, &str, f64) -> Vec<Anomaly> = Watch::detect_anomalies;
+
+    let _: fn(&ScopedWatch) -> &str = ScopedWatch::prefix;
+    let _: fn(&ScopedWatch) -> &Watch = ScopedWatch::watch;
+    let _: fn(&ScopedWatch, &str) -> String = ScopedWatch::full_name;
+    let _: fn(&ScopedWatch, &str) -> ScopedWatch = ScopedWatch::scoped;
+    let _: fn(&ScopedWatch, &str, u64) = ScopedWatch::record;
+    let _: fn(&ScopedWatch, &str, Instant) -> u64 = ScopedWatch::record_instant;
+    let _: fn(&ScopedWatch, &str) -> Timer = ScopedWatch::timer;
+    let _: fn(&ScopedWatch) -> HashMap<String, WatchStats> = ScopedWatch::snapshot;
+    let _: for<'a> fn(&'a MetricTree, &str) -> Option<&'a MetricTree> = MetricTree::get;
+    let _: fn(&MetricTree) -> u64 = MetricTree::total_count;
+    let _: fn(&Heatmap, usize) -> u128 = Heatmap::column_start_ns;
+    let _: fn(&Heatmap) -> u64 = Heatmap::total;
+}